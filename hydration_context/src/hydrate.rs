@@ -3,10 +3,11 @@
 // as a result, we'll just allow deprecated for now
 #![allow(deprecated)]
 
-use super::{SerializedDataId, SharedContext};
+use super::{SerializedDataId, SharedContext, BINARY_KEY};
 use crate::{PinnedFuture, PinnedStream};
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
 use core::fmt::Debug;
-use js_sys::Array;
+use js_sys::{Array, Reflect};
 use once_cell::sync::Lazy;
 use std::{
     fmt::Display,
@@ -132,6 +133,18 @@ impl SharedContext for HydrateSharedContext {
         __RESOLVED_RESOURCES.with(|r| r.get(id.0 as u32).as_string())
     }
 
+    fn read_bytes(&self, id: &SerializedDataId) -> Option<Vec<u8>> {
+        // binary data is sent as an object holding its base64, while text is sent as a string
+        let encoded = __RESOLVED_RESOURCES.with(|r| {
+            let data = r.get(id.0 as u32);
+            if !data.is_object() {
+                return None;
+            }
+            Reflect::get(&data, &BINARY_KEY.into()).ok()?.as_string()
+        })?;
+        STANDARD_NO_PAD.decode(encoded).ok()
+    }
+
     fn await_data(&self, _id: &SerializedDataId) -> Option<String> {
         todo!()
     }
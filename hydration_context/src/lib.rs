@@ -19,9 +19,10 @@ mod csr;
 #[cfg_attr(docsrs, doc(cfg(feature = "browser")))]
 mod hydrate;
 mod ssr;
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
 #[cfg(feature = "browser")]
 pub use csr::*;
-use futures::Stream;
+use futures::{FutureExt, Stream};
 #[cfg(feature = "browser")]
 pub use hydrate::*;
 use serde::{Deserialize, Serialize};
@@ -62,6 +63,39 @@ impl From<SerializedDataId> for ErrorId {
     }
 }
 
+/// Data that will be serialized from the server to the client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SerializedData {
+    /// Data that has been serialized to a string, like JSON.
+    Text(String),
+    /// Binary data, like the output of a binary codec.
+    ///
+    /// [`SsrSharedContext`](crate::SsrSharedContext) still embeds this in the HTML as base64
+    /// text, which takes about a third more bytes than the data itself, the same as a binary
+    /// codec's own base64 output. What it adds is that the client can tell the data is binary
+    /// and read it with [`SharedContext::read_bytes`], and that a custom context can take the
+    /// raw bytes with [`SsrSharedContext::consume_data`](crate::SsrSharedContext::consume_data)
+    /// and send them some other way.
+    Binary(Vec<u8>),
+}
+
+/// The property of the JavaScript object that [`SerializedData::Binary`] is embedded in the HTML
+/// as, which holds the bytes as unpadded base64.
+const BINARY_KEY: &str = "b";
+
+impl SerializedData {
+    /// Converts the data into a string.
+    ///
+    /// Binary data is encoded as unpadded base64, which is how binary codecs encode their
+    /// output when it can only be sent as text.
+    pub fn into_text(self) -> String {
+        match self {
+            SerializedData::Text(text) => text,
+            SerializedData::Binary(bytes) => STANDARD_NO_PAD.encode(bytes),
+        }
+    }
+}
+
 /// Information that will be shared between the server and the client.
 pub trait SharedContext: Debug {
     /// Whether the application is running in the browser.
@@ -81,6 +115,23 @@ pub trait SharedContext: Debug {
     /// In browser implementations, this should be a no-op.
     fn write_async(&self, id: SerializedDataId, fut: PinnedFuture<String>);
 
+    /// The given [`Future`] should resolve with some data that can be serialized
+    /// from the server to the client, which may be either text or binary data. This will be
+    /// polled as part of the process of building the HTTP response, *not* when it is first
+    /// created.
+    ///
+    /// In browser implementations, this should be a no-op.
+    ///
+    /// By default, the data is converted to text with [`SerializedData::into_text`] and
+    /// passed to [`write_async`](SharedContext::write_async).
+    fn write_async_data(
+        &self,
+        id: SerializedDataId,
+        fut: PinnedFuture<SerializedData>,
+    ) {
+        self.write_async(id, Box::pin(fut.map(SerializedData::into_text)))
+    }
+
     /// Reads the current value of some data from the shared context, if it has been
     /// sent from the server. This returns the serialized data as a `String` that should
     /// be deserialized.
@@ -89,6 +140,15 @@ pub trait SharedContext: Debug {
     /// always return [`None`].
     fn read_data(&self, id: &SerializedDataId) -> Option<String>;
 
+    /// Reads the current value of some binary data from the shared context, if it has been
+    /// sent from the server as [`SerializedData::Binary`].
+    ///
+    /// On the server and in client-side rendered implementations, this should
+    /// always return [`None`], which is the default.
+    fn read_bytes(&self, _id: &SerializedDataId) -> Option<Vec<u8>> {
+        None
+    }
+
     /// Returns a [`Future`] that resolves with a `String` that should
     /// be deserialized once the given piece of server data has resolved.
     ///
@@ -1,9 +1,10 @@
-use super::{SerializedDataId, SharedContext};
+use super::{SerializedData, SerializedDataId, SharedContext, BINARY_KEY};
 use crate::{PinnedFuture, PinnedStream};
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
 use futures::{
     future::join_all,
     stream::{self, once},
-    Stream, StreamExt,
+    FutureExt, Stream, StreamExt,
 };
 use or_poisoned::OrPoisoned;
 use std::{
@@ -19,7 +20,8 @@ use std::{
 };
 use throw_error::{Error, ErrorId};
 
-type AsyncDataBuf = Arc<RwLock<Vec<(SerializedDataId, PinnedFuture<String>)>>>;
+type AsyncDataBuf =
+    Arc<RwLock<Vec<(SerializedDataId, PinnedFuture<SerializedData>)>>>;
 type ErrorBuf = Arc<RwLock<Vec<(SerializedDataId, ErrorId, Error)>>>;
 type SealedErrors = Arc<RwLock<HashSet<SerializedDataId>>>;
 
@@ -66,6 +68,23 @@ impl SsrSharedContext {
     /// WARNING: this will clear the internal buffers, it should only be called once.
     /// A second call would return an empty `vec![]`.
     pub async fn consume_buffers(&self) -> Vec<(SerializedDataId, String)> {
+        self.consume_data()
+            .await
+            .into_iter()
+            .map(|(id, data)| (id, data.into_text()))
+            .collect()
+    }
+
+    /// Consumes the buffers in the same way as [`consume_buffers`](Self::consume_buffers),
+    /// but returns binary data as the raw bytes of [`SerializedData::Binary`], rather than
+    /// encoding it as base64 text. This is for custom contexts that send binary data in a
+    /// channel of their own; the HTML written by this context always holds it as base64.
+    ///
+    /// WARNING: this will clear the internal buffers, it should only be called once.
+    /// A second call would return an empty `vec![]`.
+    pub async fn consume_data(
+        &self,
+    ) -> Vec<(SerializedDataId, SerializedData)> {
         let sync_data = mem::take(&mut *self.sync_buf.write().or_poisoned());
         let async_data = mem::take(&mut *self.async_buf.write().or_poisoned());
 
@@ -108,6 +127,14 @@ impl SharedContext for SsrSharedContext {
     }
 
     fn write_async(&self, id: SerializedDataId, fut: PinnedFuture<String>) {
+        self.write_async_data(id, Box::pin(fut.map(SerializedData::Text)))
+    }
+
+    fn write_async_data(
+        &self,
+        id: SerializedDataId,
+        fut: PinnedFuture<SerializedData>,
+    ) {
         self.async_buf.write().or_poisoned().push((id, fut))
     }
 
@@ -281,12 +308,9 @@ impl Stream for AsyncDataStream {
                     async_buf.push((id, fut));
                 }
                 Poll::Ready(data) => {
-                    let data = data.replace('<', "\\u003c");
-                    _ = write!(
-                        resolved,
-                        "__RESOLVED_RESOURCES[{}] = {:?};",
-                        id.0, data
-                    );
+                    _ = write!(resolved, "__RESOLVED_RESOURCES[{}] = ", id.0);
+                    write_js_value(&data, &mut resolved);
+                    resolved.push(';');
                 }
             }
         }
@@ -315,13 +339,88 @@ impl Stream for AsyncDataStream {
 }
 
 #[derive(Debug)]
-struct ResolvedData(SerializedDataId, String);
+struct ResolvedData(SerializedDataId, SerializedData);
 
 impl ResolvedData {
     pub fn write_to_buf(&self, buf: &mut String) {
         let ResolvedData(id, ser) = self;
-        // escapes < to prevent it being interpreted as another opening HTML tag
-        let ser = ser.replace('<', "\\u003c");
-        write!(buf, "{}: {:?}", id.0, ser).unwrap();
+        write!(buf, "{}: ", id.0).unwrap();
+        write_js_value(ser, buf);
+    }
+}
+
+/// Writes serialized data as a JavaScript expression.
+///
+/// Text is written as a string literal. Binary data is written as an object holding its base64,
+/// like `{b:"AQID"}`, so that the client can tell it apart from text.
+fn write_js_value(data: &SerializedData, buf: &mut String) {
+    match data {
+        SerializedData::Text(ser) => {
+            // escapes < to prevent it being interpreted as another opening HTML tag
+            let ser = ser.replace('<', "\\u003c");
+            write!(buf, "{ser:?}").unwrap();
+        }
+        SerializedData::Binary(bytes) => {
+            // the base64 alphabet needs no escaping in a string or a <script>
+            let encoded = STANDARD_NO_PAD.encode(bytes);
+            write!(buf, "{{{BINARY_KEY}:\"{encoded}\"}}").unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn writes_binary_data_as_base64() {
+        let mut buf = String::new();
+        write_js_value(&SerializedData::Binary(vec![0, 60, 255]), &mut buf);
+        assert_eq!(buf, r#"{b:"ADz/"}"#);
+
+        // about 4 bytes of HTML for every 3 bytes of data
+        let bytes = (0..=255).cycle().take(3000).collect::<Vec<u8>>();
+        let mut buf = String::new();
+        write_js_value(&SerializedData::Binary(bytes.clone()), &mut buf);
+        assert_eq!(buf.len(), 4000 + r#"{b:""}"#.len());
+        let encoded = &buf[r#"{b:""#.len()..buf.len() - 2];
+        assert_eq!(STANDARD_NO_PAD.decode(encoded).unwrap(), bytes);
+
+        let mut buf = String::new();
+        write_js_value(&SerializedData::Text("<b>".into()), &mut buf);
+        assert_eq!(buf, r#""\\u003cb>""#);
+    }
+
+    #[test]
+    fn streams_binary_resources() {
+        let cx = SsrSharedContext::new();
+        let id = cx.next_id();
+        cx.write_async_data(
+            id,
+            Box::pin(async { SerializedData::Binary(vec![1, 2]) }),
+        );
+        let chunks =
+            block_on(cx.pending_data().unwrap().collect::<Vec<_>>()).concat();
+        assert!(
+            chunks.contains(r#"__RESOLVED_RESOURCES[0] = {b:"AQI"};"#),
+            "{chunks}"
+        );
+    }
+
+    #[test]
+    fn consume_buffers_encodes_binary_data_as_text() {
+        let cx = SsrSharedContext::new();
+        let text = cx.next_id();
+        cx.write_async(text.clone(), Box::pin(async { "1".to_string() }));
+        let binary = cx.next_id();
+        cx.write_async_data(
+            binary.clone(),
+            Box::pin(async { SerializedData::Binary(vec![1, 2, 3]) }),
+        );
+        assert_eq!(
+            block_on(cx.consume_buffers()),
+            [(text, "1".to_string()), (binary, "AQID".to_string())]
+        );
     }
 }
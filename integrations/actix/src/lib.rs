@@ -41,7 +41,11 @@ use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use send_wrapper::SendWrapper;
 use server_fn::{
-    redirect::REDIRECT_HEADER, request::actix::ActixRequest, ServerFnError,
+    middleware::{BoxedService, Layer},
+    redirect::REDIRECT_HEADER,
+    request::actix::ActixRequest,
+    response::actix::ActixResponse as ServerFnActixResponse,
+    ServerFnError,
 };
 use std::{
     collections::HashSet,
@@ -49,6 +53,7 @@ use std::{
     future::Future,
     ops::{Deref, DerefMut},
     path::Path,
+    pin::Pin,
     sync::Arc,
 };
 
@@ -195,6 +200,41 @@ impl ExtendResponse for ActixResponse {
     }
 }
 
+/// Gives each call in a batch of server function calls its own [`ResponseOptions`], so that
+/// the status and headers it sets apply to its own response rather than the whole batch.
+struct BatchCallLayer;
+
+impl Layer<ActixRequest, ServerFnActixResponse> for BatchCallLayer {
+    fn layer(
+        &self,
+        inner: BoxedService<ActixRequest, ServerFnActixResponse>,
+    ) -> BoxedService<ActixRequest, ServerFnActixResponse> {
+        BoxedService::new(BatchCallService(inner))
+    }
+}
+
+struct BatchCallService(BoxedService<ActixRequest, ServerFnActixResponse>);
+
+impl server_fn::middleware::Service<ActixRequest, ServerFnActixResponse>
+    for BatchCallService
+{
+    fn run(
+        &mut self,
+        req: ActixRequest,
+    ) -> Pin<Box<dyn Future<Output = ServerFnActixResponse> + Send>> {
+        let res_options = ResponseOptions::default();
+        let res = Owner::new().with(|| {
+            provide_context(res_options.clone());
+            ScopedFuture::new(self.0 .0.run(req))
+        });
+        Box::pin(async move {
+            let mut res = ActixResponse(res.await.take());
+            res.extend_response(&res_options);
+            res.0.into()
+        })
+    }
+}
+
 /// Provides an easy way to redirect the user from within a server function.
 ///
 /// Calling `redirect` in a server function will redirect the browser in three
@@ -344,7 +384,11 @@ pub fn handle_server_fns_with_context(
             let path = req.path();
             let method = req.method();
             if let Some(mut service) =
-                server_fn::actix::get_server_fn_service(path, method)
+                server_fn::actix::get_server_fn_service_with_call_layer(
+                    path,
+                    method,
+                    BatchCallLayer,
+                )
             {
                 let owner = Owner::new();
                 owner
@@ -355,6 +399,32 @@ pub fn handle_server_fns_with_context(
                             let res_options = ResponseOptions::default();
                             provide_context(res_options.clone());
 
+                            // reject forged requests before the server fn runs
+                            let payload =
+                                match server_fn::csrf::actix::verify_request(
+                                    &req,
+                                    payload,
+                                    |cookie| {
+                                        if let Ok(cookie) =
+                                            HeaderValue::from_str(&cookie)
+                                        {
+                                            res_options.append_header(
+                                                header::SET_COOKIE,
+                                                cookie,
+                                            );
+                                        }
+                                    },
+                                )
+                                .await
+                                {
+                                    Ok(payload) => payload,
+                                    Err(res) => {
+                                        let mut res = ActixResponse(res);
+                                        res.extend_response(&res_options);
+                                        return res.0;
+                                    }
+                                };
+
                             // store Accepts and Referer in case we need them for redirect (below)
                             let accepts_html = req
                                 .headers()
@@ -750,7 +820,26 @@ fn provide_contexts(
 ) {
     let path = leptos_corrected_path(&req);
 
-    provide_context(RequestUrl::new(&path));
+    if let Some(csrf) = server_fn::csrf::csrf_protection() {
+        let cookies = req
+            .headers()
+            .get(header::COOKIE)
+            .and_then(|v| v.to_str().ok());
+        let (token, cookie) = csrf.session_token(cookies);
+        if let Some(cookie) =
+            cookie.and_then(|cookie| HeaderValue::from_str(&cookie).ok())
+        {
+            res_options.append_header(header::SET_COOKIE, cookie);
+        }
+        provide_context(token);
+    }
+
+    let header_value =
+        |name| req.headers().get(name).and_then(|v| v.to_str().ok());
+    provide_context(RequestUrl::new(&path).with_headers(
+        header_value(header::COOKIE),
+        header_value(header::ACCEPT_LANGUAGE),
+    ));
     provide_context(meta_context.clone());
     provide_context(res_options.clone());
     provide_context(req);
@@ -895,6 +984,13 @@ impl ActixPath for Vec<PathSegment> {
                     path.push_str(s);
                     path.push('}');
                 }
+                PathSegment::ConstrainedParam(s, constraint) => {
+                    path.push('{');
+                    path.push_str(s);
+                    path.push(':');
+                    path.push_str(&constraint.to_regex());
+                    path.push('}');
+                }
                 PathSegment::Splat(s) => {
                     path.push('{');
                     path.push_str(s);
@@ -909,6 +1005,10 @@ impl ActixPath for Vec<PathSegment> {
                     );
                     Default::default()
                 }
+                _ => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("unsupported path segment: {segment:?}");
+                }
             }
         }
         path
@@ -72,7 +72,9 @@ use leptos_router::{
 #[cfg(feature = "default")]
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
-use server_fn::{redirect::REDIRECT_HEADER, ServerFnError};
+use server_fn::{
+    middleware::BoxedService, redirect::REDIRECT_HEADER, ServerFnError,
+};
 #[cfg(feature = "default")]
 use std::path::Path;
 use std::{collections::HashSet, fmt::Debug, io, pin::Pin, sync::Arc};
@@ -377,7 +379,11 @@ async fn handle_server_fns_inner(
     let (req, parts) = generate_request_and_parts(req);
 
     if let Some(mut service) =
-        server_fn::axum::get_server_fn_service(&path, method)
+        server_fn::axum::get_server_fn_service_with_call_layer(
+            &path,
+            method,
+            BatchCallLayer,
+        )
     {
         let owner = Owner::new();
         owner
@@ -388,6 +394,26 @@ async fn handle_server_fns_inner(
                     let res_options = ResponseOptions::default();
                     provide_context(res_options.clone());
 
+                    // reject forged requests before the server fn runs
+                    let req = match server_fn::csrf::axum::verify_request(
+                        req,
+                        |cookie| {
+                            if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+                                res_options
+                                    .append_header(header::SET_COOKIE, cookie);
+                            }
+                        },
+                    )
+                    .await
+                    {
+                        Ok(req) => req,
+                        Err(res) => {
+                            let mut res = AxumResponse(res);
+                            res.extend_response(&res_options);
+                            return Ok(res.0);
+                        }
+                    };
+
                     // store Accepts and Referer in case we need them for redirect (below)
                     let accepts_html = req
                         .headers()
@@ -436,6 +462,43 @@ async fn handle_server_fns_inner(
     .expect("could not build Response")
 }
 
+/// Gives each call in a batch of server function calls its own [`ResponseOptions`], so that
+/// the status and headers it sets apply to its own response rather than the whole batch.
+struct BatchCallLayer;
+
+impl server_fn::middleware::Layer<Request<Body>, Response<Body>>
+    for BatchCallLayer
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request<Body>, Response<Body>>,
+    ) -> BoxedService<Request<Body>, Response<Body>> {
+        BoxedService::new(BatchCallService(inner))
+    }
+}
+
+struct BatchCallService(BoxedService<Request<Body>, Response<Body>>);
+
+impl server_fn::middleware::Service<Request<Body>, Response<Body>>
+    for BatchCallService
+{
+    fn run(
+        &mut self,
+        req: Request<Body>,
+    ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+        let res_options = ResponseOptions::default();
+        let res = Owner::new().with(|| {
+            provide_context(res_options.clone());
+            ScopedFuture::new(self.0 .0.run(req))
+        });
+        Box::pin(async move {
+            let mut res = AxumResponse(res.await);
+            res.extend_response(&res_options);
+            res.0
+        })
+    }
+}
+
 /// A stream of bytes of HTML.
 pub type PinnedHtmlStream =
     Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
@@ -928,7 +991,25 @@ fn provide_contexts(
     parts: Parts,
     default_res_options: ResponseOptions,
 ) {
-    provide_context(RequestUrl::new(path));
+    if let Some(csrf) = server_fn::csrf::csrf_protection() {
+        let cookies = parts
+            .headers
+            .get(header::COOKIE)
+            .and_then(|v| v.to_str().ok());
+        let (token, cookie) = csrf.session_token(cookies);
+        if let Some(cookie) =
+            cookie.and_then(|cookie| HeaderValue::from_str(&cookie).ok())
+        {
+            default_res_options.append_header(header::SET_COOKIE, cookie);
+        }
+        provide_context(token);
+    }
+    let header_value =
+        |name| parts.headers.get(name).and_then(|v| v.to_str().ok());
+    provide_context(RequestUrl::new(path).with_headers(
+        header_value(header::COOKIE),
+        header_value(header::ACCEPT_LANGUAGE),
+    ));
     provide_context(meta_context.clone());
     provide_context(parts);
     provide_context(default_res_options);
@@ -1694,7 +1775,8 @@ impl AxumPath for Vec<PathSegment> {
             }
             match segment {
                 PathSegment::Static(s) => path.push_str(s),
-                PathSegment::Param(s) => {
+                // Axum can't check the constraint, so the router does that when rendering
+                PathSegment::Param(s) | PathSegment::ConstrainedParam(s, _) => {
                     path.push(':');
                     path.push_str(s);
                 }
@@ -1711,6 +1793,10 @@ impl AxumPath for Vec<PathSegment> {
                     );
                     Default::default()
                 }
+                _ => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("unsupported path segment: {segment:?}");
+                }
             }
         }
         path
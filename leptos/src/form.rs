@@ -3,12 +3,13 @@ use leptos_dom::helpers::window;
 use leptos_server::{ServerAction, ServerMultiAction};
 use serde::de::DeserializeOwned;
 use server_fn::{
-    client::Client, codec::PostUrl, request::ClientReq, ServerFn, ServerFnError,
+    client::Client, codec::PostUrl, csrf::CSRF_FIELD, request::ClientReq,
+    ServerFn, ServerFnError,
 };
 use tachys::{
     either::Either,
     html::{
-        element::{form, Form},
+        element::{form, input, Form},
         event::submit,
     },
     reactive_graph::node_ref::NodeRef,
@@ -136,7 +137,7 @@ where
         .action(ServFn::url())
         .method("post")
         .on(submit, on_submit)
-        .child(children());
+        .child((csrf_input(), children()));
     if let Some(node_ref) = node_ref {
         Either::Left(action_form.node_ref(node_ref))
     } else {
@@ -201,7 +202,7 @@ where
         .method("post")
         .attr("method", "post")
         .on(submit, on_submit)
-        .child(children());
+        .child((csrf_input(), children()));
     if let Some(node_ref) = node_ref {
         Either::Left(action_form.node_ref(node_ref))
     } else {
@@ -209,6 +210,31 @@ where
     }
 }
 
+/// A hidden input that carries the CSRF token of the session, so that a form post passes the
+/// [CSRF checks](server_fn::csrf) even if it is sent before WASM has loaded.
+///
+/// This is included in [`ActionForm`] and [`MultiActionForm`]. It renders nothing if CSRF
+/// protection has not been turned on.
+pub fn csrf_input() -> impl IntoView {
+    let token = SharedValue::new(|| {
+        #[cfg(feature = "ssr")]
+        {
+            use_context::<server_fn::csrf::CsrfToken>().map(|token| token.0)
+        }
+        #[cfg(not(feature = "ssr"))]
+        {
+            server_fn::csrf::token_from_cookie()
+        }
+    })
+    .into_inner();
+    token.map(|token| {
+        input()
+            .attr("type", "hidden")
+            .attr("name", CSRF_FIELD)
+            .attr("value", token)
+    })
+}
+
 /// Resolves a redirect location to an (absolute) URL.
 pub(crate) fn resolve_redirect_url(loc: &str) -> Option<web_sys::Url> {
     let origin = match window().location().origin() {
@@ -91,6 +91,10 @@
 //! - **`rkyv`** In SSR/hydrate mode, uses [`rkyv`](https://docs.rs/rkyv/latest/rkyv/) to serialize resources and send them
 //!   from the server to the client.
 //! - **`tracing`** Adds support for [`tracing`](https://docs.rs/tracing/latest/tracing/).
+//! - **`devtools`** Records the reactive graph so that it can be inspected by developer tools.
+//!   See `reactive_graph::devtools`.
+//! - **`openapi`** In SSR mode, describes every server function in an OpenAPI document. Argument and
+//!   return types must implement `JsonSchema`. See `server_fn::openapi`.
 //!
 //! **Important Note:** You must enable one of `csr`, `hydrate`, or `ssr` to tell Leptos
 //! which mode your app is operating in. You should only enable one of these per build target,
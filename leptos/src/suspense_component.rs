@@ -7,7 +7,9 @@ use hydration_context::SerializedDataId;
 use leptos_macro::component;
 use reactive_graph::{
     computed::{
-        suspense::{LocalResourceNotifier, SuspenseContext},
+        suspense::{
+            LocalResourceNotifier, SuspenseBoundaries, SuspenseContext,
+        },
         ArcMemo, ScopedFuture,
     },
     effect::RenderEffect,
@@ -124,6 +126,12 @@ where
             }
         });
 
+        // lets an enclosing scope (like the router, when restoring the scroll position) wait
+        // until this boundary has loaded
+        if let Some(boundaries) = use_context::<SuspenseBoundaries>() {
+            boundaries.register(none_pending.clone());
+        }
+
         OwnedView::new(SuspenseBoundary::<false, _, _> {
             id,
             none_pending,
@@ -5,9 +5,12 @@ use crate::{
 };
 use leptos_macro::component;
 use reactive_graph::{
-    computed::{suspense::SuspenseContext, ArcMemo},
+    computed::{
+        suspense::{SuspenseBoundaries, SuspenseContext},
+        ArcMemo,
+    },
     effect::Effect,
-    owner::{provide_context, Owner},
+    owner::{provide_context, use_context, Owner},
     signal::ArcRwSignal,
     traits::{Get, Set, Track, With},
     wrappers::write::SignalSetter,
@@ -118,6 +121,12 @@ where
             });
         }
 
+        // lets an enclosing scope (like the router, when restoring the scroll position) wait
+        // until this boundary has loaded
+        if let Some(boundaries) = use_context::<SuspenseBoundaries>() {
+            boundaries.register(none_pending.clone());
+        }
+
         OwnedView::new(SuspenseBoundary::<true, _, _> {
             id,
             none_pending,
@@ -128,4 +128,36 @@ pub mod tests {
             TypeId::of::<codec::PostUrl>()
         );
     }
+
+    #[test]
+    fn server_protobuf_input_is_the_message() {
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct Note {
+            #[prost(string, tag = "1")]
+            pub text: String,
+        }
+
+        #[server(input = codec::Protobuf)]
+        pub async fn save_note(note: Note) -> Result<(), ServerFnError> {
+            Ok(())
+        }
+
+        use prost::Message;
+
+        let note = Note {
+            text: "hello".to_string(),
+        };
+        let args = SaveNote { note: note.clone() };
+        // the arguments are encoded as the message itself, not wrapped in another one
+        assert_eq!(args.encode_to_vec(), note.encode_to_vec());
+        assert_eq!(args.encoded_len(), note.encoded_len());
+
+        let decoded = SaveNote::decode(&note.encode_to_vec()[..]).unwrap();
+        assert_eq!(decoded.note, note);
+        assert_eq!(SaveNote::default().note, Note::default());
+
+        let mut cleared = args;
+        cleared.clear();
+        assert_eq!(cleared.note, Note::default());
+    }
 }
@@ -12,17 +12,31 @@ mod multi_action;
 pub use multi_action::*;
 mod once_resource;
 pub use once_resource::*;
+mod query;
+pub use query::*;
 mod resource;
 pub use resource::*;
 mod shared;
 
 use base64::{engine::general_purpose::STANDARD_NO_PAD, DecodeError, Engine};
+use hydration_context::SerializedData;
 pub use shared::*;
 
 /// Encodes data into a string.
 pub trait IntoEncodedString {
     /// Encodes the data.
     fn into_encoded_string(self) -> String;
+
+    /// Converts the encoded data into the form in which it is sent from the server to the
+    /// client.
+    ///
+    /// By default, this is sent as text, using [`into_encoded_string`](Self::into_encoded_string).
+    fn into_serialized_data(self) -> SerializedData
+    where
+        Self: Sized,
+    {
+        SerializedData::Text(self.into_encoded_string())
+    }
 }
 
 /// Decodes data from a string.
@@ -37,6 +51,16 @@ pub trait FromEncodedStr {
     fn from_encoded_str(
         data: &str,
     ) -> Result<Self::DecodedType<'_>, Self::DecodingError>;
+
+    /// Takes data that was sent from the server as [`SerializedData::Binary`], if it can be
+    /// decoded from raw bytes.
+    ///
+    /// By default, this returns `None`.
+    fn from_encoded_bytes(
+        _data: Vec<u8>,
+    ) -> Option<Self::DecodedType<'static>> {
+        None
+    }
 }
 
 impl IntoEncodedString for String {
@@ -60,6 +84,10 @@ impl IntoEncodedString for Vec<u8> {
     fn into_encoded_string(self) -> String {
         STANDARD_NO_PAD.encode(self)
     }
+
+    fn into_serialized_data(self) -> SerializedData {
+        SerializedData::Binary(self)
+    }
 }
 
 impl FromEncodedStr for [u8] {
@@ -71,6 +99,10 @@ impl FromEncodedStr for [u8] {
     ) -> Result<Self::DecodedType<'_>, Self::DecodingError> {
         STANDARD_NO_PAD.decode(data)
     }
+
+    fn from_encoded_bytes(data: Vec<u8>) -> Option<Self::DecodedType<'static>> {
+        Some(data)
+    }
 }
 
 #[cfg(feature = "tachys")]
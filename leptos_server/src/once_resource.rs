@@ -159,13 +159,13 @@ where
             }
 
             if shared_context.get_is_hydrating() {
-                shared_context.write_async(
+                shared_context.write_async_data(
                     id,
                     Box::pin(async move {
                         ready_fut.await;
                         let value = value.read().or_poisoned();
                         let value = value.as_ref().unwrap();
-                        Ser::encode(value).unwrap().into_encoded_string()
+                        Ser::encode(value).unwrap().into_serialized_data()
                     }),
                 );
             }
@@ -0,0 +1,656 @@
+use crate::{ArcResource, FromEncodedStr, IntoEncodedString};
+use codee::{string::JsonSerdeCodec, Decoder, Encoder};
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use or_poisoned::OrPoisoned;
+use reactive_graph::{
+    graph::untrack,
+    signal::ArcRwSignal,
+    traits::{Get, ReadUntracked, Update},
+};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+use web_time::Instant;
+
+/// Options that control how long the values in a [`QueryCache`] are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryOptions {
+    /// How long a value is fresh after it has been loaded. A fresh value is used without
+    /// loading it again.
+    ///
+    /// Defaults to zero, i.e., values are reloaded in the background every time they are used.
+    /// A value that has just been reloaded is passed to the resources that are using it
+    /// without being reloaded again.
+    pub stale_time: Duration,
+    /// How long a value is kept after it has been loaded. A value that is older than
+    /// `stale_time`, but not older than `ttl`, is used immediately while it is reloaded in the
+    /// background. An older value is discarded, and has to be loaded again before it is used.
+    ///
+    /// Defaults to five minutes.
+    pub ttl: Duration,
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        Self {
+            stale_time: Duration::ZERO,
+            ttl: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// A keyed cache for the values loaded by resources, which allows them to be reused across
+/// component remounts and route changes.
+///
+/// Values are cached with stale-while-revalidate semantics, as configured by
+/// [`QueryOptions`]: a stale value is shown immediately while it is reloaded in the
+/// background, and the resources that use it update once the new value has loaded. Only one
+/// request is made at a time for each key, no matter how many resources are loading it.
+///
+/// The cache should be created once per application (for example, in the root component) and
+/// shared using context. When rendering on the server, this means that each request has its
+/// own cache, and values loaded on the server are used to fill the cache during hydration.
+///
+/// ```rust,ignore
+/// #[component]
+/// fn App() -> impl IntoView {
+///     provide_context(QueryCache::<UserId, User>::new());
+///     // ...
+/// }
+///
+/// #[component]
+/// fn UserProfile(id: Signal<UserId>) -> impl IntoView {
+///     let users = expect_context::<QueryCache<UserId, User>>();
+///     let user = users.resource(move || id.get(), get_user);
+///     // ...
+/// }
+/// ```
+pub struct QueryCache<K, T> {
+    entries: Arc<RwLock<HashMap<K, CacheEntry<T>>>>,
+    options: QueryOptions,
+}
+
+struct CacheEntry<T> {
+    value: Option<CachedValue<T>>,
+    pending: Option<Shared<BoxFuture<'static, T>>>,
+    // notified when a value has been loaded in the background, or invalidated
+    version: ArcRwSignal<usize>,
+}
+
+impl<T> Default for CacheEntry<T> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            pending: None,
+            version: ArcRwSignal::new(0),
+        }
+    }
+}
+
+struct CachedValue<T> {
+    value: T,
+    loaded_at: Instant,
+    invalidated: bool,
+}
+
+impl<T> CachedValue<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            loaded_at: Instant::now(),
+            invalidated: false,
+        }
+    }
+}
+
+enum Lookup<T> {
+    Fresh(T),
+    Stale(T, Shared<BoxFuture<'static, T>>, ArcRwSignal<usize>),
+    Loading(Shared<BoxFuture<'static, T>>),
+}
+
+impl<K, T> Clone for QueryCache<K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+            options: self.options,
+        }
+    }
+}
+
+impl<K, T> Debug for QueryCache<K, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryCache")
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, T> Default for QueryCache<K, T> {
+    fn default() -> Self {
+        Self::with_options(QueryOptions::default())
+    }
+}
+
+impl<K, T> QueryCache<K, T> {
+    /// Creates an empty cache, with the default [`QueryOptions`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty cache with the given options.
+    pub fn with_options(options: QueryOptions) -> Self {
+        Self {
+            entries: Default::default(),
+            options,
+        }
+    }
+
+    /// The options used by this cache.
+    pub fn options(&self) -> QueryOptions {
+        self.options
+    }
+}
+
+impl<K, T> QueryCache<K, T>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    T: Clone + Send + Sync + 'static,
+{
+    /// Creates a new resource with the encoding [`JsonSerdeCodec`], which loads its value
+    /// through this cache.
+    ///
+    /// This takes a `key` function and a `fetcher`. Like the `source` of an [`ArcResource`],
+    /// the key is reactively tracked. Whenever it changes, the cached value for the new key is
+    /// used if there is one, and the `fetcher` is only run if that value is missing or stale.
+    #[track_caller]
+    pub fn resource<Fut>(
+        &self,
+        key: impl Fn() -> K + Send + Sync + 'static,
+        fetcher: impl Fn(K) -> Fut + Send + Sync + 'static,
+    ) -> ArcResource<T>
+    where
+        JsonSerdeCodec: Encoder<T> + Decoder<T>,
+        <JsonSerdeCodec as Encoder<T>>::Error: Debug,
+        <JsonSerdeCodec as Decoder<T>>::Error: Debug,
+        <<JsonSerdeCodec as Decoder<T>>::Encoded as FromEncodedStr>::DecodingError:
+            Debug,
+        <JsonSerdeCodec as Encoder<T>>::Encoded: IntoEncodedString,
+        <JsonSerdeCodec as Decoder<T>>::Encoded: FromEncodedStr,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        self.resource_with_codec(key, fetcher)
+    }
+
+    /// Creates a new resource with the encoding `Ser`, which loads its value through this
+    /// cache.
+    ///
+    /// This takes a `key` function and a `fetcher`. Like the `source` of an [`ArcResource`],
+    /// the key is reactively tracked. Whenever it changes, the cached value for the new key is
+    /// used if there is one, and the `fetcher` is only run if that value is missing or stale.
+    #[track_caller]
+    pub fn resource_with_codec<Ser, Fut>(
+        &self,
+        key: impl Fn() -> K + Send + Sync + 'static,
+        fetcher: impl Fn(K) -> Fut + Send + Sync + 'static,
+    ) -> ArcResource<T, Ser>
+    where
+        Ser: Encoder<T> + Decoder<T>,
+        <Ser as Encoder<T>>::Error: Debug,
+        <Ser as Decoder<T>>::Error: Debug,
+        <<Ser as Decoder<T>>::Encoded as FromEncodedStr>::DecodingError: Debug,
+        <Ser as Encoder<T>>::Encoded: IntoEncodedString,
+        <Ser as Decoder<T>>::Encoded: FromEncodedStr,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let key = Arc::new(key);
+        let fetcher = Arc::new(fetcher);
+
+        // the version of the entry is tracked along with the key, so that the resource runs
+        // again when a value is loaded in the background or invalidated
+        let source = {
+            let cache = self.clone();
+            let key = Arc::clone(&key);
+            move || {
+                let key = key();
+                let version = cache.version(&key);
+                (key, version.get())
+            }
+        };
+        let resource = {
+            let cache = self.clone();
+            let last_run = Arc::new(Mutex::new(None::<(K, usize)>));
+            ArcResource::new_with_options(
+                source,
+                move |(key, version)| {
+                    let fetcher = Arc::clone(&fetcher);
+                    // a run that was caused by a new value for the same key uses that value,
+                    // rather than loading it again
+                    let updated = last_run
+                        .lock()
+                        .or_poisoned()
+                        .replace((key.clone(), version))
+                        .is_some_and(|(last_key, last_version)| {
+                            last_key == key && last_version != version
+                        });
+                    cache.fetch_inner(key, move |key| fetcher(key), updated)
+                },
+                false,
+            )
+        };
+
+        // a value that was serialized from the server is used to fill the cache
+        if let Some(value) = resource.read_untracked().as_ref() {
+            self.fill(untrack(|| key()), value.clone());
+        }
+
+        resource
+    }
+
+    /// Loads the value for the given key, using the cached value if there is one.
+    ///
+    /// If the cached value is stale, it is returned immediately, and the `fetcher` is run in
+    /// the background to replace it. If there is no cached value, this waits for the `fetcher`
+    /// to load it, or for a request for the same key that is already in progress.
+    pub fn fetch<Fut>(
+        &self,
+        key: K,
+        fetcher: impl FnOnce(K) -> Fut + Send + 'static,
+    ) -> impl Future<Output = T> + Send + 'static
+    where
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        self.fetch_inner(key, fetcher, false)
+    }
+
+    /// Loads the value for the given key. If `updated` is `true`, the value has just been
+    /// replaced, and is used without being loaded again unless it has been invalidated.
+    fn fetch_inner<Fut>(
+        &self,
+        key: K,
+        fetcher: impl FnOnce(K) -> Fut + Send + 'static,
+        updated: bool,
+    ) -> impl Future<Output = T> + Send + 'static
+    where
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let this = self.clone();
+        // nothing happens until the future is polled, so that creating a resource that was
+        // hydrated with a value does not start loading it again
+        async move {
+            match this.lookup(key, fetcher, updated) {
+                Lookup::Fresh(value) => value,
+                Lookup::Stale(value, pending, version) => {
+                    reactive_graph::spawn(async move {
+                        pending.await;
+                        version.update(|n| *n += 1);
+                    });
+                    value
+                }
+                Lookup::Loading(pending) => pending.await,
+            }
+        }
+    }
+
+    /// Returns the cached value for the given key, if there is one, even if it is stale.
+    pub fn get(&self, key: &K) -> Option<T> {
+        let mut entries = self.entries.write().or_poisoned();
+        self.evict_expired(&mut entries);
+        entries
+            .get(key)
+            .and_then(|entry| entry.value.as_ref())
+            .map(|cached| cached.value.clone())
+    }
+
+    /// Replaces the cached value for the given key, and updates any resources that use it.
+    pub fn set(&self, key: K, value: T) {
+        let version = {
+            let mut entries = self.entries.write().or_poisoned();
+            let entry = entries.entry(key).or_default();
+            entry.value = Some(CachedValue::new(value));
+            entry.version.clone()
+        };
+        version.update(|n| *n += 1);
+    }
+
+    /// Marks the cached value for the given key as stale, so that it will be loaded again.
+    ///
+    /// Any resources that use it are updated once the new value has loaded.
+    pub fn invalidate(&self, key: &K) {
+        let version = {
+            let mut entries = self.entries.write().or_poisoned();
+            entries.get_mut(key).map(|entry| {
+                if let Some(cached) = &mut entry.value {
+                    cached.invalidated = true;
+                }
+                entry.version.clone()
+            })
+        };
+        if let Some(version) = version {
+            version.update(|n| *n += 1);
+        }
+    }
+
+    /// Marks every cached value as stale, so that they will all be loaded again.
+    pub fn invalidate_all(&self) {
+        let versions = {
+            let mut entries = self.entries.write().or_poisoned();
+            entries
+                .values_mut()
+                .map(|entry| {
+                    if let Some(cached) = &mut entry.value {
+                        cached.invalidated = true;
+                    }
+                    entry.version.clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        for version in versions {
+            version.update(|n| *n += 1);
+        }
+    }
+
+    /// Removes every cached value.
+    pub fn clear(&self) {
+        self.entries.write().or_poisoned().clear();
+    }
+
+    fn version(&self, key: &K) -> ArcRwSignal<usize> {
+        if let Some(entry) = self.entries.read().or_poisoned().get(key) {
+            return entry.version.clone();
+        }
+        self.entries
+            .write()
+            .or_poisoned()
+            .entry(key.clone())
+            .or_default()
+            .version
+            .clone()
+    }
+
+    fn fill(&self, key: K, value: T) {
+        let mut entries = self.entries.write().or_poisoned();
+        let entry = entries.entry(key).or_default();
+        if entry.value.is_none() {
+            entry.value = Some(CachedValue::new(value));
+        }
+    }
+
+    /// Drops every value that is older than `ttl`.
+    ///
+    /// The entries themselves are kept, because resources that use them are subscribed to
+    /// their versions.
+    fn evict_expired(&self, entries: &mut HashMap<K, CacheEntry<T>>) {
+        for entry in entries.values_mut() {
+            if entry.value.as_ref().is_some_and(|cached| {
+                cached.loaded_at.elapsed() > self.options.ttl
+            }) {
+                entry.value = None;
+            }
+        }
+    }
+
+    fn lookup<Fut>(
+        &self,
+        key: K,
+        fetcher: impl FnOnce(K) -> Fut,
+        updated: bool,
+    ) -> Lookup<T>
+    where
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let (cached, version) = {
+            let mut entries = self.entries.write().or_poisoned();
+            self.evict_expired(&mut entries);
+            let entry = entries.entry(key.clone()).or_default();
+
+            let cached = entry.value.as_ref().map(|cached| {
+                let is_fresh = !cached.invalidated
+                    && (updated
+                        || cached.loaded_at.elapsed()
+                            < self.options.stale_time);
+                (cached.value.clone(), is_fresh)
+            });
+            match (cached, &entry.pending) {
+                (Some((value, true)), _) => return Lookup::Fresh(value),
+                // only one request is made for each key at a time
+                (Some((value, _)), Some(pending)) => {
+                    return Lookup::Stale(
+                        value,
+                        pending.clone(),
+                        entry.version.clone(),
+                    )
+                }
+                (None, Some(pending)) => {
+                    return Lookup::Loading(pending.clone())
+                }
+                (cached, None) => (cached, entry.version.clone()),
+            }
+        };
+
+        // the lock is not held while the fetcher runs, because it may use the cache itself
+        let fut = fetcher(key.clone());
+        let pending = {
+            let entries = Arc::clone(&self.entries);
+            let key = key.clone();
+            async move {
+                let value = fut.await;
+                if let Some(entry) = entries.write().or_poisoned().get_mut(&key)
+                {
+                    entry.value = Some(CachedValue::new(value.clone()));
+                    entry.pending = None;
+                }
+                value
+            }
+            .boxed()
+            .shared()
+        };
+        // another request for the same key may have started while the lock was released
+        let pending = self
+            .entries
+            .write()
+            .or_poisoned()
+            .entry(key)
+            .or_default()
+            .pending
+            .get_or_insert(pending)
+            .clone();
+
+        match cached {
+            Some((value, _)) => Lookup::Stale(value, pending, version),
+            None => Lookup::Loading(pending),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use any_spawner::Executor;
+    use reactive_graph::{owner::Owner, traits::GetUntracked};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counting_fetcher(
+        calls: &Arc<AtomicUsize>,
+    ) -> impl Fn(u32) -> futures::future::Ready<u32> + Send + Sync + 'static
+    {
+        let calls = Arc::clone(calls);
+        move |key| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            futures::future::ready(key * 2)
+        }
+    }
+
+    async fn settle() {
+        for _ in 0..20 {
+            Executor::tick().await;
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn fetches_once_for_a_single_read() {
+        _ = Executor::init_tokio();
+        let owner = Owner::new();
+        owner.set();
+
+        let cache = QueryCache::<u32, u32>::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resource = cache.resource(|| 1, counting_fetcher(&calls));
+        assert_eq!(resource.clone().await, 2);
+        settle().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // with a zero `stale_time`, a second read revalidates the value exactly once
+        let remounted = cache.resource(|| 1, counting_fetcher(&calls));
+        assert_eq!(remounted.clone().await, 2);
+        settle().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(resource.get_untracked(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn does_not_fetch_after_set() {
+        _ = Executor::init_tokio();
+        let owner = Owner::new();
+        owner.set();
+
+        let cache = QueryCache::<u32, u32>::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resource = cache.resource(|| 1, counting_fetcher(&calls));
+        assert_eq!(resource.clone().await, 2);
+        cache.set(1, 5);
+        settle().await;
+        assert_eq!(resource.get_untracked(), Some(5));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        cache.invalidate(&1);
+        settle().await;
+        assert_eq!(resource.get_untracked(), Some(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn serves_stale_value_while_revalidating() {
+        _ = Executor::init_tokio();
+
+        let cache = QueryCache::<u32, u32>::new();
+        assert_eq!(cache.fetch(1, |_| async { 2 }).await, 2);
+
+        // the stale value is returned at once, and replaced in the background
+        assert_eq!(cache.fetch(1, |_| async { 3 }).await, 2);
+        settle().await;
+        assert_eq!(cache.get(&1), Some(3));
+    }
+
+    #[tokio::test]
+    async fn does_not_reload_fresh_value() {
+        _ = Executor::init_tokio();
+
+        let cache = QueryCache::<u32, u32>::with_options(QueryOptions {
+            stale_time: Duration::from_secs(60),
+            ..Default::default()
+        });
+        let calls = Arc::new(AtomicUsize::new(0));
+        assert_eq!(cache.fetch(1, counting_fetcher(&calls)).await, 2);
+        assert_eq!(cache.fetch(1, counting_fetcher(&calls)).await, 2);
+        settle().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn reloads_invalidated_value() {
+        _ = Executor::init_tokio();
+
+        let cache = QueryCache::<u32, u32>::with_options(QueryOptions {
+            stale_time: Duration::from_secs(60),
+            ..Default::default()
+        });
+        assert_eq!(cache.fetch(1, |_| async { 2 }).await, 2);
+        cache.invalidate(&1);
+        assert_eq!(cache.fetch(1, |_| async { 3 }).await, 2);
+        settle().await;
+        assert_eq!(cache.get(&1), Some(3));
+        assert_eq!(cache.fetch(1, |_| async { 4 }).await, 3);
+    }
+
+    #[tokio::test]
+    async fn waits_for_expired_value_to_reload() {
+        _ = Executor::init_tokio();
+
+        let cache = QueryCache::<u32, u32>::with_options(QueryOptions {
+            ttl: Duration::from_millis(10),
+            ..Default::default()
+        });
+        assert_eq!(cache.fetch(1, |_| async { 2 }).await, 2);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.fetch(1, |_| async { 3 }).await, 3);
+    }
+
+    #[tokio::test]
+    async fn evicts_expired_values_of_other_keys() {
+        _ = Executor::init_tokio();
+
+        let cache = QueryCache::<u32, u32>::with_options(QueryOptions {
+            ttl: Duration::from_millis(10),
+            ..Default::default()
+        });
+        assert_eq!(cache.fetch(1, |_| async { 2 }).await, 2);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.fetch(2, |_| async { 4 }).await, 4);
+        let entries = cache.entries.read().unwrap();
+        assert!(entries[&1].value.is_none());
+        assert!(entries[&2].value.is_some());
+    }
+
+    #[tokio::test]
+    async fn deduplicates_concurrent_requests() {
+        _ = Executor::init_tokio();
+
+        let cache = QueryCache::<u32, u32>::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let fetcher = || {
+            let calls = Arc::clone(&calls);
+            move |key| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    tokio::task::yield_now().await;
+                    key * 2
+                }
+            }
+        };
+        let (a, b) = futures::join!(
+            cache.fetch(1, fetcher()),
+            cache.fetch(1, fetcher())
+        );
+        assert_eq!((a, b), (2, 2));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn fetcher_can_use_the_cache() {
+        _ = Executor::init_tokio();
+
+        let cache = QueryCache::<u32, u32>::new();
+        cache.set(2, 5);
+        let value = cache
+            .fetch(1, {
+                let cache = cache.clone();
+                move |_| {
+                    let other = cache.get(&2).unwrap_or_default();
+                    async move { other + 1 }
+                }
+            })
+            .await;
+        assert_eq!(value, 6);
+    }
+}
@@ -280,14 +280,14 @@ where
             }
 
             if shared_context.get_is_hydrating() {
-                shared_context.write_async(
+                shared_context.write_async_data(
                     id,
                     Box::pin(async move {
                         ready_fut.await;
                         value.with_untracked(|data| match &data {
                             // TODO handle serialization errors
                             Some(val) => {
-                                Ser::encode(val).unwrap().into_encoded_string()
+                                Ser::encode(val).unwrap().into_serialized_data()
                             }
                             _ => unreachable!(),
                         })
@@ -341,6 +341,22 @@ where
 
         let shared_context = Owner::current_shared_context();
         if let Some(shared_context) = shared_context {
+            // binary data is still embedded as base64; the shared context decodes it to bytes
+            let bytes = shared_context
+                .read_bytes(id)
+                .and_then(<Ser as Decoder<T>>::Encoded::from_encoded_bytes);
+            if let Some(encoded) = bytes {
+                match Ser::decode(encoded.borrow()) {
+                    Ok(value) => return Some(value),
+                    #[allow(unused)]
+                    Err(e) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!("couldn't deserialize: {e:?}");
+                        return None;
+                    }
+                }
+            }
+
             let value = shared_context.read_data(id);
             if let Some(value) = value {
                 let encoded =
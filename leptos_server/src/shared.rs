@@ -226,14 +226,24 @@ where
             let serialized = sc.as_ref().and_then(|sc| sc.read_data(&id));
             let hydrating =
                 sc.as_ref().map(|sc| sc.during_hydration()).unwrap_or(false);
+            let bytes = sc
+                .as_ref()
+                .and_then(|sc| sc.read_bytes(&id))
+                .and_then(<Ser as Decoder<T>>::Encoded::from_encoded_bytes);
             value = if hydrating {
-                let value = match serialized {
-                    None => {
+                let value = match (bytes, serialized) {
+                    (Some(encoded), _) => Ser::decode(encoded.borrow())
+                        .inspect_err(|_e| {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!("{_e:?}");
+                        })
+                        .ok(),
+                    (None, None) => {
                         #[cfg(feature = "tracing")]
                         tracing::error!("couldn't deserialize");
                         None
                     }
-                    Some(data) => {
+                    (None, Some(data)) => {
                         match <Ser as Decoder<T>>::Encoded::from_encoded_str(
                             &data,
                         ) {
@@ -267,9 +277,9 @@ where
                 if let Some(sc) = sc {
                     if sc.get_is_hydrating() {
                         match Ser::encode(&init)
-                            .map(IntoEncodedString::into_encoded_string)
+                            .map(IntoEncodedString::into_serialized_data)
                         {
-                            Ok(value) => sc.write_async(
+                            Ok(value) => sc.write_async_data(
                                 id,
                                 Box::pin(async move { value }),
                             ),
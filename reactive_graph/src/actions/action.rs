@@ -1,3 +1,4 @@
+use super::optimistic::{Optimistic, OptimisticHandler};
 use crate::{
     computed::{ArcMemo, Memo},
     diagnostics::is_suppressing_resource_load,
@@ -6,7 +7,10 @@ use crate::{
         SyncStorage,
     },
     signal::{ArcRwSignal, RwSignal},
-    traits::{DefinedAt, Dispose, Get, GetUntracked, GetValue, Set, Update},
+    traits::{
+        DefinedAt, Dispose, Get, GetUntracked, GetValue, Set, SetValue, Update,
+        WithValue,
+    },
     unwrap_signal,
 };
 use any_spawner::Executor;
@@ -97,6 +101,7 @@ pub struct ArcAction<I, O> {
     value: ArcRwSignal<Option<O>>,
     version: ArcRwSignal<usize>,
     dispatched: ArcStoredValue<usize>,
+    optimistic: ArcStoredValue<Option<OptimisticHandler<I, O>>>,
     #[allow(clippy::complexity)]
     action_fn: Arc<
         dyn Fn(&I) -> Pin<Box<dyn Future<Output = O> + Send>> + Send + Sync,
@@ -113,6 +118,7 @@ impl<I, O> Clone for ArcAction<I, O> {
             value: self.value.clone(),
             version: self.version.clone(),
             dispatched: self.dispatched.clone(),
+            optimistic: self.optimistic.clone(),
             action_fn: self.action_fn.clone(),
             #[cfg(any(debug_assertions, leptos_debuginfo))]
             defined_at: self.defined_at,
@@ -197,6 +203,7 @@ where
             value: ArcRwSignal::new(value),
             version: Default::default(),
             dispatched: Default::default(),
+            optimistic: ArcStoredValue::new(None),
             action_fn: Arc::new(move |input| Box::pin(action_fn(input))),
             #[cfg(any(debug_assertions, leptos_debuginfo))]
             defined_at: Location::caller(),
@@ -213,6 +220,29 @@ where
     }
 }
 
+impl<I, T, E> ArcAction<I, Result<T, E>>
+where
+    I: 'static,
+    T: 'static,
+    E: 'static,
+{
+    /// Registers an optimistic update, which is applied whenever the action is dispatched.
+    ///
+    /// The function is called with the input before the action runs, and returns an
+    /// [`Optimistic`] describing the change it has made. That change is committed if the action
+    /// resolves to `Ok(_)`, and rolled back if it resolves to `Err(_)` or is aborted.
+    ///
+    /// Registering a new optimistic update replaces the previous one.
+    #[track_caller]
+    pub fn optimistic(
+        &self,
+        update: impl Fn(&I) -> Optimistic + Send + Sync + 'static,
+    ) {
+        self.optimistic
+            .set_value(Some(OptimisticHandler::new(update)));
+    }
+}
+
 /// A handle that allows aborting an in-flight action. It is returned from [`Action::dispatch`] or
 /// [`ArcAction::dispatch`].
 #[derive(Debug)]
@@ -226,6 +256,11 @@ impl ActionAbortHandle {
     /// effects created by that `Future` no longer run: for example, if the action dispatches an
     /// HTTP request, whether that request is actually canceled or not depends on whether the
     /// request library actually cancels a request when its `Future` is dropped.
+    ///
+    /// The `Future` is dropped as soon as the abort is received. Server function calls made with
+    /// the browser and `reqwest` clients are canceled when this happens, and the server drops
+    /// the server function’s own `Future` when it notices that the client has disconnected (see
+    /// `server_fn::disconnect::on_client_disconnect`).
     pub fn abort(self) {
         let _ = self.0.send(());
     }
@@ -241,6 +276,9 @@ where
     pub fn dispatch(&self, input: I) -> ActionAbortHandle {
         let (abort_tx, mut abort_rx) = oneshot::channel();
         if !is_suppressing_resource_load() {
+            let optimistic = self.optimistic.with_value(|handler| {
+                handler.as_ref().map(|handler| handler.apply(&input))
+            });
             let mut fut = (self.action_fn)(&input).fuse();
 
             // Update the state before loading
@@ -259,11 +297,20 @@ where
                     select! {
                         // if the abort message has been sent, bail and do nothing
                         _ = abort_rx => {
+                            // drop the action's future right away, so that any request it
+                            // has made is canceled
+                            drop(fut);
                             in_flight.update(|n| *n = n.saturating_sub(1));
+                            if let Some(optimistic) = optimistic {
+                                optimistic.settle(None);
+                            }
                         },
                         // otherwise, update the value
                         result = fut => {
                             in_flight.update(|n| *n = n.saturating_sub(1));
+                            if let Some(optimistic) = optimistic {
+                                optimistic.settle(Some(&result));
+                            }
                             let is_latest = dispatched.get_value() <= current_version;
                             if is_latest {
                                 version.update(|n| *n += 1);
@@ -293,6 +340,9 @@ where
     pub fn dispatch_local(&self, input: I) -> ActionAbortHandle {
         let (abort_tx, mut abort_rx) = oneshot::channel();
         if !is_suppressing_resource_load() {
+            let optimistic = self.optimistic.with_value(|handler| {
+                handler.as_ref().map(|handler| handler.apply(&input))
+            });
             let mut fut = (self.action_fn)(&input).fuse();
 
             // Update the state before loading
@@ -311,11 +361,20 @@ where
                     select! {
                         // if the abort message has been sent, bail and do nothing
                         _ = abort_rx => {
+                            // drop the action's future right away, so that any request it
+                            // has made is canceled
+                            drop(fut);
                             in_flight.update(|n| *n = n.saturating_sub(1));
+                            if let Some(optimistic) = optimistic {
+                                optimistic.settle(None);
+                            }
                         },
                         // otherwise, update the value
                         result = fut => {
                             in_flight.update(|n| *n = n.saturating_sub(1));
+                            if let Some(optimistic) = optimistic {
+                                optimistic.settle(Some(&result));
+                            }
                             let is_latest = dispatched.get_value() <= current_version;
                             if is_latest {
                                 version.update(|n| *n += 1);
@@ -367,6 +426,7 @@ where
             value: ArcRwSignal::new(value),
             version: Default::default(),
             dispatched: Default::default(),
+            optimistic: ArcStoredValue::new(None),
             action_fn: Arc::new(move |input| {
                 Box::pin(SendWrapper::new(action_fn(input)))
             }),
@@ -703,6 +763,31 @@ where
     }
 }
 
+impl<I, T, E, S> Action<I, Result<T, E>, S>
+where
+    I: 'static,
+    T: 'static,
+    E: 'static,
+    S: Storage<ArcAction<I, Result<T, E>>>,
+{
+    /// Registers an optimistic update, which is applied whenever the action is dispatched.
+    ///
+    /// The function is called with the input before the action runs, and returns an
+    /// [`Optimistic`] describing the change it has made. That change is committed if the action
+    /// resolves to `Ok(_)`, and rolled back if it resolves to `Err(_)` or is aborted.
+    ///
+    /// Registering a new optimistic update replaces the previous one.
+    #[track_caller]
+    pub fn optimistic(
+        &self,
+        update: impl Fn(&I) -> Optimistic + Send + Sync + 'static,
+    ) {
+        self.inner
+            .try_with_value(|inner| inner.optimistic(update))
+            .unwrap_or_else(unwrap_signal!(self))
+    }
+}
+
 impl<I, O> Action<I, O, LocalStorage>
 where
     I: 'static,
@@ -1,8 +1,15 @@
+use super::optimistic::{AppliedOptimistic, Optimistic, OptimisticHandler};
 use crate::{
     diagnostics::is_suppressing_resource_load,
-    owner::{ArenaItem, FromLocal, LocalStorage, Storage, SyncStorage},
+    owner::{
+        ArcStoredValue, ArenaItem, FromLocal, LocalStorage, Storage,
+        StoredValue, SyncStorage,
+    },
     signal::{ArcReadSignal, ArcRwSignal, ReadSignal, RwSignal},
-    traits::{DefinedAt, Dispose, GetUntracked, Set, Update},
+    traits::{
+        DefinedAt, Dispose, GetUntracked, Set, SetValue, Update, UpdateValue,
+        WithValue,
+    },
     unwrap_signal,
 };
 use std::{fmt::Debug, future::Future, panic::Location, pin::Pin, sync::Arc};
@@ -235,6 +242,32 @@ where
     }
 }
 
+impl<I, T, E, S> MultiAction<I, Result<T, E>, S>
+where
+    I: 'static,
+    T: 'static,
+    E: 'static,
+    S: Storage<ArcMultiAction<I, Result<T, E>>>,
+{
+    /// Registers an optimistic update, which is applied whenever a value is dispatched.
+    ///
+    /// The function is called with the input before the submission runs, and returns an
+    /// [`Optimistic`] describing the change it has made. That change is committed if the
+    /// submission resolves to `Ok(_)`, and rolled back if it resolves to `Err(_)` or is
+    /// canceled.
+    ///
+    /// Registering a new optimistic update replaces the previous one.
+    #[track_caller]
+    pub fn optimistic(
+        &self,
+        update: impl Fn(&I) -> Optimistic + Send + Sync + 'static,
+    ) {
+        self.inner
+            .try_with_value(|inner| inner.optimistic(update))
+            .unwrap_or_else(unwrap_signal!(self))
+    }
+}
+
 impl<I, O> MultiAction<I, O>
 where
     I: Send + Sync + 'static,
@@ -359,6 +392,7 @@ where
 pub struct ArcMultiAction<I, O> {
     version: ArcRwSignal<usize>,
     submissions: ArcRwSignal<Vec<ArcSubmission<I, O>>>,
+    optimistic: ArcStoredValue<Option<OptimisticHandler<I, O>>>,
     #[allow(clippy::complexity)]
     action_fn: Arc<
         dyn Fn(&I) -> Pin<Box<dyn Future<Output = O> + Send>> + Send + Sync,
@@ -387,6 +421,7 @@ where
         Self {
             version: self.version.clone(),
             submissions: self.submissions.clone(),
+            optimistic: self.optimistic.clone(),
             action_fn: Arc::clone(&self.action_fn),
         }
     }
@@ -433,6 +468,7 @@ impl<I, O> ArcMultiAction<I, O> {
         Self {
             version: ArcRwSignal::new(0),
             submissions: ArcRwSignal::new(Vec::new()),
+            optimistic: ArcStoredValue::new(None),
             action_fn,
         }
     }
@@ -491,6 +527,9 @@ where
     /// ```
     pub fn dispatch(&self, input: I) {
         if !is_suppressing_resource_load() {
+            let optimistic = self.optimistic.with_value(|handler| {
+                handler.as_ref().map(|handler| handler.apply(&input))
+            });
             let fut = (self.action_fn)(&input);
 
             let submission = ArcSubmission {
@@ -498,6 +537,7 @@ where
                 value: ArcRwSignal::new(None),
                 pending: ArcRwSignal::new(true),
                 canceled: ArcRwSignal::new(false),
+                optimistic: ArcStoredValue::new(optimistic),
             };
 
             self.submissions
@@ -509,6 +549,9 @@ where
                 let new_value = fut.await;
                 let canceled = submission.canceled.get_untracked();
                 if !canceled {
+                    if let Some(optimistic) = submission.take_optimistic() {
+                        optimistic.settle(Some(&new_value));
+                    }
                     submission.value.try_set(Some(new_value));
                 }
                 submission.input.try_set(None);
@@ -565,6 +608,7 @@ where
             value: ArcRwSignal::new(Some(value)),
             pending: ArcRwSignal::new(false),
             canceled: ArcRwSignal::new(false),
+            optimistic: ArcStoredValue::new(None),
         };
 
         self.submissions
@@ -573,6 +617,30 @@ where
     }
 }
 
+impl<I, T, E> ArcMultiAction<I, Result<T, E>>
+where
+    I: 'static,
+    T: 'static,
+    E: 'static,
+{
+    /// Registers an optimistic update, which is applied whenever a value is dispatched.
+    ///
+    /// The function is called with the input before the submission runs, and returns an
+    /// [`Optimistic`] describing the change it has made. That change is committed if the
+    /// submission resolves to `Ok(_)`, and rolled back if it resolves to `Err(_)` or is
+    /// canceled.
+    ///
+    /// Registering a new optimistic update replaces the previous one.
+    #[track_caller]
+    pub fn optimistic(
+        &self,
+        update: impl Fn(&I) -> Optimistic + Send + Sync + 'static,
+    ) {
+        self.optimistic
+            .set_value(Some(OptimisticHandler::new(update)));
+    }
+}
+
 impl<I, O> ArcMultiAction<I, O> {
     /// The set of all submissions to this multi-action.
     /// ```rust
@@ -650,6 +718,8 @@ pub struct ArcSubmission<I, O> {
     pending: ArcRwSignal<bool>,
     /// Controls this submission has been canceled.
     canceled: ArcRwSignal<bool>,
+    /// The optimistic update applied when this was dispatched, if it has not yet settled.
+    optimistic: ArcStoredValue<Option<AppliedOptimistic<O>>>,
 }
 
 impl<I, O> ArcSubmission<I, O>
@@ -689,6 +759,13 @@ where
         // TODO if we set these up to race against a cancel signal, we could actually drop the
         // futures
         self.canceled.try_set(true);
+        if let Some(optimistic) = self.take_optimistic() {
+            optimistic.settle(None);
+        }
+    }
+
+    fn take_optimistic(&self) -> Option<AppliedOptimistic<O>> {
+        self.optimistic.try_update_value(Option::take).flatten()
     }
 }
 
@@ -699,6 +776,7 @@ impl<I, O> Clone for ArcSubmission<I, O> {
             value: self.value.clone(),
             pending: self.pending.clone(),
             canceled: self.canceled.clone(),
+            optimistic: self.optimistic.clone(),
         }
     }
 }
@@ -718,6 +796,8 @@ where
     pending: RwSignal<bool>,
     /// Controls this submission has been canceled.
     canceled: RwSignal<bool>,
+    /// The optimistic update applied when this was dispatched, if it has not yet settled.
+    optimistic: StoredValue<Option<AppliedOptimistic<O>>>,
 }
 
 impl<I, O> From<ArcSubmission<I, O>> for Submission<I, O>
@@ -731,12 +811,14 @@ where
             value,
             pending,
             canceled,
+            optimistic,
         } = value;
         Self {
             input: input.into(),
             value: value.into(),
             pending: pending.into(),
             canceled: canceled.into(),
+            optimistic: optimistic.into(),
         }
     }
 }
@@ -752,12 +834,14 @@ where
             value,
             pending,
             canceled,
+            optimistic,
         } = value;
         Self {
             input: RwSignal::from_local(input),
             value: RwSignal::from_local(value),
             pending: pending.into(),
             canceled: canceled.into(),
+            optimistic: optimistic.into(),
         }
     }
 }
@@ -803,6 +887,11 @@ impl<I, O, S> Submission<I, O, S> {
     #[track_caller]
     pub fn cancel(&self) {
         self.canceled.try_set(true);
+        if let Some(optimistic) =
+            self.optimistic.try_update_value(Option::take).flatten()
+        {
+            optimistic.settle(None);
+        }
     }
 }
 
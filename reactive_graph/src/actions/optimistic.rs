@@ -0,0 +1,334 @@
+use crate::{
+    graph::{AnySource, ToAnySource},
+    traits::Update,
+};
+use or_poisoned::OrPoisoned;
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+};
+
+/// A change that has been applied optimistically while an action is pending, and that can be
+/// rolled back if the action fails.
+///
+/// An `Optimistic` is returned from the function registered with
+/// [`ArcAction::optimistic`](super::ArcAction::optimistic) or
+/// [`ArcMultiAction::optimistic`](super::ArcMultiAction::optimistic). It is committed if
+/// the action resolves to `Ok(_)`, and rolled back if it resolves to `Err(_)` or is aborted.
+///
+/// ```rust
+/// # use reactive_graph::actions::*;
+/// # use reactive_graph::prelude::*;
+/// # use reactive_graph::signal::ArcRwSignal;
+/// # tokio_test::block_on(async move {
+/// # any_spawner::Executor::init_tokio(); let owner = reactive_graph::owner::Owner::new(); owner.set();
+/// # let _guard = reactive_graph::diagnostics::SpecialNonReactiveZone::enter();
+/// let todos = ArcRwSignal::new(vec!["Buy milk".to_string()]);
+///
+/// let add_todo = ArcAction::new(|task: &String| {
+///     let task = task.clone();
+///     async move {
+///         if task.is_empty() {
+///             Err("empty task")
+///         } else {
+///             Ok(())
+///         }
+///     }
+/// });
+/// add_todo.optimistic({
+///     let todos = todos.clone();
+///     move |task: &String| {
+///         let task = task.clone();
+///         Optimistic::update(todos.clone(), move |todos| {
+///             todos.push(task.clone())
+///         })
+///     }
+/// });
+///
+/// // the new todo is shown immediately
+/// add_todo.dispatch(String::new());
+/// assert_eq!(todos.get().len(), 2);
+///
+/// // and removed again once the action has failed
+/// # any_spawner::Executor::tick().await;
+/// assert_eq!(todos.get().len(), 1);
+/// # });
+/// ```
+#[must_use = "an optimistic update does nothing unless it is returned to an \
+              action, committed, or rolled back"]
+pub struct Optimistic {
+    settle: Option<Box<dyn FnOnce(bool) + Send + Sync>>,
+}
+
+impl Optimistic {
+    /// Creates an optimistic update that runs the given function if it is rolled back.
+    ///
+    /// The optimistic change itself should already have been applied when this is called.
+    pub fn new(rollback: impl FnOnce() + Send + Sync + 'static) -> Self {
+        Self::from_settle(move |rolled_back| {
+            if rolled_back {
+                rollback();
+            }
+        })
+    }
+
+    fn from_settle(settle: impl FnOnce(bool) + Send + Sync + 'static) -> Self {
+        Self {
+            settle: Some(Box::new(settle)),
+        }
+    }
+
+    /// An optimistic update that does not change anything.
+    pub fn none() -> Self {
+        Self { settle: None }
+    }
+
+    /// Updates the value of a signal, and reverts the update if it is rolled back.
+    ///
+    /// Other optimistic updates to the same signal may still be pending when this one is
+    /// rolled back. To revert only this change, the signal is reset to the value it had before
+    /// any of the pending updates were applied (including the updates that have been committed
+    /// since), and the updates that are still pending are applied again in the order in which
+    /// they were made. This is why `fun` may be called more than once.
+    ///
+    /// Any other changes made to the signal while the update was pending are lost when it is
+    /// rolled back.
+    pub fn update<S>(
+        signal: S,
+        fun: impl Fn(&mut S::Value) + Send + Sync + 'static,
+    ) -> Self
+    where
+        S: Update + ToAnySource + Send + Sync + 'static,
+        S::Value: Clone + Send + Sync + 'static,
+    {
+        let source = signal.to_any_source();
+        let key = source.0;
+        let fun: Layer<S::Value> = Arc::new(fun);
+
+        let id = {
+            let mut pending = pending().lock().or_poisoned();
+            if !pending
+                .get(&key)
+                .is_some_and(|entry| entry.is_for::<S::Value>())
+            {
+                let Some(confirmed) =
+                    signal.try_maybe_update(|value| (false, value.clone()))
+                else {
+                    return Self::none();
+                };
+                pending.insert(
+                    key,
+                    PendingSource {
+                        source,
+                        layers: Box::new(Layers {
+                            confirmed,
+                            pending: Vec::new(),
+                        }),
+                    },
+                );
+            }
+            let Some(layers) = pending
+                .get_mut(&key)
+                .and_then(|entry| entry.layers_mut::<S::Value>())
+            else {
+                return Self::none();
+            };
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            layers.pending.push((id, Arc::clone(&fun)));
+            id
+        };
+        signal.try_update(|value| fun(value));
+
+        Self::from_settle(move |rolled_back| {
+            let rebased = {
+                let mut pending = pending().lock().or_poisoned();
+                let Some(layers) = pending
+                    .get_mut(&key)
+                    .and_then(|entry| entry.layers_mut::<S::Value>())
+                else {
+                    return;
+                };
+                let rebased = layers.settle(id, rolled_back);
+                if layers.pending.is_empty() {
+                    pending.remove(&key);
+                }
+                rebased
+            };
+            if let Some(rebased) = rebased {
+                signal.try_update(|value| *value = rebased);
+            }
+        })
+    }
+
+    /// Combines this optimistic update with another one, so that both are committed or rolled
+    /// back together.
+    pub fn and(mut self, mut other: Optimistic) -> Self {
+        match (self.settle.take(), other.settle.take()) {
+            (None, None) => Self::none(),
+            (Some(settle), None) | (None, Some(settle)) => Self {
+                settle: Some(settle),
+            },
+            (Some(first), Some(second)) => {
+                Self::from_settle(move |rolled_back| {
+                    // undo the changes in the reverse order to which they were made
+                    second(rolled_back);
+                    first(rolled_back);
+                })
+            }
+        }
+    }
+
+    /// Keeps the optimistic change.
+    pub fn commit(mut self) {
+        if let Some(settle) = self.settle.take() {
+            settle(false);
+        }
+    }
+
+    /// Reverts the optimistic change.
+    pub fn rollback(mut self) {
+        if let Some(settle) = self.settle.take() {
+            settle(true);
+        }
+    }
+}
+
+impl Drop for Optimistic {
+    // an update that is dropped without being settled is kept
+    fn drop(&mut self) {
+        if let Some(settle) = self.settle.take() {
+            settle(false);
+        }
+    }
+}
+
+impl Default for Optimistic {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl Debug for Optimistic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Optimistic")
+            .field("is_settled", &self.settle.is_none())
+            .finish()
+    }
+}
+
+/// The optimistic updates that are pending on each signal, keyed by the signal's address.
+static PENDING: OnceLock<Mutex<HashMap<usize, PendingSource>>> =
+    OnceLock::new();
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn pending() -> &'static Mutex<HashMap<usize, PendingSource>> {
+    PENDING.get_or_init(Default::default)
+}
+
+/// The optimistic updates that are pending on a signal, along with a weak reference to it.
+///
+/// The weak reference keeps the signal's allocation, and therefore its address, from being
+/// reused while the entry exists. An entry whose signal has been dropped is stale, and is
+/// replaced by the next update made at the same address.
+struct PendingSource {
+    source: AnySource,
+    layers: Box<dyn Any + Send + Sync>,
+}
+
+impl PendingSource {
+    /// Whether this entry belongs to a signal that is still alive, and holds a value of type `T`.
+    fn is_for<T: 'static>(&self) -> bool {
+        self.source.1.strong_count() > 0 && self.layers.is::<Layers<T>>()
+    }
+
+    fn layers_mut<T: 'static>(&mut self) -> Option<&mut Layers<T>> {
+        self.layers.downcast_mut()
+    }
+}
+
+/// The optimistic updates that are still pending on a single signal.
+struct Layers<T> {
+    /// The value of the signal without any of the pending updates.
+    confirmed: T,
+    pending: Vec<(usize, Layer<T>)>,
+}
+
+type Layer<T> = Arc<dyn Fn(&mut T) + Send + Sync>;
+
+impl<T: Clone> Layers<T> {
+    /// Removes an update from the pending ones. Returns the new value of the signal if it has
+    /// to be rebuilt because the update was rolled back.
+    fn settle(&mut self, id: usize, rolled_back: bool) -> Option<T> {
+        let index = self.pending.iter().position(|(other, _)| *other == id)?;
+        let (_, fun) = self.pending.remove(index);
+        if rolled_back {
+            let mut value = self.confirmed.clone();
+            for (_, fun) in &self.pending {
+                fun(&mut value);
+            }
+            Some(value)
+        } else {
+            fun(&mut self.confirmed);
+            None
+        }
+    }
+}
+
+/// The function registered to apply an optimistic update when an action is dispatched.
+pub(crate) struct OptimisticHandler<I, O> {
+    #[allow(clippy::type_complexity)]
+    apply: Arc<dyn Fn(&I) -> Optimistic + Send + Sync>,
+    is_err: fn(&O) -> bool,
+}
+
+impl<I, O> Clone for OptimisticHandler<I, O> {
+    fn clone(&self) -> Self {
+        Self {
+            apply: Arc::clone(&self.apply),
+            is_err: self.is_err,
+        }
+    }
+}
+
+impl<I, T, E> OptimisticHandler<I, Result<T, E>> {
+    pub(crate) fn new(
+        apply: impl Fn(&I) -> Optimistic + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            apply: Arc::new(apply),
+            is_err: Result::is_err,
+        }
+    }
+}
+
+impl<I, O> OptimisticHandler<I, O> {
+    pub(crate) fn apply(&self, input: &I) -> AppliedOptimistic<O> {
+        AppliedOptimistic {
+            update: (self.apply)(input),
+            is_err: self.is_err,
+        }
+    }
+}
+
+/// An optimistic update for a single dispatch, waiting for its result.
+pub(crate) struct AppliedOptimistic<O> {
+    update: Optimistic,
+    is_err: fn(&O) -> bool,
+}
+
+impl<O> AppliedOptimistic<O> {
+    /// Commits or rolls back the update, depending on the result of the action. `None` means
+    /// that the action was aborted.
+    pub(crate) fn settle(self, result: Option<&O>) {
+        match result {
+            Some(result) if !(self.is_err)(result) => self.update.commit(),
+            _ => self.update.rollback(),
+        }
+    }
+}
@@ -162,11 +162,14 @@ where
 
             MemoInner::new(Arc::new(fun), subscriber)
         });
-        Self {
+        let this = Self {
             #[cfg(any(debug_assertions, leptos_debuginfo))]
             defined_at: Location::caller(),
             inner,
-        }
+        };
+        #[cfg(feature = "devtools")]
+        crate::devtools::source_created(crate::devtools::NodeKind::Memo, &this);
+        this
     }
 }
 
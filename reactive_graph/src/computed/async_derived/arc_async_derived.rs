@@ -248,6 +248,11 @@ macro_rules! spawn_derived {
             inner: Arc::clone(&inner),
             loading: Arc::new(AtomicBool::new(!is_ready)),
         };
+        #[cfg(feature = "devtools")]
+        crate::devtools::source_created(
+            crate::devtools::NodeKind::AsyncDerived,
+            &this,
+        );
         let any_subscriber = this.to_any_subscriber();
         let initial_fut = if $should_track {
             owner.with_cleanup(|| {
@@ -424,6 +429,9 @@ impl<T: 'static> ArcAsyncDerived<T> {
     ) {
         loading.store(false, Ordering::Relaxed);
 
+        #[cfg(feature = "devtools")]
+        crate::devtools::notified(Arc::as_ptr(inner) as usize);
+
         let prev_state = mem::replace(
             &mut inner.write().or_poisoned().state,
             AsyncDerivedState::Notifying,
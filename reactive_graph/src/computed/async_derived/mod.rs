@@ -57,10 +57,13 @@ impl<Fut: Future> Future for ScopedFuture<Fut> {
 /// Utilities used to track whether asynchronous computeds are currently loading.
 pub mod suspense {
     use crate::{
+        computed::ArcMemo,
+        effect::Effect,
+        owner::Owner,
         signal::ArcRwSignal,
-        traits::{Update, Write},
+        traits::{Get, Update, With, Write},
     };
-    use futures::channel::oneshot::Sender;
+    use futures::channel::oneshot::{self, Sender};
     use or_poisoned::OrPoisoned;
     use slotmap::{DefaultKey, SlotMap};
     use std::sync::{Arc, Mutex};
@@ -103,6 +106,53 @@ pub mod suspense {
         }
     }
 
+    /// Keeps track of the suspense boundaries created beneath the owner that provides it via
+    /// context, so that it is possible to wait until all of them have finished loading.
+    #[derive(Clone, Debug, Default)]
+    pub struct SuspenseBoundaries(
+        ArcRwSignal<SlotMap<DefaultKey, ArcMemo<bool>>>,
+    );
+
+    impl SuspenseBoundaries {
+        /// Adds a boundary, given a memo that is `true` while none of its tasks are pending.
+        ///
+        /// The boundary is removed again when the current owner is cleaned up.
+        pub fn register(&self, none_pending: ArcMemo<bool>) {
+            let key = self.0.write().insert(none_pending);
+            let boundaries = self.0.clone();
+            Owner::on_cleanup(move || {
+                boundaries.update(|boundaries| {
+                    boundaries.remove(key);
+                });
+            });
+        }
+
+        /// Whether none of the boundaries have pending tasks. This is reactive.
+        pub fn all_resolved(&self) -> bool {
+            self.0
+                .with(|boundaries| boundaries.values().all(|memo| memo.get()))
+        }
+
+        /// Resolves once none of the boundaries have pending tasks.
+        pub async fn resolved(&self) {
+            let (tx, rx) = oneshot::channel();
+            let tx = Mutex::new(Some(tx));
+            let owner = Owner::new();
+            owner.with(|| {
+                let this = self.clone();
+                Effect::new_isomorphic(move |_| {
+                    if this.all_resolved() {
+                        if let Some(tx) = tx.lock().or_poisoned().take() {
+                            _ = tx.send(());
+                        }
+                    }
+                });
+            });
+            _ = rx.await;
+            owner.cleanup();
+        }
+    }
+
     /// A unique identifier that removes itself from the set of tasks when it is dropped.
     #[derive(Debug)]
     pub struct TaskHandle {
@@ -134,6 +134,8 @@ where
             if changed {
                 let subs = reactivity_lock.subscribers.clone();
                 drop(reactivity_lock);
+                #[cfg(feature = "devtools")]
+                crate::devtools::notified(any_subscriber.0);
                 for sub in subs {
                     // don't trigger reruns of effects/memos
                     // basically: if one of the observers has triggered this memo to
@@ -0,0 +1,590 @@
+//! Inspection of the reactive graph, for use by developer tools.
+//!
+//! When the `devtools` feature is enabled, the reactive system records the creation of
+//! reactive nodes and owners, the subscriptions between nodes, notifications sent by sources,
+//! the runs of effects, and the disposal of nodes and owners.
+//!
+//! This information can be consumed in two ways:
+//! 1. [`snapshot`] returns the current state of the graph and owner tree, which can be queried
+//!    to find out which sources a node depends on, which nodes depend on it, and where each of
+//!    them was defined.
+//! 2. [`events`] returns a stream of [`DevtoolsEvent`]s, which can be forwarded to a browser
+//!    extension, a terminal tool, or a log. (With the `serde` feature, all of these types can be
+//!    serialized.)
+//!
+//! Nodes and owners are identified by the same `usize` ids used by
+//! [`AnySource`], [`AnySubscriber`], and [`Owner::debug_id`]. These are not stable between runs.
+//!
+//! Owners are removed as soon as they are dropped. Disposed nodes are swept lazily, whenever a
+//! snapshot is taken and once enough nodes have been disposed by owners since the last sweep, so
+//! a [`DevtoolsEvent::NodeDisposed`] event can arrive some time after the node was disposed.
+//!
+//! ```rust
+//! # use reactive_graph::prelude::*;
+//! # use reactive_graph::{computed::ArcMemo, devtools, signal::ArcRwSignal};
+//! # let owner = reactive_graph::owner::Owner::new(); owner.set();
+//! # let _guard = reactive_graph::diagnostics::SpecialNonReactiveZone::enter();
+//! let count = ArcRwSignal::new(1);
+//! let double = ArcMemo::new({
+//!     let count = count.clone();
+//!     move |_| count.get() * 2
+//! });
+//! assert_eq!(double.get(), 2);
+//!
+//! let graph = devtools::snapshot();
+//! let memo = graph.node(devtools::node_id(&double)).unwrap();
+//! assert_eq!(memo.kind, devtools::NodeKind::Memo);
+//! assert_eq!(memo.sources, vec![devtools::node_id(&count)]);
+//! ```
+
+use crate::{
+    graph::{AnySource, AnySubscriber, Source, Subscriber, ToAnySource},
+    owner::Owner,
+    traits::DefinedAt,
+};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use or_poisoned::OrPoisoned;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    panic::Location,
+    sync::{Mutex, OnceLock, Weak},
+};
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+/// The smallest number of nodes that have to be disposed before the registry is swept, so that
+/// small graphs are not swept every time an owner is cleaned up.
+const MIN_SWEEP_THRESHOLD: usize = 256;
+
+/// The type of a reactive node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NodeKind {
+    /// A signal, i.e., a source of reactivity that holds a value.
+    Signal,
+    /// A trigger, i.e., a source of reactivity with no value.
+    Trigger,
+    /// A memo.
+    Memo,
+    /// An async derived signal.
+    AsyncDerived,
+    /// An effect.
+    Effect,
+    /// A render effect.
+    RenderEffect,
+    /// Any other node, which was only seen as a source or subscriber.
+    Other,
+}
+
+/// The location in the source code at which a node was defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SourceLocation {
+    /// The name of the source file.
+    pub file: &'static str,
+    /// The line number.
+    pub line: u32,
+    /// The column number.
+    pub column: u32,
+}
+
+impl From<&'static Location<'static>> for SourceLocation {
+    fn from(location: &'static Location<'static>) -> Self {
+        Self {
+            file: location.file(),
+            line: location.line(),
+            column: location.column(),
+        }
+    }
+}
+
+/// Something that has happened in the reactive graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DevtoolsEvent {
+    /// A reactive node has been created.
+    NodeCreated {
+        /// The id of the node.
+        id: usize,
+        /// The type of the node.
+        kind: NodeKind,
+        /// Where the node was defined.
+        defined_at: Option<SourceLocation>,
+        /// The owner that was current when the node was created.
+        owner: Option<usize>,
+    },
+    /// A subscriber has started tracking a source.
+    Subscribed {
+        /// The id of the source.
+        source: usize,
+        /// The id of the subscriber.
+        subscriber: usize,
+    },
+    /// A subscriber has stopped tracking a source.
+    Unsubscribed {
+        /// The id of the source.
+        source: usize,
+        /// The id of the subscriber.
+        subscriber: usize,
+    },
+    /// A source has notified its subscribers that it has changed.
+    Notified {
+        /// The id of the source.
+        source: usize,
+    },
+    /// An effect has run.
+    EffectRun {
+        /// The id of the effect.
+        id: usize,
+    },
+    /// A reactive node has been disposed.
+    NodeDisposed {
+        /// The id of the node.
+        id: usize,
+    },
+    /// An owner has been created.
+    OwnerCreated {
+        /// The id of the owner.
+        id: usize,
+        /// The id of its parent, if any.
+        parent: Option<usize>,
+    },
+    /// An owner has been disposed.
+    OwnerDisposed {
+        /// The id of the owner.
+        id: usize,
+    },
+}
+
+/// The state of a single reactive node, as part of a [`GraphSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NodeSnapshot {
+    /// The id of the node.
+    pub id: usize,
+    /// The type of the node.
+    pub kind: NodeKind,
+    /// Where the node was defined.
+    pub defined_at: Option<SourceLocation>,
+    /// The owner that was current when the node was created.
+    pub owner: Option<usize>,
+    /// The ids of the sources this node is subscribed to.
+    pub sources: Vec<usize>,
+    /// The ids of the subscribers to this node.
+    pub subscribers: Vec<usize>,
+    /// How many times this node has notified its subscribers.
+    pub notifications: usize,
+    /// How many times this node has run, if it is an effect.
+    pub runs: usize,
+}
+
+/// The state of a single owner, as part of a [`GraphSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OwnerSnapshot {
+    /// The id of the owner.
+    pub id: usize,
+    /// The id of its parent, if any.
+    pub parent: Option<usize>,
+    /// The ids of its children.
+    pub children: Vec<usize>,
+    /// The ids of the reactive nodes created while this was the current owner.
+    pub nodes: Vec<usize>,
+}
+
+/// The state of the reactive graph and owner tree at some point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GraphSnapshot {
+    /// All reactive nodes, ordered by id.
+    pub nodes: Vec<NodeSnapshot>,
+    /// All owners, ordered by id.
+    pub owners: Vec<OwnerSnapshot>,
+}
+
+impl GraphSnapshot {
+    /// Returns the node with the given id.
+    pub fn node(&self, id: usize) -> Option<&NodeSnapshot> {
+        self.nodes
+            .binary_search_by_key(&id, |node| node.id)
+            .ok()
+            .map(|idx| &self.nodes[idx])
+    }
+
+    /// Returns the owner with the given id.
+    pub fn owner(&self, id: usize) -> Option<&OwnerSnapshot> {
+        self.owners
+            .binary_search_by_key(&id, |owner| owner.id)
+            .ok()
+            .map(|idx| &self.owners[idx])
+    }
+
+    /// Returns the sources the node with the given id is subscribed to.
+    pub fn sources_of(&self, id: usize) -> Vec<&NodeSnapshot> {
+        self.node(id)
+            .map(|node| {
+                node.sources
+                    .iter()
+                    .filter_map(|id| self.node(*id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the subscribers to the node with the given id.
+    pub fn subscribers_of(&self, id: usize) -> Vec<&NodeSnapshot> {
+        self.node(id)
+            .map(|node| {
+                node.subscribers
+                    .iter()
+                    .filter_map(|id| self.node(*id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns all the nodes that were defined at the given line of the given file.
+    pub fn defined_at(&self, file: &str, line: u32) -> Vec<&NodeSnapshot> {
+        self.nodes
+            .iter()
+            .filter(|node| {
+                node.defined_at.is_some_and(|location| {
+                    location.file == file && location.line == line
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns a snapshot of the current state of the reactive graph and owner tree.
+pub fn snapshot() -> GraphSnapshot {
+    let mut registry = registry().lock().or_poisoned();
+    registry.collect();
+
+    let mut nodes = registry
+        .nodes
+        .iter()
+        .map(|(id, node)| {
+            let mut sources = node.sources.iter().copied().collect::<Vec<_>>();
+            sources.sort_unstable();
+            let mut subscribers =
+                node.subscribers.iter().copied().collect::<Vec<_>>();
+            subscribers.sort_unstable();
+            NodeSnapshot {
+                id: *id,
+                kind: node.kind,
+                defined_at: node.defined_at,
+                owner: node.owner,
+                sources,
+                subscribers,
+                notifications: node.notifications,
+                runs: node.runs,
+            }
+        })
+        .collect::<Vec<_>>();
+    nodes.sort_unstable_by_key(|node| node.id);
+
+    let mut owners = registry
+        .owners
+        .iter()
+        .map(|(id, owner)| {
+            let mut children = registry
+                .owners
+                .iter()
+                .filter(|(_, child)| child.parent == Some(*id))
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+            children.sort_unstable();
+            let mut nodes = registry
+                .nodes
+                .iter()
+                .filter(|(_, node)| node.owner == Some(*id))
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+            nodes.sort_unstable();
+            OwnerSnapshot {
+                id: *id,
+                parent: owner.parent,
+                children,
+                nodes,
+            }
+        })
+        .collect::<Vec<_>>();
+    owners.sort_unstable_by_key(|owner| owner.id);
+
+    GraphSnapshot { nodes, owners }
+}
+
+/// Returns the id used to identify a reactive source in snapshots and events.
+pub fn node_id(source: &impl ToAnySource) -> usize {
+    source.to_any_source().0
+}
+
+/// Returns a stream of all the events that happen in the reactive graph from now on.
+///
+/// Events are buffered until they are read, so the stream should be consumed (or dropped)
+/// promptly.
+pub fn events() -> UnboundedReceiver<DevtoolsEvent> {
+    let (tx, rx) = unbounded();
+    registry().lock().or_poisoned().listeners.push(tx);
+    rx
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    REGISTRY.get_or_init(Default::default)
+}
+
+#[derive(Default)]
+struct Registry {
+    nodes: FxHashMap<usize, NodeEntry>,
+    owners: FxHashMap<usize, OwnerEntry>,
+    listeners: Vec<UnboundedSender<DevtoolsEvent>>,
+    /// The number of nodes that owners have disposed since the last sweep.
+    disposed_since_sweep: usize,
+}
+
+struct NodeEntry {
+    kind: NodeKind,
+    defined_at: Option<SourceLocation>,
+    owner: Option<usize>,
+    sources: FxHashSet<usize>,
+    subscribers: FxHashSet<usize>,
+    notifications: usize,
+    runs: usize,
+    node: WeakNode,
+}
+
+enum WeakNode {
+    Source(Weak<dyn Source + Send + Sync>),
+    Subscriber(Weak<dyn Subscriber + Send + Sync>),
+}
+
+impl WeakNode {
+    fn is_alive(&self) -> bool {
+        match self {
+            WeakNode::Source(weak) => weak.strong_count() > 0,
+            WeakNode::Subscriber(weak) => weak.strong_count() > 0,
+        }
+    }
+}
+
+struct OwnerEntry {
+    parent: Option<usize>,
+}
+
+impl Registry {
+    fn emit(&mut self, event: DevtoolsEvent) {
+        self.listeners
+            .retain(|tx| tx.unbounded_send(event.clone()).is_ok());
+    }
+
+    fn insert_node(
+        &mut self,
+        id: usize,
+        kind: NodeKind,
+        defined_at: Option<&'static Location<'static>>,
+        node: WeakNode,
+    ) {
+        // ids are addresses, so a new node can reuse the id of one that has been dropped
+        if self.nodes.contains_key(&id) {
+            self.remove_node(id);
+        }
+        let defined_at = defined_at.map(SourceLocation::from);
+        let owner = Owner::current().map(|owner| owner.debug_id());
+        self.nodes.insert(
+            id,
+            NodeEntry {
+                kind,
+                defined_at,
+                owner,
+                sources: Default::default(),
+                subscribers: Default::default(),
+                notifications: 0,
+                runs: 0,
+                node,
+            },
+        );
+        self.emit(DevtoolsEvent::NodeCreated {
+            id,
+            kind,
+            defined_at,
+            owner,
+        });
+    }
+
+    fn remove_node(&mut self, id: usize) {
+        if let Some(node) = self.nodes.remove(&id) {
+            for source in node.sources {
+                if let Some(source) = self.nodes.get_mut(&source) {
+                    source.subscribers.remove(&id);
+                }
+            }
+            for subscriber in node.subscribers {
+                if let Some(subscriber) = self.nodes.get_mut(&subscriber) {
+                    subscriber.sources.remove(&id);
+                }
+            }
+            self.emit(DevtoolsEvent::NodeDisposed { id });
+        }
+    }
+
+    fn collect(&mut self) {
+        self.disposed_since_sweep = 0;
+        let dead_nodes = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| !node.node.is_alive())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in dead_nodes {
+            self.remove_node(id);
+        }
+    }
+}
+
+fn with_registry(fun: impl FnOnce(&mut Registry)) {
+    fun(&mut registry().lock().or_poisoned());
+}
+
+pub(crate) fn source_created(
+    kind: NodeKind,
+    source: &(impl ToAnySource + DefinedAt),
+) {
+    let AnySource(id, weak, ..) = source.to_any_source();
+    with_registry(|registry| {
+        registry.insert_node(
+            id,
+            kind,
+            source.defined_at(),
+            WeakNode::Source(weak),
+        )
+    });
+}
+
+pub(crate) fn subscriber_created(
+    kind: NodeKind,
+    subscriber: &AnySubscriber,
+    defined_at: Option<&'static Location<'static>>,
+) {
+    with_registry(|registry| {
+        registry.insert_node(
+            subscriber.0,
+            kind,
+            defined_at,
+            WeakNode::Subscriber(subscriber.1.clone()),
+        )
+    });
+}
+
+pub(crate) fn subscribed(source: &AnySource, subscriber: &AnySubscriber) {
+    with_registry(|registry| {
+        // nodes that were not created by this crate are only seen when they are tracked
+        if !registry.nodes.contains_key(&source.0) {
+            registry.insert_node(
+                source.0,
+                NodeKind::Other,
+                source.defined_at(),
+                WeakNode::Source(source.1.clone()),
+            );
+        }
+        if !registry.nodes.contains_key(&subscriber.0) {
+            registry.insert_node(
+                subscriber.0,
+                NodeKind::Other,
+                None,
+                WeakNode::Subscriber(subscriber.1.clone()),
+            );
+        }
+
+        let is_new = registry
+            .nodes
+            .get_mut(&source.0)
+            .map(|node| node.subscribers.insert(subscriber.0))
+            .unwrap_or(false);
+        if let Some(node) = registry.nodes.get_mut(&subscriber.0) {
+            node.sources.insert(source.0);
+        }
+        if is_new {
+            registry.emit(DevtoolsEvent::Subscribed {
+                source: source.0,
+                subscriber: subscriber.0,
+            });
+        }
+    });
+}
+
+pub(crate) fn unsubscribed(source: &AnySource, subscriber: &AnySubscriber) {
+    with_registry(|registry| {
+        let was_subscribed = registry
+            .nodes
+            .get_mut(&source.0)
+            .map(|node| node.subscribers.remove(&subscriber.0))
+            .unwrap_or(false);
+        if let Some(node) = registry.nodes.get_mut(&subscriber.0) {
+            node.sources.remove(&source.0);
+        }
+        if was_subscribed {
+            registry.emit(DevtoolsEvent::Unsubscribed {
+                source: source.0,
+                subscriber: subscriber.0,
+            });
+        }
+    });
+}
+
+pub(crate) fn notified(source: usize) {
+    with_registry(|registry| {
+        if let Some(node) = registry.nodes.get_mut(&source) {
+            node.notifications += 1;
+        }
+        registry.emit(DevtoolsEvent::Notified { source });
+    });
+}
+
+pub(crate) fn effect_run(effect: &AnySubscriber) {
+    with_registry(|registry| {
+        if let Some(node) = registry.nodes.get_mut(&effect.0) {
+            node.runs += 1;
+        }
+        registry.emit(DevtoolsEvent::EffectRun { id: effect.0 });
+    });
+}
+
+pub(crate) fn owner_created(owner: &Owner) {
+    let id = owner.debug_id();
+    let parent = {
+        let mut inner = owner.inner.write().or_poisoned();
+        inner.debug_id = id;
+        inner.parent.clone()
+    };
+    let parent = parent
+        .filter(|parent| parent.strong_count() > 0)
+        .map(|parent| parent.as_ptr() as usize);
+    with_registry(|registry| {
+        registry.owners.insert(id, OwnerEntry { parent });
+        registry.emit(DevtoolsEvent::OwnerCreated { id, parent });
+    });
+}
+
+pub(crate) fn owner_dropped(id: usize) {
+    with_registry(|registry| {
+        if registry.owners.remove(&id).is_some() {
+            registry.emit(DevtoolsEvent::OwnerDisposed { id });
+        }
+    });
+}
+
+/// Records that an owner has disposed of some nodes, and sweeps the registry for dropped nodes
+/// once enough of them have piled up.
+///
+/// Sweeping checks every node, so it only happens once the number of disposed nodes is
+/// proportional to the size of the registry.
+pub(crate) fn nodes_disposed(count: usize) {
+    with_registry(|registry| {
+        registry.disposed_since_sweep += count;
+        let threshold = (registry.nodes.len() / 2).max(MIN_SWEEP_THRESHOLD);
+        if registry.disposed_since_sweep >= threshold {
+            registry.collect();
+        }
+    });
+}
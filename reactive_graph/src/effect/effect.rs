@@ -13,6 +13,7 @@ use futures::StreamExt;
 use or_poisoned::OrPoisoned;
 use std::{
     mem,
+    panic::Location,
     sync::{atomic::AtomicBool, Arc, RwLock},
 };
 
@@ -92,7 +93,10 @@ impl<S> Dispose for Effect<S> {
     }
 }
 
-fn effect_base() -> (Receiver, Owner, Arc<RwLock<EffectInner>>) {
+#[cfg_attr(not(feature = "devtools"), allow(unused_variables))]
+fn effect_base(
+    defined_at: &'static Location<'static>,
+) -> (Receiver, Owner, Arc<RwLock<EffectInner>>) {
     let (mut observer, rx) = channel();
 
     // spawn the effect asynchronously
@@ -107,6 +111,13 @@ fn effect_base() -> (Receiver, Owner, Arc<RwLock<EffectInner>>) {
         sources: SourceSet::new(),
     }));
 
+    #[cfg(feature = "devtools")]
+    crate::devtools::subscriber_created(
+        crate::devtools::NodeKind::Effect,
+        &inner.to_any_subscriber(),
+        Some(defined_at),
+    );
+
     (rx, owner, inner)
 }
 
@@ -155,12 +166,14 @@ impl Effect<LocalStorage> {
     /// This spawns a task on the local thread using
     /// [`spawn_local`](any_spawner::Executor::spawn_local). For an effect that can be spawned on
     /// any thread, use [`new_sync`](Effect::new_sync).
+    #[track_caller]
     pub fn new<T, M>(mut fun: impl EffectFunction<T, M> + 'static) -> Self
     where
         T: 'static,
     {
+        let defined_at = Location::caller();
         let inner = cfg!(feature = "effects").then(|| {
-            let (mut rx, owner, inner) = effect_base();
+            let (mut rx, owner, inner) = effect_base(defined_at);
             let value = Arc::new(RwLock::new(None::<T>));
             let mut first_run = true;
 
@@ -177,6 +190,8 @@ impl Effect<LocalStorage> {
                         {
                             first_run = false;
                             subscriber.clear_sources(&subscriber);
+                            #[cfg(feature = "devtools")]
+                            crate::devtools::effect_run(&subscriber);
 
                             let old_value =
                                 mem::take(&mut *value.write().or_poisoned());
@@ -300,6 +315,7 @@ impl Effect<LocalStorage> {
     /// # }).await;
     /// # });
     /// ```
+    #[track_caller]
     pub fn watch<D, T>(
         mut dependency_fn: impl FnMut() -> D + 'static,
         mut handler: impl FnMut(&D, Option<&D>, Option<T>) -> T + 'static,
@@ -309,8 +325,9 @@ impl Effect<LocalStorage> {
         D: 'static,
         T: 'static,
     {
+        let defined_at = Location::caller();
         let inner = cfg!(feature = "effects").then(|| {
-            let (mut rx, owner, inner) = effect_base();
+            let (mut rx, owner, inner) = effect_base(defined_at);
             let mut first_run = true;
             let dep_value = Arc::new(RwLock::new(None::<D>));
             let watch_value = Arc::new(RwLock::new(None::<T>));
@@ -328,6 +345,8 @@ impl Effect<LocalStorage> {
                             }) || first_run)
                         {
                             subscriber.clear_sources(&subscriber);
+                            #[cfg(feature = "devtools")]
+                            crate::devtools::effect_run(&subscriber);
 
                             let old_dep_value = mem::take(
                                 &mut *dep_value.write().or_poisoned(),
@@ -373,14 +392,16 @@ impl Effect<SyncStorage> {
     ///
     /// This spawns a task that can be run on any thread. For an effect that will be spawned on
     /// the current thread, use [`new`](Effect::new).
+    #[track_caller]
     pub fn new_sync<T, M>(
         mut fun: impl EffectFunction<T, M> + Send + Sync + 'static,
     ) -> Self
     where
         T: Send + Sync + 'static,
     {
+        let defined_at = Location::caller();
         let inner = cfg!(feature = "effects").then(|| {
-            let (mut rx, owner, inner) = effect_base();
+            let (mut rx, owner, inner) = effect_base(defined_at);
             let mut first_run = true;
             let value = Arc::new(RwLock::new(None::<T>));
 
@@ -397,6 +418,8 @@ impl Effect<SyncStorage> {
                         {
                             first_run = false;
                             subscriber.clear_sources(&subscriber);
+                            #[cfg(feature = "devtools")]
+                            crate::devtools::effect_run(&subscriber);
 
                             let old_value =
                                 mem::take(&mut *value.write().or_poisoned());
@@ -421,13 +444,15 @@ impl Effect<SyncStorage> {
     /// that are read inside it change.
     ///
     /// This will run whether the `effects` feature is enabled or not.
+    #[track_caller]
     pub fn new_isomorphic<T, M>(
         mut fun: impl EffectFunction<T, M> + Send + Sync + 'static,
     ) -> Self
     where
         T: Send + Sync + 'static,
     {
-        let (mut rx, owner, inner) = effect_base();
+        let defined_at = Location::caller();
+        let (mut rx, owner, inner) = effect_base(defined_at);
         let mut first_run = true;
         let value = Arc::new(RwLock::new(None::<T>));
 
@@ -444,6 +469,8 @@ impl Effect<SyncStorage> {
                     {
                         first_run = false;
                         subscriber.clear_sources(&subscriber);
+                        #[cfg(feature = "devtools")]
+                        crate::devtools::effect_run(&subscriber);
 
                         let old_value =
                             mem::take(&mut *value.write().or_poisoned());
@@ -466,6 +493,7 @@ impl Effect<SyncStorage> {
     }
 
     /// This is to [`Effect::watch`] what [`Effect::new_sync`] is to [`Effect::new`].
+    #[track_caller]
     pub fn watch_sync<D, T>(
         mut dependency_fn: impl FnMut() -> D + Send + Sync + 'static,
         mut handler: impl FnMut(&D, Option<&D>, Option<T>) -> T
@@ -478,7 +506,8 @@ impl Effect<SyncStorage> {
         D: Send + Sync + 'static,
         T: Send + Sync + 'static,
     {
-        let (mut rx, owner, inner) = effect_base();
+        let defined_at = Location::caller();
+        let (mut rx, owner, inner) = effect_base(defined_at);
         let mut first_run = true;
         let dep_value = Arc::new(RwLock::new(None::<D>));
         let watch_value = Arc::new(RwLock::new(None::<T>));
@@ -497,6 +526,8 @@ impl Effect<SyncStorage> {
                             }) || first_run)
                         {
                             subscriber.clear_sources(&subscriber);
+                            #[cfg(feature = "devtools")]
+                            crate::devtools::effect_run(&subscriber);
 
                             let old_dep_value = mem::take(
                                 &mut *dep_value.write().or_poisoned(),
@@ -13,6 +13,7 @@ use or_poisoned::OrPoisoned;
 use std::{
     fmt::Debug,
     mem,
+    panic::Location,
     sync::{Arc, RwLock, Weak},
 };
 
@@ -53,18 +54,22 @@ where
     T: 'static,
 {
     /// Creates a new render effect, which immediately runs `fun`.
+    #[track_caller]
     pub fn new(fun: impl FnMut(Option<T>) -> T + 'static) -> Self {
         Self::new_with_value(fun, None)
     }
 
     /// Creates a new render effect with an initial value.
+    #[track_caller]
     pub fn new_with_value(
         fun: impl FnMut(Option<T>) -> T + 'static,
         initial_value: Option<T>,
     ) -> Self {
+        #[cfg_attr(not(feature = "devtools"), allow(unused_variables))]
         fn erased<T>(
             mut fun: Box<dyn FnMut(Option<T>) -> T + 'static>,
             initial_value: Option<T>,
+            defined_at: &'static Location<'static>,
         ) -> RenderEffect<T> {
             let (observer, mut rx) = channel();
             let value = Arc::new(RwLock::new(None::<T>));
@@ -74,8 +79,16 @@ where
                 observer,
                 sources: SourceSet::new(),
             }));
+            #[cfg(feature = "devtools")]
+            crate::devtools::subscriber_created(
+                crate::devtools::NodeKind::RenderEffect,
+                &inner.to_any_subscriber(),
+                Some(defined_at),
+            );
 
             let initial_value = cfg!(feature = "effects").then(|| {
+                #[cfg(feature = "devtools")]
+                crate::devtools::effect_run(&inner.to_any_subscriber());
                 owner.with(|| {
                     inner
                         .to_any_subscriber()
@@ -97,6 +110,8 @@ where
                                 })
                             {
                                 subscriber.clear_sources(&subscriber);
+                                #[cfg(feature = "devtools")]
+                                crate::devtools::effect_run(&subscriber);
 
                                 let old_value = mem::take(
                                     &mut *value.write().or_poisoned(),
@@ -114,7 +129,7 @@ where
             RenderEffect { value, inner }
         }
 
-        erased(Box::new(fun), initial_value)
+        erased(Box::new(fun), initial_value, Location::caller())
     }
 
     /// Mutably accesses the current value.
@@ -136,11 +151,14 @@ where
     T: Send + Sync + 'static,
 {
     /// Creates a render effect that will run whether the `effects` feature is enabled or not.
+    #[track_caller]
     pub fn new_isomorphic(
         fun: impl FnMut(Option<T>) -> T + Send + Sync + 'static,
     ) -> Self {
+        #[cfg_attr(not(feature = "devtools"), allow(unused_variables))]
         fn erased<T: Send + Sync + 'static>(
             mut fun: Box<dyn FnMut(Option<T>) -> T + Send + Sync + 'static>,
+            defined_at: &'static Location<'static>,
         ) -> RenderEffect<T> {
             let (observer, mut rx) = channel();
             let value = Arc::new(RwLock::new(None::<T>));
@@ -150,7 +168,15 @@ where
                 observer,
                 sources: SourceSet::new(),
             }));
-
+            #[cfg(feature = "devtools")]
+            crate::devtools::subscriber_created(
+                crate::devtools::NodeKind::RenderEffect,
+                &inner.to_any_subscriber(),
+                Some(defined_at),
+            );
+
+            #[cfg(feature = "devtools")]
+            crate::devtools::effect_run(&inner.to_any_subscriber());
             let initial_value = owner
                 .with(|| inner.to_any_subscriber().with_observer(|| fun(None)));
             *value.write().or_poisoned() = Some(initial_value);
@@ -167,6 +193,8 @@ where
                             })
                         {
                             subscriber.clear_sources(&subscriber);
+                            #[cfg(feature = "devtools")]
+                            crate::devtools::effect_run(&subscriber);
 
                             let old_value =
                                 mem::take(&mut *value.write().or_poisoned());
@@ -182,7 +210,7 @@ where
             RenderEffect { value, inner }
         }
 
-        erased(Box::new(fun))
+        erased(Box::new(fun), Location::caller())
     }
 }
 
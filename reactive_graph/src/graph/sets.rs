@@ -37,6 +37,8 @@ impl SourceSet {
 
     pub fn clear_sources(&mut self, subscriber: &AnySubscriber) {
         for source in self.take() {
+            #[cfg(feature = "devtools")]
+            crate::devtools::unsubscribed(&source, subscriber);
             source.remove_subscriber(subscriber);
         }
     }
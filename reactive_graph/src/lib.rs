@@ -77,6 +77,8 @@ use std::{fmt::Arguments, future::Future};
 pub mod actions;
 pub(crate) mod channel;
 pub mod computed;
+#[cfg(feature = "devtools")]
+pub mod devtools;
 pub mod diagnostics;
 pub mod effect;
 pub mod graph;
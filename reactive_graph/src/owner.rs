@@ -131,6 +131,8 @@ impl Owner {
                     .map(|parent| parent.read().or_poisoned().arena.clone())
                     .unwrap_or_default(),
                 paused: false,
+                #[cfg(feature = "devtools")]
+                debug_id: 0,
             })),
             #[cfg(feature = "hydration")]
             shared_context,
@@ -142,6 +144,8 @@ impl Owner {
                 .children
                 .push(Arc::downgrade(&this.inner));
         }
+        #[cfg(feature = "devtools")]
+        crate::devtools::owner_created(&this);
         this
     }
 
@@ -165,10 +169,14 @@ impl Owner {
                 #[cfg(feature = "sandboxed-arenas")]
                 arena: Default::default(),
                 paused: false,
+                #[cfg(feature = "devtools")]
+                debug_id: 0,
             })),
             #[cfg(feature = "hydration")]
             shared_context,
         };
+        #[cfg(feature = "devtools")]
+        crate::devtools::owner_created(&this);
         this.set();
         this
     }
@@ -190,11 +198,15 @@ impl Owner {
                 #[cfg(feature = "sandboxed-arenas")]
                 arena,
                 paused,
+                #[cfg(feature = "devtools")]
+                debug_id: 0,
             })),
             #[cfg(feature = "hydration")]
             shared_context: self.shared_context.clone(),
         };
         inner.children.push(Arc::downgrade(&child.inner));
+        #[cfg(feature = "devtools")]
+        crate::devtools::owner_created(&child);
         child
     }
 
@@ -406,6 +418,8 @@ pub(crate) struct OwnerInner {
     #[cfg(feature = "sandboxed-arenas")]
     arena: Arc<RwLock<ArenaMap>>,
     paused: bool,
+    #[cfg(feature = "devtools")]
+    pub debug_id: usize,
 }
 
 impl Debug for OwnerInner {
@@ -433,6 +447,8 @@ impl Drop for OwnerInner {
 
         let nodes = mem::take(&mut self.nodes);
         if !nodes.is_empty() {
+            #[cfg(feature = "devtools")]
+            let count = nodes.len();
             #[cfg(not(feature = "sandboxed-arenas"))]
             Arena::with_mut(|arena| {
                 for node in nodes {
@@ -446,7 +462,12 @@ impl Drop for OwnerInner {
                     _ = arena.remove(node);
                 }
             }
+            #[cfg(feature = "devtools")]
+            crate::devtools::nodes_disposed(count);
         }
+
+        #[cfg(feature = "devtools")]
+        crate::devtools::owner_dropped(self.debug_id);
     }
 }
 
@@ -474,6 +495,8 @@ impl Cleanup for RwLock<OwnerInner> {
         }
 
         if !nodes.is_empty() {
+            #[cfg(feature = "devtools")]
+            let count = nodes.len();
             #[cfg(not(feature = "sandboxed-arenas"))]
             Arena::with_mut(|arena| {
                 for node in nodes {
@@ -488,6 +511,9 @@ impl Cleanup for RwLock<OwnerInner> {
                     _ = arena.remove(node);
                 }
             }
+
+            #[cfg(feature = "devtools")]
+            crate::devtools::nodes_disposed(count);
         }
     }
 }
@@ -153,12 +153,18 @@ impl<T> ArcRwSignal<T> {
     )]
     #[track_caller]
     pub fn new(value: T) -> Self {
-        Self {
+        let this = Self {
             #[cfg(any(debug_assertions, leptos_debuginfo))]
             defined_at: Location::caller(),
             value: Arc::new(RwLock::new(value)),
             inner: Arc::new(RwLock::new(SubscriberSet::new())),
-        }
+        };
+        #[cfg(feature = "devtools")]
+        crate::devtools::source_created(
+            crate::devtools::NodeKind::Signal,
+            &this,
+        );
+        this
     }
 
     /// Returns a read-only handle to the signal.
@@ -22,11 +22,17 @@ impl ArcTrigger {
     /// Creates a new trigger.
     #[track_caller]
     pub fn new() -> Self {
-        Self {
+        let this = Self {
             #[cfg(any(debug_assertions, leptos_debuginfo))]
             defined_at: Location::caller(),
             inner: Default::default(),
-        }
+        };
+        #[cfg(feature = "devtools")]
+        crate::devtools::source_created(
+            crate::devtools::NodeKind::Trigger,
+            &this,
+        );
+        this
     }
 }
 
@@ -115,6 +115,8 @@ impl<T: Source + ToAnySource + DefinedAt> Track for T {
         }
 
         if let Some(subscriber) = Observer::get() {
+            #[cfg(feature = "devtools")]
+            crate::devtools::subscribed(&self.to_any_source(), &subscriber);
             subscriber.add_source(self.to_any_source());
             self.add_subscriber(subscriber);
         } else {
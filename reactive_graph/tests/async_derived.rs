@@ -137,3 +137,44 @@ async fn async_derived_with_initial() {
     signal2.set(1);
     assert_eq!(derived.await, 2);
 }
+
+#[tokio::test]
+async fn suspense_boundaries_resolve_once_none_pending() {
+    use reactive_graph::{
+        computed::{suspense::SuspenseBoundaries, ArcMemo},
+        signal::ArcRwSignal,
+    };
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    _ = Executor::init_tokio();
+    let owner = Owner::new();
+    owner.set();
+
+    let boundaries = SuspenseBoundaries::default();
+    let none_pending = ArcRwSignal::new(false);
+    boundaries.register(ArcMemo::new({
+        let none_pending = none_pending.clone();
+        move |_| none_pending.get()
+    }));
+    assert!(!boundaries.all_resolved());
+
+    let resolved = Arc::new(AtomicBool::new(false));
+    Executor::spawn({
+        let boundaries = boundaries.clone();
+        let resolved = Arc::clone(&resolved);
+        async move {
+            boundaries.resolved().await;
+            resolved.store(true, Ordering::Relaxed);
+        }
+    });
+    Executor::tick().await;
+    assert!(!resolved.load(Ordering::Relaxed));
+
+    none_pending.set(true);
+    Executor::tick().await;
+    Executor::tick().await;
+    assert!(resolved.load(Ordering::Relaxed));
+}
@@ -0,0 +1,115 @@
+#[cfg(all(feature = "devtools", feature = "effects"))]
+pub mod imports {
+    pub use any_spawner::Executor;
+    pub use futures::StreamExt;
+    pub use reactive_graph::{
+        devtools::{self, DevtoolsEvent, NodeKind},
+        effect::Effect,
+        graph::ToAnySubscriber,
+        owner::Owner,
+        prelude::*,
+        signal::{ArcRwSignal, RwSignal},
+    };
+    pub use tokio::task;
+}
+
+#[cfg(all(feature = "devtools", feature = "effects"))]
+#[tokio::test]
+async fn records_effect_runs_and_notifications() {
+    use imports::*;
+
+    _ = Executor::init_tokio();
+    let owner = Owner::new();
+    owner.set();
+    task::LocalSet::new()
+        .run_until(async {
+            let a = RwSignal::new(0);
+            let effect = Effect::new(move |_| {
+                a.get();
+            });
+            let effect_id = effect.to_any_subscriber().0;
+            let signal_id = devtools::node_id(&a);
+
+            Executor::tick().await;
+            a.set(1);
+            Executor::tick().await;
+
+            let graph = devtools::snapshot();
+            let effect = graph.node(effect_id).unwrap();
+            assert_eq!(effect.kind, NodeKind::Effect);
+            assert_eq!(effect.runs, 2);
+            assert_eq!(effect.sources, vec![signal_id]);
+            assert!(effect.defined_at.is_some());
+
+            let signal = graph.node(signal_id).unwrap();
+            assert_eq!(signal.kind, NodeKind::Signal);
+            assert_eq!(signal.notifications, 1);
+            assert_eq!(signal.subscribers, vec![effect_id]);
+            assert_eq!(signal.owner, Some(owner.debug_id()));
+            assert!(graph
+                .owner(owner.debug_id())
+                .unwrap()
+                .nodes
+                .contains(&signal_id));
+        })
+        .await;
+}
+
+#[cfg(all(feature = "devtools", feature = "effects"))]
+#[tokio::test]
+async fn streams_creation_and_disposal() {
+    use imports::*;
+
+    let mut events = devtools::events();
+
+    let signal = ArcRwSignal::new(0);
+    let id = devtools::node_id(&signal);
+    drop(signal);
+    assert!(devtools::snapshot().node(id).is_none());
+
+    let mut created = false;
+    while let Some(event) = events.next().await {
+        match event {
+            DevtoolsEvent::NodeCreated {
+                id: created_id,
+                kind,
+                ..
+            } if created_id == id => {
+                assert_eq!(kind, NodeKind::Signal);
+                created = true;
+            }
+            DevtoolsEvent::NodeDisposed { id: disposed_id }
+                if disposed_id == id =>
+            {
+                assert!(created);
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(all(feature = "devtools", feature = "effects"))]
+#[tokio::test]
+async fn removes_dropped_owners_and_their_nodes() {
+    use imports::*;
+
+    let owner = Owner::new();
+    let child = owner.child();
+    let owner_id = owner.debug_id();
+    let child_id = child.debug_id();
+    let signal = child.with(|| RwSignal::new(0));
+    let signal_id = devtools::node_id(&signal);
+
+    let graph = devtools::snapshot();
+    assert_eq!(graph.owner(owner_id).unwrap().children, vec![child_id]);
+    assert_eq!(graph.node(signal_id).unwrap().owner, Some(child_id));
+
+    drop(child);
+    drop(owner);
+
+    let graph = devtools::snapshot();
+    assert!(graph.owner(owner_id).is_none());
+    assert!(graph.owner(child_id).is_none());
+    assert!(graph.node(signal_id).is_none());
+}
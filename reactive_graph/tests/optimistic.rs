@@ -0,0 +1,194 @@
+use any_spawner::Executor;
+use futures::channel::oneshot;
+use reactive_graph::{
+    actions::{ArcAction, Optimistic},
+    owner::Owner,
+    signal::{ArcRwSignal, RwSignal},
+    traits::Get,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+type Results = Arc<
+    Mutex<
+        HashMap<&'static str, (oneshot::Receiver<bool>, oneshot::Sender<()>)>,
+    >,
+>;
+
+/// An action that adds a todo optimistically, and only resolves once the test has decided
+/// whether it succeeds.
+struct AddTodo {
+    action: ArcAction<&'static str, Result<(), ()>>,
+    todos: ArcRwSignal<Vec<&'static str>>,
+    results: Results,
+    senders:
+        HashMap<&'static str, (oneshot::Sender<bool>, oneshot::Receiver<()>)>,
+}
+
+impl AddTodo {
+    fn new() -> Self {
+        let todos = ArcRwSignal::new(Vec::new());
+        let results = Results::default();
+        let action = ArcAction::new({
+            let results = Arc::clone(&results);
+            move |task: &&'static str| {
+                let (result, done) =
+                    results.lock().unwrap().remove(task).unwrap();
+                async move {
+                    let succeeds = result.await.unwrap();
+                    // the update is settled as soon as this future resolves,
+                    // without yielding to the test again
+                    done.send(()).unwrap();
+                    if succeeds {
+                        Ok(())
+                    } else {
+                        Err(())
+                    }
+                }
+            }
+        });
+        action.optimistic({
+            let todos = todos.clone();
+            move |task: &&'static str| {
+                let task = *task;
+                Optimistic::update(todos.clone(), move |todos| todos.push(task))
+            }
+        });
+        Self {
+            action,
+            todos,
+            results,
+            senders: HashMap::new(),
+        }
+    }
+
+    fn dispatch(&mut self, task: &'static str) {
+        let (result_tx, result_rx) = oneshot::channel();
+        let (done_tx, done_rx) = oneshot::channel();
+        self.results
+            .lock()
+            .unwrap()
+            .insert(task, (result_rx, done_tx));
+        self.senders.insert(task, (result_tx, done_rx));
+        self.action.dispatch(task);
+    }
+
+    async fn resolve(&mut self, task: &'static str, succeeds: bool) {
+        let (result, done) = self.senders.remove(task).unwrap();
+        result.send(succeeds).unwrap();
+        done.await.unwrap();
+        // let the action's task run to completion
+        Executor::tick().await;
+    }
+
+    fn todos(&self) -> Vec<&'static str> {
+        self.todos.get()
+    }
+}
+
+#[tokio::test]
+async fn keeps_update_when_action_succeeds() {
+    _ = Executor::init_tokio();
+    let owner = Owner::new();
+    owner.set();
+
+    let mut add_todo = AddTodo::new();
+    add_todo.dispatch("a");
+    assert_eq!(add_todo.todos(), ["a"]);
+
+    add_todo.resolve("a", true).await;
+    assert_eq!(add_todo.todos(), ["a"]);
+}
+
+#[tokio::test]
+async fn rolls_back_update_when_action_fails() {
+    _ = Executor::init_tokio();
+    let owner = Owner::new();
+    owner.set();
+
+    let mut add_todo = AddTodo::new();
+    add_todo.dispatch("a");
+    assert_eq!(add_todo.todos(), ["a"]);
+
+    add_todo.resolve("a", false).await;
+    assert!(add_todo.todos().is_empty());
+}
+
+#[tokio::test]
+async fn rolling_back_keeps_other_pending_updates() {
+    _ = Executor::init_tokio();
+    let owner = Owner::new();
+    owner.set();
+
+    let mut add_todo = AddTodo::new();
+    add_todo.dispatch("a");
+    add_todo.dispatch("b");
+    add_todo.dispatch("c");
+    assert_eq!(add_todo.todos(), ["a", "b", "c"]);
+
+    add_todo.resolve("a", false).await;
+    assert_eq!(add_todo.todos(), ["b", "c"]);
+
+    add_todo.resolve("c", true).await;
+    assert_eq!(add_todo.todos(), ["b", "c"]);
+
+    add_todo.resolve("b", false).await;
+    assert_eq!(add_todo.todos(), ["c"]);
+}
+
+#[tokio::test]
+async fn rolling_back_keeps_committed_updates() {
+    _ = Executor::init_tokio();
+    let owner = Owner::new();
+    owner.set();
+
+    let mut add_todo = AddTodo::new();
+    add_todo.dispatch("a");
+    add_todo.dispatch("b");
+
+    add_todo.resolve("a", true).await;
+    add_todo.resolve("b", false).await;
+    assert_eq!(add_todo.todos(), ["a"]);
+
+    add_todo.dispatch("c");
+    assert_eq!(add_todo.todos(), ["a", "c"]);
+    add_todo.resolve("c", false).await;
+    assert_eq!(add_todo.todos(), ["a"]);
+}
+
+#[test]
+fn combined_updates_are_rolled_back_together() {
+    let todos = ArcRwSignal::new(vec!["a"]);
+    let done = ArcRwSignal::new(0);
+
+    let update = Optimistic::update(todos.clone(), |todos| todos.push("b"))
+        .and(Optimistic::update(done.clone(), |done| *done += 1));
+    assert_eq!(todos.get(), ["a", "b"]);
+    assert_eq!(done.get(), 1);
+
+    update.rollback();
+    assert_eq!(todos.get(), ["a"]);
+    assert_eq!(done.get(), 0);
+}
+
+#[test]
+fn updates_to_new_signals_ignore_pending_updates_to_disposed_ones() {
+    let owner = Owner::new();
+    let numbers = owner.with(|| RwSignal::new(vec![1]));
+    let pending = Optimistic::update(numbers, |numbers| numbers.push(2));
+    owner.cleanup();
+    drop(owner);
+
+    // new signals, of any type, are not confused with the one that was disposed
+    for _ in 0..16 {
+        let words = ArcRwSignal::new(vec!["a"]);
+        let update = Optimistic::update(words.clone(), |words| words.push("b"));
+        assert_eq!(words.get(), ["a", "b"]);
+        update.rollback();
+        assert_eq!(words.get(), ["a"]);
+    }
+
+    pending.rollback();
+}
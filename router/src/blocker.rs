@@ -0,0 +1,228 @@
+use crate::location::Url;
+use leptos::{ev, prelude::window_event_listener};
+use or_poisoned::OrPoisoned;
+use reactive_graph::{
+    graph::untrack,
+    owner::on_cleanup,
+    signal::ArcRwSignal,
+    traits::{Get, Set, With},
+};
+use send_wrapper::SendWrapper;
+use std::{
+    fmt::{self, Debug},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+static NEXT_BLOCKER_ID: AtomicUsize = AtomicUsize::new(0);
+
+type Resume = SendWrapper<Box<dyn FnOnce()>>;
+
+/// A handle to a navigation blocker created with [`use_blocker`](crate::hooks::use_blocker).
+///
+/// While its condition is `true`, any navigation away from the current page is held back
+/// instead of happening: clicking an [`<A>`](crate::components::A) or another link, calling the
+/// function returned by [`use_navigate`](crate::hooks::use_navigate), submitting a
+/// [`<Form>`](crate::components::Form), or going back or forward in the browser’s history.
+/// The held navigation can then be allowed with [`proceed`](Blocker::proceed), or canceled with
+/// [`reset`](Blocker::reset), for example after asking the user whether to discard their
+/// unsaved changes.
+#[derive(Clone)]
+pub struct Blocker {
+    id: usize,
+    condition: Arc<dyn Fn() -> bool + Send + Sync>,
+    pending: ArcRwSignal<Option<Url>>,
+    resume: Arc<Mutex<Option<Resume>>>,
+}
+
+impl Debug for Blocker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blocker")
+            .field("id", &self.id)
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Blocker {
+    pub(crate) fn new(
+        condition: impl Fn() -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            id: NEXT_BLOCKER_ID.fetch_add(1, Ordering::Relaxed),
+            condition: Arc::new(condition),
+            pending: Default::default(),
+            resume: Default::default(),
+        }
+    }
+
+    /// Whether a navigation is currently being held back. This is reactive.
+    pub fn is_blocked(&self) -> bool {
+        self.pending.with(Option::is_some)
+    }
+
+    /// The URL of the navigation that is being held back, if any. This is reactive.
+    pub fn location(&self) -> Option<Url> {
+        self.pending.get()
+    }
+
+    /// Allows the navigation that is being held back to continue.
+    pub fn proceed(&self) {
+        let resume = self.resume.lock().or_poisoned().take();
+        self.pending.set(None);
+        if let Some(resume) = resume {
+            (resume.take())();
+        }
+    }
+
+    /// Cancels the navigation that is being held back, staying on the current page.
+    pub fn reset(&self) {
+        self.resume.lock().or_poisoned().take();
+        self.pending.set(None);
+    }
+
+    /// Also asks the browser to confirm before the page is closed, reloaded, or left for
+    /// another site, while the blocker’s condition is `true`.
+    ///
+    /// The browser shows its own dialog for this, which cannot be customized.
+    pub fn with_before_unload(self) -> Self {
+        let handle = window_event_listener(ev::beforeunload, {
+            let condition = Arc::clone(&self.condition);
+            move |ev| {
+                if untrack(|| condition()) {
+                    ev.prevent_default();
+                    // older browsers only show the prompt if a return value is set
+                    ev.set_return_value("");
+                }
+            }
+        });
+        on_cleanup(move || handle.remove());
+        self
+    }
+
+    fn hold(&self, to: Url, resume: Resume) {
+        *self.resume.lock().or_poisoned() = Some(resume);
+        self.pending.set(Some(to));
+    }
+}
+
+/// The blockers registered for a router.
+#[derive(Clone, Default)]
+pub(crate) struct Blockers(Arc<Mutex<Vec<Blocker>>>);
+
+impl Debug for Blockers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blockers")
+            .field("len", &self.0.lock().or_poisoned().len())
+            .finish()
+    }
+}
+
+impl Blockers {
+    pub fn add(&self, blocker: Blocker) {
+        self.0.lock().or_poisoned().push(blocker);
+    }
+
+    pub fn remove(&self, blocker: &Blocker) {
+        self.0.lock().or_poisoned().retain(|b| b.id != blocker.id);
+    }
+
+    /// Checks whether a navigation to `to` should be held back by one of the blockers.
+    ///
+    /// If it is, the blocker keeps `resume`, which continues the navigation, until it is told to
+    /// [`proceed`](Blocker::proceed), and `true` is returned.
+    pub fn block(&self, to: &Url, resume: impl FnOnce() + 'static) -> bool {
+        let blocker = self
+            .0
+            .lock()
+            .or_poisoned()
+            .iter()
+            .find(|blocker| untrack(|| (blocker.condition)()))
+            .cloned();
+        match blocker {
+            Some(blocker) => {
+                blocker.hold(to.clone(), SendWrapper::new(Box::new(resume)));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blocker, Blockers};
+    use crate::location::{LocationProvider, MemoryHistory, Url};
+    use reactive_graph::{
+        signal::ArcRwSignal,
+        traits::{GetUntracked, Set},
+    };
+    use std::{cell::Cell, rc::Rc};
+
+    fn url(path: &str) -> Url {
+        MemoryHistory::parse(path).unwrap()
+    }
+
+    #[test]
+    pub fn lets_navigation_through_while_condition_is_false() {
+        let dirty = ArcRwSignal::new(false);
+        let blockers = Blockers::default();
+        blockers.add(Blocker::new({
+            let dirty = dirty.clone();
+            move || dirty.get_untracked()
+        }));
+
+        assert!(!blockers.block(&url("/a"), || {}));
+        dirty.set(true);
+        assert!(blockers.block(&url("/a"), || {}));
+    }
+
+    #[test]
+    pub fn holds_navigation_until_proceed() {
+        let blocker = Blocker::new(|| true);
+        let blockers = Blockers::default();
+        blockers.add(blocker.clone());
+
+        let navigated = Rc::new(Cell::new(false));
+        assert!(blockers.block(&url("/a"), {
+            let navigated = Rc::clone(&navigated);
+            move || navigated.set(true)
+        }));
+        assert!(!navigated.get());
+        assert_eq!(blocker.location().unwrap().path(), "/a");
+
+        blocker.proceed();
+        assert!(navigated.get());
+        assert!(blocker.location().is_none());
+    }
+
+    #[test]
+    pub fn drops_navigation_on_reset() {
+        let blocker = Blocker::new(|| true);
+        let blockers = Blockers::default();
+        blockers.add(blocker.clone());
+
+        let navigated = Rc::new(Cell::new(false));
+        blockers.block(&url("/a"), {
+            let navigated = Rc::clone(&navigated);
+            move || navigated.set(true)
+        });
+        blocker.reset();
+        assert!(!blocker.is_blocked());
+
+        blocker.proceed();
+        assert!(!navigated.get());
+    }
+
+    #[test]
+    pub fn removed_blocker_no_longer_blocks() {
+        let blocker = Blocker::new(|| true);
+        let blockers = Blockers::default();
+        blockers.add(blocker.clone());
+        blockers.remove(&blocker);
+
+        assert!(!blockers.block(&url("/a"), || {}));
+    }
+}
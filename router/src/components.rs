@@ -3,19 +3,30 @@ pub use super::{form::*, link::*};
 use crate::location::RequestUrl;
 pub use crate::nested_router::Outlet;
 use crate::{
+    blocker::Blockers,
     flat_router::FlatRoutesView,
     hooks::use_navigate,
+    i18n::RouterLocale,
     location::{
-        BrowserUrl, Location, LocationChange, LocationProvider, State, Url,
+        BrowserUrl, Location, LocationChange, LocationProvider, RouterLocation,
+        State, Url,
     },
     navigate::NavigateOptions,
     nested_router::NestedRoutesView,
     resolve_path::resolve_path,
-    ChooseView, MatchNestedRoutes, NestedRoute, RouteDefs, SsrMode,
+    ChooseView, Locales, MatchNestedRoutes, NestedRoute, RouteDefs,
+    RouteLoader, SsrMode,
 };
+use any_spawner::Executor;
+#[cfg(feature = "i18n")]
+use either_of::Either;
 use either_of::EitherOf3;
 use leptos::{children, prelude::*};
+#[cfg(feature = "i18n")]
+use leptos_meta::Html;
+use or_poisoned::OrPoisoned;
 use reactive_graph::{
+    computed::suspense::SuspenseBoundaries,
     owner::{provide_context, use_context, Owner},
     signal::ArcRwSignal,
     traits::{GetUntracked, ReadUntracked, Set},
@@ -25,7 +36,7 @@ use std::{
     borrow::Cow,
     fmt::{Debug, Display},
     mem,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 use tachys::view::any_view::AnyView;
@@ -59,6 +70,20 @@ pub fn Router<Chil>(
     /// A signal that will be set while the navigation process is underway.
     #[prop(optional, into)]
     set_is_routing: Option<SignalSetter<bool>>,
+    /// The location provider that will be used to read and update the current URL.
+    ///
+    /// Defaults to [`BrowserUrl`] in the browser, and to the current request's URL on the
+    /// server. A [`HashUrl`](crate::location::HashUrl) keeps the route in the hash of the
+    /// browser's URL, and a [`MemoryHistory`](crate::location::MemoryHistory) can be used to
+    /// run the router without a browser. Any other
+    /// [`LocationProvider`](crate::location::LocationProvider) can be used as well.
+    #[prop(optional, into)]
+    location: Option<RouterLocation>,
+    /// The locales the routes are served in. When set, each route can also be reached with a
+    /// locale prefix, like `/de/about`. With the `i18n` feature, the router also sets the `lang`
+    /// of the `<html>` element. See [`Locales`].
+    #[prop(optional)]
+    locales: Option<Locales>,
     // TODO trailing slashes
     ///// How trailing slashes should be handled in [`Route`] paths.
     //#[prop(optional)]
@@ -71,32 +96,37 @@ pub fn Router<Chil>(
 where
     Chil: IntoView,
 {
-    #[cfg(feature = "ssr")]
-    let (location_provider, current_url, redirect_hook) = {
-        let req = use_context::<RequestUrl>().expect("no RequestUrl provided");
-        let parsed = req.parse().expect("could not parse RequestUrl");
-        let current_url = ArcRwSignal::new(parsed);
+    // provided before the location is initialized, so that its listeners can find them
+    let blockers = Blockers::default();
+    provide_context(blockers.clone());
+    provide_context(SuspenseBoundaries::default());
 
-        (None, current_url, Box::new(move |_: &str| {}))
+    #[cfg(feature = "ssr")]
+    let (location_provider, current_url, redirect_hook) = match location {
+        Some(location) => init_location(location, base.clone()),
+        None => {
+            let req =
+                use_context::<RequestUrl>().expect("no RequestUrl provided");
+            let parsed = req.parse().expect("could not parse RequestUrl");
+            let current_url = ArcRwSignal::new(parsed);
+
+            (
+                None,
+                current_url,
+                Box::new(move |_: &str| {}) as RedirectHook,
+            )
+        }
     };
 
     #[cfg(not(feature = "ssr"))]
-    let (location_provider, current_url, redirect_hook) = {
-        let owner = Owner::current();
-        let location =
-            BrowserUrl::new().expect("could not access browser navigation"); // TODO options here
-        location.init(base.clone());
-        provide_context(location.clone());
-        let current_url = location.as_url().clone();
-
-        let redirect_hook = Box::new(move |loc: &str| {
-            if let Some(owner) = &owner {
-                owner.with(|| BrowserUrl::redirect(loc));
-            }
-        });
-
-        (Some(location), current_url, redirect_hook)
-    };
+    let (location_provider, current_url, redirect_hook) = init_location(
+        location.unwrap_or_else(|| {
+            BrowserUrl::new()
+                .expect("could not access browser navigation") // TODO options here
+                .into()
+        }),
+        base.clone(),
+    );
     // provide router context
     let state = ArcRwSignal::new(State::new(None));
     let location = Location::new(current_url.read_only(), state.read_only());
@@ -104,6 +134,11 @@ where
     // set server function redirect hook
     _ = server_fn::redirect::set_redirect_hook(redirect_hook);
 
+    let locale = locales
+        .map(|locales| RouterLocale::new(locales, base.clone(), &current_url));
+    #[cfg(feature = "i18n")]
+    let lang = locale.as_ref().map(|locale| locale.locale.clone());
+
     provide_context(RouterContext {
         base,
         current_url,
@@ -112,10 +147,48 @@ where
         set_is_routing,
         query_mutations: Default::default(),
         location_provider,
+        prefetch: Default::default(),
+        blockers,
+        locale,
     });
 
     let children = children.into_inner();
-    children()
+    #[cfg(feature = "i18n")]
+    let view = match lang {
+        Some(lang) => Either::Left((
+            view! { <Html {..} lang=move || lang.get() /> },
+            children(),
+        )),
+        None => Either::Right(children()),
+    };
+    #[cfg(not(feature = "i18n"))]
+    let view = children();
+    view
+}
+
+type RedirectHook = Box<dyn Fn(&str) + Send + Sync>;
+
+/// Sets up the location provider for a [`Router`], returning it along with the current URL
+/// and a hook used to handle server function redirects.
+fn init_location(
+    location: RouterLocation,
+    base: Option<Cow<'static, str>>,
+) -> (Option<RouterLocation>, ArcRwSignal<Url>, RedirectHook) {
+    let owner = Owner::current();
+    location.init(base);
+    location.provide_context();
+    let current_url = location.as_url().clone();
+
+    let redirect_hook = Box::new({
+        let location = location.clone();
+        move |loc: &str| {
+            if let Some(owner) = &owner {
+                owner.with(|| location.redirect(loc));
+            }
+        }
+    });
+
+    (Some(location), current_url, redirect_hook)
 }
 
 #[derive(Clone)]
@@ -127,7 +200,24 @@ pub(crate) struct RouterContext {
     pub set_is_routing: Option<SignalSetter<bool>>,
     pub query_mutations:
         ArcStoredValue<Vec<(Oco<'static, str>, Option<String>)>>,
-    pub location_provider: Option<BrowserUrl>,
+    pub location_provider: Option<RouterLocation>,
+    /// Set by [`Routes`] or [`FlatRoutes`], to load the code for the routes matching a path.
+    pub prefetch: ArcStoredValue<Option<Prefetcher>>,
+    pub blockers: Blockers,
+    pub locale: Option<RouterLocale>,
+}
+
+pub(crate) type Prefetcher = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Creates a [`Prefetcher`] that spawns the prefetches of the routes matching a path.
+fn prefetcher<Defs>(routes: RouteDefs<Defs>) -> Prefetcher
+where
+    Defs: MatchNestedRoutes + Send + 'static,
+{
+    let routes = Mutex::new(routes);
+    Arc::new(move |path: &str| {
+        Executor::spawn_local(routes.lock().or_poisoned().prefetch(path));
+    })
 }
 
 impl RouterContext {
@@ -144,17 +234,21 @@ impl RouterContext {
             resolve_path("", path, None)
         };
 
-        let mut url = match resolved_to.map(|to| BrowserUrl::parse(&to)) {
-            Some(Ok(url)) => url,
-            Some(Err(e)) => {
-                leptos::logging::error!("Error parsing URL: {e:?}");
-                return;
-            }
-            None => {
-                leptos::logging::error!("Error resolving relative URL.");
-                return;
-            }
-        };
+        let mut url =
+            match resolved_to.map(|to| match &self.location_provider {
+                Some(location_provider) => location_provider.parse(&to),
+                None => BrowserUrl::parse(&to).map_err(|e| format!("{e:?}")),
+            }) {
+                Some(Ok(url)) => url,
+                Some(Err(e)) => {
+                    leptos::logging::error!("Error parsing URL: {e}");
+                    return;
+                }
+                None => {
+                    leptos::logging::error!("Error resolving relative URL.");
+                    return;
+                }
+            };
         let query_mutations =
             mem::take(&mut *self.query_mutations.write_value());
         if !query_mutations.is_empty() {
@@ -172,6 +266,30 @@ impl RouterContext {
                 .into()
         }
 
+        let is_current = *current == url;
+        drop(current);
+
+        let resume = {
+            let this = self.clone();
+            let url = url.clone();
+            let path = path.to_string();
+            let options = options.clone();
+            move || this.complete_navigate(url, &path, options)
+        };
+        if !is_current && self.blockers.block(&url, resume) {
+            return;
+        }
+        self.complete_navigate(url, path, options);
+    }
+
+    /// Navigates to a resolved URL, once it has passed the [`Blockers`].
+    fn complete_navigate(
+        &self,
+        url: Url,
+        path: &str,
+        options: NavigateOptions,
+    ) {
+        let current = self.current_url.read_untracked();
         if url.origin() != current.origin() {
             window().location().set_href(path).unwrap();
             return;
@@ -186,6 +304,9 @@ impl RouterContext {
         let value = url.to_full_path();
         if current != url {
             drop(current);
+            if let Some(location_provider) = &self.location_provider {
+                location_provider.before_navigate();
+            }
             self.current_url.set(url);
         }
 
@@ -207,6 +328,19 @@ impl RouterContext {
         let base = self.base.as_deref().unwrap_or_default();
         resolve_path(base, path, from)
     }
+
+    /// Adds a locale prefix to a resolved path, if the router has locales: `locale`, or else the
+    /// prefix of the current URL.
+    pub fn localize(&self, path: String, locale: Option<&str>) -> String {
+        match &self.locale {
+            Some(router_locale) => router_locale.localize(
+                self.base.as_deref().unwrap_or_default(),
+                &path,
+                locale,
+            ),
+            None => path,
+        }
+    }
 }
 
 impl Debug for RouterContext {
@@ -235,11 +369,13 @@ where
     FallbackFn: FnOnce() -> Fallback + Clone + Send + 'static,
     Fallback: IntoView + 'static,
 {
-    let location = use_context::<BrowserUrl>();
+    let location = use_context::<RouterLocation>();
     let RouterContext {
         current_url,
         base,
         set_is_routing,
+        prefetch,
+        locale,
         ..
     } = use_context()
         .expect("<Routes> should be used inside a <Router> component");
@@ -251,7 +387,9 @@ where
     let routes = RouteDefs::new_with_base(
         children.into_inner(),
         base.clone().unwrap_or_default(),
-    );
+    )
+    .with_locales(locale.map(|locale| locale.locales));
+    prefetch.set_value(Some(prefetcher(routes.clone())));
     let outer_owner =
         Owner::current().expect("creating Routes, but no Owner was found");
     move || {
@@ -288,11 +426,13 @@ where
     FallbackFn: FnOnce() -> Fallback + Clone + Send + 'static,
     Fallback: IntoView + 'static,
 {
-    let location = use_context::<BrowserUrl>();
+    let location = use_context::<RouterLocation>();
     let RouterContext {
         current_url,
         base,
         set_is_routing,
+        prefetch,
+        locale,
         ..
     } = use_context()
         .expect("<FlatRoutes> should be used inside a <Router> component");
@@ -307,7 +447,9 @@ where
     let routes = RouteDefs::new_with_base(
         children.into_inner(),
         base.clone().unwrap_or_default(),
-    );
+    )
+    .with_locales(locale.map(|locale| locale.locales));
+    prefetch.set_value(Some(prefetcher(routes.clone())));
 
     let outer_owner =
         Owner::current().expect("creating Router, but no Owner was found");
@@ -344,11 +486,15 @@ pub fn Route<Segments, View>(
     /// Defaults to out-of-order streaming.
     #[prop(optional)]
     ssr: SsrMode,
-) -> NestedRoute<Segments, (), (), View>
+    /// Loads data for this route as soon as it is matched, in parallel with any parent or child
+    /// routes. The data can be accessed with [`use_loader_data`](crate::hooks::use_loader_data).
+    #[prop(optional, into)]
+    loader: RouteLoader,
+) -> NestedRoute<Segments, (), RouteLoader, View>
 where
     View: ChooseView,
 {
-    NestedRoute::new(path, view).ssr_mode(ssr)
+    NestedRoute::new(path, view).ssr_mode(ssr).loader(loader)
 }
 
 /// Describes a portion of the nested layout of the app, specifying the route it should match
@@ -368,12 +514,19 @@ pub fn ParentRoute<Segments, View, Children>(
     /// Defaults to out-of-order streaming.
     #[prop(optional)]
     ssr: SsrMode,
-) -> NestedRoute<Segments, Children, (), View>
+    /// Loads data for this route as soon as it is matched, in parallel with any parent or child
+    /// routes. The data can be accessed with [`use_loader_data`](crate::hooks::use_loader_data).
+    #[prop(optional, into)]
+    loader: RouteLoader,
+) -> NestedRoute<Segments, Children, RouteLoader, View>
 where
     View: ChooseView,
 {
     let children = children.into_inner();
-    NestedRoute::new(path, view).ssr_mode(ssr).child(children)
+    NestedRoute::new(path, view)
+        .ssr_mode(ssr)
+        .child(children)
+        .loader(loader)
 }
 
 /// Describes a route that is guarded by a certain condition. This works the same way as
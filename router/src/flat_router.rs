@@ -1,6 +1,6 @@
 use crate::{
     hooks::Matched,
-    location::{LocationProvider, Url},
+    location::{RouterLocation, Url},
     matching::{MatchParams, RouteDefs},
     params::ParamsMap,
     view_transition::start_view_transition,
@@ -30,9 +30,9 @@ use tachys::{
     },
 };
 
-pub(crate) struct FlatRoutesView<Loc, Defs, FalFn> {
+pub(crate) struct FlatRoutesView<Defs, FalFn> {
     pub current_url: ArcRwSignal<Url>,
-    pub location: Option<Loc>,
+    pub location: Option<RouterLocation>,
     pub routes: RouteDefs<Defs>,
     pub fallback: FalFn,
     pub outer_owner: Owner,
@@ -70,9 +70,8 @@ impl Mountable for FlatRoutesViewState {
     }
 }
 
-impl<Loc, Defs, FalFn, Fal> Render for FlatRoutesView<Loc, Defs, FalFn>
+impl<Defs, FalFn, Fal> Render for FlatRoutesView<Defs, FalFn>
 where
-    Loc: LocationProvider,
     Defs: MatchNestedRoutes + 'static,
     FalFn: FnOnce() -> Fal + Send,
     Fal: IntoAny,
@@ -143,6 +142,7 @@ where
                             provide_context(params_memo);
                             provide_context(url);
                             provide_context(Matched(ArcMemo::from(matched)));
+                            view.preload().await;
                             OwnedView::new(view.choose().await)
                         }
                     })
@@ -295,6 +295,7 @@ where
                             provide_context(Matched(ArcMemo::from(
                                 new_matched,
                             )));
+                            view.preload().await;
                             let view = OwnedView::new(
                                 if let Some(set_is_routing) = set_is_routing {
                                     set_is_routing.set(true);
@@ -339,15 +340,14 @@ where
     }
 }
 
-impl<Loc, Defs, FalFn, Fal> AddAnyAttr for FlatRoutesView<Loc, Defs, FalFn>
+impl<Defs, FalFn, Fal> AddAnyAttr for FlatRoutesView<Defs, FalFn>
 where
-    Loc: LocationProvider + Send,
     Defs: MatchNestedRoutes + Send + 'static,
     FalFn: FnOnce() -> Fal + Send,
     Fal: RenderHtml + 'static,
 {
     type Output<SomeNewAttr: leptos::attr::Attribute> =
-        FlatRoutesView<Loc, Defs, FalFn>;
+        FlatRoutesView<Defs, FalFn>;
 
     fn add_any_attr<NewAttr: leptos::attr::Attribute>(
         self,
@@ -360,9 +360,8 @@ where
     }
 }
 
-impl<Loc, Defs, FalFn, Fal> FlatRoutesView<Loc, Defs, FalFn>
+impl<Defs, FalFn, Fal> FlatRoutesView<Defs, FalFn>
 where
-    Loc: LocationProvider + Send,
     Defs: MatchNestedRoutes + Send + 'static,
     FalFn: FnOnce() -> Fal + Send,
     Fal: RenderHtml + 'static,
@@ -399,6 +398,7 @@ where
                             provide_context(url);
                             provide_context(params_memo);
                             provide_context(Matched(ArcMemo::from(matched)));
+                            view.preload().await;
                             view.choose().await
                         })
                     })
@@ -412,9 +412,8 @@ where
     }
 }
 
-impl<Loc, Defs, FalFn, Fal> RenderHtml for FlatRoutesView<Loc, Defs, FalFn>
+impl<Defs, FalFn, Fal> RenderHtml for FlatRoutesView<Defs, FalFn>
 where
-    Loc: LocationProvider + Send,
     Defs: MatchNestedRoutes + Send + 'static,
     FalFn: FnOnce() -> Fal + Send,
     Fal: RenderHtml + 'static,
@@ -440,7 +439,7 @@ where
         if RouteList::is_generating() {
             // add routes
             let (base, routes) = self.routes.generate_routes();
-            let routes = routes
+            let mut routes = routes
                 .into_iter()
                 .map(|data| {
                     let path = base
@@ -460,6 +459,7 @@ where
                     )
                 })
                 .collect::<Vec<_>>();
+            self.routes.add_locale_listings(&mut routes);
 
             // add fallback
             // TODO fix: causes overlapping route issues on Axum
@@ -579,6 +579,7 @@ where
                             provide_context(params_memo);
                             provide_context(url);
                             provide_context(Matched(ArcMemo::from(matched)));
+                            view.preload().await;
                             OwnedView::new(view.choose().await)
                         }
                     })
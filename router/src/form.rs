@@ -1,11 +1,20 @@
 use crate::{
+    blocker::Blockers,
     components::ToHref,
     hooks::{has_router, use_navigate, use_resolved_path},
     location::{BrowserUrl, LocationProvider},
     NavigateOptions,
 };
-use leptos::{ev, html::form, logging::*, prelude::*, task::spawn_local};
-use std::{error::Error, sync::Arc};
+use leptos::{
+    ev,
+    form::csrf_input,
+    html::form,
+    logging::*,
+    prelude::*,
+    server_fn::csrf::{token_from_cookie, CSRF_HEADER},
+    task::spawn_local,
+};
+use std::{cell::Cell, error::Error, rc::Rc, sync::Arc};
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 use web_sys::{FormData, RequestRedirect, Response};
 
@@ -62,12 +71,13 @@ where
         action: &str,
         form_data: FormData,
     ) -> Result<gloo_net::http::Response, gloo_net::Error> {
-        gloo_net::http::Request::post(action)
+        let mut req = gloo_net::http::Request::post(action)
             .header("Accept", "application/json")
-            .redirect(RequestRedirect::Follow)
-            .body(form_data)?
-            .send()
-            .await
+            .redirect(RequestRedirect::Follow);
+        if let Some(token) = token_from_cookie() {
+            req = req.header(CSRF_HEADER, &token);
+        }
+        req.body(form_data)?.send().await
     }
 
     async fn post_params(
@@ -75,13 +85,14 @@ where
         enctype: &str,
         params: web_sys::UrlSearchParams,
     ) -> Result<gloo_net::http::Response, gloo_net::Error> {
-        gloo_net::http::Request::post(action)
+        let mut req = gloo_net::http::Request::post(action)
             .header("Accept", "application/json")
             .header("Content-Type", enctype)
-            .redirect(RequestRedirect::Follow)
-            .body(params)?
-            .send()
-            .await
+            .redirect(RequestRedirect::Follow);
+        if let Some(token) = token_from_cookie() {
+            req = req.header(CSRF_HEADER, &token);
+        }
+        req.body(params)?.send().await
     }
 
     fn inner(
@@ -100,6 +111,9 @@ where
     ) -> impl IntoView {
         let action_version = version;
         let navigate = has_router.then(use_navigate);
+        let blockers = use_context::<Blockers>();
+        // set when a blocked submission is allowed to proceed and is submitted again
+        let skip_blockers = Rc::new(Cell::new(false));
         let on_submit = {
             move |ev: web_sys::SubmitEvent| {
                 let navigate = navigate.clone();
@@ -115,6 +129,31 @@ where
                 let (form, method, action, enctype) =
                     extract_form_attributes(&ev);
 
+                // GET submissions are checked by `navigate`, but POST submissions need to be
+                // checked against the router's blockers here
+                if method == "post" && !skip_blockers.replace(false) {
+                    let blocked = blockers.as_ref().is_some_and(|blockers| {
+                        BrowserUrl::parse(&action).is_ok_and(|to| {
+                            blockers.block(&to, {
+                                let form = form.clone();
+                                let submitter = ev.submitter();
+                                let skip_blockers = Rc::clone(&skip_blockers);
+                                move || {
+                                    skip_blockers.set(true);
+                                    _ = form.request_submit_with_submitter(
+                                        submitter.as_ref(),
+                                    );
+                                }
+                            })
+                        })
+                    });
+                    if blocked {
+                        ev.prevent_default();
+                        ev.stop_propagation();
+                        return;
+                    }
+                }
+
                 let form_data =
                     web_sys::FormData::new_with_form(&form).unwrap_throw();
                 if let Some(on_form_data) = on_form_data.clone() {
@@ -298,13 +337,15 @@ where
         };
 
         let method = method.unwrap_or("get");
+        // a plain form post needs to carry the CSRF token in its body
+        let csrf_input = method.eq_ignore_ascii_case("post").then(csrf_input);
 
         form()
             .attr("method", method)
             .attr("action", move || action.get())
             .attr("enctype", enctype)
             .on(ev::submit, on_submit)
-            .child(children())
+            .child((csrf_input, children()))
     }
 
     let has_router = has_router();
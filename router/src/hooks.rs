@@ -1,13 +1,17 @@
 use crate::{
+    blocker::Blocker,
     components::RouterContext,
     location::{Location, Url},
+    matching::LoaderData,
     navigate::NavigateOptions,
     params::{Params, ParamsError, ParamsMap},
 };
-use leptos::{leptos_dom::helpers::request_animation_frame, oco::Oco};
+use leptos::{
+    leptos_dom::helpers::request_animation_frame, oco::Oco, server::Resource,
+};
 use reactive_graph::{
     computed::{ArcMemo, Memo},
-    owner::{expect_context, use_context},
+    owner::{expect_context, on_cleanup, use_context},
     signal::{ArcRwSignal, ReadSignal},
     traits::{Get, GetUntracked, ReadUntracked, With, WriteValue},
     wrappers::write::SignalSetter,
@@ -189,6 +193,25 @@ pub fn use_params_map() -> Memo<ParamsMap> {
     use_params_raw().into()
 }
 
+/// Returns the data loaded by the `loader` of the closest matched route that loads a `T`.
+///
+/// The loader for each matched route starts as soon as navigation does, so the data for nested
+/// routes is loaded in parallel instead of one level at a time. It can be read like any other
+/// resource, typically inside a `<Suspense/>` or `<Transition/>`.
+#[track_caller]
+pub fn use_loader_data<T>() -> Resource<T>
+where
+    T: Send + Sync + 'static,
+{
+    use_context::<LoaderData<T>>()
+        .expect(
+            "Tried to access loader data outside a matched <Route> with a \
+             loader of this type.",
+        )
+        .0
+        .into()
+}
+
 /// Returns the current route params, parsed into the given type, or an error.
 #[track_caller]
 pub fn use_params<T>() -> Memo<Result<T, ParamsError>>
@@ -278,6 +301,80 @@ pub fn use_navigate() -> impl Fn(&str, NavigateOptions) + Clone {
     move |path: &str, options: NavigateOptions| cx.navigate(path, options)
 }
 
+/// Holds back navigations away from the current page while `condition` returns `true`, for
+/// example to ask whether to discard unsaved changes. See [`Blocker`].
+///
+/// The blocker is removed when the component that created it is unmounted.
+///
+/// ```rust
+/// # use leptos::prelude::*;
+/// # use leptos_router::hooks::use_blocker;
+/// # if false { // can't actually navigate, no <Router/>
+/// let (dirty, set_dirty) = signal(false);
+/// let blocker = use_blocker(move || dirty.get()).with_before_unload();
+///
+/// view! {
+///     <Show when={
+///         let blocker = blocker.clone();
+///         move || blocker.is_blocked()
+///     }>
+///         <p>"Discard your changes?"</p>
+///         <button on:click={
+///             let blocker = blocker.clone();
+///             move |_| blocker.proceed()
+///         }>"Discard"</button>
+///         <button on:click={
+///             let blocker = blocker.clone();
+///             move |_| blocker.reset()
+///         }>"Keep editing"</button>
+///     </Show>
+/// };
+/// # }
+/// ```
+#[track_caller]
+pub fn use_blocker(
+    condition: impl Fn() -> bool + Send + Sync + 'static,
+) -> Blocker {
+    let RouterContext { blockers, .. } = use_context()
+        .expect("You cannot call `use_blocker` outside a <Router>.");
+    let blocker = Blocker::new(condition);
+    blockers.add(blocker.clone());
+    on_cleanup({
+        let blocker = blocker.clone();
+        move || blockers.remove(&blocker)
+    });
+    blocker
+}
+
+/// Returns the current locale of a [`Router`](crate::components::Router) with
+/// [`Locales`](crate::Locales): the locale in the prefix of the URL, or else the one the user
+/// prefers.
+///
+/// ```rust
+/// # use leptos::prelude::*;
+/// # use leptos_router::hooks::use_locale;
+/// # if false { // can't actually read the locale, no <Router/>
+/// let locale = use_locale();
+/// let greeting = move || match locale.get().as_str() {
+///     "de" => "Hallo!",
+///     "fr" => "Bonjour !",
+///     _ => "Hello!",
+/// };
+/// # }
+/// ```
+///
+/// # Panics
+/// Panics if the router has no locales.
+#[track_caller]
+pub fn use_locale() -> Memo<String> {
+    let RouterContext { locale, .. } = use_context()
+        .expect("You cannot call `use_locale` outside a <Router>.");
+    locale
+        .expect("You cannot call `use_locale` in a <Router> without `locales`.")
+        .locale
+        .into()
+}
+
 /// Returns a reactive string that contains the route that was matched for
 /// this [`Route`](crate::components::Route).
 #[track_caller]
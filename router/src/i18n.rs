@@ -0,0 +1,404 @@
+use crate::location::Url;
+use leptos::prelude::*;
+use reactive_graph::{computed::ArcMemo, signal::ArcRwSignal};
+use std::{borrow::Cow, sync::Arc};
+
+/// The locales that the routes of a [`Router`](crate::components::Router) are served in.
+///
+/// Every route can be reached both without a locale prefix (`/about`) and with the prefix of any
+/// of the locales (`/de/about`, `/fr/about`), so the route definitions only need to be written
+/// once. The current locale can be read with [`use_locale`](crate::hooks::use_locale).
+///
+/// With the `i18n` feature, the locale is also set as the `lang` of the `<html>` element through
+/// `leptos_meta`, so the app needs to call `leptos_meta::provide_meta_context` above the router.
+/// On the server, the `lang` is only rendered if a `leptos_meta::ServerMetaContext` is provided,
+/// as the integrations do for the app's shell.
+///
+/// When the URL has no locale prefix, the locale is the one saved in a cookie by an earlier visit
+/// to a prefixed URL, or else the best match for the `Accept-Language` header (the browser's
+/// languages, on the client), or else the first of the locales.
+///
+/// ```rust
+/// use leptos::prelude::*;
+/// use leptos_router::{components::*, path, Locales};
+///
+/// #[component]
+/// pub fn App() -> impl IntoView {
+///     view! {
+///         <Router locales=Locales::new(["en", "de", "fr"])>
+///             <Routes fallback=|| "Not found.">
+///                 // matches `/about`, `/en/about`, `/de/about` and `/fr/about`
+///                 <Route path=path!("/about") view=|| "About" />
+///             </Routes>
+///         </Router>
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Locales {
+    locales: Arc<[Cow<'static, str>]>,
+    cookie: Option<&'static str>,
+}
+
+impl Locales {
+    /// Creates the set of locales. The first one is the default locale.
+    ///
+    /// # Panics
+    /// Panics if `locales` is empty.
+    pub fn new<T>(locales: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let locales = locales.into_iter().map(Into::into).collect::<Arc<[_]>>();
+        assert!(!locales.is_empty(), "`Locales` needs at least one locale");
+        Self {
+            locales,
+            cookie: Some("locale"),
+        }
+    }
+
+    /// Sets the name of the cookie the locale is remembered in, or `None` to not remember it.
+    /// Defaults to `"locale"`.
+    pub fn cookie(mut self, name: Option<&'static str>) -> Self {
+        self.cookie = name;
+        self
+    }
+
+    /// The locale used when no other locale is preferred.
+    pub fn default_locale(&self) -> &str {
+        &self.locales[0]
+    }
+
+    /// Iterates over the locales.
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.locales.iter().map(AsRef::as_ref)
+    }
+
+    /// Returns the locale matching `locale`, ignoring case.
+    fn find(&self, locale: &str) -> Option<&str> {
+        self.iter().find(|l| l.eq_ignore_ascii_case(locale))
+    }
+
+    /// Splits the locale prefix off a path, so that `/de/about` becomes `(Some("de"), "/about")`.
+    pub(crate) fn split_path<'a>(
+        &self,
+        path: &'a str,
+    ) -> (Option<&str>, &'a str) {
+        let Some(trimmed) = path.strip_prefix('/') else {
+            return (None, path);
+        };
+        let end = trimmed.find(['/', '?', '#']).unwrap_or(trimmed.len());
+        match self.find(&trimmed[..end]) {
+            Some(locale) => {
+                let rest = &trimmed[end..];
+                (Some(locale), if rest.is_empty() { "/" } else { rest })
+            }
+            None => (None, path),
+        }
+    }
+
+    /// Adds a locale prefix to a path within the router's `base`.
+    ///
+    /// The prefix is `locale`, if given, and replaces any prefix the path already has. Otherwise,
+    /// a path that has no prefix gets the `current` one.
+    pub(crate) fn localize(
+        &self,
+        base: &str,
+        path: &str,
+        locale: Option<&str>,
+        current: Option<&str>,
+    ) -> String {
+        let base = base.trim_end_matches('/');
+        let Some(rest) = path
+            .strip_prefix(base)
+            .filter(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+            .filter(|_| path.starts_with('/'))
+        else {
+            return path.to_string();
+        };
+        let (prefix, rest) = self.split_path(rest);
+        let locale = match (locale, prefix) {
+            (Some(locale), _) => locale,
+            (None, None) => match current {
+                Some(current) => current,
+                None => return path.to_string(),
+            },
+            (None, Some(_)) => return path.to_string(),
+        };
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        if rest.is_empty() || rest.starts_with(['?', '#']) {
+            format!("{base}/{locale}{rest}")
+        } else {
+            format!("{base}/{locale}/{rest}")
+        }
+    }
+
+    /// The locale to use when the URL has no prefix: the one in the cookie, or else the best match
+    /// for the user's `languages`, or else the default locale.
+    pub(crate) fn preferred<'a>(
+        &self,
+        cookies: Option<&str>,
+        languages: impl IntoIterator<Item = &'a str>,
+    ) -> &str {
+        cookies
+            .and_then(|cookies| self.cookie_locale(cookies))
+            .or_else(|| self.negotiate(languages))
+            .unwrap_or_else(|| self.default_locale())
+    }
+
+    /// Reads the locale from the value of a `Cookie` header.
+    fn cookie_locale(&self, cookies: &str) -> Option<&str> {
+        let name = self.cookie?;
+        cookies
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| self.find(value))
+    }
+
+    /// Finds the locale that best matches a list of language tags, in order of preference. A
+    /// locale matches a tag if they are equal, or else if they have the same primary language.
+    fn negotiate<'a>(
+        &self,
+        languages: impl IntoIterator<Item = &'a str>,
+    ) -> Option<&str> {
+        fn primary(tag: &str) -> &str {
+            tag.split(['-', '_']).next().unwrap_or_default()
+        }
+
+        languages.into_iter().find_map(|tag| {
+            self.find(tag).or_else(|| {
+                self.iter().find(|locale| {
+                    primary(locale).eq_ignore_ascii_case(primary(tag))
+                })
+            })
+        })
+    }
+
+    /// Saves `locale` in the cookie, so that it is used for later URLs without a prefix.
+    fn remember(&self, locale: &str) {
+        use wasm_bindgen::JsCast;
+
+        let document = browser_window().and_then(|window| window.document());
+        if let (Some(name), Some(document)) = (self.cookie, document) {
+            _ = document
+                .unchecked_into::<web_sys::HtmlDocument>()
+                .set_cookie(&format!(
+                    "{name}={locale}; path=/; max-age=31536000; samesite=lax"
+                ));
+        }
+    }
+}
+
+/// The browser's `window`, or `None` when the router is not running in a browser, like in a
+/// native client or in tests.
+fn browser_window() -> Option<web_sys::Window> {
+    if cfg!(target_arch = "wasm32") {
+        web_sys::window()
+    } else {
+        None
+    }
+}
+
+/// Parses the language tags of an `Accept-Language` header, from the most to the least preferred.
+#[cfg(any(feature = "ssr", test))]
+fn accept_language(header: &str) -> Vec<&str> {
+    let mut languages = header
+        .split(',')
+        .filter_map(|language| {
+            let mut parts = language.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && tag != "*" && quality > 0.0)
+                .then_some((tag, quality))
+        })
+        .collect::<Vec<_>>();
+    // a stable sort keeps tags of equal quality in the order they were given
+    languages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    languages.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// The locale state of a [`Router`](crate::components::Router) with [`Locales`].
+#[derive(Clone, Debug)]
+pub(crate) struct RouterLocale {
+    pub locales: Locales,
+    /// The locale in the prefix of the current URL.
+    pub prefix: ArcMemo<Option<String>>,
+    /// The current locale.
+    pub locale: ArcMemo<String>,
+}
+
+impl RouterLocale {
+    pub fn new(
+        locales: Locales,
+        base: Option<Cow<'static, str>>,
+        current_url: &ArcRwSignal<Url>,
+    ) -> Self {
+        let prefix = ArcMemo::new({
+            let locales = locales.clone();
+            let current_url = current_url.clone();
+            move |_| {
+                current_url.with(|url| {
+                    let base = base.as_deref().unwrap_or_default();
+                    let path = url.path();
+                    let path = path
+                        .strip_prefix(base.trim_end_matches('/'))
+                        .unwrap_or(path);
+                    locales.split_path(path).0.map(String::from)
+                })
+            }
+        });
+        let preferred = ArcRwSignal::new(Self::preferred(&locales));
+        let locale = ArcMemo::new({
+            let prefix = prefix.clone();
+            let preferred = preferred.clone();
+            move |_| prefix.get().unwrap_or_else(|| preferred.get())
+        });
+
+        // a locale chosen by visiting a prefixed URL is kept for URLs without a prefix
+        Effect::new({
+            let locales = locales.clone();
+            let prefix = prefix.clone();
+            move |_| {
+                if let Some(prefix) = prefix.get() {
+                    if preferred.get_untracked() != prefix {
+                        locales.remember(&prefix);
+                        preferred.set(prefix);
+                    }
+                }
+            }
+        });
+
+        Self {
+            locales,
+            prefix,
+            locale,
+        }
+    }
+
+    /// The locale the user prefers, from the request on the server, or from the browser. Outside
+    /// a browser, this is the default locale.
+    fn preferred(locales: &Locales) -> String {
+        #[cfg(feature = "ssr")]
+        let preferred = match use_context::<crate::location::RequestUrl>() {
+            Some(req) => locales.preferred(
+                req.cookie(),
+                accept_language(req.accept_language().unwrap_or_default()),
+            ),
+            None => locales.default_locale(),
+        };
+
+        #[cfg(not(feature = "ssr"))]
+        let preferred = match browser_window() {
+            Some(window) => {
+                use wasm_bindgen::JsCast;
+
+                let cookies = window.document().and_then(|document| {
+                    document
+                        .unchecked_into::<web_sys::HtmlDocument>()
+                        .cookie()
+                        .ok()
+                });
+                let languages = window
+                    .navigator()
+                    .languages()
+                    .iter()
+                    .filter_map(|language| language.as_string())
+                    .collect::<Vec<_>>();
+                locales.preferred(
+                    cookies.as_deref(),
+                    languages.iter().map(String::as_str),
+                )
+            }
+            None => locales.default_locale(),
+        };
+
+        preferred.to_string()
+    }
+
+    /// Adds the locale prefix to a path, tracking the current URL's prefix.
+    pub fn localize(
+        &self,
+        base: &str,
+        path: &str,
+        locale: Option<&str>,
+    ) -> String {
+        self.prefix.with(|current| {
+            self.locales
+                .localize(base, path, locale, current.as_deref())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{accept_language, Locales};
+
+    fn locales() -> Locales {
+        Locales::new(["en", "de", "fr-CA"])
+    }
+
+    #[test]
+    fn splits_locale_prefix() {
+        let locales = locales();
+        assert_eq!(locales.split_path("/de/about"), (Some("de"), "/about"));
+        assert_eq!(locales.split_path("/de"), (Some("de"), "/"));
+        assert_eq!(locales.split_path("/de/"), (Some("de"), "/"));
+        assert_eq!(locales.split_path("/FR-ca/x"), (Some("fr-CA"), "/x"));
+        assert_eq!(locales.split_path("/design"), (None, "/design"));
+        assert_eq!(locales.split_path("/"), (None, "/"));
+    }
+
+    #[test]
+    fn localizes_paths() {
+        let locales = locales();
+        assert_eq!(
+            locales.localize("", "/about", None, Some("de")),
+            "/de/about"
+        );
+        assert_eq!(locales.localize("", "/about", None, None), "/about");
+        assert_eq!(locales.localize("", "/", None, Some("de")), "/de");
+        assert_eq!(locales.localize("", "/?q=1", None, Some("de")), "/de?q=1");
+        // an explicit locale replaces the prefix, but the current one does not
+        assert_eq!(locales.localize("", "/de/x", Some("en"), None), "/en/x");
+        assert_eq!(locales.localize("", "/de/x", None, Some("en")), "/de/x");
+        // the prefix follows the base, and paths outside it are left alone
+        assert_eq!(
+            locales.localize("/app", "/app/about", None, Some("de")),
+            "/app/de/about"
+        );
+        assert_eq!(
+            locales.localize("/app", "/other", None, Some("de")),
+            "/other"
+        );
+        assert_eq!(
+            locales.localize("", "https://example.com", None, Some("de")),
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn parses_accept_language() {
+        assert_eq!(
+            accept_language("fr;q=0.5, de-DE, de;q=0.9, *;q=0.1, it;q=0"),
+            ["de-DE", "de", "fr"]
+        );
+        assert!(accept_language("").is_empty());
+    }
+
+    #[test]
+    fn picks_preferred_locale() {
+        let locales = locales();
+        assert_eq!(
+            locales.preferred(Some("a=b; locale=fr-ca"), ["de"]),
+            "fr-CA"
+        );
+        assert_eq!(locales.preferred(Some("locale=es"), ["de-AT", "en"]), "de");
+        assert_eq!(locales.preferred(None, ["fr-FR"]), "fr-CA");
+        assert_eq!(locales.preferred(None, ["es"]), "en");
+        let no_cookie = locales.cookie(None);
+        assert_eq!(no_cookie.preferred(Some("locale=de"), []), "en");
+    }
+}
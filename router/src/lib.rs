@@ -115,6 +115,8 @@
 //! - `ssr` Server-side rendering: Generate an HTML string (typically on the server)
 //! - `nightly`: On `nightly` Rust, enables the function-call syntax for signal getters and setters.
 //! - `tracing`: Enables support for the `tracing` crate.
+//! - `i18n`: Sets the `lang` of the `<html>` element to the locale of a router with
+//!   [`Locales`], using `leptos_meta`.
 //!
 //! [`Leptos`]: <https://github.com/leptos-rs/leptos>
 //! [`router`]: <https://github.com/leptos-rs/leptos/blob/main/examples/router/src/lib.rs>
@@ -124,6 +126,7 @@
 #![cfg_attr(feature = "nightly", feature(auto_traits))]
 #![cfg_attr(feature = "nightly", feature(negative_impls))]
 
+mod blocker;
 /// Components for route definition and for enhanced links and forms.
 pub mod components;
 /// An optimized "flat" router without nested routes.
@@ -132,6 +135,7 @@ mod form;
 mod generate_route_list;
 /// Hooks that can be used to access router state inside your components.
 pub mod hooks;
+mod i18n;
 mod link;
 /// Utilities for accessing the current location.
 pub mod location;
@@ -145,14 +149,20 @@ pub mod params;
 mod ssr_mode;
 /// Support for static routing.
 pub mod static_routes;
+mod typed_route;
 
+pub use blocker::*;
 pub use generate_route_list::*;
+pub use i18n::*;
 #[doc(inline)]
 pub use leptos_router_macro::path;
+#[doc(inline)]
+pub use leptos_router_macro::TypedRoute;
 pub use matching::*;
 pub use method::*;
 pub use navigate::*;
 pub use ssr_mode::*;
+pub use typed_route::*;
 
 pub(crate) mod view_transition {
     use js_sys::{Function, Promise, Reflect};
@@ -1,7 +1,23 @@
-use crate::{components::RouterContext, hooks::use_resolved_path};
-use leptos::{children::Children, oco::Oco, prelude::*};
-use reactive_graph::{computed::ArcMemo, owner::use_context};
-use std::{borrow::Cow, rc::Rc};
+use crate::{
+    components::RouterContext, hooks::use_resolved_path,
+    location::RouterLocation,
+};
+use leptos::{children::Children, either::EitherOf3, ev, oco::Oco, prelude::*};
+use reactive_graph::{
+    computed::ArcMemo,
+    owner::{on_cleanup, use_context},
+};
+use send_wrapper::SendWrapper;
+use std::{
+    borrow::Cow,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{IntersectionObserver, IntersectionObserverEntry};
 
 /// Describes a value that is either a static or a reactive URL, i.e.,
 /// a [`String`], a [`&str`], or a reactive `Fn() -> String`.
@@ -55,6 +71,19 @@ where
     }
 }
 
+/// When an [`A`] loads the code for a [lazily loaded route](crate::Lazy) it links to, before the
+/// link is followed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Prefetch {
+    /// The route is loaded when the link is followed.
+    #[default]
+    None,
+    /// The route is loaded when the pointer moves over the link, or the link receives focus.
+    Hover,
+    /// The route is loaded as soon as the link is scrolled into view.
+    Visible,
+}
+
 /// An HTML [`a`](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/a)
 /// progressively enhanced to use client-side routing.
 ///
@@ -95,6 +124,15 @@ pub fn A<H>(
     /// If `true`, the router will scroll to the top of the window at the end of navigation. Defaults to `true`.
     #[prop(default = true)]
     scroll: bool,
+    /// When to load the code for a [lazily loaded route](crate::Lazy) this links to, before the
+    /// link is followed. Defaults to [`Prefetch::None`].
+    #[prop(optional)]
+    prefetch: Prefetch,
+    /// For a [`Router`](crate::components::Router) with [`Locales`](crate::Locales), the locale
+    /// to link to, which replaces any locale prefix in `href`. Defaults to the locale in the
+    /// prefix of the current URL, if any.
+    #[prop(optional, into)]
+    locale: Option<Oco<'static, str>>,
     /// The nodes or elements to be shown inside the link.
     children: Children,
 ) -> impl IntoView
@@ -108,9 +146,13 @@ where
         children: Children,
         strict_trailing_slash: bool,
         scroll: bool,
+        prefetch: Prefetch,
     ) -> impl IntoView {
-        let RouterContext { current_url, .. } =
-            use_context().expect("tried to use <A/> outside a <Router/>.");
+        let RouterContext {
+            current_url,
+            prefetch: prefetcher,
+            ..
+        } = use_context().expect("tried to use <A/> outside a <Router/>.");
         let is_active = {
             let href = href.clone();
             move || {
@@ -128,9 +170,34 @@ where
             }
         };
 
-        view! {
+        // each link loads its route at most once
+        let prefetched = Arc::new(AtomicBool::new(false));
+        let prefetch_route = {
+            let href = href.clone();
+            move || {
+                if prefetched.swap(true, Ordering::Relaxed) {
+                    return;
+                }
+                let prefetcher = prefetcher.get_value();
+                if let (Some(prefetcher), Some(to)) =
+                    (prefetcher, href.get_untracked())
+                {
+                    prefetcher(to.split(['?', '#']).next().unwrap_or_default());
+                }
+            }
+        };
+
+        // hash routes are linked as `#/path`, so that they also work when opened in a new tab
+        let location = use_context::<RouterLocation>();
+        let link = view! {
             <a
-                href=move || href.get().unwrap_or_default()
+                href=move || {
+                    let href = href.get().unwrap_or_default();
+                    match &location {
+                        Some(location) => location.to_href(href),
+                        None => href,
+                    }
+                }
                 target=target
                 aria-current=move || if is_active() { Some("page") } else { None }
                 data-noscroll=!scroll
@@ -139,10 +206,79 @@ where
                 {children()}
             </a>
         }
+        .into_inner();
+
+        // links that are not prefetched do not need to listen for anything
+        match prefetch {
+            Prefetch::None => EitherOf3::A(link),
+            Prefetch::Hover => EitherOf3::B(
+                link.on(ev::mouseenter, {
+                    let prefetch_route = prefetch_route.clone();
+                    move |_| prefetch_route()
+                })
+                .on(ev::focus, move |_| prefetch_route()),
+            ),
+            Prefetch::Visible => {
+                let node_ref = NodeRef::<leptos::html::A>::new();
+                Effect::new(move |_| {
+                    if let Some(el) = node_ref.get() {
+                        prefetch_when_visible(&el, prefetch_route.clone());
+                    }
+                });
+                EitherOf3::C(link.node_ref(node_ref))
+            }
+        }
     }
 
+    let router = use_context::<RouterContext>()
+        .expect("tried to use <A/> outside a <Router/>.");
     let href = use_resolved_path(move || href.to_href()());
-    inner(href, target, exact, children, strict_trailing_slash, scroll)
+    // only links are localized, so that form actions and asset paths keep pointing to the server
+    let href = ArcMemo::new(move |_| {
+        href.get()
+            .map(|href| router.localize(href, locale.as_deref()))
+    });
+    inner(
+        href,
+        target,
+        exact,
+        children,
+        strict_trailing_slash,
+        scroll,
+        prefetch,
+    )
+}
+
+/// Calls `prefetch` once `el` has scrolled into view.
+fn prefetch_when_visible(
+    el: &web_sys::HtmlAnchorElement,
+    prefetch: impl Fn() + 'static,
+) {
+    let callback = Closure::<dyn Fn(js_sys::Array, IntersectionObserver)>::new(
+        move |entries: js_sys::Array, observer: IntersectionObserver| {
+            let visible = entries.iter().any(|entry| {
+                entry
+                    .unchecked_into::<IntersectionObserverEntry>()
+                    .is_intersecting()
+            });
+            if visible {
+                observer.disconnect();
+                prefetch();
+            }
+        },
+    );
+    let Ok(observer) =
+        IntersectionObserver::new(callback.as_ref().unchecked_ref())
+    else {
+        return;
+    };
+    observer.observe(el);
+
+    let observer = SendWrapper::new((observer, callback));
+    on_cleanup(move || {
+        let (observer, _callback) = observer.take();
+        observer.disconnect();
+    });
 }
 
 // Test if `href` is active for `location`.  Assumes _both_ `href` and `location` begin with a `'/'`.
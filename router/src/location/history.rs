@@ -1,8 +1,12 @@
-use super::{handle_anchor_click, LocationChange, LocationProvider, Url};
-use crate::{hooks::use_navigate, params::ParamsMap};
+use super::{
+    handle_anchor_click, parse_url, scroll::ScrollRestoration, LocationChange,
+    LocationProvider, Url,
+};
+use crate::{blocker::Blockers, hooks::use_navigate, params::ParamsMap};
+use any_spawner::Executor;
 use core::fmt;
 use futures::channel::oneshot;
-use js_sys::{try_iter, Array, JsString};
+use js_sys::{try_iter, Array, JsString, Object, Reflect};
 use leptos::prelude::*;
 use or_poisoned::OrPoisoned;
 use reactive_graph::{
@@ -12,19 +16,46 @@ use reactive_graph::{
 use std::{
     borrow::Cow,
     boxed::Box,
+    cell::Cell,
+    rc::Rc,
     string::String,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
 };
 use tachys::dom::{document, window};
 use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 use web_sys::{Event, UrlSearchParams};
 
+/// A [`LocationProvider`] that uses the browser's
+/// [History API](https://developer.mozilla.org/en-US/docs/Web/API/History_API). This is the
+/// default for a [`Router`](crate::components::Router).
+///
+/// The scroll position of the window is saved for each history entry, and restored when going
+/// back or forward to it, once the route has loaded and its `<Suspense/>` boundaries have
+/// resolved. The position of any other scrolling element is saved and restored as well if it
+/// has a `data-scroll-restoration-id` attribute with an ID that is unique on the page:
+///
+/// ```rust,ignore
+/// view! {
+///     <ul class="sidebar" data-scroll-restoration-id="sidebar">
+///         // ...
+///     </ul>
+/// }
+/// ```
+///
+/// To tell its entries apart, the router adds a key and the entry's position in the history to
+/// the [`state`](https://developer.mozilla.org/en-US/docs/Web/API/History/state) of each entry.
+/// A state that is not an object is left as it is, so positions are not restored for that entry.
 #[derive(Clone)]
 pub struct BrowserUrl {
     url: ArcRwSignal<Url>,
     pub(crate) pending_navigation: Arc<Mutex<Option<oneshot::Sender<()>>>>,
     pub(crate) path_stack: ArcStoredValue<Vec<Url>>,
     pub(crate) is_back: ArcRwSignal<bool>,
+    scroll: ScrollRestoration,
+    index: EntryIndex,
 }
 
 impl fmt::Debug for BrowserUrl {
@@ -33,29 +64,6 @@ impl fmt::Debug for BrowserUrl {
     }
 }
 
-impl BrowserUrl {
-    fn scroll_to_el(loc_scroll: bool) {
-        if let Ok(hash) = window().location().hash() {
-            if !hash.is_empty() {
-                let hash = js_sys::decode_uri(&hash[1..])
-                    .ok()
-                    .and_then(|decoded| decoded.as_string())
-                    .unwrap_or(hash);
-                let el = document().get_element_by_id(&hash);
-                if let Some(el) = el {
-                    el.scroll_into_view();
-                    return;
-                }
-            }
-        }
-
-        // scroll to top
-        if loc_scroll {
-            window().scroll_to_with_x_and_y(0.0, 0.0);
-        }
-    }
-}
-
 impl LocationProvider for BrowserUrl {
     type Error = JsValue;
 
@@ -69,6 +77,8 @@ impl LocationProvider for BrowserUrl {
             pending_navigation: Default::default(),
             path_stack,
             is_back: Default::default(),
+            scroll: Default::default(),
+            index: Default::default(),
         })
     }
 
@@ -116,124 +126,39 @@ impl LocationProvider for BrowserUrl {
     }
 
     fn init(&self, base: Option<Cow<'static, str>>) {
-        let window = window();
-        let navigate = {
-            let url = self.url.clone();
-            let pending = Arc::clone(&self.pending_navigation);
-            let this = self.clone();
-            move |new_url: Url, loc| {
-                let same_path = {
-                    let curr = url.read_untracked();
-                    curr.origin() == new_url.origin()
-                        && curr.path() == new_url.path()
-                };
-
-                url.set(new_url.clone());
-                if same_path {
-                    this.complete_navigation(&loc);
-                }
-                let pending = Arc::clone(&pending);
-                let (tx, rx) = oneshot::channel::<()>();
-                if !same_path {
-                    *pending.lock().or_poisoned() = Some(tx);
-                }
-                let url = url.clone();
-                let this = this.clone();
-                async move {
-                    if !same_path {
-                        // if it has been canceled, ignore
-                        // otherwise, complete navigation -- i.e., set URL in address bar
-                        if rx.await.is_ok() {
-                            // only update the URL in the browser if this is still the current URL
-                            // if we've navigated to another page in the meantime, don't update the
-                            // browser URL
-                            let curr = url.read_untracked();
-                            if curr == new_url {
-                                this.complete_navigation(&loc);
-                            }
-                        }
-                    }
-                }
-            }
-        };
-
-        let handle_anchor_click =
-            handle_anchor_click(base, Self::parse_with_base, navigate);
-        let closure = Closure::wrap(Box::new(move |ev: Event| {
-            if let Err(e) = handle_anchor_click(ev) {
-                #[cfg(feature = "tracing")]
-                tracing::error!("{e:?}");
-                #[cfg(not(feature = "tracing"))]
-                web_sys::console::error_1(&e);
-            }
-        }) as Box<dyn FnMut(Event)>)
-        .into_js_value();
-        window
-            .add_event_listener_with_callback(
-                "click",
-                closure.as_ref().unchecked_ref(),
-            )
-            .expect(
-                "couldn't add `click` listener to `window` to handle `<a>` \
-                 clicks",
-            );
-
-        // handle popstate event (forward/back navigation)
-        let cb = {
-            let url = self.url.clone();
-            let path_stack = self.path_stack.clone();
-            let is_back = self.is_back.clone();
-            move || match Self::current() {
-                Ok(new_url) => {
-                    let stack = path_stack.read_value();
-                    let is_navigating_back = stack.len() == 1
-                        || (stack.len() >= 2
-                            && stack.get(stack.len() - 2) == Some(&new_url));
-
-                    is_back.set(is_navigating_back);
-
-                    url.set(new_url);
-                }
-                Err(e) => {
-                    #[cfg(feature = "tracing")]
-                    tracing::error!("{e:?}");
-                    #[cfg(not(feature = "tracing"))]
-                    web_sys::console::error_1(&e);
-                }
-            }
-        };
-        let closure =
-            Closure::wrap(Box::new(cb) as Box<dyn Fn()>).into_js_value();
-        window
-            .add_event_listener_with_callback(
-                "popstate",
-                closure.as_ref().unchecked_ref(),
-            )
-            .expect("couldn't add `popstate` listener to `window`");
+        self.scroll.init();
+        init_history(
+            self,
+            &self.url,
+            &self.pending_navigation,
+            &self.path_stack,
+            &self.is_back,
+            &self.index,
+            Some(&self.scroll),
+            base,
+        );
     }
 
     fn ready_to_complete(&self) {
         if let Some(tx) = self.pending_navigation.lock().or_poisoned().take() {
             _ = tx.send(());
         }
+        self.scroll.ready();
     }
 
     fn complete_navigation(&self, loc: &LocationChange) {
         let history = window().history().unwrap();
+        let state = self.scroll.push(&loc.state.to_js_value());
+        let state = self.index.next(&state, loc.replace);
 
         if loc.replace {
             history
-                .replace_state_with_url(
-                    &loc.state.to_js_value(),
-                    "",
-                    Some(&loc.value),
-                )
+                .replace_state_with_url(&state, "", Some(&loc.value))
                 .unwrap();
         } else {
             // push the "forward direction" marker
-            let state = &loc.state.to_js_value();
             history
-                .push_state_with_url(state, "", Some(&loc.value))
+                .push_state_with_url(&state, "", Some(&loc.value))
                 .unwrap();
         }
 
@@ -245,7 +170,12 @@ impl LocationProvider for BrowserUrl {
         }
 
         // scroll to el
-        Self::scroll_to_el(loc.scroll);
+        let hash = window().location().hash().unwrap_or_default();
+        self.scroll.scroll_to_hash(hash, loc.scroll);
+    }
+
+    fn before_navigate(&self) {
+        self.scroll.save();
     }
 
     fn redirect(loc: &str) {
@@ -271,6 +201,481 @@ impl LocationProvider for BrowserUrl {
     }
 }
 
+/// A [`LocationProvider`] that keeps the route in the hash fragment of the browser's URL
+/// ([`location.hash`](https://developer.mozilla.org/en-US/docs/Web/API/Location/hash)), so
+/// that `https://example.com/app/#/users/1?tab=posts` is routed as `/users/1?tab=posts`.
+///
+/// This is useful for client-side rendered apps that are served as static files, from a host
+/// that cannot be configured to serve the app for every path. The hash is never sent to the
+/// server, so this cannot be used with server-side rendering.
+///
+/// Links created with [`<A>`](crate::components::A) are rendered with the `#` prefix, so they
+/// can also be opened in a new tab. Navigating with [`use_navigate`], a `GET` [`<Form>`]
+/// submission, or an ordinary `<a>` link uses the same route paths as [`BrowserUrl`]. A link to
+/// an in-page anchor, like `<a href="#section">`, is not a route, and is left to the browser.
+///
+/// ```rust,ignore
+/// view! {
+///     <Router location=HashUrl::new().unwrap()>
+///         // ...
+///     </Router>
+/// }
+/// ```
+///
+/// [`<Form>`]: crate::components::Form
+#[derive(Clone)]
+pub struct HashUrl {
+    url: ArcRwSignal<Url>,
+    pending_navigation: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    path_stack: ArcStoredValue<Vec<Url>>,
+    is_back: ArcRwSignal<bool>,
+    index: EntryIndex,
+}
+
+impl fmt::Debug for HashUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashUrl").finish_non_exhaustive()
+    }
+}
+
+impl HashUrl {
+    /// Returns the route stored in a hash fragment, like `/users?tab=posts` for
+    /// `#/users?tab=posts`, or `None` if the fragment is an in-page anchor like `#section`. An
+    /// empty fragment is the root route.
+    fn route(hash: &str) -> Option<&str> {
+        let fragment = hash.strip_prefix('#').unwrap_or(hash);
+        if fragment.is_empty() {
+            Some("/")
+        } else if fragment.starts_with('/') {
+            Some(fragment)
+        } else {
+            None
+        }
+    }
+
+    /// Parses the route stored in a hash fragment. An in-page anchor is parsed as the root
+    /// route.
+    fn parse_route(hash: &str, origin: &str) -> Result<Url, JsValue> {
+        let route = Self::route(hash).unwrap_or("/");
+        parse_url(route, origin).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+impl LocationProvider for HashUrl {
+    type Error = JsValue;
+
+    fn new() -> Result<Self, JsValue> {
+        let url = ArcRwSignal::new(Self::current()?);
+        let path_stack = ArcStoredValue::new(
+            Self::current().map(|n| vec![n]).unwrap_or_default(),
+        );
+        Ok(Self {
+            url,
+            pending_navigation: Default::default(),
+            path_stack,
+            is_back: Default::default(),
+            index: Default::default(),
+        })
+    }
+
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        &self.url
+    }
+
+    fn current() -> Result<Url, Self::Error> {
+        let location = window().location();
+        Self::parse_route(&location.hash()?, &location.origin()?)
+    }
+
+    fn parse(url: &str) -> Result<Url, Self::Error> {
+        let base = window().location().origin()?;
+        Self::parse_with_base(url, &base)
+    }
+
+    fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error> {
+        // a full URL (like the `href` of a clicked link) carries its route in the hash,
+        // while a path passed to `navigate` is already the route itself
+        let parsed = parse_url(url, base)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if parsed.hash().starts_with("#/") {
+            Self::parse_route(parsed.hash(), parsed.origin())
+        } else {
+            Ok(parsed)
+        }
+    }
+
+    fn to_href(path: String) -> String {
+        if path.starts_with('/') {
+            format!("#{path}")
+        } else {
+            path
+        }
+    }
+
+    fn init(&self, base: Option<Cow<'static, str>>) {
+        init_history(
+            self,
+            &self.url,
+            &self.pending_navigation,
+            &self.path_stack,
+            &self.is_back,
+            &self.index,
+            None,
+            base,
+        );
+    }
+
+    fn ready_to_complete(&self) {
+        if let Some(tx) = self.pending_navigation.lock().or_poisoned().take() {
+            _ = tx.send(());
+        }
+    }
+
+    fn complete_navigation(&self, loc: &LocationChange) {
+        let history = window().history().unwrap();
+        let href = format!("#{}", loc.value);
+        let state = self.index.next(&loc.state.to_js_value(), loc.replace);
+
+        if loc.replace {
+            history
+                .replace_state_with_url(&state, "", Some(&href))
+                .unwrap();
+        } else {
+            history
+                .push_state_with_url(&state, "", Some(&href))
+                .unwrap();
+        }
+
+        if let Ok(url) = Self::current() {
+            self.path_stack.write_value().push(url);
+            self.is_back.set(false);
+        }
+
+        // the route's own hash is nested inside the URL's hash
+        let hash = self.url.read_untracked().hash().to_string();
+        scroll_to_hash(&hash, loc.scroll);
+    }
+
+    fn redirect(loc: &str) {
+        BrowserUrl::redirect(loc)
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        self.is_back.read_only().into()
+    }
+}
+
+/// The ways in which the providers that use the browser's History API read the browser's URL
+/// differently.
+trait HistoryLocation: LocationProvider<Error = JsValue> {
+    /// Parses the `href` of a clicked link, or returns `None` if the link is not to a route and
+    /// should be followed by the browser.
+    fn parse_link(href: &str, base: &str) -> Result<Option<Url>, JsValue> {
+        Self::parse_with_base(href, base).map(Some)
+    }
+
+    /// Reads the URL after a `popstate` event, or returns `None` if the browser has moved to a
+    /// location that is not a route.
+    fn current_route() -> Result<Option<Url>, JsValue> {
+        Self::current().map(Some)
+    }
+}
+
+impl HistoryLocation for BrowserUrl {}
+
+impl HistoryLocation for HashUrl {
+    fn parse_link(href: &str, base: &str) -> Result<Option<Url>, JsValue> {
+        let fragment = href.split_once('#').map(|(_, fragment)| fragment);
+        match fragment {
+            Some(fragment) if Self::route(fragment).is_none() => Ok(None),
+            _ => Self::parse_with_base(href, base).map(Some),
+        }
+    }
+
+    fn current_route() -> Result<Option<Url>, JsValue> {
+        let location = window().location();
+        let hash = location.hash()?;
+        match Self::route(&hash) {
+            Some(_) => Self::parse_route(&hash, &location.origin()?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Sets up the global `click` and `popstate` listeners shared by the providers that use the
+/// browser's History API.
+#[allow(clippy::too_many_arguments)]
+fn init_history<L>(
+    this: &L,
+    url: &ArcRwSignal<Url>,
+    pending_navigation: &Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    path_stack: &ArcStoredValue<Vec<Url>>,
+    is_back: &ArcRwSignal<bool>,
+    index: &EntryIndex,
+    scroll: Option<&ScrollRestoration>,
+    base: Option<Cow<'static, str>>,
+) where
+    L: HistoryLocation,
+{
+    let window = window();
+    let blockers = use_context::<Blockers>();
+    index.init();
+    let navigate = {
+        let url = url.clone();
+        let pending = Arc::clone(pending_navigation);
+        let this = this.clone();
+        move |new_url: Url, loc| {
+            let same_path = {
+                let curr = url.read_untracked();
+                curr.origin() == new_url.origin()
+                    && curr.path() == new_url.path()
+            };
+
+            this.before_navigate();
+            url.set(new_url.clone());
+            if same_path {
+                this.complete_navigation(&loc);
+            }
+            let pending = Arc::clone(&pending);
+            let (tx, rx) = oneshot::channel::<()>();
+            if !same_path {
+                *pending.lock().or_poisoned() = Some(tx);
+            }
+            let url = url.clone();
+            let this = this.clone();
+            async move {
+                if !same_path {
+                    // if it has been canceled, ignore
+                    // otherwise, complete navigation -- i.e., set URL in address bar
+                    if rx.await.is_ok() {
+                        // only update the URL in the browser if this is still the current URL
+                        // if we've navigated to another page in the meantime, don't update the
+                        // browser URL
+                        let curr = url.read_untracked();
+                        if curr == new_url {
+                            this.complete_navigation(&loc);
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    // link clicks are held back while a blocker is active
+    let navigate = {
+        let url = url.clone();
+        let blockers = blockers.clone();
+        move |new_url: Url, loc: LocationChange| {
+            let blocked = blockers.as_ref().is_some_and(|blockers| {
+                *url.read_untracked() != new_url
+                    && blockers.block(&new_url, {
+                        let navigate = navigate.clone();
+                        let new_url = new_url.clone();
+                        let loc = loc.clone();
+                        move || Executor::spawn_local(navigate(new_url, loc))
+                    })
+            });
+            let navigation = (!blocked).then(|| navigate(new_url, loc));
+            async move {
+                if let Some(navigation) = navigation {
+                    navigation.await;
+                }
+            }
+        }
+    };
+
+    let handle_anchor_click =
+        handle_anchor_click(base, L::parse_link, navigate);
+    let closure = Closure::wrap(Box::new(move |ev: Event| {
+        if let Err(e) = handle_anchor_click(ev) {
+            #[cfg(feature = "tracing")]
+            tracing::error!("{e:?}");
+            #[cfg(not(feature = "tracing"))]
+            web_sys::console::error_1(&e);
+        }
+    }) as Box<dyn FnMut(Event)>)
+    .into_js_value();
+    window
+        .add_event_listener_with_callback(
+            "click",
+            closure.as_ref().unchecked_ref(),
+        )
+        .expect(
+            "couldn't add `click` listener to `window` to handle `<a>` \
+             clicks",
+        );
+
+    // handle popstate event (forward/back navigation)
+    let cb = {
+        let url = url.clone();
+        let path_stack = path_stack.clone();
+        let is_back = is_back.clone();
+        // a blocked back or forward navigation has already happened in the browser's history, so
+        // it is undone, and redone if the blocker lets it proceed
+        let undoing = Rc::new(Cell::new(false));
+        let redoing = Rc::new(Cell::new(false));
+        let history = window.history().unwrap();
+        let index = index.clone();
+        let scroll = scroll.cloned();
+        move || match L::current_route() {
+            // the browser has scrolled to an in-page anchor, and the route stays the same
+            Ok(None) => {}
+            Ok(Some(new_url)) => {
+                if undoing.replace(false) {
+                    return;
+                }
+                let stack = path_stack.read_value();
+                let is_navigating_back = stack.len() == 1
+                    || (stack.len() >= 2
+                        && stack.get(stack.len() - 2) == Some(&new_url));
+                let new_index =
+                    history.state().ok().and_then(|state| entry_index(&state));
+
+                if !redoing.replace(false) {
+                    // how far the browser has moved through its history; without an index for
+                    // the entry, this is a guess of a single step
+                    let delta = match new_index {
+                        Some(new_index) => {
+                            i64::from(new_index) - i64::from(index.get())
+                        }
+                        None if is_navigating_back => -1,
+                        None => 1,
+                    } as i32;
+                    let blocked = blockers.as_ref().is_some_and(|blockers| {
+                        blockers.block(&new_url, {
+                            let redoing = Rc::clone(&redoing);
+                            let history = history.clone();
+                            move || {
+                                redoing.set(true);
+                                _ = history.go_with_delta(delta);
+                            }
+                        })
+                    });
+                    if blocked {
+                        undoing.set(true);
+                        _ = history.go_with_delta(-delta);
+                        return;
+                    }
+                }
+
+                if let Some(new_index) = new_index {
+                    index.set(new_index);
+                }
+
+                if let Some(scroll) = &scroll {
+                    scroll.pop();
+                }
+                is_back.set(is_navigating_back);
+
+                url.set(new_url);
+            }
+            Err(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!("{e:?}");
+                #[cfg(not(feature = "tracing"))]
+                web_sys::console::error_1(&e);
+            }
+        }
+    };
+    let closure = Closure::wrap(Box::new(cb) as Box<dyn Fn()>).into_js_value();
+    window
+        .add_event_listener_with_callback(
+            "popstate",
+            closure.as_ref().unchecked_ref(),
+        )
+        .expect("couldn't add `popstate` listener to `window`");
+}
+
+/// The property of the history state that holds the position of its entry in the session
+/// history.
+const INDEX_KEY: &str = "__leptos_router_index";
+
+/// The position of the current entry in the session history, which is stored in the state of
+/// each entry so that a blocked back or forward navigation can be undone by the number of steps
+/// it went.
+#[derive(Clone, Default)]
+struct EntryIndex(Arc<AtomicU32>);
+
+impl EntryIndex {
+    /// Reads the index of the current entry, giving it one if it is new.
+    fn init(&self) {
+        let history = window().history().unwrap();
+        let state = history.state().unwrap_or(JsValue::UNDEFINED);
+        match entry_index(&state) {
+            Some(index) => self.set(index),
+            None => {
+                // a new entry is the last one, since it replaced any that came after
+                let index = history.length().unwrap_or(1).saturating_sub(1);
+                self.set(index);
+                _ = history.replace_state(&with_entry_index(&state, index), "");
+            }
+        }
+    }
+
+    fn get(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, index: u32) {
+        self.0.store(index, Ordering::Relaxed);
+    }
+
+    /// Returns the history state for the entry that a navigation pushes, or replaces the current
+    /// entry with.
+    fn next(&self, state: &JsValue, replace: bool) -> JsValue {
+        if !replace {
+            self.set(self.get() + 1);
+        }
+        with_entry_index(state, self.get())
+    }
+}
+
+fn entry_index(state: &JsValue) -> Option<u32> {
+    if !state.is_object() {
+        return None;
+    }
+    Reflect::get(state, &INDEX_KEY.into())
+        .ok()
+        .and_then(|index| index.as_f64())
+        .map(|index| index as u32)
+}
+
+/// Adds the entry index to a copy of the history state. A state that is not an object is left
+/// as it is, so a blocked navigation to its entry is undone by a single step.
+fn with_entry_index(state: &JsValue, index: u32) -> JsValue {
+    let indexed = if state.is_undefined() || state.is_null() {
+        Object::new()
+    } else if state.is_object() && !Array::is_array(state) {
+        Object::assign(&Object::new(), state.unchecked_ref())
+    } else {
+        return state.clone();
+    };
+    _ = Reflect::set(&indexed, &INDEX_KEY.into(), &index.into());
+    indexed.into()
+}
+
+/// Scrolls to the element whose ID matches the given hash, or to the top of the page if there
+/// is no such element and `loc_scroll` is `true`. Returns whether the element was found.
+pub(super) fn scroll_to_hash(hash: &str, loc_scroll: bool) -> bool {
+    if !hash.is_empty() {
+        let hash = js_sys::decode_uri(&hash[1..])
+            .ok()
+            .and_then(|decoded| decoded.as_string())
+            .unwrap_or_else(|| hash.to_string());
+        let el = document().get_element_by_id(&hash);
+        if let Some(el) = el {
+            el.scroll_into_view();
+            return true;
+        }
+    }
+
+    // scroll to top
+    if loc_scroll {
+        window().scroll_to_with_x_and_y(0.0, 0.0);
+    }
+    false
+}
+
 fn search_params_from_web_url(
     params: &web_sys::UrlSearchParams,
 ) -> Result<ParamsMap, JsValue> {
@@ -313,3 +718,67 @@ pub(crate) fn resolve_redirect_url(loc: &str) -> Option<web_sys::Url> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{HashUrl, HistoryLocation};
+    use crate::location::LocationProvider;
+
+    const ORIGIN: &str = "https://example.com";
+
+    #[test]
+    fn parses_route_from_hash() {
+        let url = HashUrl::parse_route("#/a?b=1", ORIGIN).unwrap();
+        assert_eq!(url.path(), "/a");
+        assert_eq!(url.search(), "b=1");
+        assert_eq!(url.search_params().get_str("b"), Some("1"));
+        assert_eq!(url.hash(), "");
+
+        // the route can have a hash of its own
+        let url = HashUrl::parse_route("#/a#section", ORIGIN).unwrap();
+        assert_eq!(url.path(), "/a");
+        assert_eq!(url.hash(), "#section");
+
+        // an empty hash is the root route, and so is an in-page anchor
+        for hash in ["", "#", "#anchor"] {
+            let url = HashUrl::parse_route(hash, ORIGIN).unwrap();
+            assert_eq!(url.path(), "/", "{hash}");
+        }
+        assert_eq!(HashUrl::route("#anchor"), None);
+    }
+
+    #[test]
+    fn parses_full_urls_and_paths() {
+        let url =
+            HashUrl::parse_with_base("https://example.com/app/#/a?b=1", ORIGIN)
+                .unwrap();
+        assert_eq!(url.path(), "/a");
+        assert_eq!(url.search(), "b=1");
+
+        // a path passed to `navigate` is the route itself
+        let url = HashUrl::parse_with_base("/a?b=1", ORIGIN).unwrap();
+        assert_eq!(url.path(), "/a");
+        assert_eq!(url.search(), "b=1");
+
+        let url =
+            HashUrl::parse_with_base("https://example.com/", ORIGIN).unwrap();
+        assert_eq!(url.path(), "/");
+        assert_eq!(url.hash(), "");
+
+        let url =
+            HashUrl::parse_with_base("https://example.com/page#anchor", ORIGIN)
+                .unwrap();
+        assert_eq!(url.path(), "/page");
+        assert_eq!(url.hash(), "#anchor");
+    }
+
+    #[test]
+    fn leaves_in_page_anchors_to_the_browser() {
+        let link = |href| HashUrl::parse_link(href, ORIGIN).unwrap();
+        assert!(link("https://example.com/#anchor").is_none());
+        assert!(link("https://example.com/page#anchor").is_none());
+        assert_eq!(link("https://example.com/#/a?b=1").unwrap().path(), "/a");
+        assert_eq!(link("https://example.com/#").unwrap().path(), "/");
+        assert_eq!(link("https://example.com/about").unwrap().path(), "/about");
+    }
+}
@@ -0,0 +1,411 @@
+use super::{parse_url, LocationChange, LocationProvider, State, Url};
+use crate::hooks::use_navigate;
+use core::fmt;
+use leptos::prelude::*;
+use reactive_graph::{
+    signal::ArcRwSignal,
+    traits::{GetUntracked, Set},
+};
+use std::borrow::Cow;
+
+/// A [`LocationProvider`] that keeps its history in memory, rather than using the browser's
+/// [`History`](https://developer.mozilla.org/en-US/docs/Web/API/History) API.
+///
+/// This can be used wherever there is no browser address bar to synchronize with: in native
+/// unit tests, in headless environments, or in a webview that manages its own navigation.
+/// Like the browser's history, it keeps a stack of entries that can be navigated with
+/// [`back`](MemoryHistory::back) and [`forward`](MemoryHistory::forward).
+///
+/// ```rust,ignore
+/// let history = MemoryHistory::with_url("/users/1").unwrap();
+/// view! {
+///     <Router location=history.clone()>
+///         // ...
+///     </Router>
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MemoryHistory {
+    url: ArcRwSignal<Url>,
+    stack: ArcStoredValue<HistoryStack>,
+    is_back: ArcRwSignal<bool>,
+}
+
+#[derive(Debug)]
+struct HistoryStack {
+    entries: Vec<(Url, State)>,
+    index: usize,
+}
+
+impl fmt::Debug for MemoryHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryHistory").finish_non_exhaustive()
+    }
+}
+
+impl MemoryHistory {
+    /// Creates a new history, with a single entry for the given URL.
+    pub fn with_url(url: &str) -> Result<Self, url::ParseError> {
+        let url = Self::parse(url)?;
+        Ok(Self {
+            url: ArcRwSignal::new(url.clone()),
+            stack: ArcStoredValue::new(HistoryStack {
+                entries: vec![(url, State::default())],
+                index: 0,
+            }),
+            is_back: Default::default(),
+        })
+    }
+
+    /// Adds a new entry with the given [`State`] to the history, and navigates to it.
+    ///
+    /// Any entries after the current one are discarded, in the same way that navigating in a
+    /// browser clears the forward history.
+    pub fn push(&self, url: &str, state: State) -> Result<(), url::ParseError> {
+        self.navigate_to(url, false, state)
+    }
+
+    /// Replaces the current entry in the history with the given URL and [`State`], and
+    /// navigates to it.
+    pub fn replace(
+        &self,
+        url: &str,
+        state: State,
+    ) -> Result<(), url::ParseError> {
+        self.navigate_to(url, true, state)
+    }
+
+    /// Navigates to the previous entry in the history, if there is one.
+    pub fn back(&self) {
+        self.go(-1);
+    }
+
+    /// Navigates to the next entry in the history, if there is one.
+    pub fn forward(&self) {
+        self.go(1);
+    }
+
+    /// Moves `delta` entries backward (if negative) or forward (if positive) in the history.
+    ///
+    /// The position is clamped to the entries that exist.
+    pub fn go(&self, delta: isize) {
+        let url = self.stack.try_update_value(|stack| {
+            let last = stack.entries.len() - 1;
+            let index = stack.index.saturating_add_signed(delta).min(last);
+            if index == stack.index {
+                return None;
+            }
+            stack.index = index;
+            Some(stack.entries[index].0.clone())
+        });
+        if let Some(Some(url)) = url {
+            self.is_back.set(delta < 0);
+            self.url.set(url);
+        }
+    }
+
+    /// Whether there is an entry before the current one.
+    pub fn can_go_back(&self) -> bool {
+        self.stack.with_value(|stack| stack.index > 0)
+    }
+
+    /// Whether there is an entry after the current one.
+    pub fn can_go_forward(&self) -> bool {
+        self.stack
+            .with_value(|stack| stack.index + 1 < stack.entries.len())
+    }
+
+    /// The number of entries in the history.
+    pub fn len(&self) -> usize {
+        self.stack.with_value(|stack| stack.entries.len())
+    }
+
+    /// Whether the history is empty. This is never the case, as it always contains at least
+    /// the entry it was created with.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The [`State`] that was stored with the current entry.
+    pub fn state(&self) -> State {
+        self.stack
+            .with_value(|stack| stack.entries[stack.index].1.clone())
+    }
+
+    fn navigate_to(
+        &self,
+        url: &str,
+        replace: bool,
+        state: State,
+    ) -> Result<(), url::ParseError> {
+        let url = Self::parse(url)?;
+        self.stack.update_value(|stack| {
+            if replace {
+                stack.entries[stack.index] = (url.clone(), state);
+            } else {
+                stack.entries.truncate(stack.index + 1);
+                stack.entries.push((url.clone(), state));
+                stack.index += 1;
+            }
+        });
+        self.is_back.set(false);
+        if self.url.get_untracked() != url {
+            self.url.set(url);
+        }
+        Ok(())
+    }
+}
+
+impl LocationProvider for MemoryHistory {
+    type Error = url::ParseError;
+
+    fn new() -> Result<Self, Self::Error> {
+        Self::with_url("/")
+    }
+
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        &self.url
+    }
+
+    /// A `MemoryHistory` is not tied to any global location, so this always returns the root
+    /// URL. Use [`as_url`](LocationProvider::as_url) to access the URL of a particular history.
+    fn current() -> Result<Url, Self::Error> {
+        Self::parse("/")
+    }
+
+    fn init(&self, _base: Option<Cow<'static, str>>) {}
+
+    fn ready_to_complete(&self) {}
+
+    fn complete_navigation(&self, loc: &LocationChange) {
+        if let Err(e) =
+            self.navigate_to(&loc.value, loc.replace, loc.state.clone())
+        {
+            leptos::logging::error!("Error parsing URL: {e:?}");
+        }
+    }
+
+    fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error> {
+        parse_url(url, base)
+    }
+
+    fn redirect(loc: &str) {
+        let navigate = use_navigate();
+        navigate(loc, Default::default());
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        self.is_back.read_only().into()
+    }
+}
+
+impl Default for MemoryHistory {
+    fn default() -> Self {
+        Self::with_url("/").expect("the root URL is always valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryHistory;
+    use crate::location::{LocationChange, LocationProvider, State};
+    use reactive_graph::traits::GetUntracked;
+
+    fn path(history: &MemoryHistory) -> String {
+        history.as_url().get_untracked().path().to_string()
+    }
+
+    #[test]
+    pub fn should_start_at_initial_url() {
+        let history = MemoryHistory::with_url("/foo#bar").unwrap();
+        let url = history.as_url().get_untracked();
+        assert_eq!(url.path(), "/foo");
+        assert_eq!(url.hash(), "#bar");
+        assert_eq!(history.len(), 1);
+        assert!(!history.can_go_back());
+    }
+
+    #[test]
+    pub fn should_navigate_back_and_forward() {
+        let history = MemoryHistory::default();
+        history.push("/a", State::default()).unwrap();
+        history.push("/b", State::default()).unwrap();
+        assert_eq!(path(&history), "/b");
+
+        history.back();
+        assert_eq!(path(&history), "/a");
+        assert!(history.is_back().get_untracked());
+
+        history.back();
+        history.back();
+        assert_eq!(path(&history), "/");
+
+        history.forward();
+        assert_eq!(path(&history), "/a");
+        assert!(!history.is_back().get_untracked());
+        assert!(history.can_go_forward());
+    }
+
+    #[test]
+    pub fn should_discard_forward_entries_on_push() {
+        let history = MemoryHistory::default();
+        history.push("/a", State::default()).unwrap();
+        history.push("/b", State::default()).unwrap();
+        history.go(-2);
+        history.push("/c", State::default()).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(!history.can_go_forward());
+        history.back();
+        assert_eq!(path(&history), "/");
+    }
+
+    #[test]
+    pub fn should_replace_current_entry() {
+        let history = MemoryHistory::default();
+        history.push("/a", State::default()).unwrap();
+        history.complete_navigation(&LocationChange {
+            value: "/b".into(),
+            replace: true,
+            ..Default::default()
+        });
+        assert_eq!(path(&history), "/b");
+        assert_eq!(history.len(), 2);
+        history.back();
+        assert_eq!(path(&history), "/");
+    }
+
+    /// Routes are rendered to HTML, as there is no DOM to mount them to in native tests.
+    #[cfg(feature = "ssr")]
+    mod router {
+        use super::MemoryHistory;
+        use crate::{
+            components::{Route, Router, Routes, A},
+            hooks::use_location,
+            location::{LocationChange, LocationProvider, State, Url},
+            StaticSegment,
+        };
+        use leptos::prelude::*;
+        use reactive_graph::{signal::ArcRwSignal, traits::GetUntracked};
+        use std::borrow::Cow;
+
+        #[test]
+        pub fn should_drive_router() {
+            let owner = Owner::new();
+            owner.set();
+            let history = MemoryHistory::with_url("/b").unwrap();
+            let pathname = StoredValue::new(None);
+            let provided = StoredValue::new(false);
+            let app = {
+                let history = history.clone();
+                move || {
+                    view! {
+                        <Router location=history.clone()>
+                            {move || {
+                                pathname.set_value(Some(use_location().pathname));
+                                // the provider itself is available, like `BrowserUrl` is
+                                provided.set_value(
+                                    use_context::<MemoryHistory>().is_some(),
+                                );
+                            }}
+                            <Routes fallback=|| "not found">
+                                <Route path=StaticSegment("a") view=|| "page a" />
+                                <Route path=StaticSegment("b") view=|| "page b" />
+                            </Routes>
+                        </Router>
+                    }
+                }
+            };
+            let html = app().to_html();
+            assert!(html.contains("page b"), "{html}");
+            assert!(provided.get_value());
+
+            history.push("/a", State::default()).unwrap();
+            let pathname = pathname.get_value().unwrap();
+            assert_eq!(pathname.get_untracked(), "/a");
+            let html = app().to_html();
+            assert!(html.contains("page a"), "{html}");
+
+            history.push("/c", State::default()).unwrap();
+            let html = app().to_html();
+            assert!(html.contains("not found"), "{html}");
+        }
+
+        /// A provider that is not built into the router, which links to its routes with `#!`.
+        #[derive(Clone)]
+        struct HashBang(MemoryHistory);
+
+        impl LocationProvider for HashBang {
+            type Error = url::ParseError;
+
+            fn new() -> Result<Self, Self::Error> {
+                MemoryHistory::new().map(Self)
+            }
+
+            fn as_url(&self) -> &ArcRwSignal<Url> {
+                self.0.as_url()
+            }
+
+            fn current() -> Result<Url, Self::Error> {
+                MemoryHistory::current()
+            }
+
+            fn init(&self, base: Option<Cow<'static, str>>) {
+                self.0.init(base)
+            }
+
+            fn ready_to_complete(&self) {}
+
+            fn complete_navigation(&self, loc: &LocationChange) {
+                self.0.complete_navigation(loc)
+            }
+
+            fn parse_with_base(
+                url: &str,
+                base: &str,
+            ) -> Result<Url, Self::Error> {
+                MemoryHistory::parse_with_base(url, base)
+            }
+
+            fn to_href(path: String) -> String {
+                format!("#!{path}")
+            }
+
+            fn redirect(loc: &str) {
+                MemoryHistory::redirect(loc)
+            }
+
+            fn is_back(&self) -> ReadSignal<bool> {
+                self.0.is_back()
+            }
+        }
+
+        #[test]
+        pub fn should_drive_router_with_custom_provider() {
+            let owner = Owner::new();
+            owner.set();
+            let history = MemoryHistory::with_url("/b").unwrap();
+            let app = {
+                let history = history.clone();
+                move || {
+                    view! {
+                        <Router location=HashBang(history.clone())>
+                            <A href="/a">"to a"</A>
+                            <Routes fallback=|| "not found">
+                                <Route path=StaticSegment("a") view=|| "page a" />
+                                <Route path=StaticSegment("b") view=|| "page b" />
+                            </Routes>
+                        </Router>
+                    }
+                }
+            };
+            let html = app().to_html();
+            assert!(html.contains("page b"), "{html}");
+            assert!(html.contains(r##"href="#!/a""##), "{html}");
+
+            history.replace("/a", State::default()).unwrap();
+            assert_eq!(history.len(), 1);
+            let html = app().to_html();
+            assert!(html.contains("page a"), "{html}");
+        }
+    }
+}
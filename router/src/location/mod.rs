@@ -11,15 +11,18 @@ use reactive_graph::{
     traits::With,
 };
 use send_wrapper::SendWrapper;
-use std::{borrow::Cow, future::Future};
+use std::{borrow::Cow, future::Future, sync::Arc};
 use tachys::dom::window;
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{Event, HtmlAnchorElement, MouseEvent};
 
 mod history;
+mod memory;
+mod scroll;
 mod server;
 use crate::params::ParamsMap;
 pub use history::*;
+pub use memory::*;
 pub use server::*;
 
 pub(crate) const BASE: &str = "https://leptos.dev";
@@ -100,11 +103,11 @@ impl Url {
     }
 
     pub fn escape(s: &str) -> String {
-        #[cfg(not(feature = "ssr"))]
+        #[cfg(all(not(feature = "ssr"), target_arch = "wasm32"))]
         {
             js_sys::encode_uri_component(s).as_string().unwrap()
         }
-        #[cfg(feature = "ssr")]
+        #[cfg(any(feature = "ssr", not(target_arch = "wasm32")))]
         {
             percent_encoding::utf8_percent_encode(
                 s,
@@ -115,7 +118,7 @@ impl Url {
     }
 
     pub fn unescape(s: &str) -> String {
-        #[cfg(feature = "ssr")]
+        #[cfg(any(feature = "ssr", not(target_arch = "wasm32")))]
         {
             percent_encoding::percent_decode_str(s)
                 .decode_utf8()
@@ -123,7 +126,7 @@ impl Url {
                 .to_string()
         }
 
-        #[cfg(not(feature = "ssr"))]
+        #[cfg(all(not(feature = "ssr"), target_arch = "wasm32"))]
         {
             match js_sys::decode_uri_component(s) {
                 Ok(v) => v.into(),
@@ -133,7 +136,7 @@ impl Url {
     }
 
     pub fn unescape_minimal(s: &str) -> String {
-        #[cfg(not(feature = "ssr"))]
+        #[cfg(all(not(feature = "ssr"), target_arch = "wasm32"))]
         {
             match js_sys::decode_uri(s) {
                 Ok(v) => v.into(),
@@ -141,7 +144,7 @@ impl Url {
             }
         }
 
-        #[cfg(feature = "ssr")]
+        #[cfg(any(feature = "ssr", not(target_arch = "wasm32")))]
         {
             Self::unescape(s)
         }
@@ -230,18 +233,188 @@ pub trait LocationProvider: Clone + 'static {
     /// Update the browser's history to reflect a new location.
     fn complete_navigation(&self, loc: &LocationChange);
 
+    /// Called when the URL is about to change, while the current page is still rendered.
+    fn before_navigate(&self) {}
+
     fn parse(url: &str) -> Result<Url, Self::Error> {
         Self::parse_with_base(url, BASE)
     }
 
     fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error>;
 
+    /// Converts a route path into the `href` of a link to that route. Defaults to the path
+    /// itself.
+    fn to_href(path: String) -> String {
+        path
+    }
+
     fn redirect(loc: &str);
 
     /// Whether we are currently in a "back" navigation.
     fn is_back(&self) -> ReadSignal<bool>;
 }
 
+/// The [`LocationProvider`] used by a [`Router`](crate::components::Router).
+///
+/// The router uses [`BrowserUrl`] by default. Any other provider, like [`HashUrl`],
+/// [`MemoryHistory`], or one of your own, can be passed to its `location` prop, and converted
+/// into this type with `.into()`.
+#[derive(Clone)]
+pub struct RouterLocation(Arc<dyn DynLocationProvider>);
+
+impl Debug for RouterLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RouterLocation").finish_non_exhaustive()
+    }
+}
+
+/// An object-safe version of [`LocationProvider`], so that the router does not need to be
+/// generic over its location.
+trait DynLocationProvider: Send + Sync {
+    fn as_url(&self) -> &ArcRwSignal<Url>;
+
+    fn init(&self, base: Option<Cow<'static, str>>);
+
+    fn ready_to_complete(&self);
+
+    fn complete_navigation(&self, change: &LocationChange);
+
+    fn before_navigate(&self);
+
+    fn is_back(&self) -> ReadSignal<bool>;
+
+    fn parse(&self, url: &str) -> Result<Url, String>;
+
+    fn to_href(&self, path: String) -> String;
+
+    fn redirect(&self, loc: &str);
+
+    fn provide_context(&self);
+}
+
+impl<T> DynLocationProvider for T
+where
+    T: LocationProvider + Send + Sync,
+{
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        LocationProvider::as_url(self)
+    }
+
+    fn init(&self, base: Option<Cow<'static, str>>) {
+        LocationProvider::init(self, base)
+    }
+
+    fn ready_to_complete(&self) {
+        LocationProvider::ready_to_complete(self)
+    }
+
+    fn complete_navigation(&self, change: &LocationChange) {
+        LocationProvider::complete_navigation(self, change)
+    }
+
+    fn before_navigate(&self) {
+        LocationProvider::before_navigate(self)
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        LocationProvider::is_back(self)
+    }
+
+    fn parse(&self, url: &str) -> Result<Url, String> {
+        T::parse(url).map_err(|e| format!("{e:?}"))
+    }
+
+    fn to_href(&self, path: String) -> String {
+        T::to_href(path)
+    }
+
+    fn redirect(&self, loc: &str) {
+        T::redirect(loc)
+    }
+
+    fn provide_context(&self) {
+        provide_context(self.clone())
+    }
+}
+
+impl RouterLocation {
+    pub(crate) fn as_url(&self) -> &ArcRwSignal<Url> {
+        self.0.as_url()
+    }
+
+    pub(crate) fn init(&self, base: Option<Cow<'static, str>>) {
+        self.0.init(base)
+    }
+
+    pub(crate) fn ready_to_complete(&self) {
+        self.0.ready_to_complete()
+    }
+
+    pub(crate) fn complete_navigation(&self, change: &LocationChange) {
+        self.0.complete_navigation(change)
+    }
+
+    pub(crate) fn before_navigate(&self) {
+        self.0.before_navigate()
+    }
+
+    pub(crate) fn is_back(&self) -> ReadSignal<bool> {
+        self.0.is_back()
+    }
+
+    /// Parses a URL, relative to the origin of this location.
+    pub(crate) fn parse(&self, url: &str) -> Result<Url, String> {
+        self.0.parse(url)
+    }
+
+    /// Converts a route path into the `href` of a link to that route.
+    pub(crate) fn to_href(&self, path: String) -> String {
+        self.0.to_href(path)
+    }
+
+    pub(crate) fn redirect(&self, loc: &str) {
+        self.0.redirect(loc)
+    }
+
+    /// Provides both this location and the provider it wraps, like [`BrowserUrl`], as context.
+    pub(crate) fn provide_context(&self) {
+        provide_context(self.clone());
+        self.0.provide_context();
+    }
+}
+
+impl<T> From<T> for RouterLocation
+where
+    T: LocationProvider + Send + Sync,
+{
+    fn from(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+}
+
+/// Parses a URL relative to `base` without the browser's URL parser, so that it also works
+/// outside a browser.
+pub(crate) fn parse_url(url: &str, base: &str) -> Result<Url, url::ParseError> {
+    let base = url::Url::parse(base)?;
+    let url = url::Url::options().base_url(Some(&base)).parse(url)?;
+
+    let search_params = url
+        .query_pairs()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<ParamsMap>();
+
+    Ok(Url {
+        origin: url.origin().unicode_serialization(),
+        path: url.path().to_string(),
+        search: url.query().unwrap_or_default().to_string(),
+        search_params,
+        hash: url
+            .fragment()
+            .map(|hash| format!("#{hash}"))
+            .unwrap_or_default(),
+    })
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct State(Option<SendWrapper<JsValue>>);
 
@@ -276,7 +449,7 @@ where
 
 pub(crate) fn handle_anchor_click<NavFn, NavFut>(
     router_base: Option<Cow<'static, str>>,
-    parse_with_base: fn(&str, &str) -> Result<Url, JsValue>,
+    parse_link: fn(&str, &str) -> Result<Option<Url>, JsValue>,
     navigate: NavFn,
 ) -> Box<dyn Fn(Event) -> Result<(), JsValue>>
 where
@@ -327,7 +500,10 @@ where
                 return Ok(());
             }
 
-            let url = parse_with_base(href.as_str(), &origin).unwrap();
+            // let browser handle the link if it is not a route, like an in-page anchor
+            let Some(url) = parse_link(href.as_str(), &origin)? else {
+                return Ok(());
+            };
             let path_name = Url::unescape_minimal(&url.path);
 
             // let browser handle this event if it leaves our domain
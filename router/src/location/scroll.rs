@@ -0,0 +1,272 @@
+use futures::channel::oneshot;
+use js_sys::{Array, Object, Reflect, JSON};
+use leptos::prelude::*;
+use or_poisoned::OrPoisoned;
+use reactive_graph::computed::suspense::SuspenseBoundaries;
+use std::sync::{Arc, Mutex};
+use tachys::dom::{document, window};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{Element, ScrollRestoration as BrowserScrollRestoration};
+
+/// The property of the history state that identifies a history entry.
+const ENTRY_KEY: &str = "__leptos_router_key";
+
+/// The prefix of the session storage keys that scroll positions are saved under.
+const STORAGE_PREFIX: &str = "leptos_router_scroll:";
+
+/// The attribute that opts an element in to having its scroll position restored.
+const CONTAINER_ATTR: &str = "data-scroll-restoration-id";
+
+/// Saves the scroll positions of the page for each history entry, and restores them when going
+/// back or forward to that entry.
+#[derive(Clone, Default)]
+pub(crate) struct ScrollRestoration(Arc<Mutex<ScrollState>>);
+
+#[derive(Default)]
+struct ScrollState {
+    /// The key of the current history entry.
+    current: Option<String>,
+    /// Whether the current entry's scroll positions should be restored once its route has
+    /// rendered.
+    restore: bool,
+    boundaries: Option<SuspenseBoundaries>,
+}
+
+impl ScrollRestoration {
+    /// Takes over scroll restoration from the browser, and restores the scroll positions of the
+    /// current entry if the page was reloaded.
+    pub fn init(&self) {
+        let history = window().history().unwrap();
+        _ = history.set_scroll_restoration(BrowserScrollRestoration::Manual);
+
+        let state = history.state().unwrap_or(JsValue::UNDEFINED);
+        let key = match entry_key(&state) {
+            Some(key) => key,
+            None => {
+                let key = new_entry_key();
+                _ = history.replace_state(&with_entry_key(&state, &key), "");
+                key
+            }
+        };
+        {
+            let mut inner = self.0.lock().or_poisoned();
+            inner.current = Some(key);
+            inner.boundaries = use_context::<SuspenseBoundaries>();
+            inner.restore = true;
+        }
+        self.ready();
+
+        // the positions are also saved when the page is reloaded or left
+        let closure = Closure::wrap(Box::new({
+            let this = self.clone();
+            move || this.save()
+        }) as Box<dyn Fn()>)
+        .into_js_value();
+        window()
+            .add_event_listener_with_callback(
+                "pagehide",
+                closure.as_ref().unchecked_ref(),
+            )
+            .expect("couldn't add `pagehide` listener to `window`");
+    }
+
+    /// Saves the scroll positions of the current entry. This should be called before the page
+    /// changes.
+    pub fn save(&self) {
+        let Some(key) = self.0.lock().or_poisoned().current.clone() else {
+            return;
+        };
+        let positions = Array::new();
+        let window = window();
+        positions.push(&position(
+            "",
+            window.scroll_x().unwrap_or_default(),
+            window.scroll_y().unwrap_or_default(),
+        ));
+        for (id, container) in containers() {
+            positions.push(&position(
+                &id,
+                container.scroll_left(),
+                container.scroll_top(),
+            ));
+        }
+        if let (Ok(Some(storage)), Ok(positions)) =
+            (window.session_storage(), JSON::stringify(&positions))
+        {
+            _ = storage.set_item(
+                &format!("{STORAGE_PREFIX}{key}"),
+                &String::from(positions),
+            );
+        }
+    }
+
+    /// Returns the history state for a new entry, keyed so that its scroll positions can be
+    /// saved.
+    pub fn push(&self, state: &JsValue) -> JsValue {
+        let state = with_entry_key(state, &new_entry_key());
+        let mut inner = self.0.lock().or_poisoned();
+        inner.current = entry_key(&state);
+        inner.restore = false;
+        state
+    }
+
+    /// Switches to the entry the browser has gone back or forward to, after saving the
+    /// positions of the one it has left.
+    pub fn pop(&self) {
+        self.save();
+        let state = window()
+            .history()
+            .and_then(|history| history.state())
+            .unwrap_or(JsValue::UNDEFINED);
+        let mut inner = self.0.lock().or_poisoned();
+        inner.current = entry_key(&state);
+        inner.restore = true;
+    }
+
+    /// Restores the positions of the current entry, if needed, once the route has rendered and
+    /// its suspense boundaries have resolved.
+    pub fn ready(&self) {
+        let (key, boundaries) = {
+            let mut inner = self.0.lock().or_poisoned();
+            if !std::mem::take(&mut inner.restore) {
+                return;
+            }
+            (inner.current.clone(), inner.boundaries.clone())
+        };
+        let this = self.clone();
+        leptos::task::spawn_local(async move {
+            settled(boundaries).await;
+            // if another navigation has happened in the meantime, it handles scrolling
+            if this.0.lock().or_poisoned().current != key {
+                return;
+            }
+            let restored = key.is_some_and(|key| restore(&key));
+            if !restored {
+                let hash = window().location().hash().unwrap_or_default();
+                super::history::scroll_to_hash(&hash, false);
+            }
+        });
+    }
+
+    /// Scrolls to the element matching the hash, which may be rendered only once async data has
+    /// loaded.
+    pub fn scroll_to_hash(&self, hash: String, loc_scroll: bool) {
+        if hash.is_empty() || super::history::scroll_to_hash(&hash, loc_scroll)
+        {
+            return;
+        }
+        let (key, boundaries) = {
+            let inner = self.0.lock().or_poisoned();
+            (inner.current.clone(), inner.boundaries.clone())
+        };
+        let this = self.clone();
+        leptos::task::spawn_local(async move {
+            settled(boundaries).await;
+            if this.0.lock().or_poisoned().current == key {
+                super::history::scroll_to_hash(&hash, false);
+            }
+        });
+    }
+}
+
+/// Restores the positions saved for an entry, returning `false` if there are none.
+fn restore(key: &str) -> bool {
+    let window = window();
+    let Some(positions) = window
+        .session_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| {
+            storage.get_item(&format!("{STORAGE_PREFIX}{key}")).ok()
+        })
+        .flatten()
+        .and_then(|positions| JSON::parse(&positions).ok())
+        .and_then(|positions| positions.dyn_into::<Array>().ok())
+    else {
+        return false;
+    };
+    let containers = containers().collect::<Vec<_>>();
+    for position in positions.iter() {
+        let Ok(position) = position.dyn_into::<Array>() else {
+            continue;
+        };
+        let id = position.get(0).as_string().unwrap_or_default();
+        let x = position.get(1).as_f64().unwrap_or_default();
+        let y = position.get(2).as_f64().unwrap_or_default();
+        if id.is_empty() {
+            window.scroll_to_with_x_and_y(x, y);
+        } else if let Some((_, container)) = containers
+            .iter()
+            .find(|(container_id, _)| *container_id == id)
+        {
+            container.set_scroll_left(x as i32);
+            container.set_scroll_top(y as i32);
+        }
+    }
+    true
+}
+
+/// The elements on the page that have opted in to scroll restoration, with their IDs.
+fn containers() -> impl Iterator<Item = (String, Element)> {
+    let containers = document()
+        .query_selector_all(&format!("[{CONTAINER_ATTR}]"))
+        .ok();
+    let len = containers.as_ref().map(|c| c.length()).unwrap_or_default();
+    (0..len)
+        .filter_map(move |idx| containers.as_ref()?.get(idx))
+        .filter_map(|node| node.dyn_into::<Element>().ok())
+        .filter_map(|container| {
+            Some((container.get_attribute(CONTAINER_ATTR)?, container))
+        })
+}
+
+/// Waits for the page to render, and for any suspense boundaries on it to resolve.
+async fn settled(boundaries: Option<SuspenseBoundaries>) {
+    next_frame().await;
+    if let Some(boundaries) = boundaries {
+        boundaries.resolved().await;
+        // lets the resolved boundaries render their children
+        next_frame().await;
+    }
+}
+
+async fn next_frame() {
+    let (tx, rx) = oneshot::channel();
+    request_animation_frame(move || _ = tx.send(()));
+    _ = rx.await;
+}
+
+fn position(id: &str, x: impl Into<f64>, y: impl Into<f64>) -> Array {
+    Array::of3(&id.into(), &x.into().into(), &y.into().into())
+}
+
+fn new_entry_key() -> String {
+    format!(
+        "{:x}{:x}",
+        js_sys::Date::now() as u64,
+        (js_sys::Math::random() * f64::from(u32::MAX)) as u32
+    )
+}
+
+fn entry_key(state: &JsValue) -> Option<String> {
+    if !state.is_object() {
+        return None;
+    }
+    Reflect::get(state, &ENTRY_KEY.into())
+        .ok()
+        .and_then(|key| key.as_string())
+}
+
+/// Adds the entry key to a copy of the history state. A state that is not an object is left as
+/// it is, so its scroll positions are not saved.
+fn with_entry_key(state: &JsValue, key: &str) -> JsValue {
+    let keyed = if state.is_undefined() || state.is_null() {
+        Object::new()
+    } else if state.is_object() && !Array::is_array(state) {
+        Object::assign(&Object::new(), state.unchecked_ref())
+    } else {
+        return state.clone();
+    };
+    _ = Reflect::set(&keyed, &ENTRY_KEY.into(), &key.into());
+    keyed.into()
+}
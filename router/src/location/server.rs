@@ -3,18 +3,48 @@ use crate::params::ParamsMap;
 use std::sync::Arc;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct RequestUrl(Arc<str>);
+pub struct RequestUrl {
+    url: Arc<str>,
+    cookie: Option<Arc<str>>,
+    accept_language: Option<Arc<str>>,
+}
 
 impl RequestUrl {
     /// Creates a server-side request URL from a path.
     pub fn new(path: &str) -> Self {
-        Self(path.into())
+        Self {
+            url: path.into(),
+            cookie: None,
+            accept_language: None,
+        }
+    }
+
+    /// Adds the `Cookie` and `Accept-Language` headers of the request, which a router with
+    /// [`Locales`](crate::Locales) uses to pick the locale of URLs without a locale prefix.
+    pub fn with_headers(
+        mut self,
+        cookie: Option<&str>,
+        accept_language: Option<&str>,
+    ) -> Self {
+        self.cookie = cookie.map(Into::into);
+        self.accept_language = accept_language.map(Into::into);
+        self
+    }
+
+    /// The request's `Cookie` header.
+    pub fn cookie(&self) -> Option<&str> {
+        self.cookie.as_deref()
+    }
+
+    /// The request's `Accept-Language` header.
+    pub fn accept_language(&self) -> Option<&str> {
+        self.accept_language.as_deref()
     }
 }
 
 impl AsRef<str> for RequestUrl {
     fn as_ref(&self) -> &str {
-        &self.0
+        &self.url
     }
 }
 
@@ -31,7 +61,7 @@ impl RequestUrl {
 
     pub fn parse_with_base(&self, base: &str) -> Result<Url, url::ParseError> {
         let base = url::Url::parse(base)?;
-        let url = url::Url::options().base_url(Some(&base)).parse(&self.0)?;
+        let url = url::Url::options().base_url(Some(&base)).parse(&self.url)?;
 
         let search_params = url
             .query_pairs()
@@ -1,5 +1,9 @@
+use super::lazy_view::LazyView;
 use either_of::*;
-use std::{future::Future, marker::PhantomData};
+use futures::FutureExt;
+use reactive_graph::owner::Owner;
+use send_wrapper::SendWrapper;
+use std::{fmt::Debug, future::Future, marker::PhantomData, sync::Arc};
 use tachys::view::any_view::{AnyView, IntoAny};
 
 pub trait ChooseView
@@ -9,6 +13,11 @@ where
     fn choose(self) -> impl Future<Output = AnyView>;
 
     fn preload(&self) -> impl Future<Output = ()>;
+
+    /// Loads the code for this view ahead of time, without rendering it.
+    fn prefetch(&self) -> impl Future<Output = ()> {
+        async {}
+    }
 }
 
 impl<F, View> ChooseView for F
@@ -28,28 +37,128 @@ where
     T: LazyRoute,
 {
     async fn choose(self) -> AnyView {
-        T::data().view().await.into_any()
+        let mut view = Box::pin(T::data().view());
+        if let Some(view) = view.as_mut().now_or_never() {
+            return LazyView::Ready(view).into_any();
+        }
+        // the server always renders the route itself
+        if Owner::current_shared_context().is_some_and(|sc| !sc.is_browser()) {
+            return LazyView::Ready(view.await).into_any();
+        }
+        LazyView::Pending {
+            view: SendWrapper::new(view),
+            fallback: self.fallback.map(|fallback| fallback()),
+        }
+        .into_any()
     }
 
     async fn preload(&self) {
-        T::data().view().await;
+        // with a fallback, navigation can happen right away, and the fallback is shown until the
+        // route has loaded; while hydrating, the server-rendered HTML is shown instead
+        let rendered_elsewhere = Owner::current_shared_context()
+            .is_some_and(|sc| !sc.is_browser() || sc.during_hydration());
+        if self.fallback.is_none() && !rendered_elsewhere {
+            T::data().view().await;
+        }
+    }
+
+    async fn prefetch(&self) {
+        T::prefetch().await;
     }
 }
 
+/// A route whose view is loaded lazily, the first time the route is matched.
+///
+/// Use it as the view of a route by wrapping it in [`Lazy`]. Together with the `#[lazy]` macro,
+/// this moves the code for the route’s view out of the main WASM bundle, into a separate chunk
+/// that is only downloaded when it’s needed.
+///
+/// For the route to be [prefetched](crate::components::Prefetch), the view function itself can
+/// be returned from the lazily loaded function, so that loading it doesn’t render anything:
+///
+/// ```rust,ignore
+/// # use leptos::prelude::*;
+/// # use leptos_router::{components::*, path, Lazy, LazyRoute};
+/// struct SettingsRoute;
+///
+/// #[lazy]
+/// async fn settings_view() -> fn(SettingsRoute) -> AnyView {
+///     |_| view! { <h1>"Settings"</h1> }.into_any()
+/// }
+///
+/// impl LazyRoute for SettingsRoute {
+///     fn data() -> Self {
+///         SettingsRoute
+///     }
+///
+///     async fn view(self) -> AnyView {
+///         settings_view().await(self)
+///     }
+///
+///     async fn prefetch() {
+///         settings_view().await;
+///     }
+/// }
+///
+/// #[component]
+/// fn App() -> impl IntoView {
+///     view! {
+///         <Router>
+///             <A href="/settings" prefetch=Prefetch::Hover>"Settings"</A>
+///             <Routes fallback=|| "Not found.">
+///                 <Route
+///                     path=path!("/settings")
+///                     view=Lazy::<SettingsRoute>::new().fallback(|| "Loading...")
+///                 />
+///             </Routes>
+///         </Router>
+///     }
+/// }
+/// ```
 pub trait LazyRoute: Send + 'static {
+    /// Creates the data for the route. This is called when the route is matched, before its view
+    /// has loaded.
     fn data() -> Self;
 
+    /// Loads and renders the view of the route.
     fn view(self) -> impl Future<Output = AnyView>;
+
+    /// Loads the code for the view without rendering it.
+    ///
+    /// This is used to prefetch the route before it is visited. Does nothing by default.
+    fn prefetch() -> impl Future<Output = ()> {
+        async {}
+    }
 }
 
-#[derive(Debug)]
+type LazyFallback = Arc<dyn Fn() -> AnyView + Send + Sync>;
+
+/// The view of a [`LazyRoute`].
+///
+/// On the server, the route is rendered right away. When the page is hydrated before the route
+/// has loaded, the server-rendered HTML is kept on the page until it has. When navigating to the
+/// route in the browser, the [`fallback`](Lazy::fallback) is shown while it loads; without a
+/// fallback, navigation waits for the route to load.
 pub struct Lazy<T> {
     ty: PhantomData<T>,
+    fallback: Option<LazyFallback>,
+}
+
+impl<T> Debug for Lazy<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Lazy")
+            .field("ty", &self.ty)
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
 }
 
 impl<T> Clone for Lazy<T> {
     fn clone(&self) -> Self {
-        Self { ty: self.ty }
+        Self {
+            ty: self.ty,
+            fallback: self.fallback.clone(),
+        }
     }
 }
 
@@ -57,12 +166,23 @@ impl<T> Lazy<T> {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Sets a view that is shown while the route is loading.
+    pub fn fallback<F, View>(mut self, fallback: F) -> Self
+    where
+        F: Fn() -> View + Send + Sync + 'static,
+        View: IntoAny,
+    {
+        self.fallback = Some(Arc::new(move || fallback().into_any()));
+        self
+    }
 }
 
 impl<T> Default for Lazy<T> {
     fn default() -> Self {
         Self {
             ty: Default::default(),
+            fallback: None,
         }
     }
 }
@@ -93,6 +213,13 @@ where
             Either::Right(f) => f.preload().await,
         }
     }
+
+    async fn prefetch(&self) {
+        match self {
+            Either::Left(f) => f.prefetch().await,
+            Either::Right(f) => f.prefetch().await,
+        }
+    }
 }
 
 macro_rules! tuples {
@@ -112,6 +239,12 @@ macro_rules! tuples {
                     $($either::$ty(f) => f.preload().await,)*
                 }
             }
+
+            async fn prefetch(&self) {
+                match self {
+                    $($either::$ty(f) => f.prefetch().await,)*
+                }
+            }
         }
     };
 }
@@ -1,4 +1,5 @@
 use super::{PartialPathMatch, PathSegment, PossibleRouteMatch};
+use crate::ParamConstraint;
 use core::iter;
 use std::borrow::Cow;
 
@@ -75,6 +76,55 @@ impl PossibleRouteMatch for ParamSegment {
     }
 }
 
+/// A segment that captures a value from the url and maps it to a key, but only matches if the
+/// value meets a [`ParamConstraint`].
+///
+/// If it does not, the route does not match, so the next sibling route is tried instead.
+///
+/// # Examples
+/// ```rust
+/// # (|| -> Option<()> { // Option does not impl Terminate, so no main
+/// use leptos::prelude::*;
+/// use leptos_router::{
+///     path, ConstrainedParamSegment, ParamConstraint, PossibleRouteMatch,
+///     StaticSegment,
+/// };
+///
+/// // Manual definition
+/// let manual = (
+///     StaticSegment("users"),
+///     ConstrainedParamSegment("id", ParamConstraint::Int),
+/// );
+/// let params = manual.test("/users/42")?.params();
+/// assert_eq!(params.last()?.1, "42");
+/// assert!(manual.test("/users/new").is_none());
+///
+/// // Macro definition
+/// assert_eq!(path!("/users/:id<int>"), manual);
+/// assert!(path!("/:tab<posts|likes>").test("/posts").is_some());
+/// assert!(path!("/:slug([a-z-]+)").test("/hello-world").is_some());
+///
+/// # Some(())
+/// # })().unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConstrainedParamSegment(pub &'static str, pub ParamConstraint);
+
+impl PossibleRouteMatch for ConstrainedParamSegment {
+    fn test<'a>(&self, path: &'a str) -> Option<PartialPathMatch<'a>> {
+        let matched = ParamSegment(self.0).test(path)?;
+        matched
+            .params
+            .iter()
+            .all(|(_, value)| self.1.is_satisfied_by(value))
+            .then_some(matched)
+    }
+
+    fn generate_path(&self, path: &mut Vec<PathSegment>) {
+        path.push(PathSegment::ConstrainedParam(self.0.into(), self.1));
+    }
+}
+
 /// A segment that captures all remaining values from the url and maps it to a key.
 ///
 /// A [`WildcardSegment`] __must__ be the last segment of your path definition.
@@ -210,7 +260,8 @@ impl PossibleRouteMatch for OptionalParamSegment {
 mod tests {
     use super::PossibleRouteMatch;
     use crate::{
-        OptionalParamSegment, ParamSegment, StaticSegment, WildcardSegment,
+        ConstrainedParamSegment, OptionalParamSegment, ParamConstraint,
+        ParamRegex, ParamSegment, StaticSegment, WildcardSegment,
     };
 
     #[test]
@@ -247,6 +298,50 @@ mod tests {
         assert_eq!(params[1], ("b".into(), "bar".into()));
     }
 
+    #[test]
+    fn constrained_param_matches_only_valid_values() {
+        let def = (
+            StaticSegment("users"),
+            ConstrainedParamSegment("id", ParamConstraint::Int),
+        );
+        let matched = def.test("/users/-42").expect("couldn't match route");
+        assert_eq!(matched.params()[0], ("id".into(), "-42".into()));
+        assert!(def.test("/users/new").is_none());
+        assert!(def.test("/users/-").is_none());
+
+        let def = ConstrainedParamSegment(
+            "tab",
+            ParamConstraint::OneOf(&["posts", "likes"]),
+        );
+        assert!(def.test("/likes").is_some());
+        assert!(def.test("/follows").is_none());
+
+        static SLUG: ParamRegex = ParamRegex::new("[a-z]+(-[a-z]+)*");
+        let def =
+            ConstrainedParamSegment("slug", ParamConstraint::Regex(&SLUG));
+        assert!(def.test("/hello-world/").is_some());
+        assert!(def.test("/Hello").is_none());
+        assert!(def.test("/hello-").is_none());
+    }
+
+    #[test]
+    fn invalid_regex_constraint_never_matches() {
+        static INVALID: ParamRegex = ParamRegex::new("(foo");
+        let def =
+            ConstrainedParamSegment("a", ParamConstraint::Regex(&INVALID));
+        assert!(def.test("/foo").is_none());
+        assert!(def.test("/(foo").is_none());
+    }
+
+    #[test]
+    fn constraint_regex_matches_same_values() {
+        assert_eq!(ParamConstraint::Int.to_regex(), "-?[0-9]+");
+        assert_eq!(
+            ParamConstraint::OneOf(&["a.b", "c"]).to_regex(),
+            "(?:a\\.b|c)"
+        );
+    }
+
     #[test]
     fn splat_should_match_all() {
         let path = "/foo/bar/////";
@@ -348,4 +443,80 @@ mod tests {
         assert_eq!(params[0], ("a".into(), "foo".into()));
         assert_eq!(params[1], ("b".into(), "qux".into()));
     }
+
+    #[test]
+    fn optional_param_after_required_segments() {
+        let path = "/foo/bar";
+        let def = (StaticSegment("foo"), OptionalParamSegment("a"));
+        let matched = def.test(path).expect("couldn't match route");
+        assert_eq!(matched.matched(), "/foo/bar");
+        assert_eq!(matched.remaining(), "");
+        let params = matched.params();
+        assert_eq!(params[0], ("a".into(), "bar".into()));
+    }
+
+    #[test]
+    fn optional_param_after_required_segments_can_be_omitted() {
+        let path = "/foo";
+        let def = (StaticSegment("foo"), OptionalParamSegment("a"));
+        let matched = def.test(path).expect("couldn't match route");
+        assert_eq!(matched.matched(), "/foo");
+        assert_eq!(matched.remaining(), "");
+        assert!(matched.params().is_empty());
+    }
+
+    #[test]
+    fn optional_param_between_static_segments() {
+        let def = (
+            StaticSegment("foo"),
+            OptionalParamSegment("a"),
+            StaticSegment("bar"),
+        );
+
+        let matched = def.test("/foo/bar").expect("couldn't match route");
+        assert_eq!(matched.matched(), "/foo/bar");
+        assert_eq!(matched.remaining(), "");
+        assert!(matched.params().is_empty());
+
+        let matched = def.test("/foo/qux/bar").expect("couldn't match route");
+        assert_eq!(matched.matched(), "/foo/qux/bar");
+        assert_eq!(matched.remaining(), "");
+        assert_eq!(matched.params()[0], ("a".into(), "qux".into()));
+
+        assert!(def.test("/foo/qux").is_none());
+    }
+
+    #[test]
+    fn optional_params_around_static_segment() {
+        let def = (
+            OptionalParamSegment("a"),
+            StaticSegment("foo"),
+            OptionalParamSegment("b"),
+        );
+
+        let matched = def.test("/x/foo/y").expect("couldn't match route");
+        assert_eq!(matched.matched(), "/x/foo/y");
+        assert_eq!(
+            matched.params(),
+            [("a".into(), "x".into()), ("b".into(), "y".into())]
+        );
+
+        let matched = def.test("/foo").expect("couldn't match route");
+        assert_eq!(matched.matched(), "/foo");
+        assert!(matched.params().is_empty());
+    }
+
+    #[test]
+    fn required_params_are_unaffected_by_optionals() {
+        let def = (ParamSegment("a"), OptionalParamSegment("b"));
+
+        let matched = def.test("/x").expect("couldn't match route");
+        assert_eq!(matched.params(), [("a".into(), "x".into())]);
+
+        let matched = def.test("/x/y").expect("couldn't match route");
+        assert_eq!(
+            matched.params(),
+            [("a".into(), "x".into()), ("b".into(), "y".into())]
+        );
+    }
 }
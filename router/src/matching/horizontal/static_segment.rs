@@ -75,9 +75,15 @@ impl<T: AsPath> PossibleRouteMatch for StaticSegment<T> {
         }
 
         for char in test {
-            let n = this.next();
             // when we get a closing /, stop matching
-            if char == '/' || n.is_none() {
+            // this is checked before taking the next character of the segment, so that a
+            // shorter path segment (`/foo` for `food`) leaves part of the segment unmatched,
+            // and is rejected below
+            if char == '/' {
+                break;
+            }
+            let n = this.next();
+            if n.is_none() {
                 break;
             }
             // if the next character in the path matches the
@@ -161,6 +167,13 @@ mod tests {
         assert!(def.test(path).is_none());
     }
 
+    #[test]
+    fn single_static_prefix_mismatch() {
+        let path = "/foo/bar";
+        let def = StaticSegment("food");
+        assert!(def.test(path).is_none());
+    }
+
     #[test]
     fn single_static_mismatch_on_enum() {
         let path = "/foo";
@@ -261,4 +274,27 @@ mod tests {
         let params = matched.params();
         assert!(params.is_empty());
     }
+
+    #[test]
+    fn single_static_match_with_remaining_segments() {
+        let path = "/foo/bar";
+        let def = StaticSegment("foo");
+        let matched = def.test(path).expect("couldn't match route");
+        assert_eq!(matched.matched(), "/foo");
+        assert_eq!(matched.remaining(), "/bar");
+    }
+
+    #[test]
+    fn single_static_shorter_segment_mismatch() {
+        assert!(StaticSegment("foo").test("/fo").is_none());
+        assert!(StaticSegment("foo").test("/fo/o").is_none());
+    }
+
+    #[test]
+    fn empty_static_matches_root() {
+        let matched =
+            StaticSegment("").test("/").expect("couldn't match route");
+        assert_eq!(matched.matched(), "");
+        assert_eq!(matched.remaining(), "/");
+    }
 }
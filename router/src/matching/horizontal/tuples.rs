@@ -17,6 +17,9 @@ macro_rules! tuples {
                 #[allow(non_snake_case)]
                 let ($first, $($ty,)*) = &self;
 
+                // optional fields are counted from 1, and the nth one is tried while
+                // `nth_field <= include_optionals`: the first pass tries all of them, and each
+                // retry leaves out the last one that is still included
                 loop {
                     let mut nth_field = 0;
                     let mut matched_len = 0;
@@ -25,7 +28,10 @@ macro_rules! tuples {
                     let mut p = Vec::new();
                     let mut m = String::new();
 
-                    if !$first::OPTIONAL || nth_field < include_optionals {
+                    if $first::OPTIONAL {
+                        nth_field += 1;
+                    }
+                    if !$first::OPTIONAL || nth_field <= include_optionals {
                         match $first.test(r) {
                             None => {
                                 return None;
@@ -43,7 +49,7 @@ macro_rules! tuples {
                         if $ty::OPTIONAL {
                             nth_field += 1;
                         }
-                        if !$ty::OPTIONAL || nth_field < include_optionals {
+                        if !$ty::OPTIONAL || nth_field <= include_optionals {
                             let PartialPathMatch {
                                 remaining,
                                 matched,
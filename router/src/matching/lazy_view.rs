@@ -0,0 +1,341 @@
+use reactive_graph::spawn_local_scoped_with_cancellation;
+use send_wrapper::SendWrapper;
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+};
+use tachys::{
+    html::attribute::Attribute,
+    hydration::Cursor,
+    renderer::{types, CastFrom, Rndr},
+    ssr::StreamBuilder,
+    view::{
+        add_attr::AddAnyAttr,
+        any_view::{AnyView, AnyViewState},
+        Mountable, Position, PositionState, Render, RenderHtml,
+    },
+};
+
+// the view of a lazy route is wrapped in these markers when it is rendered on the server, so that
+// its HTML can be found again if the route has not loaded yet when the page is hydrated
+const START_MARKER: &str = "lazy";
+const END_MARKER: &str = "/lazy";
+
+type LazyFuture = Pin<Box<dyn Future<Output = AnyView>>>;
+
+/// The view of a [`Lazy`](super::Lazy) route, which may still be loading.
+pub(crate) enum LazyView {
+    Ready(AnyView),
+    Pending {
+        view: SendWrapper<LazyFuture>,
+        fallback: Option<AnyView>,
+    },
+}
+
+pub(crate) struct LazyViewState {
+    start: types::Placeholder,
+    end: types::Placeholder,
+    content: Rc<RefCell<LazyContent>>,
+}
+
+struct LazyContent {
+    // the fallback while the route is loading, and the route's view once it has loaded
+    view: Option<AnyViewState>,
+    // server-rendered HTML that stays on the page until the route has loaded
+    server_nodes: Vec<types::Node>,
+}
+
+impl LazyContent {
+    fn unmount(&mut self) {
+        self.view.unmount();
+        for node in &self.server_nodes {
+            if let Some(parent) =
+                Rndr::get_parent(node).and_then(types::Element::cast_from)
+            {
+                Rndr::remove_node(&parent, node);
+            }
+        }
+    }
+
+    fn mount(&mut self, parent: &types::Element, marker: Option<&types::Node>) {
+        for node in &self.server_nodes {
+            Rndr::insert_node(parent, node, marker);
+        }
+        self.view.mount(parent, marker);
+    }
+}
+
+impl LazyViewState {
+    fn new(
+        start: types::Placeholder,
+        end: types::Placeholder,
+        view: Option<AnyViewState>,
+        server_nodes: Vec<types::Node>,
+    ) -> Self {
+        Self {
+            start,
+            end,
+            content: Rc::new(RefCell::new(LazyContent { view, server_nodes })),
+        }
+    }
+
+    /// Swaps in the view once the route has loaded, replacing the fallback.
+    fn load(&self, view: SendWrapper<LazyFuture>) {
+        let end = self.end.clone();
+        // if this state is replaced before the route has loaded, the view is no longer needed
+        let content = Rc::downgrade(&self.content);
+        spawn_local_scoped_with_cancellation(async move {
+            let view = view.take().await;
+            let Some(content) = Weak::upgrade(&content) else {
+                return;
+            };
+            let mut view = view.build();
+            let mut content = content.borrow_mut();
+            end.insert_before_this(&mut view);
+            content.unmount();
+            content.view = Some(view);
+        });
+    }
+
+    /// Hydrates the server-rendered HTML once the route has loaded.
+    fn hydrate_when_loaded<const FROM_SERVER: bool>(
+        &self,
+        view: SendWrapper<LazyFuture>,
+    ) {
+        let start = self.start.clone();
+        let content = Rc::downgrade(&self.content);
+        spawn_local_scoped_with_cancellation(async move {
+            let view = view.take().await;
+            let Some(content) = Weak::upgrade(&content) else {
+                return;
+            };
+            let mut content = content.borrow_mut();
+            let parent = Rndr::get_parent(start.as_ref())
+                .and_then(types::Element::cast_from);
+            let view = match parent {
+                Some(parent) => {
+                    let cursor = Cursor::new(parent);
+                    cursor.set(start.into());
+                    let position = PositionState::new(Position::NextChild);
+                    view.hydrate::<FROM_SERVER>(&cursor, &position)
+                }
+                // the route is no longer on the page, so there is nothing left to hydrate
+                None => {
+                    content.unmount();
+                    view.build()
+                }
+            };
+            // the view now owns the server-rendered nodes
+            content.server_nodes.clear();
+            content.view = Some(view);
+        });
+    }
+}
+
+impl Mountable for LazyViewState {
+    fn unmount(&mut self) {
+        self.start.unmount();
+        self.content.borrow_mut().unmount();
+        self.end.unmount();
+    }
+
+    fn mount(&mut self, parent: &types::Element, marker: Option<&types::Node>) {
+        self.start.mount(parent, marker);
+        self.content.borrow_mut().mount(parent, marker);
+        self.end.mount(parent, marker);
+    }
+
+    fn insert_before_this(&self, child: &mut dyn Mountable) -> bool {
+        self.start.insert_before_this(child)
+    }
+}
+
+impl Render for LazyView {
+    type State = LazyViewState;
+
+    fn build(self) -> Self::State {
+        let start = Rndr::create_placeholder();
+        let end = Rndr::create_placeholder();
+        match self {
+            LazyView::Ready(view) => {
+                LazyViewState::new(start, end, Some(view.build()), Vec::new())
+            }
+            LazyView::Pending { view, fallback } => {
+                let state = LazyViewState::new(
+                    start,
+                    end,
+                    fallback.map(Render::build),
+                    Vec::new(),
+                );
+                state.load(view);
+                state
+            }
+        }
+    }
+
+    fn rebuild(self, state: &mut Self::State) {
+        let mut new_state = self.build();
+        state.insert_before_this(&mut new_state);
+        state.unmount();
+        *state = new_state;
+    }
+}
+
+impl AddAnyAttr for LazyView {
+    type Output<SomeNewAttr: Attribute> = LazyView;
+
+    fn add_any_attr<NewAttr: Attribute>(
+        self,
+        attr: NewAttr,
+    ) -> Self::Output<NewAttr>
+    where
+        Self::Output<NewAttr>: RenderHtml,
+    {
+        match self {
+            LazyView::Ready(view) => LazyView::Ready(view.add_any_attr(attr)),
+            pending => pending,
+        }
+    }
+}
+
+impl RenderHtml for LazyView {
+    type AsyncOutput = Self;
+
+    const MIN_LENGTH: usize = START_MARKER.len() + END_MARKER.len() + 14;
+
+    fn dry_resolve(&mut self) {
+        if let LazyView::Ready(view) = self {
+            view.dry_resolve();
+        }
+    }
+
+    async fn resolve(self) -> Self::AsyncOutput {
+        match self {
+            LazyView::Ready(view) => LazyView::Ready(view.resolve().await),
+            pending => pending,
+        }
+    }
+
+    fn html_len(&self) -> usize {
+        Self::MIN_LENGTH
+            + match self {
+                LazyView::Ready(view) => view.html_len(),
+                LazyView::Pending { fallback, .. } => {
+                    fallback.as_ref().map(AnyView::html_len).unwrap_or(0)
+                }
+            }
+    }
+
+    fn to_html_with_buf(
+        self,
+        buf: &mut String,
+        position: &mut Position,
+        escape: bool,
+        mark_branches: bool,
+    ) {
+        buf.push_str("<!--");
+        buf.push_str(START_MARKER);
+        buf.push_str("-->");
+        *position = Position::NextChild;
+        match self {
+            LazyView::Ready(view) => {
+                view.to_html_with_buf(buf, position, escape, mark_branches)
+            }
+            LazyView::Pending { fallback, .. } => {
+                fallback.to_html_with_buf(buf, position, escape, mark_branches)
+            }
+        }
+        buf.push_str("<!--");
+        buf.push_str(END_MARKER);
+        buf.push_str("-->");
+        *position = Position::NextChild;
+    }
+
+    fn to_html_async_with_buf<const OUT_OF_ORDER: bool>(
+        self,
+        buf: &mut StreamBuilder,
+        position: &mut Position,
+        escape: bool,
+        mark_branches: bool,
+    ) where
+        Self: Sized,
+    {
+        buf.push_sync("<!--");
+        buf.push_sync(START_MARKER);
+        buf.push_sync("-->");
+        *position = Position::NextChild;
+        match self {
+            LazyView::Ready(view) => view
+                .to_html_async_with_buf::<OUT_OF_ORDER>(
+                    buf,
+                    position,
+                    escape,
+                    mark_branches,
+                ),
+            LazyView::Pending { fallback, .. } => fallback
+                .to_html_async_with_buf::<OUT_OF_ORDER>(
+                    buf,
+                    position,
+                    escape,
+                    mark_branches,
+                ),
+        }
+        buf.push_sync("<!--");
+        buf.push_sync(END_MARKER);
+        buf.push_sync("-->");
+        *position = Position::NextChild;
+    }
+
+    fn hydrate<const FROM_SERVER: bool>(
+        self,
+        cursor: &Cursor,
+        position: &PositionState,
+    ) -> Self::State {
+        let start = cursor.next_placeholder(position);
+        match self {
+            LazyView::Ready(view) => {
+                let view = view.hydrate::<FROM_SERVER>(cursor, position);
+                let end = cursor.next_placeholder(position);
+                LazyViewState::new(start, end, Some(view), Vec::new())
+            }
+            // the route's code has not loaded yet, so the server-rendered HTML is kept as it is
+            // until it has, and hydrated then
+            LazyView::Pending { view, .. } => {
+                let mut server_nodes = Vec::new();
+                let mut depth = 0_usize;
+                let mut next = Rndr::next_sibling(start.as_ref());
+                let end = loop {
+                    let node = next.expect(
+                        "the end marker of a lazy route was not found while \
+                         hydrating",
+                    );
+                    match marker_text(&node).as_deref() {
+                        Some(START_MARKER) => depth += 1,
+                        Some(END_MARKER) if depth == 0 => {
+                            break types::Placeholder::cast_from(node)
+                                .expect("marker is a comment node");
+                        }
+                        Some(END_MARKER) => depth -= 1,
+                        _ => {}
+                    }
+                    next = Rndr::next_sibling(&node);
+                    server_nodes.push(node);
+                };
+                cursor.set(end.clone().into());
+                position.set(Position::NextChild);
+
+                let state = LazyViewState::new(start, end, None, server_nodes);
+                state.hydrate_when_loaded::<FROM_SERVER>(view);
+                state
+            }
+        }
+    }
+}
+
+fn marker_text(node: &types::Node) -> Option<String> {
+    (node.node_type() == types::Node::COMMENT_NODE)
+        .then(|| node.text_content())
+        .flatten()
+}
@@ -0,0 +1,130 @@
+use crate::{params::ParamsMap, ChooseView};
+use leptos::server::ArcResource;
+use reactive_graph::{
+    computed::ArcMemo,
+    owner::{provide_context, use_context},
+    traits::Get,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Debug, future::Future, sync::Arc};
+use tachys::view::any_view::AnyView;
+
+/// Data that a route starts loading as soon as it is matched, before its view is rendered.
+pub trait RouteData: Send + Clone + 'static {
+    /// Starts loading the data.
+    ///
+    /// This is called in the reactive owner of the matched route, after its params, URL and
+    /// matched path have been provided via context.
+    fn load(&self);
+}
+
+impl RouteData for () {
+    fn load(&self) {}
+}
+
+/// Loads data for a route, given the params of the route and all of its parents.
+///
+/// The nested router runs the loader of every matched route at the same time, as soon as
+/// navigation starts, rather than waiting for each parent view to render before its child
+/// creates a resource. `<FlatRoutes/>` runs the loader of the matched route before rendering it.
+/// The result is stored in a resource that is serialized from the server to the client for
+/// hydration, and is available in the route (and any of its children) via
+/// [`use_loader_data`](crate::hooks::use_loader_data). It will load again whenever the params
+/// change.
+#[derive(Clone, Default)]
+pub struct RouteLoader {
+    #[allow(clippy::type_complexity)]
+    load: Option<Arc<dyn Fn(ArcMemo<ParamsMap>) + Send + Sync>>,
+}
+
+impl RouteLoader {
+    /// Creates a loader from an async function that takes the current params.
+    pub fn new<F, Fut>(fun: F) -> Self
+    where
+        F: Fn(ParamsMap) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        let fun = Arc::new(fun);
+        Self {
+            load: Some(Arc::new(move |params: ArcMemo<ParamsMap>| {
+                let fun = Arc::clone(&fun);
+                let data = ArcResource::new(
+                    move || params.get(),
+                    move |params| fun(params),
+                );
+                provide_context(LoaderData(data));
+            })),
+        }
+    }
+}
+
+impl<F, Fut> From<F> for RouteLoader
+where
+    F: Fn(ParamsMap) -> Fut + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn from(fun: F) -> Self {
+        Self::new(fun)
+    }
+}
+
+impl Debug for RouteLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RouteLoader")
+            .field("is_some", &self.load.is_some())
+            .finish()
+    }
+}
+
+impl RouteData for RouteLoader {
+    fn load(&self) {
+        if let Some(load) = &self.load {
+            let params = use_context::<ArcMemo<ParamsMap>>()
+                .expect("route params should be provided before loading data");
+            load(params);
+        }
+    }
+}
+
+/// The resource created by a [`RouteLoader`], provided via context.
+pub(crate) struct LoaderData<T>(pub ArcResource<T>);
+
+impl<T> Clone for LoaderData<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// The view of a matched route, which starts loading the route's data when it is preloaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteView<View, Data> {
+    view: View,
+    data: Data,
+}
+
+impl<View, Data> RouteView<View, Data> {
+    pub(crate) fn new(view: View, data: Data) -> Self {
+        Self { view, data }
+    }
+}
+
+impl<View, Data> ChooseView for RouteView<View, Data>
+where
+    View: ChooseView,
+    Data: RouteData,
+{
+    async fn choose(self) -> AnyView {
+        self.view.choose().await
+    }
+
+    async fn preload(&self) {
+        self.data.load();
+        self.view.preload().await;
+    }
+
+    async fn prefetch(&self) {
+        self.view.prefetch().await;
+    }
+}
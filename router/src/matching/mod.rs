@@ -1,22 +1,32 @@
 #![allow(missing_docs)]
 
 mod choose_view;
+mod lazy_view;
+mod loader;
 mod path_segment;
 pub(crate) mod resolve_path;
 pub use choose_view::*;
+pub use loader::*;
 pub use path_segment::*;
 mod horizontal;
 mod nested;
 mod vertical;
-use crate::{static_routes::RegenerationFn, Method, SsrMode};
+use crate::{
+    static_routes::RegenerationFn, Locales, Method, RouteListing, SsrMode,
+};
+use futures::{future::join_all, FutureExt};
 pub use horizontal::*;
 pub use nested::*;
-use std::{borrow::Cow, collections::HashSet, sync::atomic::Ordering};
+use std::{
+    borrow::Cow, collections::HashSet, future::Future, pin::Pin,
+    sync::atomic::Ordering,
+};
 pub use vertical::*;
 
 #[derive(Debug)]
 pub struct RouteDefs<Children> {
     base: Option<Cow<'static, str>>,
+    locales: Option<Locales>,
     children: Children,
 }
 
@@ -27,6 +37,7 @@ where
     fn clone(&self) -> Self {
         Self {
             base: self.base.clone(),
+            locales: self.locales.clone(),
             children: self.children.clone(),
         }
     }
@@ -36,6 +47,7 @@ impl<Children> RouteDefs<Children> {
     pub fn new(children: Children) -> Self {
         Self {
             base: None,
+            locales: None,
             children,
         }
     }
@@ -46,9 +58,16 @@ impl<Children> RouteDefs<Children> {
     ) -> Self {
         Self {
             base: Some(base.into()),
+            locales: None,
             children,
         }
     }
+
+    /// Also matches each route with the prefix of any of the `locales` after the base.
+    pub fn with_locales(mut self, locales: Option<Locales>) -> Self {
+        self.locales = locales;
+        self
+    }
 }
 
 impl<Children> RouteDefs<Children>
@@ -67,6 +86,10 @@ where
                 path.strip_prefix(base)?
             }
         };
+        let path = match &self.locales {
+            None => path,
+            Some(locales) => locales.split_path(path).1,
+        };
 
         let (matched, remaining) = self.children.match_nested(path);
         let matched = matched?;
@@ -86,6 +109,54 @@ where
     ) {
         (self.base.as_deref(), self.children.generate_routes())
     }
+
+    /// Adds a copy of each of the `routes` generated from these definitions for each locale,
+    /// with the locale's prefix following the base.
+    pub fn add_locale_listings(&self, routes: &mut Vec<RouteListing>) {
+        let Some(locales) = &self.locales else {
+            return;
+        };
+        let prefix_at = usize::from(self.base.is_some());
+        let localized = locales
+            .iter()
+            .flat_map(|locale| {
+                routes.iter().map(move |route| {
+                    let mut path = route.path().to_vec();
+                    path.insert(
+                        prefix_at.min(path.len()),
+                        PathSegment::Static(locale.to_string().into()),
+                    );
+                    RouteListing::new(
+                        path,
+                        route.mode().clone(),
+                        route.methods(),
+                        route.regenerate().to_vec(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        routes.extend(localized);
+    }
+
+    /// Loads the code for the views of the routes that match `path`, without rendering them.
+    pub fn prefetch(&self, path: &str) -> impl Future<Output = ()> {
+        fn add_prefetches<Match: MatchInterface>(
+            matched: Match,
+            prefetches: &mut Vec<Pin<Box<dyn Future<Output = ()>>>>,
+        ) {
+            let (view, child) = matched.into_view_and_child();
+            prefetches.push(Box::pin(async move { view.prefetch().await }));
+            if let Some(child) = child {
+                add_prefetches(child, prefetches);
+            }
+        }
+
+        let mut prefetches = Vec::new();
+        if let Some(matched) = self.match_route(path) {
+            add_prefetches(matched, &mut prefetches);
+        }
+        join_all(prefetches).map(|_| ())
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -139,12 +210,24 @@ pub struct GeneratedRouteData {
 
 #[cfg(test)]
 mod tests {
-    use super::{NestedRoute, ParamSegment, RouteDefs};
+    use super::{LoaderData, NestedRoute, ParamSegment, RouteDefs};
     use crate::{
-        matching::MatchParams, MatchInterface, PathSegment, StaticSegment,
+        matching::MatchParams, params::ParamsMap, ChooseView,
+        ConstrainedParamSegment, Lazy, LazyRoute, Locales, MatchInterface,
+        ParamConstraint, PathSegment, RouteListing, StaticSegment,
         WildcardSegment,
     };
+    use any_spawner::{
+        CustomExecutor, Executor, PinnedFuture, PinnedLocalFuture,
+    };
     use either_of::Either;
+    use futures::FutureExt;
+    use reactive_graph::{
+        computed::ArcMemo,
+        owner::{provide_context, use_context, Owner},
+    };
+    use std::sync::Mutex;
+    use tachys::view::any_view::{AnyView, IntoAny};
 
     #[test]
     pub fn matches_single_root_route() {
@@ -206,6 +289,86 @@ mod tests {
         assert!(matches!(matched, Either::Right(_)));
     }
 
+    #[test]
+    pub fn falls_through_when_param_constraint_fails() {
+        let routes = RouteDefs::<_>::new((
+            NestedRoute::new(
+                (
+                    StaticSegment("users"),
+                    ConstrainedParamSegment("id", ParamConstraint::Int),
+                ),
+                || (),
+            ),
+            NestedRoute::new(
+                (StaticSegment("users"), ParamSegment("slug")),
+                || (),
+            ),
+        ));
+
+        let matched = routes.match_route("/users/42").unwrap();
+        assert!(matches!(matched, Either::Left(_)));
+        assert_eq!(matched.to_params(), vec![("id".into(), "42".into())]);
+        let matched = routes.match_route("/users/new").unwrap();
+        assert!(matches!(matched, Either::Right(_)));
+        assert_eq!(matched.to_params(), vec![("slug".into(), "new".into())]);
+
+        // the constraint is kept in the generated routes
+        let (_, paths) = routes.generate_routes();
+        let paths = paths.into_iter().map(|g| g.segments).collect::<Vec<_>>();
+        assert_eq!(
+            paths[0],
+            vec![
+                PathSegment::Static("users".into()),
+                PathSegment::ConstrainedParam(
+                    "id".into(),
+                    ParamConstraint::Int
+                )
+            ]
+        );
+    }
+
+    #[test]
+    pub fn matches_optional_locale_prefix() {
+        let routes = RouteDefs::<_>::new_with_base(
+            NestedRoute::new(
+                (StaticSegment("about"), ParamSegment("id")),
+                || (),
+            ),
+            "/app",
+        )
+        .with_locales(Some(Locales::new(["en", "de"])));
+
+        for path in ["/app/about/1", "/app/de/about/1", "/app/EN/about/1"] {
+            let matched = routes.match_route(path).unwrap();
+            assert_eq!(matched.to_params(), vec![("id".into(), "1".into())]);
+        }
+        assert!(routes.match_route("/app/fr/about/1").is_none());
+        assert!(routes.match_route("/de/about/1").is_none());
+
+        // each route is also listed with each locale's prefix, after the base
+        let mut listings = vec![RouteListing::from_path([
+            PathSegment::Static("/app".into()),
+            PathSegment::Static("about".into()),
+            PathSegment::Param("id".into()),
+        ])];
+        routes.add_locale_listings(&mut listings);
+        let paths = listings
+            .iter()
+            .map(|listing| {
+                listing
+                    .path()
+                    .iter()
+                    .map(|segment| segment.as_raw_str())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            ["/app/about/id", "/app/en/about/id", "/app/de/about/id"]
+        );
+    }
+
     #[test]
     pub fn does_not_match_incomplete_route() {
         let routes: RouteDefs<_> = RouteDefs::new(
@@ -321,6 +484,158 @@ mod tests {
         let params = matched.to_params();
         assert_eq!(params, vec![("any".into(), "foobar".into())]);
     }
+
+    // the loader's task only needs to be spawned, not to run
+    struct NoopExecutor;
+
+    impl CustomExecutor for NoopExecutor {
+        fn spawn(&self, _fut: PinnedFuture<()>) {}
+
+        fn spawn_local(&self, _fut: PinnedLocalFuture<()>) {}
+
+        fn poll_local(&self) {}
+    }
+
+    #[test]
+    pub fn loader_runs_when_matched_route_is_preloaded() {
+        _ = Executor::init_custom_executor(NoopExecutor);
+
+        let routes = RouteDefs::<_>::new(
+            NestedRoute::new(
+                (StaticSegment("post"), ParamSegment("id")),
+                || (),
+            )
+            .loader(|params: ParamsMap| async move { params.get("id") }),
+        );
+        let matched = routes.match_route("/post/42").unwrap();
+        let (view, _) = MatchInterface::into_view_and_child(matched);
+
+        let owner = Owner::new();
+        owner.with(|| {
+            provide_context(ArcMemo::new(|_| ParamsMap::new()));
+            view.preload().now_or_never();
+            assert!(use_context::<LoaderData<Option<String>>>().is_some());
+        });
+    }
+
+    // the routes are rendered to HTML, as there is no DOM to mount them to
+    #[cfg(feature = "ssr")]
+    #[test]
+    pub fn flat_routes_run_loader_of_matched_route() {
+        use crate::{
+            components::{FlatRoutes, Route, Router},
+            hooks::use_loader_data,
+            location::MemoryHistory,
+        };
+        use leptos::prelude::*;
+
+        _ = Executor::init_custom_executor(NoopExecutor);
+
+        let owner = Owner::new();
+        owner.set();
+        let loaded = StoredValue::new(Vec::new());
+        let app = view! {
+            <Router location=MemoryHistory::with_url("/post/42").unwrap()>
+                <FlatRoutes fallback=|| "not found">
+                    <Route
+                        path=(StaticSegment("post"), ParamSegment("id"))
+                        loader=move |params: ParamsMap| {
+                            let id = params.get("id");
+                            loaded.update_value(|loaded| loaded.push(id.clone()));
+                            async move { id }
+                        }
+                        view=|| {
+                            let post = use_loader_data::<Option<String>>();
+                            view! { <Suspense>{move || post.get()}</Suspense> }
+                        }
+                    />
+                </FlatRoutes>
+            </Router>
+        };
+        let html = app.to_html();
+        assert!(!html.contains("not found"), "{html}");
+        assert_eq!(loaded.get_value(), [Some("42".to_string())]);
+    }
+
+    #[test]
+    pub fn prefetches_matched_lazy_routes() {
+        static PREFETCHED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+        macro_rules! lazy_route {
+            ($name:ident) => {
+                struct $name;
+
+                impl LazyRoute for $name {
+                    fn data() -> Self {
+                        $name
+                    }
+
+                    async fn view(self) -> AnyView {
+                        ().into_any()
+                    }
+
+                    async fn prefetch() {
+                        PREFETCHED.lock().unwrap().push(stringify!($name));
+                    }
+                }
+            };
+        }
+
+        lazy_route!(Settings);
+        lazy_route!(Profile);
+        lazy_route!(About);
+
+        let routes: RouteDefs<_> = RouteDefs::new((
+            NestedRoute::new(
+                StaticSegment("settings"),
+                Lazy::<Settings>::new(),
+            )
+            .child(NestedRoute::new(
+                StaticSegment("profile"),
+                Lazy::<Profile>::new(),
+            )),
+            NestedRoute::new(StaticSegment("about"), Lazy::<About>::new()),
+        ));
+
+        routes.prefetch("/settings/profile").now_or_never();
+        assert_eq!(*PREFETCHED.lock().unwrap(), ["Settings", "Profile"]);
+
+        routes.prefetch("/contact").now_or_never();
+        assert_eq!(*PREFETCHED.lock().unwrap(), ["Settings", "Profile"]);
+    }
+
+    #[test]
+    pub fn lazy_route_without_fallback_preloads_its_view() {
+        static LOADED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+        struct Dashboard;
+
+        impl LazyRoute for Dashboard {
+            fn data() -> Self {
+                Dashboard
+            }
+
+            async fn view(self) -> AnyView {
+                LOADED.lock().unwrap().push("view");
+                ().into_any()
+            }
+
+            async fn prefetch() {
+                LOADED.lock().unwrap().push("prefetch");
+            }
+        }
+
+        // navigation waits for the view itself, not only for its code
+        Lazy::<Dashboard>::new().preload().now_or_never();
+        assert_eq!(*LOADED.lock().unwrap(), ["view"]);
+
+        // with a fallback, the view is loaded when it is chosen instead
+        Lazy::<Dashboard>::new()
+            .fallback(|| "Loading...")
+            .preload()
+            .now_or_never();
+        assert_eq!(*LOADED.lock().unwrap(), ["view"]);
+    }
 }
 
 #[derive(Debug)]
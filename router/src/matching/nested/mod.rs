@@ -2,7 +2,10 @@ use super::{
     MatchInterface, MatchNestedRoutes, PartialPathMatch, PathSegment,
     PossibleRouteMatch, RouteMatchId,
 };
-use crate::{ChooseView, GeneratedRouteData, MatchParams, Method, SsrMode};
+use crate::{
+    ChooseView, GeneratedRouteData, MatchParams, Method, RouteData,
+    RouteLoader, RouteView, SsrMode,
+};
 use core::{fmt, iter};
 use either_of::Either;
 use std::{
@@ -95,6 +98,33 @@ impl<Segments, Data, View> NestedRoute<Segments, (), Data, View> {
     }
 }
 
+impl<Segments, Children, View> NestedRoute<Segments, Children, (), View> {
+    /// Adds a data loader, which runs as soon as this route is matched.
+    pub fn loader(
+        self,
+        loader: impl Into<RouteLoader>,
+    ) -> NestedRoute<Segments, Children, RouteLoader, View> {
+        let Self {
+            id,
+            segments,
+            children,
+            view,
+            ssr_mode,
+            methods,
+            ..
+        } = self;
+        NestedRoute {
+            id,
+            segments,
+            children,
+            data: loader.into(),
+            view,
+            ssr_mode,
+            methods,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub struct NestedMatch<Child, View> {
     id: RouteMatchId,
@@ -155,10 +185,11 @@ where
     Children: MatchNestedRoutes,
     Children::Match: MatchParams,
     Children: 'static,
+    Data: RouteData,
     View: ChooseView + Clone,
 {
     type Data = Data;
-    type Match = NestedMatch<Children::Match, View>;
+    type Match = NestedMatch<Children::Match, RouteView<View, Data>>;
 
     fn match_nested<'a>(
         &'a self,
@@ -198,7 +229,10 @@ where
                                     matched: matched.to_string(),
                                     params,
                                     child: inner,
-                                    view_fn: self.view.clone(),
+                                    view_fn: RouteView::new(
+                                        self.view.clone(),
+                                        self.data.clone(),
+                                    ),
                                 },
                             )),
                             remaining,
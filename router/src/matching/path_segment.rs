@@ -1,10 +1,14 @@
-use std::borrow::Cow;
+use regex_lite::Regex;
+use std::{borrow::Cow, fmt::Debug, hash::Hash, sync::OnceLock};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum PathSegment {
     Unit,
     Static(Cow<'static, str>),
     Param(Cow<'static, str>),
+    /// A param whose value must meet a [`ParamConstraint`] for the route to match.
+    ConstrainedParam(Cow<'static, str>, ParamConstraint),
     OptionalParam(Cow<'static, str>),
     Splat(Cow<'static, str>),
 }
@@ -15,12 +19,116 @@ impl PathSegment {
             PathSegment::Unit => "",
             PathSegment::Static(i) => i,
             PathSegment::Param(i) => i,
+            PathSegment::ConstrainedParam(i, _) => i,
             PathSegment::OptionalParam(i) => i,
             PathSegment::Splat(i) => i,
         }
     }
 }
 
+/// A condition that the value of a [`ConstrainedParamSegment`](crate::ConstrainedParamSegment)
+/// must meet for its route to match. Otherwise, matching continues with the next route.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParamConstraint {
+    /// An integer: one or more ASCII digits, optionally preceded by `-`.
+    Int,
+    /// One of a fixed set of values.
+    OneOf(&'static [&'static str]),
+    /// A regular expression, which must match the whole value. An invalid expression never
+    /// matches.
+    Regex(&'static ParamRegex),
+}
+
+/// A regular expression for a [`ParamConstraint::Regex`], which is compiled the first time it
+/// is used.
+///
+/// As it keeps the compiled expression, it should be stored in a `static`:
+/// ```rust
+/// use leptos_router::{ConstrainedParamSegment, ParamConstraint, ParamRegex};
+///
+/// static SLUG: ParamRegex = ParamRegex::new("[a-z]+(-[a-z]+)*");
+/// let segment = ConstrainedParamSegment("slug", ParamConstraint::Regex(&SLUG));
+/// ```
+/// The [`path`](crate::path) macro does this for each regular expression in a path.
+pub struct ParamRegex {
+    pattern: &'static str,
+    regex: OnceLock<Option<Regex>>,
+}
+
+impl ParamRegex {
+    /// Creates a regular expression from its pattern.
+    pub const fn new(pattern: &'static str) -> Self {
+        Self {
+            pattern,
+            regex: OnceLock::new(),
+        }
+    }
+
+    /// The pattern of the regular expression.
+    pub fn pattern(&self) -> &'static str {
+        self.pattern
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        self.regex
+            .get_or_init(|| Regex::new(&format!("^(?:{})$", self.pattern)).ok())
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(value))
+    }
+}
+
+impl Debug for ParamRegex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ParamRegex").field(&self.pattern).finish()
+    }
+}
+
+impl PartialEq for ParamRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Eq for ParamRegex {}
+
+impl Hash for ParamRegex {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.pattern.hash(state);
+    }
+}
+
+impl ParamConstraint {
+    /// Whether `value` meets the constraint.
+    pub fn is_satisfied_by(&self, value: &str) -> bool {
+        match self {
+            ParamConstraint::Int => {
+                let digits = value.strip_prefix('-').unwrap_or(value);
+                !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+            }
+            ParamConstraint::OneOf(values) => values.contains(&value),
+            ParamConstraint::Regex(regex) => regex.is_match(value),
+        }
+    }
+
+    /// A regular expression that matches the same values as the constraint, for servers that
+    /// support them in their own route definitions.
+    pub fn to_regex(&self) -> Cow<'static, str> {
+        match self {
+            ParamConstraint::Int => "-?[0-9]+".into(),
+            ParamConstraint::OneOf(values) => format!(
+                "(?:{})",
+                values
+                    .iter()
+                    .map(|value| regex_lite::escape(value))
+                    .collect::<Vec<_>>()
+                    .join("|")
+            )
+            .into(),
+            ParamConstraint::Regex(regex) => regex.pattern().into(),
+        }
+    }
+}
+
 pub trait ExpandOptionals {
     fn expand_optionals(&self) -> Vec<Vec<PathSegment>>;
 }
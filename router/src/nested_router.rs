@@ -1,6 +1,6 @@
 use crate::{
     hooks::Matched,
-    location::{LocationProvider, Url},
+    location::{RouterLocation, Url},
     matching::RouteDefs,
     params::ParamsMap,
     view_transition::start_view_transition,
@@ -42,8 +42,8 @@ use tachys::{
     },
 };
 
-pub(crate) struct NestedRoutesView<Loc, Defs, FalFn> {
-    pub location: Option<Loc>,
+pub(crate) struct NestedRoutesView<Defs, FalFn> {
+    pub location: Option<RouterLocation>,
     pub routes: RouteDefs<Defs>,
     pub outer_owner: Owner,
     pub current_url: ArcRwSignal<Url>,
@@ -66,9 +66,8 @@ where
     view: Rc<RefCell<EitherOf3State<(), Fal, AnyView>>>,
 }
 
-impl<Loc, Defs, FalFn, Fal> Render for NestedRoutesView<Loc, Defs, FalFn>
+impl<Defs, FalFn, Fal> Render for NestedRoutesView<Defs, FalFn>
 where
-    Loc: LocationProvider,
     Defs: MatchNestedRoutes,
     FalFn: FnOnce() -> Fal,
     Fal: Render + 'static,
@@ -141,6 +140,9 @@ where
             for outlet in &state.outlets {
                 outlet.url.set(url_snapshot.to_owned());
             }
+            if let Some(loc) = self.location {
+                loc.ready_to_complete();
+            }
             return;
         }
         // since the path didn't match, we'll update the retained path for future diffing
@@ -225,15 +227,14 @@ where
     }
 }
 
-impl<Loc, Defs, Fal, FalFn> AddAnyAttr for NestedRoutesView<Loc, Defs, FalFn>
+impl<Defs, Fal, FalFn> AddAnyAttr for NestedRoutesView<Defs, FalFn>
 where
-    Loc: LocationProvider + Send,
     Defs: MatchNestedRoutes + Send,
     FalFn: FnOnce() -> Fal + Send,
     Fal: RenderHtml + 'static,
 {
     type Output<SomeNewAttr: leptos::attr::Attribute> =
-        NestedRoutesView<Loc, Defs, FalFn>;
+        NestedRoutesView<Defs, FalFn>;
 
     fn add_any_attr<NewAttr: leptos::attr::Attribute>(
         self,
@@ -246,9 +247,8 @@ where
     }
 }
 
-impl<Loc, Defs, FalFn, Fal> RenderHtml for NestedRoutesView<Loc, Defs, FalFn>
+impl<Defs, FalFn, Fal> RenderHtml for NestedRoutesView<Defs, FalFn>
 where
-    Loc: LocationProvider + Send,
     Defs: MatchNestedRoutes + Send,
     FalFn: FnOnce() -> Fal + Send,
     Fal: RenderHtml + 'static,
@@ -274,7 +274,7 @@ where
         if RouteList::is_generating() {
             // add routes
             let (base, routes) = self.routes.generate_routes();
-            let routes = routes
+            let mut routes = routes
                 .into_iter()
                 .map(|data| {
                     let path = base
@@ -294,6 +294,7 @@ where
                     )
                 })
                 .collect::<Vec<_>>();
+            self.routes.add_locale_listings(&mut routes);
 
             // add fallback
             // TODO fix: causes overlapping route issues on Axum
@@ -439,7 +440,8 @@ where
                     );
                     drop(url);
 
-                    // TODO support for lazy hydration
+                    // lazy routes do not wait to load while hydrating: they keep the
+                    // server-rendered HTML, and hydrate it once they have loaded
                     join_all(mem::take(&mut loaders))
                         .now_or_never()
                         .expect("async routes not supported in SSR");
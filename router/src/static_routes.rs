@@ -232,12 +232,21 @@ impl StaticPath {
                         })
                         .collect::<Vec<_>>();
                 }
-                Param(name) | Splat(name) => {
+                Param(name) | ConstrainedParam(name, _) | Splat(name) => {
+                    // values that don't meet the constraint would never match this route
+                    let constraint = match segment {
+                        ConstrainedParam(_, constraint) => Some(constraint),
+                        _ => None,
+                    };
                     let mut new_paths = vec![];
                     if let Some(params) = params.as_ref() {
                         for path in paths {
                             if let Some(params) = params.get(name) {
-                                for val in params.iter() {
+                                for val in params.iter().filter(|val| {
+                                    constraint.map_or(true, |constraint| {
+                                        constraint.is_satisfied_by(val)
+                                    })
+                                }) {
                                     new_paths.push(if val.starts_with("/") {
                                         ResolvedStaticPath {
                                             path: format!(
@@ -381,6 +390,7 @@ impl ResolvedStaticPath {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ParamConstraint;
 
     #[test]
     fn static_path_segments_into_path_ignore_empty_segments() {
@@ -413,6 +423,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn static_path_segments_into_path_skip_constrained_param_mismatches() {
+        let mut params = StaticParamsMap::new();
+        params
+            .0
+            .push(("id".into(), vec!["1".into(), "new".into(), "2".into()]));
+        let segments = StaticPath::new(vec![
+            PathSegment::Static("/post".into()),
+            PathSegment::ConstrainedParam("id".into(), ParamConstraint::Int),
+        ]);
+        assert_eq!(
+            segments.into_paths(Some(params)),
+            vec![
+                ResolvedStaticPath::new("/post/1"),
+                ResolvedStaticPath::new("/post/2")
+            ]
+        );
+    }
+
     #[test]
     fn static_path_segments_into_path_no_double_slash() {
         let segments = StaticPath::new(vec![
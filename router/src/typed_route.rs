@@ -0,0 +1,149 @@
+use crate::{location::Url, params::ParamsMap, PossibleRouteMatch};
+use std::{fmt::Display, str::FromStr};
+
+/// A route whose URL is built from (and parsed into) a typed value, rather than a free-form
+/// string.
+///
+/// This is usually implemented with `#[derive(TypedRoute)]`, which generates the path segments
+/// for each route from a `#[route("...")]` attribute, using the same syntax as the
+/// [`path`](crate::path) macro. Each `:param`, `:param?` or `*wildcard` in the path must have a
+/// field of the same name, and each field must appear in the path, so a link with a misspelled
+/// route or the wrong parameters fails to compile.
+///
+/// Types that derive `TypedRoute` also implement [`ToHref`](crate::components::ToHref), so they can be used directly as the
+/// `href` of an [`A`](crate::components::A) or the `action` of a [`Form`](crate::components::Form).
+///
+/// ```rust,no_run
+/// use leptos::prelude::*;
+/// use leptos_router::{
+///     components::{Route, A},
+///     TypedRoute,
+/// };
+///
+/// #[derive(TypedRoute, Clone, Debug, PartialEq)]
+/// enum AppRoute {
+///     #[route("/")]
+///     Home,
+///     #[route("/users/:id")]
+///     User { id: usize },
+///     #[route("/search/:query?")]
+///     Search { query: Option<String> },
+/// }
+///
+/// # fn UserPage() -> impl IntoView {}
+/// // the route definition and the link share a single source of truth
+/// let route = view! { <Route path=AppRoute::user_path() view=UserPage/> };
+/// let link = view! { <A href=AppRoute::User { id: 3 }>"User 3"</A> };
+///
+/// assert_eq!(AppRoute::User { id: 3 }.to_href(), "/users/3");
+/// assert_eq!(
+///     AppRoute::from_path("/users/3"),
+///     Some(AppRoute::User { id: 3 })
+/// );
+/// ```
+///
+/// Linking to a route that does not exist, or leaving out one of its parameters, is a
+/// compile-time error:
+/// ```rust,compile_fail
+/// # use leptos_router::TypedRoute;
+/// #[derive(TypedRoute)]
+/// enum AppRoute {
+///     #[route("/users/:id")]
+///     User { id: usize },
+/// }
+///
+/// let href = AppRoute::Usr { id: 3 }.to_href();
+/// ```
+pub trait TypedRoute: Sized {
+    /// Builds the URL for this route, percent-encoding each parameter.
+    fn to_href(&self) -> String;
+
+    /// Parses a path (like the one returned by `use_location().pathname`) into a route, if it
+    /// matches one.
+    fn from_path(path: &str) -> Option<Self>;
+}
+
+/// Matches the path against a set of route segments, returning its params if the whole path
+/// was matched.
+#[doc(hidden)]
+pub fn __match_typed_route(
+    segments: &impl PossibleRouteMatch,
+    path: &str,
+) -> Option<ParamsMap> {
+    let matched = segments.test(path)?;
+    if !matched.is_complete() {
+        return None;
+    }
+    Some(matched.params().into_iter().collect())
+}
+
+/// Reads a required param from a matched route, following the same rules as
+/// [`IntoParam`](crate::params::IntoParam).
+#[doc(hidden)]
+pub fn __typed_route_param<T: FromStr>(
+    params: &ParamsMap,
+    name: &str,
+) -> Option<T> {
+    params
+        .get_str(name)
+        .and_then(|value| T::from_str(value).ok())
+}
+
+/// Reads an optional param from a matched route, following the same rules as
+/// [`IntoParam`](crate::params::IntoParam). A missing param is `Some(None)`, and a param that
+/// cannot be parsed is `None`.
+#[doc(hidden)]
+pub fn __typed_route_optional_param<T: FromStr>(
+    params: &ParamsMap,
+    name: &str,
+) -> Option<Option<T>> {
+    match params.get_str(name) {
+        None => Some(None),
+        Some(value) => T::from_str(value).ok().map(Some),
+    }
+}
+
+/// Builds the URL of a typed route one segment at a time.
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct __TypedHref(String);
+
+impl __TypedHref {
+    pub fn static_segment(&mut self, segment: &str) {
+        if segment == "/" {
+            if !self.0.ends_with('/') {
+                self.0.push('/');
+            }
+        } else {
+            self.0.push('/');
+            self.0.push_str(segment);
+        }
+    }
+
+    pub fn param(&mut self, value: &impl Display) {
+        self.0.push('/');
+        self.0.push_str(&Url::escape(&value.to_string()));
+    }
+
+    pub fn optional_param(&mut self, value: Option<&impl Display>) {
+        if let Some(value) = value {
+            self.param(value);
+        }
+    }
+
+    pub fn wildcard(&mut self, value: &impl Display) {
+        // a wildcard can span several segments, so only the segments themselves are escaped
+        for segment in value.to_string().split('/').filter(|s| !s.is_empty()) {
+            self.0.push('/');
+            self.0.push_str(&Url::escape(segment));
+        }
+    }
+
+    pub fn finish(self) -> String {
+        if self.0.is_empty() {
+            "/".to_string()
+        } else {
+            self.0
+        }
+    }
+}
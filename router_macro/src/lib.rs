@@ -9,9 +9,12 @@ use proc_macro2::Span;
 use proc_macro_error2::{abort, proc_macro_error};
 use quote::{quote, ToTokens};
 use syn::{
-    spanned::Spanned, Block, Ident, ImplItem, ItemImpl, Path, Type, TypePath,
+    parse_macro_input, spanned::Spanned, Block, DeriveInput, Ident, ImplItem,
+    ItemImpl, LitStr, Path, Type, TypePath,
 };
 
+mod typed_route;
+
 const RFC3986_UNRESERVED: [char; 4] = ['-', '.', '_', '~'];
 const RFC3986_PCHAR_OTHER: [char; 1] = ['@'];
 
@@ -38,6 +41,27 @@ const RFC3986_PCHAR_OTHER: [char; 1] = ['@'];
 ///
 /// assert_eq!(path, output);
 /// ```
+///
+/// A required param can be constrained, so that the route only matches if the param's value
+/// meets the constraint, and the next route is tried otherwise:
+/// - `:id<int>` matches an integer
+/// - `:tab<posts|likes>` matches one of the listed values
+/// - `:slug([a-z0-9-]+)` matches a regular expression, which may not contain `/`
+///
+/// ```rust
+/// use leptos_router::{
+///     path, ConstrainedParamSegment, ParamConstraint, StaticSegment,
+/// };
+///
+/// let path = path!("/users/:id<int>/:tab<posts|likes>");
+/// let output = (
+///     StaticSegment("users"),
+///     ConstrainedParamSegment("id", ParamConstraint::Int),
+///     ConstrainedParamSegment("tab", ParamConstraint::OneOf(&["posts", "likes"])),
+/// );
+///
+/// assert_eq!(path, output);
+/// ```
 /// [`Route`]: https://docs.rs/leptos_router/latest/leptos_router/components/fn.Route.html
 #[proc_macro_error2::proc_macro_error]
 #[proc_macro]
@@ -51,14 +75,22 @@ pub fn path(tokens: TokenStream) -> TokenStream {
 #[derive(Debug, PartialEq)]
 struct Segments(pub Vec<Segment>);
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 enum Segment {
     Static(String),
     Param(String),
+    ConstrainedParam(String, Constraint),
     OptionalParam(String),
     Wildcard(String),
 }
 
+#[derive(Debug, PartialEq, Clone)]
+enum Constraint {
+    Int,
+    OneOf(Vec<String>),
+    Regex(String),
+}
+
 struct SegmentParser {
     input: proc_macro::token_stream::IntoIter,
     segments: Vec<Segment>,
@@ -78,21 +110,13 @@ impl SegmentParser {
         for input in self.input.by_ref() {
             match input {
                 TokenTree::Literal(lit) => {
-                    let lit = lit.to_string();
-                    if lit.contains("//") {
-                        abort!(
-                            proc_macro2::Span::call_site(),
-                            "Consecutive '/' is not allowed"
-                        );
-                    }
-                    Self::parse_str(
-                        &mut self.segments,
-                        lit.trim_start_matches(['"', '/'])
-                            .trim_end_matches(['"', '/']),
-                    );
-                    if lit.ends_with(r#"/""#) && lit != r#""/""# {
-                        self.segments.push(Segment::Static("/".to_string()));
-                    }
+                    // parsed as a string literal, so that escapes in a regex constraint work
+                    let lit =
+                        syn::parse::<LitStr>(TokenTree::Literal(lit).into())
+                            .unwrap_or_else(|e| {
+                                abort!(e.span(), "expected a string literal")
+                            });
+                    Self::parse_path(&mut self.segments, &lit.value());
                 }
                 TokenTree::Group(_) => unimplemented!(),
                 TokenTree::Ident(_) => unimplemented!(),
@@ -101,6 +125,19 @@ impl SegmentParser {
         }
     }
 
+    pub fn parse_path(segments: &mut Vec<Segment>, path: &str) {
+        if path.contains("//") {
+            abort!(
+                proc_macro2::Span::call_site(),
+                "Consecutive '/' is not allowed"
+            );
+        }
+        Self::parse_str(segments, path.trim_matches('/'));
+        if path.ends_with('/') && path != "/" {
+            segments.push(Segment::Static("/".to_string()));
+        }
+    }
+
     pub fn parse_str(segments: &mut Vec<Segment>, current_str: &str) {
         if ["", "*"].contains(&current_str) {
             return;
@@ -108,7 +145,14 @@ impl SegmentParser {
 
         for segment in current_str.split('/') {
             if let Some(segment) = segment.strip_prefix(':') {
-                if let Some(segment) = segment.strip_suffix('?') {
+                if let Some((name, constraint)) =
+                    Self::parse_constraint(segment)
+                {
+                    segments.push(Segment::ConstrainedParam(
+                        name.to_string(),
+                        constraint,
+                    ));
+                } else if let Some(segment) = segment.strip_suffix('?') {
                     segments.push(Segment::OptionalParam(segment.to_string()));
                 } else {
                     segments.push(Segment::Param(segment.to_string()));
@@ -122,6 +166,67 @@ impl SegmentParser {
     }
 }
 
+impl SegmentParser {
+    /// Splits a param like `id<int>`, `tab<posts|likes>` or `slug([a-z-]+)` into its name and
+    /// constraint.
+    fn parse_constraint(param: &str) -> Option<(&str, Constraint)> {
+        let (name, constraint) = if let Some(start) = param.find('<') {
+            let Some(values) = param[start + 1..].strip_suffix('>') else {
+                abort!(
+                    Span::call_site(),
+                    "Unclosed param constraint: {}",
+                    param
+                )
+            };
+            let constraint = if values == "int" {
+                Constraint::Int
+            } else {
+                let values =
+                    values.split('|').map(String::from).collect::<Vec<_>>();
+                if let Some(value) = values
+                    .iter()
+                    .find(|value| value.is_empty() || !Segment::is_valid(value))
+                {
+                    abort!(
+                        Span::call_site(),
+                        "Invalid param constraint value: {}",
+                        value
+                    )
+                }
+                Constraint::OneOf(values)
+            };
+            (&param[..start], constraint)
+        } else if let Some(start) = param.find('(') {
+            let Some(pattern) = param[start + 1..].strip_suffix(')') else {
+                abort!(
+                    Span::call_site(),
+                    "Unclosed param constraint: {}",
+                    param
+                )
+            };
+            if let Err(e) = regex_lite::Regex::new(pattern) {
+                abort!(
+                    Span::call_site(),
+                    "Invalid param regex `{}`: {}",
+                    pattern,
+                    e
+                )
+            }
+            (&param[..start], Constraint::Regex(pattern.to_string()))
+        } else {
+            return None;
+        };
+        if name.ends_with('?') {
+            abort!(
+                Span::call_site(),
+                "Optional params cannot have a constraint: {}",
+                param
+            )
+        }
+        Some((name, constraint))
+    }
+}
+
 impl Segment {
     fn is_valid(segment: &str) -> bool {
         segment == "/"
@@ -142,7 +247,9 @@ impl Segment {
             Self::Static(s) if !Self::is_valid(s) => {
                 abort!(Span::call_site(), "Invalid static segment: {}", s)
             }
-            Self::Param(s) if !Self::is_valid(s) => {
+            Self::Param(s) | Self::ConstrainedParam(s, _)
+                if !Self::is_valid(s) =>
+            {
                 abort!(Span::call_site(), "Invalid param segment: {}", s)
             }
             _ => (),
@@ -175,6 +282,11 @@ impl ToTokens for Segment {
             Segment::Param(p) => {
                 tokens.extend(quote! { leptos_router::ParamSegment(#p) });
             }
+            Segment::ConstrainedParam(p, constraint) => {
+                tokens.extend(quote! {
+                    leptos_router::ConstrainedParamSegment(#p, #constraint)
+                });
+            }
             Segment::OptionalParam(p) => {
                 tokens
                     .extend(quote! { leptos_router::OptionalParamSegment(#p) });
@@ -183,6 +295,51 @@ impl ToTokens for Segment {
     }
 }
 
+impl ToTokens for Constraint {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            Constraint::Int => {
+                tokens.extend(quote! { leptos_router::ParamConstraint::Int });
+            }
+            Constraint::OneOf(values) => tokens.extend(quote! {
+                leptos_router::ParamConstraint::OneOf(&[#(#values),*])
+            }),
+            // each regular expression is compiled once, into its own `static`
+            Constraint::Regex(pattern) => tokens.extend(quote! {
+                leptos_router::ParamConstraint::Regex({
+                    static REGEX: leptos_router::ParamRegex =
+                        leptos_router::ParamRegex::new(#pattern);
+                    &REGEX
+                })
+            }),
+        }
+    }
+}
+
+impl Segments {
+    /// The type of the tuple of segments generated by [`ToTokens`].
+    fn type_tokens(&self) -> proc_macro2::TokenStream {
+        let types = self.0.iter().map(|segment| match segment {
+            Segment::Wildcard(_) => quote! { leptos_router::WildcardSegment },
+            Segment::Static(_) => {
+                quote! { leptos_router::StaticSegment<&'static str> }
+            }
+            Segment::Param(_) => quote! { leptos_router::ParamSegment },
+            Segment::ConstrainedParam(..) => {
+                quote! { leptos_router::ConstrainedParamSegment }
+            }
+            Segment::OptionalParam(_) => {
+                quote! { leptos_router::OptionalParamSegment }
+            }
+        });
+        match self.0.as_slice() {
+            [] => quote! { () },
+            [_] => quote! { (#(#types,)*) },
+            _ => quote! { (#(#types),*) },
+        }
+    }
+}
+
 impl ToTokens for Segments {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         self.ensure_valid();
@@ -194,6 +351,52 @@ impl ToTokens for Segments {
     }
 }
 
+/// Derives [`TypedRoute`] for a struct or enum, so that links to a route can be built from (and
+/// parsed into) typed values instead of free-form strings.
+///
+/// Each struct, or each variant of an enum, is annotated with a `#[route("...")]` attribute that
+/// uses the same syntax as [`path!`](path). Every `:param`, `:param?` or `*wildcard` in the path
+/// must have a named field with the same name, and every field must appear in the path. As with
+/// [`Params`], each field is parsed with [`FromStr`](std::str::FromStr), and the field for an
+/// optional `:param?` must be an `Option<_>`. Fields are written into the URL with
+/// [`Display`](std::fmt::Display).
+///
+/// This generates:
+/// - an implementation of [`TypedRoute`] and of [`ToHref`], so the route can be passed directly
+///   to `<A href=...>`
+/// - an inherent `to_href()` method and a [`Display`](std::fmt::Display) implementation
+/// - a `const fn` returning the path segments for each route, for use in the `<Route/>`
+///   definition: `path()` for a struct, and `{variant}_path()` (in snake case) for an enum
+///
+/// ```rust
+/// use leptos_router::{StaticSegment, ParamSegment, TypedRoute};
+///
+/// #[derive(TypedRoute, Debug, PartialEq)]
+/// enum AppRoute {
+///     #[route("/")]
+///     Home,
+///     #[route("/users/:id")]
+///     User { id: usize },
+/// }
+///
+/// assert_eq!(
+///     AppRoute::user_path(),
+///     (StaticSegment("users"), ParamSegment("id"))
+/// );
+/// assert_eq!(AppRoute::User { id: 3 }.to_href(), "/users/3");
+/// assert_eq!(AppRoute::from_path("/"), Some(AppRoute::Home));
+/// ```
+///
+/// [`TypedRoute`]: https://docs.rs/leptos_router/latest/leptos_router/trait.TypedRoute.html
+/// [`ToHref`]: https://docs.rs/leptos_router/latest/leptos_router/components/trait.ToHref.html
+/// [`Params`]: https://docs.rs/leptos_router/latest/leptos_router/params/trait.Params.html
+#[proc_macro_error2::proc_macro_error]
+#[proc_macro_derive(TypedRoute, attributes(route))]
+pub fn typed_route(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    typed_route::typed_route_impl(input).into()
+}
+
 /// When added to an [`impl LazyRoute`] implementation block, this will automatically
 /// add a [`lazy`] annotation to the `view` method, which will cause the code for the view
 /// to lazy-load concurrently with the `data` being loaded for the route.
@@ -0,0 +1,278 @@
+use crate::{Segment, SegmentParser, Segments};
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error2::abort;
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned, Attribute, Data, DeriveInput, Fields, Ident, LitStr,
+};
+
+/// A single route: either the struct itself, or one variant of an enum.
+struct RouteDef {
+    /// The path used to construct or destructure the route, e.g. `Self::User`.
+    ctor: TokenStream,
+    /// The name of the route in docs, e.g. `AppRoute::User`.
+    label: String,
+    /// The name of the associated function that returns the route's path segments.
+    path_fn: Ident,
+    segments: Vec<Segment>,
+    fields: Vec<Ident>,
+}
+
+pub(crate) fn typed_route_impl(input: DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    let routes = match &input.data {
+        Data::Struct(data) => vec![RouteDef::new(
+            quote! { Self },
+            name.to_string(),
+            format_ident!("path"),
+            &input.attrs,
+            &data.fields,
+            input.span(),
+        )],
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                RouteDef::new(
+                    quote! { Self::#ident },
+                    format!("{name}::{ident}"),
+                    format_ident!("{}_path", to_snake_case(&ident.to_string())),
+                    &variant.attrs,
+                    &variant.fields,
+                    variant.span(),
+                )
+            })
+            .collect(),
+        Data::Union(_) => abort!(
+            input.span(),
+            "`TypedRoute` can only be derived for structs and enums"
+        ),
+    };
+
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let path_fns = routes.iter().map(|route| {
+        let path_fn = &route.path_fn;
+        let segments = Segments(route.segments.clone());
+        let ty = segments.type_tokens();
+        let doc = format!(
+            "The path segments for [`{}`], for use in a `<Route/>` definition.",
+            route.label
+        );
+        if route.segments.is_empty() {
+            quote! {
+                #[doc = #doc]
+                pub const fn #path_fn() {}
+            }
+        } else {
+            quote! {
+                #[doc = #doc]
+                pub const fn #path_fn() -> #ty {
+                    #segments
+                }
+            }
+        }
+    });
+
+    let to_href_arms = routes.iter().map(|route| {
+        let ctor = &route.ctor;
+        let fields = &route.fields;
+        let segments = route.segments.iter().map(|segment| match segment {
+            Segment::Static(s) => quote! { href.static_segment(#s); },
+            Segment::Param(p) | Segment::ConstrainedParam(p, _) => {
+                let field = Ident::new(p, Span::call_site());
+                quote! { href.param(#field); }
+            }
+            Segment::OptionalParam(p) => {
+                let field = Ident::new(p, Span::call_site());
+                quote! { href.optional_param(#field.as_ref()); }
+            }
+            Segment::Wildcard(p) if p.is_empty() => quote! {},
+            Segment::Wildcard(p) => {
+                let field = Ident::new(p, Span::call_site());
+                quote! { href.wildcard(#field); }
+            }
+        });
+        quote! {
+            #ctor { #(#fields),* } => {
+                #(#segments)*
+            }
+        }
+    });
+
+    let from_path = routes.iter().map(|route| {
+        let ctor = &route.ctor;
+        let path_fn = &route.path_fn;
+        let fields = &route.fields;
+        let params = route.segments.iter().filter_map(|segment| {
+            let (name, read) = match segment {
+                Segment::Param(p) | Segment::ConstrainedParam(p, _) => {
+                    (p, quote! { leptos_router::__typed_route_param })
+                }
+                Segment::Wildcard(p) if !p.is_empty() => {
+                    (p, quote! { leptos_router::__typed_route_param })
+                }
+                Segment::OptionalParam(p) => {
+                    (p, quote! { leptos_router::__typed_route_optional_param })
+                }
+                _ => return None,
+            };
+            let field = Ident::new(name, Span::call_site());
+            Some(quote! {
+                let Some(#field) = #read(&__params, #name) else {
+                    break 'route;
+                };
+            })
+        });
+        quote! {
+            'route: {
+                let Some(__params) = leptos_router::__match_typed_route(
+                    &Self::#path_fn(),
+                    path,
+                ) else {
+                    break 'route;
+                };
+                #(#params)*
+                return Some(#ctor { #(#fields),* });
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#path_fns)*
+
+            /// Builds the URL for this route.
+            pub fn to_href(&self) -> String {
+                <Self as leptos_router::TypedRoute>::to_href(self)
+            }
+        }
+
+        impl #impl_generics leptos_router::TypedRoute for #name #ty_generics #where_clause {
+            fn to_href(&self) -> String {
+                #[allow(unused_mut)]
+                let mut href = leptos_router::__TypedHref::default();
+                match self {
+                    #(#to_href_arms)*
+                }
+                href.finish()
+            }
+
+            fn from_path(path: &str) -> Option<Self> {
+                #(#from_path)*
+                None
+            }
+        }
+
+        impl #impl_generics leptos_router::components::ToHref for #name #ty_generics #where_clause {
+            fn to_href(&self) -> Box<dyn Fn() -> String + '_> {
+                let href = <Self as leptos_router::TypedRoute>::to_href(self);
+                Box::new(move || href.clone())
+            }
+        }
+
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(&<Self as leptos_router::TypedRoute>::to_href(self))
+            }
+        }
+    }
+}
+
+impl RouteDef {
+    fn new(
+        ctor: TokenStream,
+        label: String,
+        path_fn: Ident,
+        attrs: &[Attribute],
+        fields: &Fields,
+        span: Span,
+    ) -> Self {
+        let path = attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("route"))
+            .unwrap_or_else(|| {
+                abort!(span, "missing `#[route(\"...\")]` attribute")
+            })
+            .parse_args::<LitStr>()
+            .unwrap_or_else(|e| {
+                abort!(
+                    e.span(),
+                    "expected a path, like `#[route(\"/users/:id\")]`"
+                )
+            });
+
+        let mut segments = Vec::new();
+        SegmentParser::parse_path(&mut segments, &path.value());
+
+        let fields = match fields {
+            Fields::Named(named) => named
+                .named
+                .iter()
+                .map(|field| field.ident.clone().unwrap())
+                .collect::<Vec<_>>(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(unnamed) => abort!(
+                unnamed.span(),
+                "route parameters must be named fields, matching the names in \
+                 the path"
+            ),
+        };
+
+        // every param in the path needs a field to fill it, and vice versa
+        let params = segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Param(p)
+                | Segment::ConstrainedParam(p, _)
+                | Segment::OptionalParam(p) => Some(p),
+                Segment::Wildcard(p) if !p.is_empty() => Some(p),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for param in &params {
+            if !fields.iter().any(|field| field == param.as_str()) {
+                abort!(
+                    path.span(),
+                    "route parameter `{}` has no matching field",
+                    param
+                );
+            }
+        }
+        for field in &fields {
+            if !params.iter().any(|param| field == param.as_str()) {
+                abort!(
+                    field.span(),
+                    "field `{}` does not appear in the route path `{}`",
+                    field,
+                    path.value()
+                );
+            }
+        }
+
+        Self {
+            ctor,
+            label,
+            path_fn,
+            segments,
+            fields,
+        }
+    }
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len());
+    for (idx, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if idx > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
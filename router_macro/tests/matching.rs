@@ -0,0 +1,93 @@
+//! Regression tests for matching the kinds of routes that apps define with `path!`, which
+//! pin the behavior of the `StaticSegment` and tuple matchers.
+
+use leptos_router::PossibleRouteMatch;
+use leptos_router_macro::path;
+
+#[test]
+fn matches_static_route() {
+    let def = path!("/users");
+
+    let matched = def.test("/users").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/users");
+    assert_eq!(matched.remaining(), "");
+
+    let matched = def.test("/users/").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/users");
+    assert_eq!(matched.remaining(), "/");
+
+    let matched = def.test("/users/42").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/users");
+    assert_eq!(matched.remaining(), "/42");
+}
+
+#[test]
+fn static_route_does_not_match_other_segments() {
+    let def = path!("/users");
+    assert!(def.test("/user").is_none());
+    assert!(def.test("/user/s").is_none());
+    assert!(def.test("/").is_none());
+}
+
+#[test]
+fn nested_static_route_matches_whole_segments() {
+    let def = path!("/foo/bar");
+    assert!(def.test("/foo/bar").is_some());
+    assert!(def.test("/foo/ba").is_none());
+    assert!(def.test("/fo/obar").is_none());
+    assert!(def.test("/food/bar").is_none());
+}
+
+#[test]
+fn matches_params_between_static_segments() {
+    let def = path!("/users/:id/posts");
+
+    let matched = def.test("/users/42/posts").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/users/42/posts");
+    assert_eq!(matched.params(), [("id".into(), "42".into())]);
+
+    assert!(def.test("/users/42").is_none());
+    assert!(def.test("/users/42/post").is_none());
+}
+
+#[test]
+fn matches_trailing_optional_param() {
+    let def = path!("/users/:id/:tab?");
+
+    let matched = def.test("/users/42").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/users/42");
+    assert_eq!(matched.params(), [("id".into(), "42".into())]);
+
+    let matched = def.test("/users/42/likes").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/users/42/likes");
+    assert_eq!(
+        matched.params(),
+        [("id".into(), "42".into()), ("tab".into(), "likes".into())]
+    );
+}
+
+#[test]
+fn matches_leading_optional_param() {
+    let def = path!("/:lang?/about");
+
+    let matched = def.test("/about").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/about");
+    assert!(matched.params().is_empty());
+
+    let matched = def.test("/de/about").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/de/about");
+    assert_eq!(matched.params(), [("lang".into(), "de".into())]);
+
+    assert!(def.test("/de").is_none());
+}
+
+#[test]
+fn matches_wildcard_route() {
+    let def = path!("/files/*path");
+
+    let matched = def.test("/files/a/b.txt").expect("couldn't match route");
+    assert_eq!(matched.matched(), "/files/a/b.txt");
+    assert_eq!(matched.params(), [("path".into(), "a/b.txt".into())]);
+
+    assert!(def.test("/file/a").is_none());
+}
@@ -1,5 +1,6 @@
 use leptos_router::{
-    OptionalParamSegment, ParamSegment, StaticSegment, WildcardSegment,
+    ConstrainedParamSegment, OptionalParamSegment, ParamConstraint, ParamRegex,
+    ParamSegment, StaticSegment, WildcardSegment,
 };
 use leptos_router_macro::path;
 
@@ -181,6 +182,46 @@ fn parses_complex() {
     );
 }
 
+#[test]
+fn parses_int_constrained_param() {
+    let output = path!("/users/:id<int>");
+    assert_eq!(
+        output,
+        (
+            StaticSegment("users"),
+            ConstrainedParamSegment("id", ParamConstraint::Int),
+        )
+    );
+}
+
+#[test]
+fn parses_one_of_constrained_param() {
+    let output = path!("/:tab<posts|likes>/:id");
+    assert_eq!(
+        output,
+        (
+            ConstrainedParamSegment(
+                "tab",
+                ParamConstraint::OneOf(&["posts", "likes"])
+            ),
+            ParamSegment("id"),
+        )
+    );
+}
+
+#[test]
+fn parses_regex_constrained_param() {
+    static SLUG: ParamRegex = ParamRegex::new(r"[a-z\d-]+");
+    let output = path!(r"/posts/:slug([a-z\d-]+)");
+    assert_eq!(
+        output,
+        (
+            StaticSegment("posts"),
+            ConstrainedParamSegment("slug", ParamConstraint::Regex(&SLUG)),
+        )
+    );
+}
+
 // #[test]
 // fn deny_consecutive_slashes() {
 //     let _ = path!("/////foo///bar/////baz/");
@@ -0,0 +1,134 @@
+use leptos_router::{
+    OptionalParamSegment, ParamSegment, StaticSegment, TypedRoute,
+    WildcardSegment,
+};
+
+#[derive(TypedRoute, Debug, PartialEq)]
+enum AppRoute {
+    #[route("/")]
+    Home,
+    #[route("/users/:id")]
+    User { id: usize },
+    #[route("/users/:id/posts/:post_id?")]
+    UserPosts { id: usize, post_id: Option<u32> },
+    #[route("/files/*path")]
+    File { path: String },
+}
+
+#[derive(TypedRoute, Debug, PartialEq)]
+#[route("/settings/")]
+struct Settings;
+
+#[test]
+fn generates_path_segments() {
+    assert_eq!(AppRoute::home_path(), ());
+    assert_eq!(
+        AppRoute::user_path(),
+        (StaticSegment("users"), ParamSegment("id"))
+    );
+    assert_eq!(
+        AppRoute::user_posts_path(),
+        (
+            StaticSegment("users"),
+            ParamSegment("id"),
+            StaticSegment("posts"),
+            OptionalParamSegment("post_id")
+        )
+    );
+    assert_eq!(
+        AppRoute::file_path(),
+        (StaticSegment("files"), WildcardSegment("path"))
+    );
+    assert_eq!(
+        Settings::path(),
+        (StaticSegment("settings"), StaticSegment("/"))
+    );
+}
+
+#[test]
+fn builds_hrefs() {
+    assert_eq!(AppRoute::Home.to_href(), "/");
+    assert_eq!(AppRoute::User { id: 42 }.to_href(), "/users/42");
+    assert_eq!(
+        AppRoute::UserPosts {
+            id: 42,
+            post_id: Some(7)
+        }
+        .to_href(),
+        "/users/42/posts/7"
+    );
+    assert_eq!(
+        AppRoute::UserPosts {
+            id: 42,
+            post_id: None
+        }
+        .to_href(),
+        "/users/42/posts"
+    );
+    assert_eq!(
+        AppRoute::File {
+            path: "docs/intro".into()
+        }
+        .to_href(),
+        "/files/docs/intro"
+    );
+    assert_eq!(Settings.to_href(), "/settings/");
+    assert_eq!(AppRoute::User { id: 1 }.to_string(), "/users/1");
+}
+
+#[test]
+fn parses_paths() {
+    assert_eq!(AppRoute::from_path("/"), Some(AppRoute::Home));
+    assert_eq!(AppRoute::from_path(""), Some(AppRoute::Home));
+    assert_eq!(
+        AppRoute::from_path("/users/42"),
+        Some(AppRoute::User { id: 42 })
+    );
+    assert_eq!(
+        AppRoute::from_path("/users/42/posts"),
+        Some(AppRoute::UserPosts {
+            id: 42,
+            post_id: None
+        })
+    );
+    assert_eq!(
+        AppRoute::from_path("/users/42/posts/7"),
+        Some(AppRoute::UserPosts {
+            id: 42,
+            post_id: Some(7)
+        })
+    );
+    assert_eq!(
+        AppRoute::from_path("/files/docs/intro"),
+        Some(AppRoute::File {
+            path: "docs/intro".into()
+        })
+    );
+    assert_eq!(Settings::from_path("/settings/"), Some(Settings));
+}
+
+#[test]
+fn rejects_mismatched_paths() {
+    assert_eq!(AppRoute::from_path("/users/abc"), None);
+    assert_eq!(AppRoute::from_path("/user/42"), None);
+    assert_eq!(AppRoute::from_path("/users/42/comments"), None);
+    assert_eq!(Settings::from_path("/"), None);
+}
+
+#[test]
+fn round_trips() {
+    let routes = [
+        AppRoute::Home,
+        AppRoute::User { id: 1 },
+        AppRoute::UserPosts {
+            id: 2,
+            post_id: Some(3),
+        },
+        AppRoute::File {
+            path: "a/b/c".into(),
+        },
+    ];
+    for route in routes {
+        assert_eq!(AppRoute::from_path(&route.to_href()), Some(route));
+    }
+}
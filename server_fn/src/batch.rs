@@ -0,0 +1,366 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::OnceLock;
+#[cfg(any(feature = "axum-no-default", feature = "actix", test))]
+use std::sync::{Mutex, PoisonError};
+
+/// The name of the endpoint that receives batched server function calls.
+///
+/// The endpoint shares the prefix of the server functions in the batch, so that it is handled
+/// by the same Axum or Actix handler as the server functions themselves. See [`batch_path`].
+pub const BATCH_ENDPOINT: &str = "_batch";
+
+/// The path of the endpoint that receives batched calls to server functions with the default
+/// `/api` prefix, relative to the server URL.
+pub const BATCH_PATH: &str = "/api/_batch";
+
+/// Returns the path of the endpoint that receives batched calls to the server function at
+/// `path`, which is the [`BATCH_ENDPOINT`] under the same prefix.
+///
+/// ```rust
+/// # use server_fn::batch::batch_path;
+/// assert_eq!(batch_path("/api/get_post"), "/api/_batch");
+/// assert_eq!(batch_path("/rpc/v1/get_post"), "/rpc/v1/_batch");
+/// ```
+pub fn batch_path(path: &str) -> String {
+    let prefix = path.rsplit_once('/').map_or("", |(prefix, _)| prefix);
+    format!("{prefix}/{BATCH_ENDPOINT}")
+}
+
+/// Whether `path` is the path of a batch endpoint, as returned by [`batch_path`].
+pub fn is_batch_path(path: &str) -> bool {
+    path.rsplit_once('/')
+        .is_some_and(|(_, endpoint)| endpoint == BATCH_ENDPOINT)
+}
+
+/// Returns the batch endpoints for the given server function paths, one for each prefix.
+#[cfg(any(feature = "axum-no-default", feature = "actix", test))]
+pub(crate) fn batch_paths<'a>(
+    paths: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'static str> {
+    // the paths are listed every time the routes are built, so each one is only allocated once
+    static INTERNED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+    let mut interned = INTERNED.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut batch_paths = Vec::new();
+    for path in paths {
+        let path = batch_path(path);
+        let path = match interned.iter().find(|interned| **interned == path) {
+            Some(interned) => *interned,
+            None => {
+                let path: &'static str = Box::leak(path.into_boxed_str());
+                interned.push(path);
+                path
+            }
+        };
+        if !batch_paths.contains(&path) {
+            batch_paths.push(path);
+        }
+    }
+    batch_paths
+}
+
+/// The `Content-Type` of a batched request or response.
+pub const BATCH_CONTENT_TYPE: &str = "application/x-server-fn-batch";
+
+/// The largest number of calls in a single batch, unless it is changed with
+/// [`set_max_batch_calls`].
+pub const DEFAULT_MAX_BATCH_CALLS: usize = 64;
+
+static MAX_BATCH_CALLS: OnceLock<usize> = OnceLock::new();
+
+/// Sets the largest number of calls in a single batch.
+///
+/// On the server, the Axum and Actix batch endpoints reject larger batches with
+/// `413 Payload Too Large` before any of their calls are run. On the client, the
+/// [`BatchClient`](crate::client::batch::BatchClient) never sends more calls than this in one
+/// request, so it should be set to the same value in both. If the client sends a batch that the
+/// server rejects as too large anyway, it sends each of its calls on its own instead.
+///
+/// If this is not set, it defaults to [`DEFAULT_MAX_BATCH_CALLS`]. It can only be set once:
+/// later calls return the maximum they were given as an error.
+pub fn set_max_batch_calls(max: usize) -> Result<(), usize> {
+    MAX_BATCH_CALLS.set(max)
+}
+
+/// Returns the maximum set by [`set_max_batch_calls`].
+pub fn get_max_batch_calls() -> usize {
+    MAX_BATCH_CALLS
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_MAX_BATCH_CALLS)
+}
+
+/// The largest body of a batch request, in bytes, unless it is changed with
+/// [`set_max_batch_body_size`].
+pub const DEFAULT_MAX_BATCH_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+static MAX_BATCH_BODY_SIZE: OnceLock<usize> = OnceLock::new();
+
+/// Sets the largest body, in bytes, that the Axum and Actix batch endpoints read. Larger bodies
+/// are rejected with `413 Payload Too Large` as soon as they pass the limit, rather than being
+/// buffered in full.
+///
+/// If this is not set, it defaults to [`DEFAULT_MAX_BATCH_BODY_SIZE`]. It can only be set once:
+/// later calls return the size they were given as an error.
+pub fn set_max_batch_body_size(max: usize) -> Result<(), usize> {
+    MAX_BATCH_BODY_SIZE.set(max)
+}
+
+/// Returns the size set by [`set_max_batch_body_size`].
+pub fn get_max_batch_body_size() -> usize {
+    MAX_BATCH_BODY_SIZE
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_MAX_BATCH_BODY_SIZE)
+}
+
+/// A single server function call, as sent to the batch endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct BatchCall {
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub content_type: Option<String>,
+    pub accepts: String,
+    #[serde(skip)]
+    pub body: Bytes,
+}
+
+impl BatchCall {
+    /// The path and query of the call, as used in the URI of the request.
+    #[cfg(any(feature = "axum-no-default", feature = "actix", test))]
+    pub fn uri(&self) -> String {
+        match &self.query {
+            Some(query) => format!("{}?{}", self.path, query),
+            None => self.path.clone(),
+        }
+    }
+}
+
+/// The response to a single server function call, as returned by the batch endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct BatchResult {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub location: Option<String>,
+    pub redirect: bool,
+    #[serde(skip)]
+    pub body: Bytes,
+}
+
+impl BatchResult {
+    /// A result for a call that could not be run at all.
+    #[cfg(any(feature = "axum-no-default", feature = "actix", test))]
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: None,
+            location: None,
+            redirect: false,
+            body: Bytes::from(message.into()),
+        }
+    }
+}
+
+/// Each call or result is encoded as a JSON header followed by its body, each prefixed with
+/// its length as a big-endian `u32`.
+trait Framed: Serialize + DeserializeOwned {
+    fn body(&self) -> &Bytes;
+
+    fn set_body(&mut self, body: Bytes);
+}
+
+impl Framed for BatchCall {
+    fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    fn set_body(&mut self, body: Bytes) {
+        self.body = body;
+    }
+}
+
+impl Framed for BatchResult {
+    fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    fn set_body(&mut self, body: Bytes) {
+        self.body = body;
+    }
+}
+
+fn encode<T: Framed>(items: &[T]) -> Result<Bytes, String> {
+    let mut buf = BytesMut::new();
+    for item in items {
+        let header = serde_json::to_vec(item).map_err(|e| e.to_string())?;
+        for part in [&header[..], &item.body()[..]] {
+            let len = u32::try_from(part.len())
+                .map_err(|_| "batched call is too large".to_string())?;
+            buf.put_u32(len);
+            buf.put_slice(part);
+        }
+    }
+    Ok(buf.freeze())
+}
+
+fn decode<T: Framed>(mut data: Bytes) -> Result<Vec<T>, String> {
+    fn read_part(data: &mut Bytes) -> Result<Bytes, String> {
+        if data.remaining() < 4 {
+            return Err("unexpected end of batch".to_string());
+        }
+        let len = data.get_u32() as usize;
+        if data.remaining() < len {
+            return Err("unexpected end of batch".to_string());
+        }
+        Ok(data.split_to(len))
+    }
+
+    let mut items = Vec::new();
+    while data.has_remaining() {
+        let header = read_part(&mut data)?;
+        let mut item: T =
+            serde_json::from_slice(&header).map_err(|e| e.to_string())?;
+        item.set_body(read_part(&mut data)?);
+        items.push(item);
+    }
+    Ok(items)
+}
+
+pub(crate) fn encode_calls(calls: &[BatchCall]) -> Result<Bytes, String> {
+    encode(calls)
+}
+
+#[cfg(any(feature = "axum-no-default", feature = "actix", test))]
+pub(crate) fn decode_calls(data: Bytes) -> Result<Vec<BatchCall>, String> {
+    decode(data)
+}
+
+#[cfg(any(feature = "axum-no-default", feature = "actix", test))]
+pub(crate) fn encode_results(results: &[BatchResult]) -> Result<Bytes, String> {
+    encode(results)
+}
+
+pub(crate) fn decode_results(data: Bytes) -> Result<Vec<BatchResult>, String> {
+    decode(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(path: &str, body: &'static [u8]) -> BatchCall {
+        BatchCall {
+            method: "POST".to_string(),
+            path: path.to_string(),
+            query: None,
+            content_type: Some("application/json".to_string()),
+            accepts: "application/json".to_string(),
+            body: Bytes::from_static(body),
+        }
+    }
+
+    #[test]
+    fn batch_path_shares_prefix() {
+        assert_eq!(batch_path("/api/get_post"), "/api/_batch");
+        assert_eq!(batch_path("/rpc/v1/get_post"), "/rpc/v1/_batch");
+        assert!(is_batch_path("/api/_batch"));
+        assert!(is_batch_path("/rpc/v1/_batch"));
+        assert!(!is_batch_path("/api/get_post"));
+        assert!(!is_batch_path("/api/_batch_post"));
+    }
+
+    #[test]
+    fn lists_one_batch_path_per_prefix() {
+        let paths = batch_paths(["/api/a", "/rpc/b", "/api/c", "/rpc/v1/d"]);
+        assert_eq!(paths, ["/api/_batch", "/rpc/_batch", "/rpc/v1/_batch"]);
+        // the same paths are reused rather than allocated again
+        let again = batch_paths(["/api/e"]);
+        assert!(std::ptr::eq(again[0], paths[0]));
+    }
+
+    #[test]
+    fn round_trips_calls() {
+        let calls = [
+            call("/api/a", br#"{"id":1}"#),
+            BatchCall {
+                method: "GET".to_string(),
+                query: Some("id=2".to_string()),
+                ..call("/api/b", b"")
+            },
+            call("/api/c", b"\x00\xff binary"),
+        ];
+        let decoded = decode_calls(encode_calls(&calls).unwrap()).unwrap();
+        assert_eq!(decoded.len(), 3);
+        for (call, decoded) in calls.iter().zip(&decoded) {
+            assert_eq!(call.method, decoded.method);
+            assert_eq!(call.path, decoded.path);
+            assert_eq!(call.query, decoded.query);
+            assert_eq!(call.content_type, decoded.content_type);
+            assert_eq!(call.accepts, decoded.accepts);
+            assert_eq!(call.body, decoded.body);
+        }
+        assert_eq!(decoded[1].uri(), "/api/b?id=2");
+    }
+
+    #[test]
+    fn round_trips_results() {
+        let results = [
+            BatchResult {
+                status: 302,
+                content_type: None,
+                location: Some("/login".to_string()),
+                redirect: true,
+                body: Bytes::new(),
+            },
+            BatchResult::error(500, "failed"),
+        ];
+        let decoded =
+            decode_results(encode_results(&results).unwrap()).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].status, 302);
+        assert_eq!(decoded[0].location.as_deref(), Some("/login"));
+        assert!(decoded[0].redirect);
+        assert!(decoded[0].body.is_empty());
+        assert_eq!(decoded[1].status, 500);
+        assert_eq!(decoded[1].body, "failed");
+    }
+
+    #[test]
+    fn frames_header_and_body_with_lengths() {
+        let encoded = encode_calls(&[call("/api/a", b"body")]).unwrap();
+        let header_len =
+            u32::from_be_bytes(encoded[..4].try_into().unwrap()) as usize;
+        let body = &encoded[4 + header_len..];
+        assert_eq!(&body[..4], 4u32.to_be_bytes());
+        assert_eq!(&body[4..], b"body");
+    }
+
+    #[test]
+    fn empty_batch_is_empty() {
+        assert!(encode_calls(&[]).unwrap().is_empty());
+        assert!(decode_calls(Bytes::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_batch() {
+        let encoded =
+            encode_calls(&[call("/api/a", b"body"), call("/api/b", b"body")])
+                .unwrap();
+        for len in [2, 10, encoded.len() - 1] {
+            assert_eq!(
+                decode_calls(encoded.slice(..len)).unwrap_err(),
+                "unexpected end of batch"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_header() {
+        let mut data = BytesMut::new();
+        data.put_u32(3);
+        data.put_slice(b"{{{");
+        data.put_u32(0);
+        assert!(decode_calls(data.freeze()).is_err());
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{error::ServerFnError, request::ClientReq, response::ClientRes};
+use crate::{
+    codec::{WebsocketSink, WebsocketStream},
+    error::ServerFnError,
+    request::ClientReq,
+    response::ClientRes,
+};
 use std::{future::Future, sync::OnceLock};
 
 static ROOT_URL: OnceLock<&'static str> = OnceLock::new();
@@ -31,21 +36,1137 @@ pub trait Client<CustErr> {
     fn send(
         req: Self::Request,
     ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send;
+
+    /// Opens a websocket connection to the server function at the given path, returning
+    /// the incoming and outgoing halves of the socket.
+    ///
+    /// This is used by the [`Websocket`](crate::codec::Websocket) protocol. By default, it
+    /// returns an error, for clients that do not support websockets.
+    fn open_websocket(
+        path: &str,
+    ) -> impl Future<
+        Output = Result<
+            (WebsocketStream, WebsocketSink),
+            ServerFnError<CustErr>,
+        >,
+    > + Send {
+        let path = path.to_string();
+        async move {
+            Err(ServerFnError::Request(format!(
+                "Could not open a websocket to {path}: this client does not \
+                 support websockets."
+            )))
+        }
+    }
+}
+
+/// Converts the server function path into a `ws://` or `wss://` URL, relative to `origin` if
+/// no server URL has been set.
+#[cfg(any(feature = "browser", feature = "reqwest"))]
+fn websocket_url(origin: &str, path: &str) -> String {
+    let server_url = get_server_url();
+    let base = if server_url.is_empty() {
+        origin
+    } else {
+        server_url
+    };
+    match base.strip_prefix("http") {
+        Some(rest) => format!("ws{rest}{path}"),
+        None => format!("{base}{path}"),
+    }
+}
+
+/// Timeouts and retries for the requests sent by the `BrowserClient` and `ReqwestClient`.
+pub mod retry {
+    use super::Client;
+    use crate::{
+        codec::{WebsocketSink, WebsocketStream},
+        error::ServerFnError,
+    };
+    #[cfg(any(feature = "browser", feature = "reqwest", test))]
+    use crate::{error::NoCustomError, response::ClientRes};
+    #[cfg(any(feature = "browser", feature = "reqwest", test))]
+    use futures::future::{select, Either};
+    use pin_project_lite::pin_project;
+    use std::{
+        cell::RefCell,
+        fmt,
+        future::Future,
+        marker::PhantomData,
+        pin::Pin,
+        sync::{Arc, OnceLock},
+        task::{Context, Poll},
+        time::Duration,
+    };
+    #[cfg(any(feature = "browser", feature = "reqwest", test))]
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        pin::pin,
+    };
+
+    static POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+
+    /// Sets the [`RetryPolicy`] used by every server function call.
+    ///
+    /// If this is not set, each call is sent once, with no timeout. A single server function can
+    /// use a different policy with [`RetryClient`].
+    pub fn set_retry_policy(policy: RetryPolicy) {
+        POLICY.set(policy).unwrap();
+    }
+
+    /// Returns the [`RetryPolicy`] used by every server function call.
+    pub fn get_retry_policy() -> RetryPolicy {
+        POLICY.get().cloned().unwrap_or_default()
+    }
+
+    /// The reason an attempt to call a server function failed.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum RetryCause {
+        /// No response arrived before the [timeout](RetryPolicy::timeout).
+        Timeout,
+        /// The request could not be sent, or the connection failed before a response arrived.
+        Network,
+        /// The server responded with this status code.
+        Status(u16),
+    }
+
+    impl RetryCause {
+        /// Whether the cause is usually temporary: a timeout, a network error, or one of the
+        /// status codes `408`, `429`, `502`, `503` or `504`.
+        ///
+        /// Other error statuses are not included, because a server function that returns an
+        /// `Err` responds with `500`, and trying again is unlikely to change its result.
+        pub fn is_transient(self) -> bool {
+            matches!(
+                self,
+                RetryCause::Timeout
+                    | RetryCause::Network
+                    | RetryCause::Status(408 | 429 | 502 | 503 | 504)
+            )
+        }
+    }
+
+    /// Describes how long to wait for a server function, and whether and when to try again if
+    /// it fails.
+    ///
+    /// By default, calls are sent once with no timeout. Once retries are enabled with
+    /// [`max_retries`](Self::max_retries), only idempotent `GET` requests (like those made by
+    /// the [`GetUrl`](crate::codec::GetUrl) and [`GetJson`](crate::codec::GetJson) encodings)
+    /// are retried, unless [`retry_non_idempotent`](Self::retry_non_idempotent) is set.
+    ///
+    /// ```rust,ignore
+    /// set_retry_policy(
+    ///     RetryPolicy::new()
+    ///         .timeout(Duration::from_secs(10))
+    ///         .max_retries(3)
+    ///         .backoff(Duration::from_millis(200), Duration::from_secs(5)),
+    /// );
+    /// ```
+    #[derive(Clone)]
+    pub struct RetryPolicy {
+        timeout: Option<Duration>,
+        max_retries: u32,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+        jitter: bool,
+        retry_non_idempotent: bool,
+        retry_if: Arc<dyn Fn(RetryCause) -> bool + Send + Sync>,
+    }
+
+    impl Default for RetryPolicy {
+        fn default() -> Self {
+            Self {
+                timeout: None,
+                max_retries: 0,
+                initial_backoff: Duration::from_millis(100),
+                max_backoff: Duration::from_secs(10),
+                jitter: true,
+                retry_non_idempotent: false,
+                retry_if: Arc::new(RetryCause::is_transient),
+            }
+        }
+    }
+
+    impl fmt::Debug for RetryPolicy {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RetryPolicy")
+                .field("timeout", &self.timeout)
+                .field("max_retries", &self.max_retries)
+                .field("initial_backoff", &self.initial_backoff)
+                .field("max_backoff", &self.max_backoff)
+                .field("jitter", &self.jitter)
+                .field("retry_non_idempotent", &self.retry_non_idempotent)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl RetryPolicy {
+        /// Creates a policy that sends each call once, with no timeout.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Fails an attempt if no response has arrived after `timeout`.
+        ///
+        /// This applies to each attempt separately, and only until the response starts: reading
+        /// the body of the response is not limited.
+        pub fn timeout(mut self, timeout: Duration) -> Self {
+            self.timeout = Some(timeout);
+            self
+        }
+
+        /// Sets how many times a failed call is tried again. Defaults to `0`.
+        pub fn max_retries(mut self, max_retries: u32) -> Self {
+            self.max_retries = max_retries;
+            self
+        }
+
+        /// Sets the delay before the first retry, which doubles with each retry until it reaches
+        /// `max`. Defaults to 100 milliseconds and 10 seconds.
+        pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+            self.initial_backoff = initial;
+            self.max_backoff = max;
+            self
+        }
+
+        /// Whether each delay is shortened by a random amount of up to half, so that many
+        /// clients that failed at the same time do not all retry at the same time. Defaults to
+        /// `true`.
+        pub fn jitter(mut self, jitter: bool) -> Self {
+            self.jitter = jitter;
+            self
+        }
+
+        /// Whether requests other than `GET` are retried too. Defaults to `false`.
+        ///
+        /// Only enable this if the server functions can safely run more than once, because a
+        /// request that timed out or failed may still have reached the server.
+        pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+            self.retry_non_idempotent = retry;
+            self
+        }
+
+        /// Decides which failures are retried. Defaults to [`RetryCause::is_transient`].
+        pub fn retry_if(
+            mut self,
+            retry_if: impl Fn(RetryCause) -> bool + Send + Sync + 'static,
+        ) -> Self {
+            self.retry_if = Arc::new(retry_if);
+            self
+        }
+
+        /// The delay before retry number `retry`, starting from `0`.
+        #[cfg(any(feature = "browser", feature = "reqwest", test))]
+        fn delay(&self, retry: u32) -> Duration {
+            let delay = self
+                .initial_backoff
+                .saturating_mul(2u32.saturating_pow(retry))
+                .min(self.max_backoff);
+            if self.jitter {
+                // `RandomState` is seeded differently each time, which is random enough here
+                let random = RandomState::new().build_hasher().finish();
+                let fraction = (random >> 11) as f64 / (1u64 << 53) as f64;
+                delay.mul_f64(1.0 - fraction / 2.0)
+            } else {
+                delay
+            }
+        }
+    }
+
+    /// Provides the [`RetryPolicy`] for the server functions that use a [`RetryClient`].
+    pub trait RetryConfig {
+        /// Returns the policy.
+        fn policy() -> RetryPolicy;
+    }
+
+    /// Implements [`Client`] by sending requests with the inner client `C`, using the
+    /// [`RetryPolicy`] from `P` instead of the one set with [`set_retry_policy`].
+    ///
+    /// The inner client must be one that applies retry policies, like the `BrowserClient` or
+    /// the `ReqwestClient`.
+    ///
+    /// ```rust,ignore
+    /// pub struct Patient;
+    ///
+    /// impl RetryConfig for Patient {
+    ///     fn policy() -> RetryPolicy {
+    ///         RetryPolicy::new()
+    ///             .timeout(Duration::from_secs(30))
+    ///             .max_retries(5)
+    ///     }
+    /// }
+    ///
+    /// #[server(client = RetryClient<BrowserClient, Patient>)]
+    /// pub async fn get_report(id: usize) -> Result<Report, ServerFnError> {
+    ///     todo!()
+    /// }
+    /// ```
+    pub struct RetryClient<C, P>(PhantomData<(C, P)>);
+
+    impl<C, P, CustErr> Client<CustErr> for RetryClient<C, P>
+    where
+        C: Client<CustErr>,
+        P: RetryConfig,
+    {
+        type Request = C::Request;
+        type Response = C::Response;
+
+        fn send(
+            req: Self::Request,
+        ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
+               + Send {
+            WithPolicy {
+                policy: Some(P::policy()),
+                inner: C::send(req),
+            }
+        }
+
+        fn open_websocket(
+            path: &str,
+        ) -> impl Future<
+            Output = Result<
+                (WebsocketStream, WebsocketSink),
+                ServerFnError<CustErr>,
+            >,
+        > + Send {
+            C::open_websocket(path)
+        }
+    }
+
+    thread_local! {
+        static CURRENT: RefCell<Option<RetryPolicy>> = const { RefCell::new(None) };
+    }
+
+    pin_project! {
+        /// Makes the policy of a [`RetryClient`] available to its inner client.
+        struct WithPolicy<Fut> {
+            policy: Option<RetryPolicy>,
+            #[pin]
+            inner: Fut,
+        }
+    }
+
+    impl<Fut: Future> Future for WithPolicy<Fut> {
+        type Output = Fut::Output;
+
+        fn poll(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Self::Output> {
+            let this = self.project();
+            CURRENT.with(|current| {
+                std::mem::swap(this.policy, &mut current.borrow_mut())
+            });
+            let res = this.inner.poll(cx);
+            CURRENT.with(|current| {
+                std::mem::swap(this.policy, &mut current.borrow_mut())
+            });
+            res
+        }
+    }
+
+    /// Sends a request according to the current [`RetryPolicy`].
+    ///
+    /// `try_clone` copies the request for the next attempt, if it can be copied; `sleep` waits
+    /// for the given time.
+    #[cfg(any(feature = "browser", feature = "reqwest", test))]
+    pub(crate) async fn send_with_retries<R, Res, Fut, Sleep>(
+        req: R,
+        idempotent: bool,
+        try_clone: impl Fn(&R) -> Option<R>,
+        send: impl Fn(R) -> Fut,
+        sleep: impl Fn(Duration) -> Sleep,
+    ) -> Result<Res, ServerFnError>
+    where
+        Res: ClientRes<NoCustomError>,
+        Fut: Future<Output = Result<Res, ServerFnError>>,
+        Sleep: Future<Output = ()>,
+    {
+        let policy = CURRENT
+            .with(|current| current.borrow().clone())
+            .unwrap_or_else(get_retry_policy);
+        let may_retry = idempotent || policy.retry_non_idempotent;
+        let mut req = req;
+        let mut retry = 0;
+        loop {
+            let next = if may_retry && retry < policy.max_retries {
+                try_clone(&req)
+            } else {
+                None
+            };
+
+            // the failed response is dropped before waiting for the next attempt
+            req = {
+                let (res, cause) = match policy.timeout {
+                    None => (send(req).await, None),
+                    Some(timeout) => {
+                        match select(pin!(send(req)), pin!(sleep(timeout)))
+                            .await
+                        {
+                            Either::Left((res, _)) => (res, None),
+                            Either::Right(_) => (
+                                Err(ServerFnError::Request(format!(
+                                    "The request timed out after {timeout:?}."
+                                ))),
+                                Some(RetryCause::Timeout),
+                            ),
+                        }
+                    }
+                };
+                let cause = cause.or(match &res {
+                    Ok(res) if !(200..=399).contains(&res.status()) => {
+                        Some(RetryCause::Status(res.status()))
+                    }
+                    Err(ServerFnError::Request(_)) => Some(RetryCause::Network),
+                    _ => None,
+                });
+                match (next, cause) {
+                    (Some(next), Some(cause)) if (policy.retry_if)(cause) => {
+                        next
+                    }
+                    _ => return res,
+                }
+            };
+            sleep(policy.delay(retry)).await;
+            retry += 1;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use bytes::Bytes;
+        use futures::{
+            executor::block_on,
+            future::{pending, ready, BoxFuture},
+            stream, FutureExt, Stream,
+        };
+        use std::{collections::VecDeque, sync::Mutex};
+
+        struct TestReq {
+            cloneable: bool,
+        }
+
+        impl TestReq {
+            fn try_clone(&self) -> Option<Self> {
+                self.cloneable.then_some(TestReq { cloneable: true })
+            }
+        }
+
+        struct TestRes(u16);
+
+        impl ClientRes<NoCustomError> for TestRes {
+            async fn try_into_string(self) -> Result<String, ServerFnError> {
+                Ok(String::new())
+            }
+
+            async fn try_into_bytes(self) -> Result<Bytes, ServerFnError> {
+                Ok(Bytes::new())
+            }
+
+            fn try_into_stream(
+                self,
+            ) -> Result<
+                impl Stream<Item = Result<Bytes, ServerFnError>>
+                    + Send
+                    + Sync
+                    + 'static,
+                ServerFnError,
+            > {
+                Ok(stream::empty())
+            }
+
+            fn status(&self) -> u16 {
+                self.0
+            }
+
+            fn status_text(&self) -> String {
+                String::new()
+            }
+
+            fn location(&self) -> String {
+                String::new()
+            }
+
+            fn has_redirect(&self) -> bool {
+                false
+            }
+        }
+
+        /// What each attempt does: respond with a status, fail to connect, or never respond.
+        #[derive(Clone, Copy)]
+        enum Attempt {
+            Status(u16),
+            Network,
+            Hang,
+        }
+
+        /// The result of sending a request, with the number of attempts made and the time
+        /// each `sleep` was asked to wait.
+        struct Outcome {
+            result: Result<u16, ServerFnError>,
+            attempts: usize,
+            sleeps: Vec<Duration>,
+        }
+
+        fn run(
+            policy: RetryPolicy,
+            idempotent: bool,
+            cloneable: bool,
+            attempts: &[Attempt],
+        ) -> Outcome {
+            let script = Arc::new(Mutex::new(
+                attempts.iter().copied().collect::<VecDeque<_>>(),
+            ));
+            let sent = Arc::new(Mutex::new(0));
+            let sleeps = Arc::new(Mutex::new(Vec::new()));
+            let send = {
+                let script = Arc::clone(&script);
+                let sent = Arc::clone(&sent);
+                move |_req: TestReq| -> BoxFuture<'static, _> {
+                    *sent.lock().unwrap() += 1;
+                    let attempt = script
+                        .lock()
+                        .unwrap()
+                        .pop_front()
+                        .expect("more attempts than expected");
+                    match attempt {
+                        Attempt::Status(status) => {
+                            ready(Ok(TestRes(status))).boxed()
+                        }
+                        Attempt::Network => ready(Err(ServerFnError::Request(
+                            "connection refused".into(),
+                        )))
+                        .boxed(),
+                        Attempt::Hang => pending().boxed(),
+                    }
+                }
+            };
+            let sleep = {
+                let sleeps = Arc::clone(&sleeps);
+                move |duration| {
+                    sleeps.lock().unwrap().push(duration);
+                    ready(())
+                }
+            };
+            let result = block_on(WithPolicy {
+                policy: Some(policy),
+                inner: send_with_retries(
+                    TestReq { cloneable },
+                    idempotent,
+                    TestReq::try_clone,
+                    send,
+                    sleep,
+                ),
+            })
+            .map(|res| res.0);
+            let attempts = *sent.lock().unwrap();
+            let sleeps = sleeps.lock().unwrap().clone();
+            Outcome {
+                result,
+                attempts,
+                sleeps,
+            }
+        }
+
+        fn retrying(max_retries: u32) -> RetryPolicy {
+            RetryPolicy::new()
+                .max_retries(max_retries)
+                .backoff(Duration::from_millis(100), Duration::from_secs(1))
+                .jitter(false)
+        }
+
+        const fn ms(ms: u64) -> Duration {
+            Duration::from_millis(ms)
+        }
+
+        #[test]
+        fn sends_once_by_default() {
+            let outcome =
+                run(RetryPolicy::new(), true, true, &[Attempt::Status(503)]);
+            assert_eq!(outcome.result, Ok(503));
+            assert_eq!(outcome.attempts, 1);
+            assert!(outcome.sleeps.is_empty());
+        }
+
+        #[test]
+        fn retries_up_to_max_retries() {
+            let outcome =
+                run(retrying(2), true, true, &[Attempt::Status(503); 3]);
+            assert_eq!(outcome.result, Ok(503));
+            assert_eq!(outcome.attempts, 3);
+            assert_eq!(outcome.sleeps, [ms(100), ms(200)]);
+        }
+
+        #[test]
+        fn stops_retrying_after_success() {
+            let outcome = run(
+                retrying(5),
+                true,
+                true,
+                &[Attempt::Network, Attempt::Status(502), Attempt::Status(200)],
+            );
+            assert_eq!(outcome.result, Ok(200));
+            assert_eq!(outcome.attempts, 3);
+        }
+
+        #[test]
+        fn does_not_retry_non_idempotent_calls() {
+            let attempts = [Attempt::Status(503), Attempt::Status(200)];
+            let outcome = run(retrying(3), false, true, &attempts);
+            assert_eq!(outcome.result, Ok(503));
+            assert_eq!(outcome.attempts, 1);
+
+            let policy = retrying(3).retry_non_idempotent(true);
+            let outcome = run(policy, false, true, &attempts);
+            assert_eq!(outcome.result, Ok(200));
+            assert_eq!(outcome.attempts, 2);
+        }
+
+        #[test]
+        fn does_not_retry_requests_that_cannot_be_copied() {
+            let attempts = [Attempt::Status(503), Attempt::Status(200)];
+            let outcome = run(retrying(3), true, false, &attempts);
+            assert_eq!(outcome.result, Ok(503));
+            assert_eq!(outcome.attempts, 1);
+        }
+
+        #[test]
+        fn only_retries_transient_failures() {
+            let outcome = run(
+                retrying(3),
+                true,
+                true,
+                &[Attempt::Status(500), Attempt::Status(200)],
+            );
+            assert_eq!(outcome.result, Ok(500));
+            assert_eq!(outcome.attempts, 1);
+
+            let policy =
+                retrying(3).retry_if(|cause| cause == RetryCause::Status(500));
+            let outcome = run(
+                policy,
+                true,
+                true,
+                &[Attempt::Status(500), Attempt::Status(200)],
+            );
+            assert_eq!(outcome.result, Ok(200));
+            assert_eq!(outcome.attempts, 2);
+        }
+
+        #[test]
+        fn times_out_each_attempt() {
+            let policy = retrying(1).timeout(Duration::from_secs(5));
+            let outcome =
+                run(policy, true, true, &[Attempt::Hang, Attempt::Hang]);
+            assert!(matches!(
+                outcome.result,
+                Err(ServerFnError::Request(e)) if e.contains("timed out")
+            ));
+            assert_eq!(outcome.attempts, 2);
+            // the timeout of the first attempt, the backoff, and the second timeout
+            assert_eq!(
+                outcome.sleeps,
+                [Duration::from_secs(5), ms(100), Duration::from_secs(5)]
+            );
+        }
+
+        #[test]
+        fn does_not_time_out_responses_that_arrive() {
+            let policy = retrying(1).timeout(Duration::from_secs(5));
+            let outcome = run(policy, true, true, &[Attempt::Status(200)]);
+            assert_eq!(outcome.result, Ok(200));
+            assert_eq!(outcome.attempts, 1);
+        }
+
+        #[test]
+        fn caps_backoff() {
+            let policy =
+                RetryPolicy::new().backoff(ms(100), ms(350)).jitter(false);
+            let delays =
+                (0..5).map(|retry| policy.delay(retry)).collect::<Vec<_>>();
+            assert_eq!(delays, [ms(100), ms(200), ms(350), ms(350), ms(350)]);
+            // doubling does not overflow
+            assert_eq!(policy.delay(u32::MAX), ms(350));
+        }
+
+        #[test]
+        fn jitter_shortens_delay_by_up_to_half() {
+            let policy = RetryPolicy::new().backoff(ms(1000), ms(1000));
+            for _ in 0..100 {
+                let delay = policy.delay(0);
+                assert!(delay >= ms(500) && delay <= ms(1000), "{delay:?}");
+            }
+        }
+
+        #[test]
+        fn transient_causes() {
+            assert!(RetryCause::Timeout.is_transient());
+            assert!(RetryCause::Network.is_transient());
+            for status in [408, 429, 502, 503, 504] {
+                assert!(RetryCause::Status(status).is_transient());
+            }
+            for status in [400, 401, 404, 500] {
+                assert!(!RetryCause::Status(status).is_transient());
+            }
+        }
+    }
+}
+
+/// Client-side middleware, which can change the requests sent by the `BrowserClient` and
+/// `ReqwestClient` and inspect their responses before they are decoded.
+///
+/// This mirrors the server-side [`middleware`](crate::middleware): a [`ClientLayer`] wraps the
+/// [`ClientService`] that sends a request. Layers are added for every server function call made
+/// by a client with [`add_layer`], and are typed by the request and response types of that
+/// client, so a layer for the browser implements `ClientLayer<BrowserRequest, BrowserResponse>`,
+/// and one for `reqwest` implements `ClientLayer<reqwest::Request, reqwest::Response>`.
+///
+/// Simple changes can be made with [`map_request`] and [`inspect_response`]:
+///
+/// ```rust,ignore
+/// add_layer::<BrowserClient>(map_request(|req: Req<4>| {
+///     req.headers().set("X-Tenant", &tenant_id());
+///     Ok(req)
+/// }));
+/// ```
+///
+/// A layer can also send a request more than once. This one refreshes an expired token and
+/// replays the call:
+///
+/// ```rust,ignore
+/// pub struct BearerToken;
+///
+/// type Service = BoxedClientService<BrowserRequest, BrowserResponse>;
+///
+/// impl ClientLayer<BrowserRequest, BrowserResponse> for BearerToken {
+///     fn layer(&self, inner: Service) -> Service {
+///         BoxedClientService::new(move |req: BrowserRequest| {
+///             let inner = inner.clone();
+///             SendWrapper::new(async move {
+///                 let replay = req.try_clone();
+///                 req.headers().set("Authorization", &format!("Bearer {}", token()));
+///                 let res = inner.run(req).await?;
+///                 match replay {
+///                     Some(replay) if res.status() == 401 => {
+///                         refresh_token().await?;
+///                         replay
+///                             .headers()
+///                             .set("Authorization", &format!("Bearer {}", token()));
+///                         inner.run(replay).await
+///                     }
+///                     _ => Ok(res),
+///                 }
+///             })
+///         })
+///     }
+/// }
+///
+/// add_layer::<BrowserClient>(BearerToken);
+/// ```
+pub mod middleware {
+    use super::Client;
+    use crate::error::{NoCustomError, ServerFnError};
+    use std::{
+        any::Any,
+        future::Future,
+        pin::Pin,
+        sync::{Arc, PoisonError, RwLock},
+    };
+
+    /// An abstraction over a client-side middleware layer, which can be used to wrap the
+    /// [`ClientService`] that sends a request.
+    pub trait ClientLayer<Req, Res>: Send + Sync + 'static {
+        /// Adds this layer to the inner service.
+        fn layer(
+            &self,
+            inner: BoxedClientService<Req, Res>,
+        ) -> BoxedClientService<Req, Res>;
+    }
+
+    /// A type-erased client service, which sends a request and returns its response.
+    ///
+    /// It can be cloned cheaply, so that a layer can run the inner service more than once.
+    pub struct BoxedClientService<Req, Res>(
+        pub Arc<dyn ClientService<Req, Res> + Send + Sync>,
+    );
+
+    impl<Req, Res> BoxedClientService<Req, Res> {
+        /// Constructs a type-erased client service from this service.
+        pub fn new(
+            service: impl ClientService<Req, Res> + Send + Sync + 'static,
+        ) -> Self {
+            Self(Arc::new(service))
+        }
+    }
+
+    impl<Req, Res> Clone for BoxedClientService<Req, Res> {
+        fn clone(&self) -> Self {
+            Self(Arc::clone(&self.0))
+        }
+    }
+
+    impl<Req, Res> ClientService<Req, Res> for BoxedClientService<Req, Res> {
+        fn run(
+            &self,
+            req: Req,
+        ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>>
+        {
+            self.0.run(req)
+        }
+    }
+
+    /// A client service sends a request and returns its response.
+    ///
+    /// This is implemented for functions that take a request and return a `Future`.
+    pub trait ClientService<Req, Res> {
+        /// Sends a request and returns its response.
+        fn run(
+            &self,
+            req: Req,
+        ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>>;
+    }
+
+    impl<Req, Res, F, Fut> ClientService<Req, Res> for F
+    where
+        F: Fn(Req) -> Fut,
+        Fut: Future<Output = Result<Res, ServerFnError>> + Send + 'static,
+    {
+        fn run(
+            &self,
+            req: Req,
+        ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>>
+        {
+            Box::pin(self(req))
+        }
+    }
+
+    /// Creates a layer that changes each request before it is sent, or fails the call if `f`
+    /// returns an error.
+    pub fn map_request<Req, Res>(
+        f: impl Fn(Req) -> Result<Req, ServerFnError> + Send + Sync + 'static,
+    ) -> impl ClientLayer<Req, Res>
+    where
+        Req: 'static,
+        Res: 'static,
+    {
+        MapRequest(Arc::new(f))
+    }
+
+    /// Creates a layer that inspects each response before it is decoded, or fails the call if
+    /// `f` returns an error.
+    pub fn inspect_response<Req, Res>(
+        f: impl Fn(&Res) -> Result<(), ServerFnError> + Send + Sync + 'static,
+    ) -> impl ClientLayer<Req, Res>
+    where
+        Req: 'static,
+        Res: 'static,
+    {
+        InspectResponse(Arc::new(f))
+    }
+
+    struct MapRequest<F>(Arc<F>);
+
+    impl<F, Req, Res> ClientLayer<Req, Res> for MapRequest<F>
+    where
+        F: Fn(Req) -> Result<Req, ServerFnError> + Send + Sync + 'static,
+        Req: 'static,
+        Res: 'static,
+    {
+        fn layer(
+            &self,
+            inner: BoxedClientService<Req, Res>,
+        ) -> BoxedClientService<Req, Res> {
+            let f = Arc::clone(&self.0);
+            BoxedClientService::new(move |req| {
+                let res = f(req).map(|req| inner.run(req));
+                async move { res?.await }
+            })
+        }
+    }
+
+    struct InspectResponse<F>(Arc<F>);
+
+    impl<F, Req, Res> ClientLayer<Req, Res> for InspectResponse<F>
+    where
+        F: Fn(&Res) -> Result<(), ServerFnError> + Send + Sync + 'static,
+        Req: 'static,
+        Res: 'static,
+    {
+        fn layer(
+            &self,
+            inner: BoxedClientService<Req, Res>,
+        ) -> BoxedClientService<Req, Res> {
+            let f = Arc::clone(&self.0);
+            BoxedClientService::new(move |req| {
+                let res = inner.run(req);
+                let f = Arc::clone(&f);
+                async move {
+                    let res = res.await?;
+                    f(&res)?;
+                    Ok(res)
+                }
+            })
+        }
+    }
+
+    // layers for all clients are kept together, each as an `Arc<dyn ClientLayer<Req, Res>>`
+    static LAYERS: RwLock<Vec<Arc<dyn Any + Send + Sync>>> =
+        RwLock::new(Vec::new());
+
+    /// Adds a layer to every server function call made by the client `C`.
+    ///
+    /// Layers run in the order they were added: the first one sees each request first and each
+    /// response last. If a [`RetryPolicy`](super::retry::RetryPolicy) is set, the layers run
+    /// once for each call, around all of its attempts.
+    pub fn add_layer<C>(layer: impl ClientLayer<C::Request, C::Response>)
+    where
+        C: Client<NoCustomError>,
+        C::Request: 'static,
+        C::Response: 'static,
+    {
+        push_layer(layer);
+    }
+
+    fn push_layer<Req, Res>(layer: impl ClientLayer<Req, Res>)
+    where
+        Req: 'static,
+        Res: 'static,
+    {
+        let layer: Arc<dyn ClientLayer<Req, Res>> = Arc::new(layer);
+        LAYERS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::new(layer));
+    }
+
+    /// Sends a request through the layers that have been added for its client, with `send`
+    /// as the innermost service.
+    #[cfg(any(feature = "browser", feature = "reqwest", test))]
+    pub(crate) async fn run_layers<Req, Res, CustErr>(
+        req: Req,
+        send: BoxedClientService<Req, Res>,
+    ) -> Result<Res, ServerFnError<CustErr>>
+    where
+        Req: 'static,
+        Res: 'static,
+    {
+        let layers = LAYERS
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter_map(|layer| {
+                layer
+                    .downcast_ref::<Arc<dyn ClientLayer<Req, Res>>>()
+                    .cloned()
+            })
+            .collect::<Vec<_>>();
+        let service = layers
+            .iter()
+            .rev()
+            .fold(send, |inner, layer| layer.layer(inner));
+        // the layers only know about the default error type
+        service.run(req).await.map_err(|e| {
+            e.map_custom(|e| ServerFnError::ServerError(e.to_string()))
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use futures::{executor::block_on, future::ready};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // layers are kept per request and response type, so each test uses its own
+        struct Req<const N: usize>(Vec<&'static str>);
+
+        #[derive(Debug, PartialEq)]
+        struct Res<const N: usize>(Vec<&'static str>);
+
+        /// Sends the request by responding with the trail of layers it went through.
+        fn send<const N: usize>() -> BoxedClientService<Req<N>, Res<N>> {
+            BoxedClientService::new(|mut req: Req<N>| {
+                req.0.push("send");
+                ready(Ok(Res(req.0)))
+            })
+        }
+
+        /// Records its name on each request and response that passes through it.
+        struct Named(&'static str);
+
+        impl<const N: usize> ClientLayer<Req<N>, Res<N>> for Named {
+            fn layer(
+                &self,
+                inner: BoxedClientService<Req<N>, Res<N>>,
+            ) -> BoxedClientService<Req<N>, Res<N>> {
+                let name = self.0;
+                BoxedClientService::new(move |mut req: Req<N>| {
+                    req.0.push(name);
+                    let res = inner.run(req);
+                    async move {
+                        let mut res = res.await?;
+                        res.0.push(name);
+                        Ok(res)
+                    }
+                })
+            }
+        }
+
+        #[test]
+        fn runs_layers_in_order_added() {
+            push_layer::<Req<0>, Res<0>>(Named("first"));
+            push_layer::<Req<0>, Res<0>>(Named("second"));
+            let res = block_on(run_layers::<_, _, NoCustomError>(
+                Req::<0>(vec![]),
+                send(),
+            ));
+            assert_eq!(
+                res,
+                Ok(Res(vec!["first", "second", "send", "second", "first"]))
+            );
+        }
+
+        #[test]
+        fn only_runs_layers_for_the_same_client() {
+            push_layer::<Req<1>, Res<1>>(Named("other"));
+            push_layer::<Req<2>, Res<2>>(Named("this"));
+            let res = block_on(run_layers::<_, _, NoCustomError>(
+                Req::<2>(vec![]),
+                send(),
+            ));
+            assert_eq!(res, Ok(Res(vec!["this", "send", "this"])));
+        }
+
+        #[test]
+        fn maps_requests() {
+            push_layer::<Req<3>, Res<3>>(map_request(|mut req: Req<3>| {
+                req.0.push("mapped");
+                Ok(req)
+            }));
+            let res = block_on(run_layers::<_, _, NoCustomError>(
+                Req::<3>(vec![]),
+                send(),
+            ));
+            assert_eq!(res, Ok(Res(vec!["mapped", "send"])));
+        }
+
+        #[test]
+        fn failing_map_request_does_not_send() {
+            push_layer::<Req<4>, Res<4>>(map_request(|req: Req<4>| {
+                if req.0.is_empty() {
+                    Err(ServerFnError::Request("no token".into()))
+                } else {
+                    Ok(req)
+                }
+            }));
+            let sent = Arc::new(AtomicUsize::new(0));
+            let send = {
+                let sent = Arc::clone(&sent);
+                BoxedClientService::new(move |req: Req<4>| {
+                    sent.fetch_add(1, Ordering::SeqCst);
+                    ready(Ok(Res::<4>(req.0)))
+                })
+            };
+            let res = block_on(run_layers::<_, _, NoCustomError>(
+                Req::<4>(vec![]),
+                send.clone(),
+            ));
+            assert_eq!(res, Err(ServerFnError::Request("no token".into())));
+            assert_eq!(sent.load(Ordering::SeqCst), 0);
+
+            let res = block_on(run_layers::<_, _, NoCustomError>(
+                Req::<4>(vec!["token"]),
+                send,
+            ));
+            assert_eq!(res, Ok(Res(vec!["token"])));
+            assert_eq!(sent.load(Ordering::SeqCst), 1);
+        }
+
+        #[test]
+        fn inspects_responses() {
+            let seen = Arc::new(AtomicUsize::new(0));
+            push_layer::<Req<5>, Res<5>>(inspect_response({
+                let seen = Arc::clone(&seen);
+                move |res: &Res<5>| {
+                    seen.fetch_add(1, Ordering::SeqCst);
+                    if res.0.contains(&"unauthorized") {
+                        Err(ServerFnError::Response("401".into()))
+                    } else {
+                        Ok(())
+                    }
+                }
+            }));
+            let res = block_on(run_layers::<_, _, NoCustomError>(
+                Req::<5>(vec![]),
+                send(),
+            ));
+            assert_eq!(res, Ok(Res(vec!["send"])));
+            let res = block_on(run_layers::<_, _, NoCustomError>(
+                Req::<5>(vec!["unauthorized"]),
+                send(),
+            ));
+            assert_eq!(res, Err(ServerFnError::Response("401".into())));
+            assert_eq!(seen.load(Ordering::SeqCst), 2);
+        }
+
+        #[test]
+        fn converts_errors_to_custom_error_type() {
+            #[derive(Debug, PartialEq)]
+            struct MyError;
+
+            let fail = |err: ServerFnError| {
+                BoxedClientService::new(move |_req: Req<6>| {
+                    ready(Err::<Res<6>, _>(err.clone()))
+                })
+            };
+            let res = block_on(run_layers::<_, _, MyError>(
+                Req::<6>(vec![]),
+                fail(ServerFnError::Request("offline".into())),
+            ));
+            assert_eq!(res, Err(ServerFnError::Request("offline".into())));
+            let res = block_on(run_layers::<_, _, MyError>(
+                Req::<6>(vec![]),
+                fail(ServerFnError::WrappedServerError(NoCustomError)),
+            ));
+            assert!(matches!(res, Err(ServerFnError::ServerError(_))));
+        }
+    }
 }
 
 #[cfg(feature = "browser")]
 /// Implements [`Client`] for a `fetch` request in the browser.
 pub mod browser {
-    use super::Client;
+    use super::{
+        middleware::{run_layers, BoxedClientService},
+        retry::send_with_retries,
+        websocket_url, Client,
+    };
     use crate::{
-        error::ServerFnError,
+        codec::{
+            transport_error, WebsocketFrame, WebsocketSink, WebsocketStream,
+        },
+        error::{NoCustomError, ServerFnError},
         request::browser::{BrowserRequest, RequestInner},
         response::browser::BrowserResponse,
     };
+    use bytes::Bytes;
+    use futures::{sink, SinkExt, StreamExt};
+    use gloo_net::{
+        http::Method,
+        websocket::{futures::WebSocket, Message},
+    };
+    use js_sys::Promise;
     use send_wrapper::SendWrapper;
-    use std::future::Future;
+    use std::{future::Future, time::Duration};
+    use wasm_bindgen::JsValue;
+    use wasm_bindgen_futures::JsFuture;
 
-    /// Implements [`Client`] for a `fetch` request in the browser.    
+    /// Implements [`Client`] for a `fetch` request in the browser.
+    ///
+    /// Dropping the `Future` of a call aborts the `fetch` with an `AbortController`, including
+    /// while the body of the response is still being read.
     pub struct BrowserClient;
 
     impl<CustErr> Client<CustErr> for BrowserClient {
@@ -56,39 +1177,133 @@ pub mod browser {
             req: Self::Request,
         ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
                + Send {
+            let send = BoxedClientService::new(|req: BrowserRequest| {
+                let idempotent = req.method() == Method::GET;
+                SendWrapper::new(send_with_retries(
+                    req,
+                    idempotent,
+                    BrowserRequest::try_clone,
+                    send_once,
+                    sleep,
+                ))
+            });
+            run_layers(req, send)
+        }
+
+        fn open_websocket(
+            path: &str,
+        ) -> impl Future<
+            Output = Result<
+                (WebsocketStream, WebsocketSink),
+                ServerFnError<CustErr>,
+            >,
+        > + Send {
+            let origin = web_sys::window()
+                .and_then(|window| window.location().origin().ok())
+                .unwrap_or_default();
+            let url = websocket_url(&origin, path);
             SendWrapper::new(async move {
-                let req = req.0.take();
-                let RequestInner {
-                    request,
-                    mut abort_ctrl,
-                } = req;
-                let res = request
-                    .send()
-                    .await
-                    .map(|res| BrowserResponse(SendWrapper::new(res)))
-                    .map_err(|e| ServerFnError::Request(e.to_string()));
-
-                // at this point, the future has successfully resolved without being dropped, so we
-                // can prevent the `AbortController` from firing
-                if let Some(ctrl) = abort_ctrl.as_mut() {
-                    ctrl.prevent_cancellation();
-                }
-                res
+                let socket = WebSocket::open(&url)
+                    .map_err(|e| ServerFnError::Request(e.to_string()))?;
+                let (sink, stream) = socket.split();
+                let stream = stream.map(|msg| match msg {
+                    Ok(Message::Bytes(bytes)) => Ok(Bytes::from(bytes)),
+                    Ok(Message::Text(text)) => Err(text),
+                    Err(e) => transport_error(e),
+                });
+                // the browser won't send this async work between threads (because it's
+                // single-threaded) so we can safely wrap this
+                let sink = sink::unfold(
+                    SendWrapper::new(sink),
+                    |mut sink, frame: WebsocketFrame| {
+                        SendWrapper::new(async move {
+                            let msg = match frame {
+                                Ok(bytes) => Message::Bytes(bytes.to_vec()),
+                                Err(text) => Message::Text(text),
+                            };
+                            sink.send(msg).await.map_err(|e| {
+                                ServerFnError::<NoCustomError>::Request(
+                                    e.to_string(),
+                                )
+                            })?;
+                            Ok(sink)
+                        })
+                    },
+                );
+                Ok((
+                    Box::pin(SendWrapper::new(stream)) as WebsocketStream,
+                    Box::pin(sink) as WebsocketSink,
+                ))
             })
         }
     }
+
+    async fn send_once(
+        req: BrowserRequest,
+    ) -> Result<BrowserResponse, ServerFnError> {
+        let RequestInner {
+            request,
+            abort_ctrl,
+            ..
+        } = req.0.take();
+        // the `AbortController` moves into the response, so that the request is still
+        // aborted if it is dropped while the body is being read
+        request
+            .send()
+            .await
+            .map(|res| {
+                BrowserResponse(
+                    SendWrapper::new(res),
+                    SendWrapper::new(abort_ctrl),
+                )
+            })
+            .map_err(|e| ServerFnError::Request(e.to_string()))
+    }
+
+    /// Waits for `duration` with `setTimeout`.
+    async fn sleep(duration: Duration) {
+        let millis = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+        let promise = Promise::new(&mut |resolve, _| {
+            let scheduled = web_sys::window().and_then(|window| {
+                window
+                    .set_timeout_with_callback_and_timeout_and_arguments_0(
+                        &resolve, millis,
+                    )
+                    .ok()
+            });
+            if scheduled.is_none() {
+                _ = resolve.call0(&JsValue::UNDEFINED);
+            }
+        });
+        _ = JsFuture::from(promise).await;
+    }
 }
 
 #[cfg(feature = "reqwest")]
 /// Implements [`Client`] for a request made by [`reqwest`].
 pub mod reqwest {
-    use super::Client;
-    use crate::{error::ServerFnError, request::reqwest::CLIENT};
-    use futures::TryFutureExt;
-    use reqwest::{Request, Response};
+    use super::{
+        middleware::{run_layers, BoxedClientService},
+        retry::send_with_retries,
+        get_server_url, websocket_url, Client,
+    };
+    use crate::{
+        codec::{
+            transport_error, WebsocketFrame, WebsocketSink, WebsocketStream,
+        },
+        error::ServerFnError,
+        request::reqwest::CLIENT,
+    };
+    use bytes::Bytes;
+    use futures::{future::ready, SinkExt, StreamExt, TryFutureExt};
+    use reqwest::{Method, Request, Response};
     use std::future::Future;
+    use tokio_tungstenite::tungstenite::Message;
 
     /// Implements [`Client`] for a request made by [`reqwest`].
+    ///
+    /// Dropping the `Future` of a call cancels the request and closes its connection, including
+    /// while the body of the response is still being read.
     pub struct ReqwestClient;
 
     impl<CustErr> Client<CustErr> for ReqwestClient {
@@ -99,9 +1314,980 @@ pub mod reqwest {
             req: Self::Request,
         ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
                + Send {
-            CLIENT
-                .execute(req)
-                .map_err(|e| ServerFnError::Request(e.to_string()))
+            #[cfg(feature = "json")]
+            let req = {
+                let mut req = req;
+                let id = crate::codec::last_event_id().and_then(|id| {
+                    reqwest::header::HeaderValue::from_str(&id).ok()
+                });
+                if let Some(id) = id {
+                    req.headers_mut().insert(crate::codec::LAST_EVENT_ID, id);
+                }
+                req
+            };
+            let send = BoxedClientService::new(|req: Request| {
+                let idempotent = req.method() == Method::GET;
+                send_with_retries(
+                    req,
+                    idempotent,
+                    Request::try_clone,
+                    |req| {
+                        CLIENT
+                            .execute(req)
+                            .map_err(|e| ServerFnError::Request(e.to_string()))
+                    },
+                    tokio::time::sleep,
+                )
+            });
+            run_layers(req, send)
+        }
+
+        async fn open_websocket(
+            path: &str,
+        ) -> Result<(WebsocketStream, WebsocketSink), ServerFnError<CustErr>>
+        {
+            // unlike in the browser, there is no origin to connect to
+            if get_server_url().is_empty() {
+                return Err(ServerFnError::Request(format!(
+                    "Could not open a websocket to {path}: no server URL has \
+                     been set with `set_server_url`."
+                )));
+            }
+            let url = websocket_url("", path);
+            let (socket, _) = tokio_tungstenite::connect_async(url)
+                .await
+                .map_err(|e| ServerFnError::Request(e.to_string()))?;
+            let (sink, stream) = socket.split();
+            let stream = stream.filter_map(|msg| {
+                ready(match msg {
+                    Ok(Message::Binary(bytes)) => Some(Ok(Bytes::from(bytes))),
+                    Ok(Message::Text(text)) => Some(Err(text)),
+                    // pings are answered by the socket itself
+                    Ok(_) => None,
+                    Err(e) => Some(transport_error(e)),
+                })
+            });
+            let sink = sink
+                .with(|frame: WebsocketFrame| {
+                    ready(Ok(match frame {
+                        Ok(bytes) => Message::Binary(bytes.into()),
+                        Err(text) => Message::Text(text),
+                    }))
+                })
+                .sink_map_err(|e: tokio_tungstenite::tungstenite::Error| {
+                    ServerFnError::Request(e.to_string())
+                });
+            Ok((Box::pin(stream), Box::pin(sink)))
         }
     }
 }
+
+#[cfg(feature = "batch")]
+/// Implements [`Client`] for server function calls that are batched into a single request.
+pub mod batch {
+    use super::Client;
+    use crate::{
+        batch::{
+            batch_path, decode_results, encode_calls, get_max_batch_calls,
+            BatchCall, BatchResult, BATCH_CONTENT_TYPE,
+        },
+        error::{ServerFnError, ServerFnErrorSerde},
+        request::{batch::BatchRequest, ClientReq},
+        response::{batch::BatchResponse, ClientRes},
+    };
+    use any_spawner::Executor;
+    use futures::{channel::oneshot, future::join_all};
+    use std::{
+        any::TypeId,
+        fmt::Display,
+        future::Future,
+        marker::PhantomData,
+        pin::Pin,
+        str::FromStr,
+        sync::{Mutex, MutexGuard, PoisonError},
+        task::{Context, Poll},
+    };
+
+    /// Implements [`Client`] by collecting all the server function calls made in the same tick
+    /// and sending them to the server as a single request, using the inner client `C`.
+    ///
+    /// The server handles the whole batch at the [batch endpoint](crate::batch::batch_path)
+    /// under the prefix of the server functions, which is served by the Axum and Actix
+    /// handlers when the `batch` feature is enabled. The request is made by `C`, so it goes to
+    /// the same server URL as the calls themselves would. Each call still receives its own
+    /// response, so an error in one call does not affect the others. If only one call is made
+    /// in a tick, it is sent as an ordinary request.
+    ///
+    /// The batch is sent from a task spawned with [`Executor`], so that cancelling one call
+    /// (for example, by aborting its action) does not cancel the others. A batch never holds
+    /// more calls than [`get_max_batch_calls`], and if the server rejects it as too large, its
+    /// calls are sent one at a time instead.
+    ///
+    /// Calls are only batched together if they use the same inner client and share a prefix.
+    /// Only server functions whose arguments are sent as a text or binary body (rather than as
+    /// form data or a stream) can be batched.
+    ///
+    /// ```rust,ignore
+    /// #[server(client = BatchClient<BrowserClient>)]
+    /// pub async fn get_post(id: usize) -> Result<Post, ServerFnError> {
+    ///     todo!()
+    /// }
+    /// ```
+    pub struct BatchClient<C>(PhantomData<C>);
+
+    type PendingCall =
+        (BatchCall, oneshot::Sender<Result<BatchResult, String>>);
+
+    /// The calls waiting to be sent to one batch endpoint by one inner client.
+    struct PendingBatch {
+        client: TypeId,
+        path: String,
+        calls: Vec<PendingCall>,
+    }
+
+    // each batch is shared between all the server functions that use the same inner client and
+    // batch endpoint, and handed to a new task by whichever of their calls notices it first
+    static PENDING: Mutex<Vec<PendingBatch>> = Mutex::new(Vec::new());
+
+    fn pending() -> MutexGuard<'static, Vec<PendingBatch>> {
+        PENDING.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push_call(client: TypeId, path: &str, call: PendingCall) {
+        let mut pending = pending();
+        match pending
+            .iter_mut()
+            .find(|batch| batch.client == client && batch.path == path)
+        {
+            Some(batch) => batch.calls.push(call),
+            None => pending.push(PendingBatch {
+                client,
+                path: path.to_string(),
+                calls: vec![call],
+            }),
+        }
+    }
+
+    fn pending_len(client: TypeId, path: &str) -> usize {
+        pending()
+            .iter()
+            .find(|batch| batch.client == client && batch.path == path)
+            .map_or(0, |batch| batch.calls.len())
+    }
+
+    fn take_calls(client: TypeId, path: &str) -> Vec<PendingCall> {
+        let mut pending = pending();
+        match pending
+            .iter()
+            .position(|batch| batch.client == client && batch.path == path)
+        {
+            Some(idx) => pending.swap_remove(idx).calls,
+            None => Vec::new(),
+        }
+    }
+
+    impl<C, CustErr> Client<CustErr> for BatchClient<C>
+    where
+        C: Client<CustErr> + 'static,
+        CustErr: FromStr + Display + Send + 'static,
+    {
+        type Request = BatchRequest;
+        type Response = BatchResponse;
+
+        async fn send(
+            req: Self::Request,
+        ) -> Result<Self::Response, ServerFnError<CustErr>> {
+            let client = TypeId::of::<C>();
+            let path = batch_path(&req.0.path);
+            let (tx, rx) = oneshot::channel();
+            push_call(client, &path, (req.0, tx));
+
+            // give any other calls made in the same tick a chance to join the batch, until
+            // the other tasks have all run without adding to it
+            loop {
+                let len = pending_len(client, &path);
+                YieldNow(false).await;
+                if pending_len(client, &path) == len {
+                    break;
+                }
+            }
+            // the task owns every call's sender, so this call only waits for its own result
+            let calls = take_calls(client, &path);
+            if !calls.is_empty() {
+                Executor::spawn(send_batches::<C, CustErr>(path, calls));
+            }
+
+            match rx.await {
+                Ok(Ok(res)) => Ok(BatchResponse(res)),
+                Ok(Err(e)) => Err(ServerFnError::de(&e)),
+                Err(_) => Err(ServerFnError::Request(
+                    "The batch containing this call was cancelled.".to_string(),
+                )),
+            }
+        }
+    }
+
+    async fn send_batches<C, CustErr>(path: String, mut calls: Vec<PendingCall>)
+    where
+        C: Client<CustErr>,
+        CustErr: FromStr + Display,
+    {
+        // the server rejects batches that are too large, so send them in parts
+        let max = get_max_batch_calls().max(1);
+        let mut batches = Vec::new();
+        while !calls.is_empty() {
+            let rest = calls.split_off(calls.len().min(max));
+            batches.push(send_batch::<C, CustErr>(&path, calls));
+            calls = rest;
+        }
+        join_all(batches).await;
+    }
+
+    async fn send_batch<C, CustErr>(path: &str, calls: Vec<PendingCall>)
+    where
+        C: Client<CustErr>,
+        CustErr: FromStr + Display,
+    {
+        let (mut calls, senders): (Vec<_>, Vec<_>) = calls.into_iter().unzip();
+        let results = if calls.len() == 1 {
+            vec![send_single::<C, CustErr>(calls.remove(0)).await]
+        } else {
+            let results = match send_multiple::<C, CustErr>(path, &calls).await
+            {
+                Ok(Some(results)) if results.len() == senders.len() => {
+                    Ok(results.into_iter().map(Ok).collect())
+                }
+                Ok(Some(results)) => {
+                    Err(ServerFnError::Deserialization(format!(
+                        "Expected {} responses from the batch endpoint, but \
+                         received {}.",
+                        senders.len(),
+                        results.len()
+                    )))
+                }
+                // the server accepts fewer calls in a batch than this client sends
+                Ok(None) => Ok(join_all(
+                    calls.into_iter().map(send_single::<C, CustErr>),
+                )
+                .await),
+                Err(e) => Err(e),
+            };
+            results.unwrap_or_else(|e| {
+                senders
+                    .iter()
+                    .map(|_| Err(e.ser().unwrap_or_default()))
+                    .collect()
+            })
+        };
+
+        for (tx, res) in senders.into_iter().zip(results) {
+            _ = tx.send(res);
+        }
+    }
+
+    async fn send_single<C, CustErr>(
+        call: BatchCall,
+    ) -> Result<BatchResult, String>
+    where
+        C: Client<CustErr>,
+        CustErr: FromStr + Display,
+    {
+        send_single_inner::<C, CustErr>(call)
+            .await
+            .map_err(|e| e.ser().unwrap_or_default())
+    }
+
+    async fn send_single_inner<C, CustErr>(
+        call: BatchCall,
+    ) -> Result<BatchResult, ServerFnError<CustErr>>
+    where
+        C: Client<CustErr>,
+    {
+        let content_type = call.content_type.as_deref().unwrap_or_default();
+        let req = match call.query.as_deref() {
+            Some(query) if call.method == "GET" => C::Request::try_new_get(
+                &call.path,
+                &call.accepts,
+                content_type,
+                query,
+            ),
+            _ => C::Request::try_new_post_bytes(
+                &call.path,
+                &call.accepts,
+                content_type,
+                call.body,
+            ),
+        }?;
+        let res = C::send(req).await?;
+        let status = res.status();
+        let content_type = res.content_type();
+        let redirect = res.has_redirect();
+        let location = Some(res.location());
+        let body = res.try_into_bytes().await?;
+        Ok(BatchResult {
+            status,
+            content_type,
+            location,
+            redirect,
+            body,
+        })
+    }
+
+    /// Sends the calls to the batch endpoint, returning `None` if the server rejected the batch
+    /// as too large.
+    async fn send_multiple<C, CustErr>(
+        path: &str,
+        calls: &[BatchCall],
+    ) -> Result<Option<Vec<BatchResult>>, ServerFnError<CustErr>>
+    where
+        C: Client<CustErr>,
+    {
+        let body = encode_calls(calls).map_err(ServerFnError::Serialization)?;
+        let req = C::Request::try_new_post_bytes(
+            path,
+            BATCH_CONTENT_TYPE,
+            BATCH_CONTENT_TYPE,
+            body,
+        )?;
+        let res = C::send(req).await?;
+        let status = res.status();
+        if status == 413 {
+            return Ok(None);
+        }
+        if !(200..=299).contains(&status) {
+            let text = res.try_into_string().await?;
+            return Err(ServerFnError::Request(format!(
+                "The batch endpoint returned {status}: {text}"
+            )));
+        }
+        let body = res.try_into_bytes().await?;
+        decode_results(body)
+            .map(Some)
+            .map_err(ServerFnError::Deserialization)
+    }
+
+    /// Yields to the executor once, so that other tasks can run before this one continues.
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{
+            batch::{decode_calls, encode_results, BATCH_ENDPOINT},
+            error::NoCustomError,
+        };
+        use bytes::Bytes;
+
+        // (client, path, number of calls) for each request sent by a `Recording` client
+        static SENT: Mutex<Vec<(usize, String, usize)>> =
+            Mutex::new(Vec::new());
+
+        // the `Recording` client that answers every batch with `413 Payload Too Large`
+        const REJECTS_BATCHES: usize = 6;
+
+        /// Records the requests it sends, and responds to each call with its path.
+        struct Recording<const ID: usize>;
+
+        fn init_executor() {
+            _ = Executor::init_futures_executor();
+        }
+
+        impl<const ID: usize> Client<NoCustomError> for Recording<ID> {
+            type Request = BatchRequest;
+            type Response = BatchResponse;
+
+            async fn send(
+                req: Self::Request,
+            ) -> Result<Self::Response, ServerFnError> {
+                let call = req.0;
+                let batched = call.path.ends_with(BATCH_ENDPOINT);
+                // respond a little later, so a call can be dropped while its batch is sent
+                YieldNow(false).await;
+                let results = if batched {
+                    decode_calls(call.body).unwrap()
+                } else {
+                    vec![call.clone()]
+                }
+                .into_iter()
+                .map(|call| BatchResult {
+                    status: 200,
+                    body: Bytes::from(call.path),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+                SENT.lock().unwrap().push((
+                    ID,
+                    call.path.clone(),
+                    results.len(),
+                ));
+                if batched && ID == REJECTS_BATCHES {
+                    return Ok(BatchResponse(BatchResult {
+                        status: 413,
+                        ..Default::default()
+                    }));
+                }
+                let body = if batched {
+                    encode_results(&results).unwrap()
+                } else {
+                    results[0].body.clone()
+                };
+                Ok(BatchResponse(BatchResult {
+                    status: 200,
+                    body,
+                    ..Default::default()
+                }))
+            }
+        }
+
+        async fn call<const ID: usize>(path: &str, yields: usize) -> String {
+            for _ in 0..yields {
+                YieldNow(false).await;
+            }
+            let req = <BatchRequest as ClientReq<NoCustomError>>::try_new_post(
+                path,
+                "text/plain",
+                "text/plain",
+                String::new(),
+            )
+            .unwrap();
+            let res = BatchClient::<Recording<ID>>::send(req).await.unwrap();
+            String::from_utf8(res.0.body.to_vec()).unwrap()
+        }
+
+        fn sent(id: usize) -> Vec<(String, usize)> {
+            let mut sent = SENT
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(client, ..)| *client == id)
+                .map(|(_, path, calls)| (path.clone(), *calls))
+                .collect::<Vec<_>>();
+            sent.sort();
+            sent
+        }
+
+        #[test]
+        fn batches_calls_per_client_and_prefix() {
+            init_executor();
+            let results = futures::executor::block_on(futures::future::join4(
+                call::<1>("/api/a", 0),
+                call::<1>("/api/b", 0),
+                call::<1>("/rpc/c", 0),
+                call::<2>("/api/d", 0),
+            ));
+            assert_eq!(
+                results,
+                (
+                    "/api/a".to_string(),
+                    "/api/b".to_string(),
+                    "/rpc/c".to_string(),
+                    "/api/d".to_string()
+                )
+            );
+            assert_eq!(
+                sent(1),
+                [("/api/_batch".to_string(), 2), ("/rpc/c".to_string(), 1)]
+            );
+            assert_eq!(sent(2), [("/api/d".to_string(), 1)]);
+        }
+
+        #[test]
+        fn waits_for_calls_made_after_yielding() {
+            init_executor();
+            // each of these calls joins the queue one yield after the previous one
+            let results = futures::executor::block_on(futures::future::join4(
+                call::<3>("/api/d", 3),
+                call::<3>("/api/c", 2),
+                call::<3>("/api/b", 1),
+                call::<3>("/api/a", 0),
+            ));
+            assert_eq!(results.0, "/api/d");
+            assert_eq!(results.3, "/api/a");
+            assert_eq!(sent(3), [("/api/_batch".to_string(), 4)]);
+        }
+
+        #[test]
+        fn splits_batches_that_are_too_large() {
+            init_executor();
+            let max = get_max_batch_calls();
+            let calls = (0..=max).map(|_| call::<4>("/api/a", 0));
+            let results =
+                futures::executor::block_on(futures::future::join_all(calls));
+            assert!(results.iter().all(|res| res == "/api/a"));
+            assert_eq!(
+                sent(4),
+                [("/api/_batch".to_string(), max), ("/api/a".to_string(), 1)]
+            );
+        }
+
+        #[test]
+        fn dropping_a_call_does_not_cancel_its_batch() {
+            init_executor();
+            let (first, abort_first) =
+                futures::future::abortable(call::<5>("/api/a", 0));
+            let results = futures::executor::block_on(futures::future::join3(
+                first,
+                call::<5>("/api/b", 0),
+                async move {
+                    // by now, the first call has taken the batch and is waiting for its result
+                    YieldNow(false).await;
+                    abort_first.abort();
+                },
+            ));
+            assert!(results.0.is_err());
+            assert_eq!(results.1, "/api/b");
+            assert_eq!(sent(5), [("/api/_batch".to_string(), 2)]);
+        }
+
+        #[test]
+        fn sends_calls_one_at_a_time_if_batch_is_rejected() {
+            init_executor();
+            let results = futures::executor::block_on(futures::future::join(
+                call::<REJECTS_BATCHES>("/api/a", 0),
+                call::<REJECTS_BATCHES>("/api/b", 0),
+            ));
+            assert_eq!(results, ("/api/a".to_string(), "/api/b".to_string()));
+            assert_eq!(
+                sent(REJECTS_BATCHES),
+                [
+                    ("/api/_batch".to_string(), 2),
+                    ("/api/a".to_string(), 1),
+                    ("/api/b".to_string(), 1)
+                ]
+            );
+        }
+    }
+}
+
+#[cfg(feature = "in-process")]
+/// Implements [`Client`] by calling registered server functions directly, for use in tests.
+pub mod in_process {
+    use super::Client;
+    use crate::{
+        codec::{Encoding, FromRes, IntoReq},
+        error::{ServerFnError, ServerFnErrorSerde},
+        middleware::Service,
+        request::in_process::InProcessRequest,
+        response::{in_process::InProcessResponse, ClientRes},
+        ServerFn,
+    };
+    use axum::body::Body;
+    use http::{Request, Response};
+    use pin_project_lite::pin_project;
+    use std::{
+        cell::RefCell,
+        future::Future,
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll},
+    };
+
+    /// Implements [`Client`] by dispatching each request directly to the server function
+    /// registered at its path, without opening any sockets.
+    ///
+    /// The arguments and the result are still encoded and decoded with the server function’s
+    /// real input and output encodings, and the request runs through the same
+    /// [`middlewares`](ServerFn::middlewares) as it would in the Axum integration, so tests
+    /// exercise serialization, middleware and error handling. Server functions are looked up in
+    /// the Axum registry, so they must have been registered with the `axum` feature.
+    ///
+    /// Form data and multipart requests are not supported.
+    ///
+    /// With the `ssr` feature, calling a server function runs its body directly, so tests
+    /// should go through [`InProcess::call`] instead. Use [`InProcess::with_request`] to add
+    /// headers or extensions that the server function will `extract()`. In a Leptos app,
+    /// `extract()` finds the request in the context set up by the integration, so the calls
+    /// should be routed through it with [`InProcess::with_handler`].
+    ///
+    /// ```rust,ignore
+    /// #[tokio::test]
+    /// async fn adds_numbers() {
+    ///     let res = InProcess::with_request(
+    ///         |req| {
+    ///             req.extensions_mut().insert(test_db());
+    ///         },
+    ///         InProcess::call(Add { a: 2, b: 3 }),
+    ///     )
+    ///     .await;
+    ///     assert_eq!(res.unwrap(), 5);
+    /// }
+    /// ```
+    pub struct InProcess;
+
+    type ModifyRequest = Arc<dyn Fn(&mut Request<Body>) + Send + Sync>;
+
+    type Handler = Arc<
+        dyn Fn(
+                Request<Body>,
+            )
+                -> Pin<Box<dyn Future<Output = Response<Body>> + Send>>
+            + Send
+            + Sync,
+    >;
+
+    /// Set by [`InProcess::with_request`] or [`InProcess::with_handler`] while a future runs.
+    #[derive(Clone)]
+    enum Scope {
+        ModifyRequest(ModifyRequest),
+        Handler(Handler),
+    }
+
+    thread_local! {
+        static SCOPES: RefCell<Vec<Scope>> = const { RefCell::new(Vec::new()) };
+    }
+
+    impl InProcess {
+        /// Calls a server function in-process, whichever [`Client`] it normally uses.
+        ///
+        /// This encodes the arguments, runs the server function with its middleware, and
+        /// decodes the response, just like a call made from the client.
+        pub async fn call<S>(
+            args: S,
+        ) -> Result<S::Output, ServerFnError<S::Error>>
+        where
+            S: ServerFn + IntoReq<S::InputEncoding, InProcessRequest, S::Error>,
+            S::Output: FromRes<S::OutputEncoding, InProcessResponse, S::Error>,
+        {
+            let req =
+                args.into_req(S::PATH, S::OutputEncoding::CONTENT_TYPE)?;
+            let res = <Self as Client<S::Error>>::send(req).await?;
+            // if it returns an error status, deserialize the error using FromStr
+            if (400..=599).contains(&ClientRes::<S::Error>::status(&res)) {
+                let text = ClientRes::<S::Error>::try_into_string(res).await?;
+                Err(ServerFnError::de(&text))
+            } else {
+                S::Output::from_res(res).await
+            }
+        }
+
+        /// Runs `fut`, sending each request that [`InProcess`] sends while it runs to `handler`.
+        ///
+        /// By default, each request is run by the server function registered at its path,
+        /// with its middleware. This replaces that, for example with
+        /// `leptos_axum::handle_server_fns` so that the request is available to `extract()`:
+        ///
+        /// ```rust,ignore
+        /// let res = InProcess::with_handler(
+        ///     |req| async move {
+        ///         leptos_axum::handle_server_fns(req).await.into_response()
+        ///     },
+        ///     InProcess::call(Add { a: 2, b: 3 }),
+        /// )
+        /// .await;
+        /// ```
+        ///
+        /// Like [`with_request`](InProcess::with_request), this only applies to the future it
+        /// wraps, so tests that run in parallel can use different handlers. If calls are nested,
+        /// the innermost handler is used.
+        pub fn with_handler<Fut, HandlerFut>(
+            handler: impl Fn(Request<Body>) -> HandlerFut + Send + Sync + 'static,
+            fut: Fut,
+        ) -> WithRequest<Fut>
+        where
+            Fut: Future,
+            HandlerFut: Future<Output = Response<Body>> + Send + 'static,
+        {
+            WithRequest {
+                scope: Scope::Handler(Arc::new(move |req| {
+                    Box::pin(handler(req))
+                })),
+                inner: fut,
+            }
+        }
+
+        /// Runs `fut`, calling `modify` on each request that [`InProcess`] sends while it runs.
+        ///
+        /// This can be used to add headers, or extensions that the server function reads with
+        /// `extract()` or `use_context()`. Nested calls are applied from the outside in.
+        pub fn with_request<Fut>(
+            modify: impl Fn(&mut Request<Body>) + Send + Sync + 'static,
+            fut: Fut,
+        ) -> WithRequest<Fut>
+        where
+            Fut: Future,
+        {
+            WithRequest {
+                scope: Scope::ModifyRequest(Arc::new(modify)),
+                inner: fut,
+            }
+        }
+    }
+
+    pin_project! {
+        /// A `Future` that modifies the requests sent by [`InProcess`], or the handler they are
+        /// sent to, while it runs.
+        ///
+        /// This is returned by [`InProcess::with_request`] and [`InProcess::with_handler`].
+        pub struct WithRequest<Fut> {
+            scope: Scope,
+            #[pin]
+            inner: Fut,
+        }
+    }
+
+    impl<Fut: Future> Future for WithRequest<Fut> {
+        type Output = Fut::Output;
+
+        fn poll(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Fut::Output> {
+            // removes the scope again, even if the inner future panics
+            struct Pop;
+
+            impl Drop for Pop {
+                fn drop(&mut self) {
+                    SCOPES.with(|scopes| scopes.borrow_mut().pop());
+                }
+            }
+
+            let this = self.project();
+            SCOPES.with(|scopes| scopes.borrow_mut().push(this.scope.clone()));
+            let _pop = Pop;
+            this.inner.poll(cx)
+        }
+    }
+
+    impl<CustErr> Client<CustErr> for InProcess {
+        type Request = InProcessRequest;
+        type Response = InProcessResponse;
+
+        async fn send(
+            req: Self::Request,
+        ) -> Result<Self::Response, ServerFnError<CustErr>> {
+            let mut req = req.0;
+            let scopes = SCOPES.with(|scopes| scopes.borrow().clone());
+            let mut handler = None;
+            for scope in scopes {
+                match scope {
+                    Scope::ModifyRequest(modify) => modify(&mut req),
+                    Scope::Handler(inner) => handler = Some(inner),
+                }
+            }
+
+            if let Some(handler) = handler {
+                return Ok(InProcessResponse(handler(req).await));
+            }
+
+            let path = req.uri().path().to_string();
+            let mut service =
+                crate::axum::get_server_fn_service(&path, req.method().clone())
+                    .ok_or_else(|| {
+                        ServerFnError::Request(format!(
+                            "Could not find a server function at the route \
+                             {path}."
+                        ))
+                    })?;
+            Ok(InProcessResponse(service.run(req).await))
+        }
+    }
+
+    #[cfg(all(test, feature = "json"))]
+    mod tests {
+        use super::*;
+        use crate::{
+            axum::register_explicit,
+            codec::Json,
+            error::NoCustomError,
+            middleware::{BoxedService, Layer},
+            response::Res,
+        };
+        use futures::executor::block_on;
+        use http::HeaderValue;
+        use serde::{Deserialize, Serialize};
+
+        /// Rejected by [`Guard`].
+        #[derive(Clone)]
+        struct Deny;
+
+        /// Rejects requests with the [`Deny`] extension, and copies the `x-name` header of the
+        /// request to the response.
+        struct Guard;
+
+        struct GuardService(BoxedService<Request<Body>, Response<Body>>);
+
+        impl Layer<Request<Body>, Response<Body>> for Guard {
+            fn layer(
+                &self,
+                inner: BoxedService<Request<Body>, Response<Body>>,
+            ) -> BoxedService<Request<Body>, Response<Body>> {
+                BoxedService::new(GuardService(inner))
+            }
+        }
+
+        impl Service<Request<Body>, Response<Body>> for GuardService {
+            fn run(
+                &mut self,
+                req: Request<Body>,
+            ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>>
+            {
+                if req.extensions().get::<Deny>().is_some() {
+                    let err = ServerFnError::<NoCustomError>::ServerError(
+                        "denied".to_string(),
+                    );
+                    let res = Response::error_response_with_status(
+                        req.uri().path(),
+                        403,
+                        &err,
+                    );
+                    return Box::pin(async move { res });
+                }
+                let name = req.headers().get("x-name").cloned();
+                let res = self.0.run(req);
+                Box::pin(async move {
+                    let mut res = res.await;
+                    if let Some(name) = name {
+                        res.headers_mut().insert("x-name", name);
+                    }
+                    res
+                })
+            }
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct Add {
+            a: u8,
+            b: u8,
+        }
+
+        impl ServerFn for Add {
+            const PATH: &'static str = "/in_process_test/add";
+            type Client = InProcess;
+            type ServerRequest = Request<Body>;
+            type ServerResponse = Response<Body>;
+            type Output = u8;
+            type InputEncoding = Json;
+            type OutputEncoding = Json;
+            type Error = NoCustomError;
+
+            fn middlewares(
+            ) -> Vec<Arc<dyn Layer<Request<Body>, Response<Body>>>>
+            {
+                vec![Arc::new(Guard)]
+            }
+
+            async fn run_body(self) -> Result<u8, ServerFnError> {
+                self.a.checked_add(self.b).ok_or_else(|| {
+                    ServerFnError::ServerError("overflow".to_string())
+                })
+            }
+        }
+
+        /// Sends the request for `add` and returns the `x-name` header of the response.
+        async fn echoed_name(add: Add) -> Option<HeaderValue> {
+            let req: InProcessRequest =
+                IntoReq::<Json, _, NoCustomError>::into_req(
+                    add,
+                    Add::PATH,
+                    Json::CONTENT_TYPE,
+                )
+                .unwrap();
+            let res = <InProcess as Client<NoCustomError>>::send(req)
+                .await
+                .unwrap();
+            res.0.headers().get("x-name").cloned()
+        }
+
+        #[test]
+        fn calls_registered_server_fn() {
+            register_explicit::<Add>();
+            assert_eq!(block_on(InProcess::call(Add { a: 1, b: 2 })), Ok(3));
+        }
+
+        #[test]
+        fn returns_server_fn_errors() {
+            register_explicit::<Add>();
+            assert_eq!(
+                block_on(InProcess::call(Add { a: 255, b: 1 })),
+                Err(ServerFnError::ServerError("overflow".to_string()))
+            );
+        }
+
+        #[test]
+        fn runs_middleware() {
+            register_explicit::<Add>();
+            let res = block_on(InProcess::with_request(
+                |req| _ = req.extensions_mut().insert(Deny),
+                InProcess::call(Add { a: 1, b: 2 }),
+            ));
+            assert_eq!(
+                res,
+                Err(ServerFnError::ServerError("denied".to_string()))
+            );
+        }
+
+        #[test]
+        fn modifies_requests_from_the_outside_in() {
+            register_explicit::<Add>();
+            let set_name = |name: &'static str| {
+                move |req: &mut Request<Body>| {
+                    req.headers_mut()
+                        .insert("x-name", HeaderValue::from_static(name));
+                }
+            };
+            let (outer, inner) =
+                block_on(InProcess::with_request(set_name("outer"), async {
+                    let outer = echoed_name(Add { a: 1, b: 2 }).await;
+                    let inner = InProcess::with_request(
+                        set_name("inner"),
+                        echoed_name(Add { a: 1, b: 2 }),
+                    )
+                    .await;
+                    (outer, inner)
+                }));
+            assert_eq!(outer.unwrap(), "outer");
+            assert_eq!(inner.unwrap(), "inner");
+            // the modifiers only apply while the future runs
+            assert_eq!(block_on(echoed_name(Add { a: 1, b: 2 })), None);
+        }
+
+        #[test]
+        fn uses_handler_only_while_future_runs() {
+            register_explicit::<Add>();
+            let handler = |_: Request<Body>| async {
+                Response::builder()
+                    .header("x-name", "handler")
+                    .body(Body::empty())
+                    .unwrap()
+            };
+            let name = block_on(InProcess::with_handler(
+                handler,
+                echoed_name(Add { a: 1, b: 2 }),
+            ));
+            assert_eq!(name.unwrap(), "handler");
+            assert_eq!(block_on(echoed_name(Add { a: 1, b: 2 })), None);
+        }
+
+        #[test]
+        fn fails_for_unregistered_path() {
+            let req = <InProcessRequest as crate::request::ClientReq<
+                NoCustomError,
+            >>::try_new_post(
+                "/in_process_test/missing",
+                "application/json",
+                "application/json",
+                "{}".to_string(),
+            )
+            .unwrap();
+            let res = block_on(<InProcess as Client<NoCustomError>>::send(req));
+            assert!(matches!(res, Err(ServerFnError::Request(_))));
+        }
+    }
+}
+
+#[cfg(feature = "in-process")]
+pub use in_process::InProcess;
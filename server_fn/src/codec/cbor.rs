@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
 use crate::{
     error::ServerFnError,
     request::{ClientReq, Req},
@@ -16,6 +16,30 @@ impl Encoding for Cbor {
     const METHOD: Method = Method::POST;
 }
 
+impl<T> Encodes<T> for Cbor
+where
+    T: Serialize,
+{
+    type Error = ciborium::ser::Error<std::io::Error>;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        let mut buffer: Vec<u8> = Vec::new();
+        ciborium::ser::into_writer(value, &mut buffer)?;
+        Ok(Bytes::from(buffer))
+    }
+}
+
+impl<T> Decodes<T> for Cbor
+where
+    T: DeserializeOwned,
+{
+    type Error = ciborium::de::Error<std::io::Error>;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        ciborium::de::from_reader(bytes.as_ref())
+    }
+}
+
 impl<CustErr, T, Request> IntoReq<Cbor, Request, CustErr> for T
 where
     Request: ClientReq<CustErr>,
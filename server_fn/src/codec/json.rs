@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, Streaming};
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, Streaming};
 use crate::{
     error::{NoCustomError, ServerFnError},
     request::{ClientReq, Req},
@@ -18,6 +18,28 @@ impl Encoding for Json {
     const METHOD: Method = Method::POST;
 }
 
+impl<T> Encodes<T> for Json
+where
+    T: Serialize,
+{
+    type Error = serde_json::Error;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        serde_json::to_vec(value).map(Bytes::from)
+    }
+}
+
+impl<T> Decodes<T> for Json
+where
+    T: DeserializeOwned,
+{
+    type Error = serde_json::Error;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        serde_json::from_slice(&bytes)
+    }
+}
+
 impl<CustErr, T, Request> IntoReq<Json, Request, CustErr> for T
 where
     Request: ClientReq<CustErr>,
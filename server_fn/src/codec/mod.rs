@@ -13,6 +13,9 @@
 //! an input combination ([`IntoReq`] and [`FromReq`]) and/or an output encoding ([`IntoRes`] and [`FromRes`]).
 //! This genuinely is an and/or: while some encodings can be used for both input and output (`Json`, `Cbor`, `Rkyv`),
 //! others can only be used for input (`GetUrl`, `MultipartData`).
+//!
+//! A single server function can also accept several encodings with [`Negotiated`], which picks one
+//! based on the `Content-Type` and `Accept` headers of each request.
 
 #[cfg(feature = "cbor")]
 mod cbor;
@@ -24,6 +27,13 @@ mod json;
 #[cfg(feature = "json")]
 pub use json::*;
 
+#[cfg(feature = "json")]
+mod sse;
+#[cfg(feature = "json")]
+pub use sse::*;
+#[cfg(feature = "json")]
+pub(crate) use sse::WithLastEventId;
+
 #[cfg(feature = "serde-lite")]
 mod serde_lite;
 #[cfg(feature = "serde-lite")]
@@ -54,11 +64,21 @@ mod postcard;
 #[cfg(feature = "postcard")]
 pub use postcard::*;
 
+#[cfg(feature = "protobuf")]
+mod protobuf;
+#[cfg(feature = "protobuf")]
+pub use protobuf::*;
+
+mod negotiate;
 mod stream;
+mod websocket;
 use crate::error::ServerFnError;
 use futures::Future;
 use http::Method;
+pub(crate) use negotiate::WithAccepts;
+pub use negotiate::{DecodesOneOf, EncodesOneOf, EncodingSet, Negotiated};
 pub use stream::*;
+pub use websocket::*;
 
 /// Serializes a data type into an HTTP request, on the client.
 ///
@@ -216,4 +236,39 @@ pub trait Encoding {
     ///
     /// This should be `POST` in most cases.
     const METHOD: Method;
+
+    /// Whether the encoding of a response is chosen to match the `Accept` header of the
+    /// request, rather than always being [`CONTENT_TYPE`](Self::CONTENT_TYPE).
+    ///
+    /// This is `true` for [`Negotiated`], which is the only encoding that reads the header.
+    const NEGOTIATED: bool = false;
+
+    /// Whether the response is a stream of server-sent events, which a client resumes by sending
+    /// the ID of the last event it received.
+    ///
+    /// This is `true` for [`Sse`], which is the only encoding that reads the `Last-Event-ID` header.
+    const SERVER_SENT_EVENTS: bool = false;
+}
+
+/// Serializes a single value into bytes using a particular [`Encoding`].
+///
+/// Unlike [`IntoReq`] and [`IntoRes`], this works on individual values rather than on whole
+/// requests or responses, which makes it useful for framing each message of a stream.
+pub trait Encodes<T>: Encoding {
+    /// The error returned if serialization fails.
+    type Error: std::fmt::Display;
+
+    /// Serializes the value.
+    fn encode(value: &T) -> Result<bytes::Bytes, Self::Error>;
+}
+
+/// Deserializes a single value from bytes using a particular [`Encoding`].
+///
+/// This is the counterpart to [`Encodes`].
+pub trait Decodes<T>: Encoding {
+    /// The error returned if deserialization fails.
+    type Error: std::fmt::Display;
+
+    /// Deserializes the value.
+    fn decode(bytes: bytes::Bytes) -> Result<T, Self::Error>;
 }
@@ -0,0 +1,501 @@
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use http::Method;
+use pin_project_lite::pin_project;
+use std::{
+    cell::RefCell,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Pass arguments and receive responses in any one of a set of encodings, in a `POST` request.
+///
+/// The set is a tuple of two to four encodings that implement [`Encodes`] and [`Decodes`], in
+/// order of preference, like `Negotiated<(Cbor, Json, MsgPack)>`.
+///
+/// - The server reads the arguments in whichever encoding matches the `Content-Type` of the
+///   request, and responds in the encoding that best matches its `Accept` header. If the
+///   `Accept` header is missing or matches none of them, it responds in the first encoding.
+/// - The client always sends its arguments in the first encoding, and asks for the response in
+///   the first encoding of the output set. It decodes the response in whichever encoding
+///   matches its `Content-Type`, falling back to the first one if there is none.
+///
+/// This lets a single endpoint serve a WASM client with a compact binary format, and other
+/// tools with a format like JSON.
+///
+/// ```rust,ignore
+/// #[server(
+///     input = Negotiated<(Cbor, Json)>,
+///     output = Negotiated<(Cbor, Json)>
+/// )]
+/// pub async fn add(a: i32, b: i32) -> Result<i32, ServerFnError> {
+///     Ok(a + b)
+/// }
+/// ```
+pub struct Negotiated<Encodings>(PhantomData<Encodings>);
+
+/// A set of encodings that can be used with [`Negotiated`].
+///
+/// This is implemented for tuples of two to four [`Encoding`]s.
+pub trait EncodingSet {
+    /// The MIME types of the encodings, in order of preference.
+    const CONTENT_TYPES: &'static [&'static str];
+}
+
+/// Serializes a value in one of the encodings of an [`EncodingSet`].
+pub trait EncodesOneOf<T>: EncodingSet {
+    /// Serializes the value in the encoding at `index` in [`EncodingSet::CONTENT_TYPES`].
+    fn encode(index: usize, value: &T) -> Result<Bytes, String>;
+}
+
+/// Deserializes a value from one of the encodings of an [`EncodingSet`].
+pub trait DecodesOneOf<T>: EncodingSet {
+    /// Deserializes the value from the encoding at `index` in [`EncodingSet::CONTENT_TYPES`].
+    fn decode(index: usize, bytes: Bytes) -> Result<T, String>;
+}
+
+macro_rules! encoding_set {
+    ($($idx:tt => $ty:ident),+) => {
+        impl<$($ty: Encoding),+> EncodingSet for ($($ty,)+) {
+            const CONTENT_TYPES: &'static [&'static str] = &[$($ty::CONTENT_TYPE),+];
+        }
+
+        impl<T, $($ty: Encodes<T>),+> EncodesOneOf<T> for ($($ty,)+) {
+            fn encode(index: usize, value: &T) -> Result<Bytes, String> {
+                match index {
+                    $($idx => $ty::encode(value).map_err(|e| e.to_string()),)+
+                    _ => Err(format!("no encoding at index {index}")),
+                }
+            }
+        }
+
+        impl<T, $($ty: Decodes<T>),+> DecodesOneOf<T> for ($($ty,)+) {
+            fn decode(index: usize, bytes: Bytes) -> Result<T, String> {
+                match index {
+                    $($idx => $ty::decode(bytes).map_err(|e| e.to_string()),)+
+                    _ => Err(format!("no encoding at index {index}")),
+                }
+            }
+        }
+    };
+}
+
+encoding_set!(0 => A, 1 => B);
+encoding_set!(0 => A, 1 => B, 2 => C);
+encoding_set!(0 => A, 1 => B, 2 => C, 3 => D);
+
+impl<E: EncodingSet> Encoding for Negotiated<E> {
+    const CONTENT_TYPE: &'static str = E::CONTENT_TYPES[0];
+    const METHOD: Method = Method::POST;
+    const NEGOTIATED: bool = true;
+}
+
+/// Returns the MIME type of a `Content-Type` header or media range, without its parameters.
+fn essence(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or_default().trim()
+}
+
+/// Finds the encoding that matches the `Content-Type` of a request.
+fn from_content_type(
+    content_type: &str,
+    content_types: &[&str],
+) -> Option<usize> {
+    let content_type = essence(content_type);
+    content_types
+        .iter()
+        .position(|ty| ty.eq_ignore_ascii_case(content_type))
+}
+
+/// Finds the encoding with the highest quality in an `Accept` header, preferring earlier
+/// encodings if several are equally acceptable.
+fn from_accepts(accepts: &str, content_types: &[&str]) -> Option<usize> {
+    let ranges = accepts
+        .split(',')
+        .map(|range| {
+            let q = range
+                .split(';')
+                .skip(1)
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (essence(range), q)
+        })
+        .collect::<Vec<_>>();
+    let mut best = None;
+    for (index, content_type) in content_types.iter().enumerate() {
+        let main_type = content_type.split('/').next().unwrap_or_default();
+        // the quality comes from the most specific range that matches
+        let q = ranges
+            .iter()
+            .filter_map(|(range, q)| {
+                if range.eq_ignore_ascii_case(content_type) {
+                    Some((2, *q))
+                } else if range
+                    .strip_suffix("/*")
+                    .is_some_and(|range| range.eq_ignore_ascii_case(main_type))
+                {
+                    Some((1, *q))
+                } else if *range == "*/*" {
+                    Some((0, *q))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, q)| q);
+        if q > 0.0 && best.map_or(true, |(_, best_q)| q > best_q) {
+            best = Some((index, q));
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+thread_local! {
+    static ACCEPTS: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Finds the encoding that best matches the `Accept` header of the request being handled, or
+/// the first one if there is no header or none of them match.
+fn accepted(content_types: &[&str]) -> usize {
+    ACCEPTS
+        .with(|accepts| {
+            accepts
+                .borrow()
+                .as_deref()
+                .and_then(|accepts| from_accepts(accepts, content_types))
+        })
+        .unwrap_or(0)
+}
+
+pin_project! {
+    /// Makes the `Accept` header of a request available to [`Negotiated`] while the response
+    /// is being created.
+    pub(crate) struct WithAccepts<Fut> {
+        accepts: Option<String>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> WithAccepts<Fut> {
+    pub fn new(accepts: Option<String>, inner: Fut) -> Self {
+        Self { accepts, inner }
+    }
+}
+
+impl<Fut: Future> Future for WithAccepts<Fut> {
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        ACCEPTS.with(|accepts| {
+            std::mem::swap(this.accepts, &mut accepts.borrow_mut())
+        });
+        let res = this.inner.poll(cx);
+        ACCEPTS.with(|accepts| {
+            std::mem::swap(this.accepts, &mut accepts.borrow_mut())
+        });
+        res
+    }
+}
+
+impl<CustErr, T, Request, E> IntoReq<Negotiated<E>, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+    E: EncodesOneOf<T>,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        let data = E::encode(0, &self).map_err(ServerFnError::Serialization)?;
+        Request::try_new_post_bytes(path, accepts, E::CONTENT_TYPES[0], data)
+    }
+}
+
+impl<CustErr, T, Request, E> FromReq<Negotiated<E>, Request, CustErr> for T
+where
+    Request: Req<CustErr> + Send + 'static,
+    E: DecodesOneOf<T>,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let index = match req.to_content_type() {
+            None => 0,
+            Some(content_type) => {
+                from_content_type(&content_type, E::CONTENT_TYPES).ok_or_else(
+                    || {
+                        ServerFnError::Args(format!(
+                            "unsupported Content-Type {content_type}, \
+                             expected one of {}",
+                            E::CONTENT_TYPES.join(", ")
+                        ))
+                    },
+                )?
+            }
+        };
+        let data = req.try_into_bytes().await?;
+        E::decode(index, data).map_err(ServerFnError::Args)
+    }
+}
+
+impl<CustErr, T, Response, E> IntoRes<Negotiated<E>, Response, CustErr> for T
+where
+    Response: Res<CustErr>,
+    T: Send,
+    E: EncodesOneOf<T>,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        let index = accepted(E::CONTENT_TYPES);
+        let data =
+            E::encode(index, &self).map_err(ServerFnError::Serialization)?;
+        Response::try_from_bytes(E::CONTENT_TYPES[index], data)
+    }
+}
+
+impl<CustErr, T, Response, E> FromRes<Negotiated<E>, Response, CustErr> for T
+where
+    Response: ClientRes<CustErr> + Send,
+    T: Send,
+    E: DecodesOneOf<T>,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        // the client asks for the first encoding, but a proxy or another server may not
+        let index = match res.content_type() {
+            None => 0,
+            Some(content_type) => {
+                from_content_type(&content_type, E::CONTENT_TYPES).ok_or_else(
+                    || {
+                        ServerFnError::Deserialization(format!(
+                            "unsupported Content-Type {content_type}, \
+                             expected one of {}",
+                            E::CONTENT_TYPES.join(", ")
+                        ))
+                    },
+                )?
+            }
+        };
+        let data = res.try_into_bytes().await?;
+        E::decode(index, data).map_err(ServerFnError::Deserialization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NoCustomError;
+    use futures::{executor::block_on, stream, Stream};
+
+    const TYPES: &[&str] =
+        &["application/cbor", "application/json", "text/plain"];
+
+    #[test]
+    fn matches_content_type() {
+        assert_eq!(from_content_type("application/json", TYPES), Some(1));
+        assert_eq!(from_content_type("Application/JSON", TYPES), Some(1));
+        assert_eq!(
+            from_content_type("text/plain; charset=utf-8", TYPES),
+            Some(2)
+        );
+        assert_eq!(from_content_type(" application/cbor ;x=y", TYPES), Some(0));
+        assert_eq!(from_content_type("application/xml", TYPES), None);
+        assert_eq!(from_content_type("*/*", TYPES), None);
+        assert_eq!(from_content_type("", TYPES), None);
+    }
+
+    #[test]
+    fn accepts_exact_types_in_order_of_preference() {
+        assert_eq!(from_accepts("application/json", TYPES), Some(1));
+        assert_eq!(
+            from_accepts("text/plain, application/json", TYPES),
+            Some(1)
+        );
+        assert_eq!(from_accepts("application/xml", TYPES), None);
+        assert_eq!(from_accepts("", TYPES), None);
+    }
+
+    #[test]
+    fn accepts_highest_quality() {
+        assert_eq!(
+            from_accepts("application/cbor;q=0.5, text/plain;q=0.8", TYPES),
+            Some(2)
+        );
+        assert_eq!(
+            from_accepts(
+                "application/json; charset=utf-8; q=0.1, text/plain; q=0.2",
+                TYPES
+            ),
+            Some(2)
+        );
+        // `q=0` means "not acceptable"
+        assert_eq!(from_accepts("application/cbor;q=0", TYPES), None);
+        // an invalid quality counts as 1
+        assert_eq!(
+            from_accepts("text/plain;q=high, application/cbor;q=0.9", TYPES),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn accepts_wildcards() {
+        assert_eq!(from_accepts("*/*", TYPES), Some(0));
+        assert_eq!(from_accepts("text/*", TYPES), Some(2));
+        assert_eq!(
+            from_accepts("application/*;q=0.5, */*;q=0.1", TYPES),
+            Some(0)
+        );
+        // the most specific range decides the quality of a type
+        assert_eq!(from_accepts("application/cbor;q=0, */*", TYPES), Some(1));
+        assert_eq!(
+            from_accepts("application/*;q=0, text/plain;q=0.1", TYPES),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn uses_accepts_of_current_request() {
+        assert_eq!(accepted(TYPES), 0);
+        let index =
+            block_on(WithAccepts::new(Some("text/plain".to_string()), async {
+                let outer = accepted(TYPES);
+                let inner = WithAccepts::new(
+                    Some("application/json".to_string()),
+                    async { accepted(TYPES) },
+                )
+                .await;
+                (outer, inner, accepted(TYPES))
+            }));
+        assert_eq!(index, (2, 1, 2));
+        assert_eq!(accepted(TYPES), 0);
+        let index = block_on(WithAccepts::new(None, async { accepted(TYPES) }));
+        assert_eq!(index, 0);
+    }
+
+    /// Encodes numbers as decimal text.
+    struct Decimal;
+
+    impl Encoding for Decimal {
+        const CONTENT_TYPE: &'static str = "text/plain";
+        const METHOD: Method = Method::POST;
+    }
+
+    impl Encodes<u32> for Decimal {
+        type Error = String;
+
+        fn encode(value: &u32) -> Result<Bytes, String> {
+            Ok(value.to_string().into())
+        }
+    }
+
+    impl Decodes<u32> for Decimal {
+        type Error = String;
+
+        fn decode(bytes: Bytes) -> Result<u32, String> {
+            String::from_utf8_lossy(&bytes)
+                .parse()
+                .map_err(|e| format!("{e}"))
+        }
+    }
+
+    /// Encodes numbers as hexadecimal text.
+    struct Hex;
+
+    impl Encoding for Hex {
+        const CONTENT_TYPE: &'static str = "text/x-hex";
+        const METHOD: Method = Method::POST;
+    }
+
+    impl Encodes<u32> for Hex {
+        type Error = String;
+
+        fn encode(value: &u32) -> Result<Bytes, String> {
+            Ok(format!("{value:x}").into())
+        }
+    }
+
+    impl Decodes<u32> for Hex {
+        type Error = String;
+
+        fn decode(bytes: Bytes) -> Result<u32, String> {
+            u32::from_str_radix(&String::from_utf8_lossy(&bytes), 16)
+                .map_err(|e| format!("{e}"))
+        }
+    }
+
+    struct TestRes {
+        content_type: Option<&'static str>,
+        body: &'static str,
+    }
+
+    impl ClientRes<NoCustomError> for TestRes {
+        async fn try_into_string(self) -> Result<String, ServerFnError> {
+            Ok(self.body.to_string())
+        }
+
+        async fn try_into_bytes(self) -> Result<Bytes, ServerFnError> {
+            Ok(Bytes::from_static(self.body.as_bytes()))
+        }
+
+        fn try_into_stream(
+            self,
+        ) -> Result<
+            impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+            ServerFnError,
+        > {
+            Ok(stream::empty())
+        }
+
+        fn status(&self) -> u16 {
+            200
+        }
+
+        fn status_text(&self) -> String {
+            "OK".to_string()
+        }
+
+        fn location(&self) -> String {
+            String::new()
+        }
+
+        fn has_redirect(&self) -> bool {
+            false
+        }
+
+        fn content_type(&self) -> Option<String> {
+            self.content_type.map(ToOwned::to_owned)
+        }
+    }
+
+    fn decode(
+        content_type: Option<&'static str>,
+        body: &'static str,
+    ) -> Result<u32, ServerFnError> {
+        block_on(
+            <u32 as FromRes<Negotiated<(Decimal, Hex)>, _, _>>::from_res(
+                TestRes { content_type, body },
+            ),
+        )
+    }
+
+    #[test]
+    fn decodes_response_by_content_type() {
+        assert_eq!(decode(Some("text/plain"), "16"), Ok(16));
+        assert_eq!(decode(Some("text/x-hex; charset=utf-8"), "10"), Ok(16));
+        assert_eq!(decode(None, "16"), Ok(16));
+        assert!(matches!(
+            decode(Some("application/json"), "16"),
+            Err(ServerFnError::Deserialization(_))
+        ));
+    }
+
+    // only `Negotiated` needs the `Accept` header copied out of the request
+    const _: () = assert!(Negotiated::<(Decimal, Hex)>::NEGOTIATED);
+    const _: () = assert!(!Decimal::NEGOTIATED);
+}
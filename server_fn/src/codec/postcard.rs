@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
 use crate::{
     error::ServerFnError,
     request::{ClientReq, Req},
@@ -16,6 +16,28 @@ impl Encoding for Postcard {
     const METHOD: Method = Method::POST;
 }
 
+impl<T> Encodes<T> for Postcard
+where
+    T: Serialize,
+{
+    type Error = postcard::Error;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        postcard::to_allocvec(value).map(Bytes::from)
+    }
+}
+
+impl<T> Decodes<T> for Postcard
+where
+    T: DeserializeOwned,
+{
+    type Error = postcard::Error;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        postcard::from_bytes(&bytes)
+    }
+}
+
 impl<T, Request, Err> IntoReq<Postcard, Request, Err> for T
 where
     Request: ClientReq<Err>,
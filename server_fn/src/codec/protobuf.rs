@@ -0,0 +1,433 @@
+use super::{
+    Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes, Streaming,
+};
+use crate::{
+    error::{NoCustomError, ServerFnError},
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::{Buf, Bytes, BytesMut};
+use futures::{stream, Stream, StreamExt};
+use http::Method;
+use prost::Message;
+use std::pin::Pin;
+
+/// Pass arguments and receive responses as Protocol Buffers in a `POST` request.
+///
+/// The arguments and the return type must implement [`prost::Message`].
+pub struct Protobuf;
+
+impl Encoding for Protobuf {
+    const CONTENT_TYPE: &'static str = "application/x-protobuf";
+    const METHOD: Method = Method::POST;
+}
+
+impl<T> Encodes<T> for Protobuf
+where
+    T: Message,
+{
+    type Error = prost::EncodeError;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        Ok(Bytes::from(value.encode_to_vec()))
+    }
+}
+
+impl<T> Decodes<T> for Protobuf
+where
+    T: Message + Default,
+{
+    type Error = prost::DecodeError;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        T::decode(bytes)
+    }
+}
+
+impl<CustErr, T, Request> IntoReq<Protobuf, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+    T: Message,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Request::try_new_post_bytes(
+            path,
+            accepts,
+            Protobuf::CONTENT_TYPE,
+            Bytes::from(self.encode_to_vec()),
+        )
+    }
+}
+
+impl<CustErr, T, Request> FromReq<Protobuf, Request, CustErr> for T
+where
+    Request: Req<CustErr> + Send + 'static,
+    T: Message + Default,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let body_bytes = req.try_into_bytes().await?;
+        T::decode(body_bytes).map_err(|e| ServerFnError::Args(e.to_string()))
+    }
+}
+
+impl<CustErr, T, Response> IntoRes<Protobuf, Response, CustErr> for T
+where
+    Response: Res<CustErr>,
+    T: Message,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Response::try_from_bytes(
+            Protobuf::CONTENT_TYPE,
+            Bytes::from(self.encode_to_vec()),
+        )
+    }
+}
+
+impl<CustErr, T, Response> FromRes<Protobuf, Response, CustErr> for T
+where
+    Response: ClientRes<CustErr> + Send,
+    T: Message + Default,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let data = res.try_into_bytes().await?;
+        T::decode(data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+/// An encoding that represents a stream of Protocol Buffers messages.
+///
+/// Each message is prefixed with its length as a varint, as written by
+/// [`Message::encode_length_delimited`], so messages can be split across or combined within
+/// the chunks of the underlying [`ByteStream`](super::ByteStream). A message longer than
+/// [`MAX_STREAMED_MESSAGE_LEN`] is rejected as soon as its length has been read, rather than
+/// buffered until it is complete.
+///
+/// A server function that uses this as its output encoding should return [`ProtobufStream`].
+///
+/// ## Browser Support for Streaming Input
+///
+/// Browser fetch requests do not currently support full request duplexing, which
+/// means that that they do begin handling responses until the full request has been sent.
+/// This means that if you use a streaming input encoding, the input stream needs to
+/// end before the output will begin.
+///
+/// Streaming requests are only allowed over HTTP2 or HTTP3.
+pub struct StreamingProtobuf;
+
+impl Encoding for StreamingProtobuf {
+    // the stream is a sequence of messages rather than a single one
+    const CONTENT_TYPE: &'static str = Streaming::CONTENT_TYPE;
+    const METHOD: Method = Streaming::METHOD;
+}
+
+/// The largest message, in bytes, that is accepted in a [`StreamingProtobuf`] stream.
+pub const MAX_STREAMED_MESSAGE_LEN: usize = 4 * 1024 * 1024;
+
+/// A stream of typed data encoded as Protocol Buffers.
+///
+/// A server function can return this type if its output encoding is [`StreamingProtobuf`].
+///
+/// ## Browser Support for Streaming Input
+///
+/// Browser fetch requests do not currently support full request duplexing, which
+/// means that that they do begin handling responses until the full request has been sent.
+/// This means that if you use a streaming input encoding, the input stream needs to
+/// end before the output will begin.
+///
+/// Streaming requests are only allowed over HTTP2 or HTTP3.
+pub struct ProtobufStream<T, CustErr = NoCustomError>(
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError<CustErr>>> + Send>>,
+);
+
+impl<T, CustErr> std::fmt::Debug for ProtobufStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ProtobufStream").finish()
+    }
+}
+
+impl<T> ProtobufStream<T> {
+    /// Creates a new `ProtobufStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<T, ServerFnError>> + Send + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+}
+
+impl<T, CustErr> ProtobufStream<T, CustErr> {
+    /// Consumes the wrapper, returning a stream of messages.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<T, ServerFnError<CustErr>>> + Send {
+        self.0
+    }
+}
+
+impl<S, T: 'static, CustErr: 'static> From<S> for ProtobufStream<T, CustErr>
+where
+    S: Stream<Item = T> + Send + 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value.map(Ok)))
+    }
+}
+
+/// Splits a stream of bytes back into the length-delimited messages it contains.
+fn length_delimited<T>(
+    stream: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+) -> impl Stream<Item = Result<T, ServerFnError>> + Send
+where
+    T: Message + Default + 'static,
+{
+    /// Decodes the next message, if the buffer holds all of it.
+    fn next_message<T: Message + Default>(
+        buf: &mut BytesMut,
+    ) -> Option<Result<T, ServerFnError>> {
+        let mut rest = &buf[..];
+        let len = match prost::decode_length_delimiter(&mut rest) {
+            Ok(len) => len,
+            // a varint is at most 10 bytes, so anything shorter may just be incomplete
+            Err(_) if buf.len() < 10 => return None,
+            Err(e) => {
+                return Some(Err(ServerFnError::Deserialization(e.to_string())))
+            }
+        };
+        if len > MAX_STREAMED_MESSAGE_LEN {
+            return Some(Err(ServerFnError::Deserialization(format!(
+                "message of {len} bytes is larger than the maximum of \
+                 {MAX_STREAMED_MESSAGE_LEN} bytes"
+            ))));
+        }
+        if rest.len() < len {
+            return None;
+        }
+        buf.advance(buf.len() - rest.len());
+        let message = buf.split_to(len).freeze();
+        Some(
+            T::decode(message)
+                .map_err(|e| ServerFnError::Deserialization(e.to_string())),
+        )
+    }
+
+    stream::unfold(
+        (Box::pin(stream), BytesMut::new(), false),
+        |(mut stream, mut buf, mut ended)| async move {
+            loop {
+                match next_message(&mut buf) {
+                    Some(Ok(message)) => {
+                        return Some((Ok(message), (stream, buf, ended)))
+                    }
+                    // the framing of the rest of the stream is unreliable after this
+                    Some(Err(e)) => {
+                        return Some((Err(e), (stream, BytesMut::new(), true)))
+                    }
+                    None if ended && buf.is_empty() => return None,
+                    None if ended => {
+                        let e = ServerFnError::Deserialization(
+                            "stream ended in the middle of a message".into(),
+                        );
+                        return Some((Err(e), (stream, BytesMut::new(), true)));
+                    }
+                    None => match stream.next().await {
+                        Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+                        Some(Err(e)) => {
+                            return Some((Err(e), (stream, buf, ended)))
+                        }
+                        None => ended = true,
+                    },
+                }
+            }
+        },
+    )
+}
+
+impl<CustErr, S, T, Request> IntoReq<StreamingProtobuf, Request, CustErr> for S
+where
+    Request: ClientReq<CustErr>,
+    S: Stream<Item = T> + Send + 'static,
+    T: Message + 'static,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Request::try_new_streaming(
+            path,
+            accepts,
+            Streaming::CONTENT_TYPE,
+            self.map(|message| {
+                Bytes::from(message.encode_length_delimited_to_vec())
+            }),
+        )
+    }
+}
+
+impl<CustErr, T, S, Request> FromReq<StreamingProtobuf, Request, CustErr> for S
+where
+    Request: Req<CustErr> + Send + 'static,
+    // The additional `Stream<Item = T>` bound is never used, but it is required to avoid an error where `T` is unconstrained
+    S: Stream<Item = T> + From<ProtobufStream<T>> + Send + 'static,
+    T: Message + Default + 'static,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let data = req.try_into_stream()?;
+        Ok(ProtobufStream::new(length_delimited(data)).into())
+    }
+}
+
+impl<CustErr, T, Response> IntoRes<StreamingProtobuf, Response, CustErr>
+    for ProtobufStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: 'static,
+    T: Message + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Response::try_from_stream(
+            Streaming::CONTENT_TYPE,
+            self.into_inner().map(|message| {
+                Ok(Bytes::from(message?.encode_length_delimited_to_vec()))
+            }),
+        )
+    }
+}
+
+impl<CustErr, T, Response> FromRes<StreamingProtobuf, Response, CustErr>
+    for ProtobufStream<T>
+where
+    Response: ClientRes<CustErr> + Send,
+    T: Message + Default + 'static,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let stream = res.try_into_stream()?;
+        Ok(ProtobufStream::new(length_delimited(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[derive(Clone, PartialEq, Message)]
+    struct Note {
+        #[prost(string, tag = "1")]
+        text: String,
+    }
+
+    fn note(text: &str) -> Note {
+        Note {
+            text: text.to_string(),
+        }
+    }
+
+    fn encoded(notes: &[Note]) -> Vec<u8> {
+        notes
+            .iter()
+            .flat_map(Message::encode_length_delimited_to_vec)
+            .collect()
+    }
+
+    fn decode_chunks(chunks: Vec<Vec<u8>>) -> Vec<Result<Note, ServerFnError>> {
+        let stream = stream::iter(chunks.into_iter().map(|c| Ok(c.into())));
+        block_on(length_delimited::<Note>(stream).collect())
+    }
+
+    #[test]
+    fn decodes_message_split_across_chunks() {
+        let long = "a".repeat(300); // needs a two-byte length
+        let data = encoded(&[note(&long)]);
+        // split inside the length prefix and again inside the message
+        let chunks =
+            vec![data[..1].to_vec(), data[1..5].to_vec(), data[5..].to_vec()];
+        assert_eq!(decode_chunks(chunks), [Ok(note(&long))]);
+
+        // and one byte at a time
+        let chunks = data.iter().map(|byte| vec![*byte]).collect();
+        assert_eq!(decode_chunks(chunks), [Ok(note(&long))]);
+    }
+
+    #[test]
+    fn decodes_several_messages_in_one_chunk() {
+        let notes = [note("one"), note(""), note("three")];
+        let chunks = vec![encoded(&notes)];
+        assert_eq!(
+            decode_chunks(chunks),
+            notes.iter().cloned().map(Ok).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn decodes_messages_across_chunk_boundaries() {
+        let data = encoded(&[note("one"), note("two")]);
+        let chunks = vec![data[..6].to_vec(), Vec::new(), data[6..].to_vec()];
+        assert_eq!(decode_chunks(chunks), [Ok(note("one")), Ok(note("two"))]);
+    }
+
+    #[test]
+    fn reports_truncated_stream() {
+        let data = encoded(&[note("one"), note("two")]);
+        let chunks = vec![data[..data.len() - 1].to_vec()];
+        let results = decode_chunks(chunks);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0], Ok(note("one")));
+        assert!(matches!(
+            &results[1],
+            Err(ServerFnError::Deserialization(e)) if e.contains("ended")
+        ));
+    }
+
+    #[test]
+    fn empty_stream_has_no_messages() {
+        assert!(decode_chunks(Vec::new()).is_empty());
+        assert!(decode_chunks(vec![Vec::new()]).is_empty());
+    }
+
+    #[test]
+    fn stops_after_invalid_length() {
+        // eleven continuation bytes can never be a valid varint
+        let chunks = vec![vec![0xff; 11], encoded(&[note("one")])];
+        let results = decode_chunks(chunks);
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(ServerFnError::Deserialization(_))));
+    }
+
+    #[test]
+    fn rejects_messages_that_are_too_large() {
+        // only the length is sent, so the message would otherwise be waited for
+        let len = MAX_STREAMED_MESSAGE_LEN + 1;
+        let mut data = Vec::new();
+        prost::encode_length_delimiter(len, &mut data).unwrap();
+        let results = decode_chunks(vec![data, encoded(&[note("one")])]);
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            &results[0],
+            Err(ServerFnError::Deserialization(e)) if e.contains("maximum")
+        ));
+
+        // a tag byte and a four-byte length, so the message is exactly the maximum
+        let long = "a".repeat(MAX_STREAMED_MESSAGE_LEN - 5);
+        assert_eq!(
+            decode_chunks(vec![encoded(&[note(&long)])]),
+            [Ok(note(&long))]
+        );
+    }
+
+    #[test]
+    fn passes_on_transport_errors() {
+        let stream = stream::iter([
+            Ok(Bytes::from(encoded(&[note("one")]))),
+            Err(ServerFnError::Response("connection reset".into())),
+        ]);
+        let results: Vec<_> =
+            block_on(length_delimited::<Note>(stream).collect());
+        assert_eq!(results[0], Ok(note("one")));
+        assert!(matches!(results[1], Err(ServerFnError::Response(_))));
+    }
+}
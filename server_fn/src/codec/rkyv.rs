@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
 use crate::{
     error::ServerFnError,
     request::{ClientReq, Req},
@@ -29,6 +29,34 @@ impl Encoding for Rkyv {
     const METHOD: Method = Method::POST;
 }
 
+impl<T> Encodes<T> for Rkyv
+where
+    T: Archive + for<'a> Serialize<RkyvSerializer<'a>>,
+{
+    type Error = rancor::Error;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        let encoded = rkyv::to_bytes::<rancor::Error>(value)?;
+        Ok(Bytes::copy_from_slice(encoded.as_ref()))
+    }
+}
+
+impl<T> Decodes<T> for Rkyv
+where
+    T: Archive,
+    T::Archived: Deserialize<T, RkyvDeserializer>
+        + for<'a> CheckBytes<RkyvValidator<'a>>,
+{
+    type Error = rancor::Error;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        // the incoming buffer is not guaranteed to be aligned
+        let mut aligned = AlignedVec::<1024>::new();
+        aligned.extend_from_slice(&bytes);
+        rkyv::from_bytes::<T, rancor::Error>(aligned.as_ref())
+    }
+}
+
 impl<CustErr, T, Request> IntoReq<Rkyv, Request, CustErr> for T
 where
     Request: ClientReq<CustErr>,
@@ -0,0 +1,634 @@
+use super::{Encoding, FromRes, IntoRes};
+use crate::{
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use futures::{stream, Stream, StreamExt};
+use http::Method;
+use pin_project_lite::pin_project;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Display},
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// An encoding that represents a stream of [Server-Sent Events](https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events).
+///
+/// A server function that uses this as its output encoding should return [`ServerSentEvents`].
+/// The data of each event is encoded as JSON, and errors are sent as events with the name
+/// `server_fn:error`, so that they can be decoded back into a [`ServerFnError`] on the client.
+///
+/// ```rust,ignore
+/// #[server(output = Sse)]
+/// async fn ticks() -> Result<ServerSentEvents<u64>, ServerFnError> {
+///     let start = last_event_id()
+///         .and_then(|id| id.parse::<u64>().ok())
+///         .map(|n| n + 1)
+///         .unwrap_or_default();
+///     Ok(futures::stream::iter(start..)
+///         .map(|n| ServerSentEvent::new(n).with_id(n.to_string()))
+///         .into())
+/// }
+///
+/// // on the client
+/// let events = ticks().await?.reconnecting(ticks);
+/// ```
+///
+/// ## Resuming a Stream
+///
+/// A stream returned by [`ServerSentEvents::reconnecting`] calls the server function again
+/// if the connection is lost, sending the `id` of the last event it received in the
+/// [`LAST_EVENT_ID`] header, as a browser `EventSource` does. On the server, the value of
+/// that header is returned by [`last_event_id`] while the server function runs, so that it
+/// can continue the stream after that event, as in the example above.
+pub struct Sse;
+
+impl Encoding for Sse {
+    const CONTENT_TYPE: &'static str = "text/event-stream";
+    const METHOD: Method = Method::POST;
+    const SERVER_SENT_EVENTS: bool = true;
+}
+
+/// The name of the header that a browser `EventSource` uses to send the ID of the last event
+/// it received when it reconnects.
+pub const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+/// The name of the event used to send a [`ServerFnError`] to the client.
+///
+/// This is namespaced so that it does not collide with the names of application events.
+const ERROR_EVENT: &str = "server_fn:error";
+
+thread_local! {
+    static CURRENT_LAST_EVENT_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Returns the ID of the last event received by a client that is resuming a stream of
+/// [`ServerSentEvents`].
+///
+/// On the server, this is the [`LAST_EVENT_ID`] header of the request, and is available
+/// while a server function that uses the [`Sse`] encoding is running. On the client, it is
+/// available while the `reconnect` function passed to [`ServerSentEvents::reconnecting`] is
+/// running, and is sent in the header of any request made there.
+pub fn last_event_id() -> Option<String> {
+    CURRENT_LAST_EVENT_ID.with(|id| id.borrow().clone())
+}
+
+pin_project! {
+    /// Makes the ID of the last event available from [`last_event_id`] while a future is
+    /// being polled.
+    pub(crate) struct WithLastEventId<Fut> {
+        last_event_id: Option<String>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> WithLastEventId<Fut> {
+    pub fn new(last_event_id: Option<String>, inner: Fut) -> Self {
+        Self {
+            last_event_id,
+            inner,
+        }
+    }
+}
+
+impl<Fut: Future> Future for WithLastEventId<Fut> {
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        CURRENT_LAST_EVENT_ID.with(|id| {
+            std::mem::swap(this.last_event_id, &mut id.borrow_mut())
+        });
+        let res = this.inner.poll(cx);
+        CURRENT_LAST_EVENT_ID.with(|id| {
+            std::mem::swap(this.last_event_id, &mut id.borrow_mut())
+        });
+        res
+    }
+}
+
+/// A single event in a [`ServerSentEvents`] stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSentEvent<T> {
+    /// The data carried by this event.
+    pub data: T,
+    /// The ID of this event, which can be used to resume the stream.
+    pub id: Option<String>,
+    /// The name of this event. Events without a name are `message` events.
+    pub event: Option<String>,
+    /// The reconnection time the client should use, if it needs to reconnect.
+    pub retry: Option<Duration>,
+}
+
+impl<T> ServerSentEvent<T> {
+    /// Creates a new event with the given data.
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            id: None,
+            event: None,
+            retry: None,
+        }
+    }
+
+    /// Sets the ID of the event.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the name of the event.
+    pub fn with_event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Sets the reconnection time for the client.
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+}
+
+impl<T> From<T> for ServerSentEvent<T> {
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+/// A stream of typed [`ServerSentEvent`]s.
+///
+/// A server function can return this type if its output encoding is [`Sse`].
+#[allow(clippy::type_complexity)]
+pub struct ServerSentEvents<T, CustErr = NoCustomError> {
+    stream: Pin<
+        Box<
+            dyn Stream<
+                    Item = Result<ServerSentEvent<T>, ServerFnError<CustErr>>,
+                > + Send,
+        >,
+    >,
+    // set when the connection the events are read from has been lost
+    disconnected: Arc<AtomicBool>,
+}
+
+impl<T, CustErr> Debug for ServerSentEvents<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerSentEvents").finish_non_exhaustive()
+    }
+}
+
+impl<T, CustErr> ServerSentEvents<T, CustErr> {
+    /// Creates a new `ServerSentEvents` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<ServerSentEvent<T>, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    ) -> Self {
+        Self {
+            stream: Box::pin(value),
+            disconnected: Default::default(),
+        }
+    }
+
+    /// Consumes the wrapper, returning a stream of events.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<ServerSentEvent<T>, ServerFnError<CustErr>>> + Send
+    {
+        self.stream
+    }
+}
+
+impl<T, CustErr> ServerSentEvents<T, CustErr>
+where
+    T: Send + 'static,
+    CustErr: Send + 'static,
+{
+    /// Resumes the stream with `reconnect` whenever its connection is lost.
+    ///
+    /// `reconnect` should call the server function that returned this stream again. While it
+    /// runs, [`last_event_id`] returns the ID of the last event that was received, which is
+    /// sent to the server in the [`LAST_EVENT_ID`] header. If `reconnect` fails, its error
+    /// is the last item of the stream.
+    ///
+    /// Errors sent by the server function itself, and the end of the stream, do not cause a
+    /// reconnection.
+    pub fn reconnecting<F, Fut>(self, reconnect: F) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Self, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    {
+        let state = Some((self, reconnect, None::<String>));
+        let stream = stream::unfold(state, |state| async move {
+            let (mut events, mut reconnect, mut last_id) = state?;
+            loop {
+                match events.stream.next().await {
+                    None => return None,
+                    Some(Err(_))
+                        if events.disconnected.load(Ordering::Relaxed) =>
+                    {
+                        // `reconnect` is called inside the scope, so that requests it
+                        // creates immediately can see the ID too
+                        let next =
+                            WithLastEventId::new(last_id.clone(), async {
+                                reconnect().await
+                            })
+                            .await;
+                        match next {
+                            Ok(next) => events = next,
+                            Err(e) => return Some((Err(e), None)),
+                        }
+                    }
+                    Some(item) => {
+                        if let Ok(ServerSentEvent { id: Some(id), .. }) = &item
+                        {
+                            last_id = Some(id.clone());
+                        }
+                        return Some((
+                            item,
+                            Some((events, reconnect, last_id)),
+                        ));
+                    }
+                }
+            }
+        });
+        Self::new(stream)
+    }
+}
+
+impl<S, E, T, CustErr> From<S> for ServerSentEvents<T, CustErr>
+where
+    S: Stream<Item = E> + Send + 'static,
+    E: Into<ServerSentEvent<T>>,
+    T: 'static,
+    CustErr: 'static,
+{
+    fn from(value: S) -> Self {
+        Self::new(value.map(|event| Ok(event.into())))
+    }
+}
+
+impl<CustErr, T, Response> IntoRes<Sse, Response, CustErr>
+    for ServerSentEvents<T, CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: FromStr + Display + 'static,
+    T: Serialize + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Response::try_from_stream(
+            Sse::CONTENT_TYPE,
+            self.into_inner().map(|event| {
+                let frame = match event.and_then(|event| {
+                    let data =
+                        serde_json::to_string(&event.data).map_err(|e| {
+                            ServerFnError::Serialization(e.to_string())
+                        })?;
+                    Ok(write_event(
+                        &data,
+                        event.id.as_deref(),
+                        event.event.as_deref(),
+                        event.retry,
+                    ))
+                }) {
+                    Ok(frame) => frame,
+                    Err(e) => write_event(
+                        &e.ser().unwrap_or_else(|_| e.to_string()),
+                        None,
+                        Some(ERROR_EVENT),
+                        None,
+                    ),
+                };
+                Ok(Bytes::from(frame))
+            }),
+        )
+    }
+}
+
+impl<CustErr, T, Response> FromRes<Sse, Response, CustErr>
+    for ServerSentEvents<T, CustErr>
+where
+    Response: ClientRes<CustErr> + Send,
+    CustErr: FromStr + Display + Send + 'static,
+    T: DeserializeOwned + Send + 'static,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let stream = res.try_into_stream()?;
+        let mut parser = EventParser::default();
+        let disconnected = Arc::new(AtomicBool::new(false));
+        let events = stream.flat_map({
+            let disconnected = Arc::clone(&disconnected);
+            move |chunk| {
+                let events = match chunk {
+                    Ok(bytes) => parser
+                        .feed(&bytes)
+                        .into_iter()
+                        .map(RawEvent::decode)
+                        .collect(),
+                    Err(e) => {
+                        disconnected.store(true, Ordering::Relaxed);
+                        vec![Err(e.map_custom(|e| {
+                            ServerFnError::ServerError(e.to_string())
+                        }))]
+                    }
+                };
+                stream::iter(events)
+            }
+        });
+        Ok(ServerSentEvents {
+            stream: Box::pin(events),
+            disconnected,
+        })
+    }
+}
+
+/// Serializes a single event in the `text/event-stream` format.
+fn write_event(
+    data: &str,
+    id: Option<&str>,
+    event: Option<&str>,
+    retry: Option<Duration>,
+) -> String {
+    // field values cannot contain line breaks, which would end the field early
+    let strip = |value: &str| value.replace(['\r', '\n'], "");
+    let mut frame = String::new();
+    if let Some(event) = event {
+        frame.push_str("event: ");
+        frame.push_str(&strip(event));
+        frame.push('\n');
+    }
+    if let Some(id) = id {
+        frame.push_str("id: ");
+        frame.push_str(&strip(id));
+        frame.push('\n');
+    }
+    if let Some(retry) = retry {
+        frame.push_str(&format!("retry: {}\n", retry.as_millis()));
+    }
+    // multi-line data is sent as one `data` field per line
+    for line in data.split('\n') {
+        frame.push_str("data: ");
+        frame.push_str(line.strip_suffix('\r').unwrap_or(line));
+        frame.push('\n');
+    }
+    frame.push('\n');
+    frame
+}
+
+/// An event that has been parsed, but whose data has not yet been deserialized.
+struct RawEvent {
+    data: String,
+    id: Option<String>,
+    event: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl RawEvent {
+    fn decode<T, CustErr>(
+        self,
+    ) -> Result<ServerSentEvent<T>, ServerFnError<CustErr>>
+    where
+        T: DeserializeOwned,
+        CustErr: FromStr + Display,
+    {
+        if self.event.as_deref() == Some(ERROR_EVENT) {
+            return Err(ServerFnError::de(&self.data));
+        }
+        let data = serde_json::from_str(&self.data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+        Ok(ServerSentEvent {
+            data,
+            id: self.id,
+            event: self.event,
+            retry: self.retry,
+        })
+    }
+}
+
+/// Incrementally parses a `text/event-stream` body, which may be split across chunks at
+/// any point.
+#[derive(Default)]
+struct EventParser {
+    buf: Vec<u8>,
+    data: Option<String>,
+    id: Option<String>,
+    event: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl EventParser {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<RawEvent> {
+        self.buf.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buf.iter().position(|b| *b == b'\n') {
+            let line = self.buf.drain(..=end).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if let Some(event) = self.process_line(line) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<RawEvent> {
+        if line.is_empty() {
+            let mut data = self.data.take()?;
+            data.pop();
+            let event = RawEvent {
+                data,
+                id: self.id.take(),
+                event: self.event.take(),
+                retry: self.retry.take(),
+            };
+            return Some(event);
+        }
+        // lines beginning with a colon are comments
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => {
+                (field, value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (line, ""),
+        };
+        match field {
+            "data" => {
+                let data = self.data.get_or_insert_with(String::new);
+                data.push_str(value);
+                data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            "event" => self.event = Some(value.to_string()),
+            "retry" => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn parse(chunks: &[&str]) -> Vec<RawEvent> {
+        let mut parser = EventParser::default();
+        chunks
+            .iter()
+            .flat_map(|chunk| parser.feed(chunk.as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn joins_multi_line_data() {
+        let events = parse(&["data: first\ndata: second\r\ndata:third\n\n"]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "first\nsecond\nthird");
+    }
+
+    #[test]
+    fn skips_comments() {
+        let events = parse(&[": keep-alive\n\n", "data: 1\n: ping\nid: a\n\n"]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "1");
+        assert_eq!(events[0].id.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn parses_events_split_across_chunks() {
+        let frame = write_event("{\"a\":1}", Some("7"), Some("tick"), None);
+        let (head, tail) = frame.split_at(9);
+        let events = parse(&[head, tail, "da", "ta: 2\n", "\n"]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data, "{\"a\":1}");
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+        assert_eq!(events[0].event.as_deref(), Some("tick"));
+        assert_eq!(events[1].data, "2");
+    }
+
+    #[test]
+    fn error_event_is_namespaced() {
+        let frame = write_event("\"oops\"", None, Some("error"), None);
+        let event = parse(&[&frame])
+            .remove(0)
+            .decode::<String, NoCustomError>()
+            .unwrap();
+        assert_eq!(event.event.as_deref(), Some("error"));
+
+        let err = ServerFnError::<NoCustomError>::ServerError("oops".into());
+        let frame =
+            write_event(&err.ser().unwrap(), None, Some(ERROR_EVENT), None);
+        let decoded =
+            parse(&[&frame]).remove(0).decode::<String, NoCustomError>();
+        assert_eq!(decoded, Err(err));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Forbidden(String);
+
+    impl FromStr for Forbidden {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(Forbidden(s.to_string()))
+        }
+    }
+
+    impl Display for Forbidden {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    #[test]
+    fn decodes_custom_errors() {
+        let err = ServerFnError::WrappedServerError(Forbidden("no".into()));
+        let frame =
+            write_event(&err.ser().unwrap(), None, Some(ERROR_EVENT), None);
+        let decoded = parse(&[&frame]).remove(0).decode::<u32, Forbidden>();
+        assert_eq!(decoded, Err(err));
+    }
+
+    fn interrupted(events: Vec<ServerSentEvent<u32>>) -> ServerSentEvents<u32> {
+        let items = events
+            .into_iter()
+            .map(Ok)
+            .chain([Err(ServerFnError::Request("connection reset".into()))]);
+        ServerSentEvents {
+            stream: Box::pin(stream::iter(items)),
+            disconnected: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    #[test]
+    fn reconnects_with_last_event_id() {
+        let first = interrupted(vec![
+            ServerSentEvent::new(1).with_id("1"),
+            ServerSentEvent::new(2),
+        ]);
+        let mut reconnects = 0;
+        let events = first.reconnecting(move || {
+            reconnects += 1;
+            let last_id = last_event_id();
+            async move {
+                match reconnects {
+                    1 => {
+                        assert_eq!(last_id.as_deref(), Some("1"));
+                        Ok(interrupted(vec![
+                            ServerSentEvent::new(3).with_id("3")
+                        ]))
+                    }
+                    _ => {
+                        assert_eq!(last_id.as_deref(), Some("3"));
+                        Err(ServerFnError::Request("offline".into()))
+                    }
+                }
+            }
+        });
+        let items = block_on(events.into_inner().collect::<Vec<_>>());
+        let data = items
+            .iter()
+            .map(|item| item.as_ref().map(|event| event.data))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            data,
+            [
+                Ok(1),
+                Ok(2),
+                Ok(3),
+                Err(&ServerFnError::Request("offline".into()))
+            ]
+        );
+        assert_eq!(last_event_id(), None);
+    }
+
+    #[test]
+    fn does_not_reconnect_after_server_errors() {
+        let events = ServerSentEvents::<u32>::new(stream::iter([Err(
+            ServerFnError::ServerError("failed".into()),
+        )]))
+        .reconnecting(|| async { panic!("should not reconnect") });
+        let items = block_on(events.into_inner().collect::<Vec<_>>());
+        assert_eq!(items, [Err(ServerFnError::ServerError("failed".into()))]);
+    }
+}
@@ -0,0 +1,471 @@
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    client::Client,
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+    request::WebsocketReq,
+    response::Res,
+};
+use bytes::Bytes;
+use futures::{
+    future::{abortable, ready},
+    stream, Future, FutureExt, Sink, SinkExt, Stream, StreamExt,
+};
+use http::Method;
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    pin::Pin,
+    str::FromStr,
+};
+
+/// A single frame sent over a server function websocket.
+///
+/// Values are sent as binary frames. An `Err(_)` is sent as a text frame containing the
+/// serialized [`ServerFnError`], so that errors can be passed in either direction without
+/// closing the connection. An empty text frame marks the end of a stream.
+pub type WebsocketFrame = Result<Bytes, String>;
+
+fn is_end_of_stream(frame: &WebsocketFrame) -> bool {
+    matches!(frame, Err(text) if text.is_empty())
+}
+
+/// The incoming half of a websocket connection.
+pub type WebsocketStream = Pin<Box<dyn Stream<Item = WebsocketFrame> + Send>>;
+
+/// The outgoing half of a websocket connection.
+pub type WebsocketSink =
+    Pin<Box<dyn Sink<WebsocketFrame, Error = ServerFnError> + Send>>;
+
+/// Converts a transport-level error into an error frame.
+#[cfg(any(
+    feature = "actix",
+    feature = "axum",
+    feature = "browser",
+    feature = "reqwest"
+))]
+pub(crate) fn transport_error(e: impl Display) -> WebsocketFrame {
+    let err = ServerFnError::<NoCustomError>::Request(e.to_string());
+    Err(err.ser().unwrap_or_else(|_| err.to_string()))
+}
+
+/// A bidirectional protocol that opens a websocket connection to the server function.
+///
+/// A server function that uses this as its input encoding takes a single [`BoxedStream`]
+/// argument, and returns a [`BoxedStream`]. Each message in the client stream is encoded
+/// with `InputEncoding`, and each message in the server stream with `OutputEncoding`, so any
+/// codec that implements [`Encodes`] and [`Decodes`] (like `Json`, `Cbor`, or `Postcard`)
+/// can be used to frame the messages.
+///
+/// ```rust,ignore
+/// #[server(input = Websocket<Json, Json>)]
+/// async fn echo(
+///     input: BoxedStream<String>,
+/// ) -> Result<BoxedStream<String>, ServerFnError> {
+///     Ok(input
+///         .into_inner()
+///         .map(|msg| msg.map(|msg| msg.to_ascii_uppercase()))
+///         .into())
+/// }
+/// ```
+///
+/// Unlike the [`Streaming`](super::Streaming) encodings, both streams are open at the same
+/// time: the server can send messages before the client has finished sending its own.
+pub struct Websocket<InputEncoding, OutputEncoding>(
+    PhantomData<fn() -> (InputEncoding, OutputEncoding)>,
+);
+
+impl<InputEncoding, OutputEncoding> Encoding
+    for Websocket<InputEncoding, OutputEncoding>
+{
+    const CONTENT_TYPE: &'static str = "application/octet-stream";
+    const METHOD: Method = Method::GET;
+}
+
+/// A stream of typed values, which is used as the input and output of a [`Websocket`]
+/// server function.
+pub struct BoxedStream<T, CustErr = NoCustomError>(
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError<CustErr>>> + Send>>,
+);
+
+impl<T, CustErr> Debug for BoxedStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BoxedStream").finish()
+    }
+}
+
+impl<T, CustErr> BoxedStream<T, CustErr> {
+    /// Creates a new `BoxedStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<T, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+
+    /// Consumes the wrapper, returning a stream of values.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<T, ServerFnError<CustErr>>> + Send {
+        self.0
+    }
+}
+
+impl<S, T, CustErr> From<S> for BoxedStream<T, CustErr>
+where
+    S: Stream<Item = Result<T, ServerFnError<CustErr>>> + Send + 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value))
+    }
+}
+
+impl<T, CustErr> BoxedStream<T, CustErr>
+where
+    T: Send + 'static,
+    CustErr: FromStr + Display + Send + 'static,
+{
+    fn from_frames<Enc>(frames: WebsocketStream) -> Self
+    where
+        Enc: Decodes<T>,
+    {
+        Self::new(
+            frames
+                .take_while(|frame| ready(!is_end_of_stream(frame)))
+                .map(|frame| match frame {
+                    Ok(bytes) => Enc::decode(bytes).map_err(|e| {
+                        ServerFnError::Deserialization(e.to_string())
+                    }),
+                    Err(text) => Err(ServerFnError::de(&text)),
+                }),
+        )
+    }
+
+    fn into_frames<Enc>(self) -> impl Stream<Item = WebsocketFrame> + Send
+    where
+        Enc: Encodes<T>,
+    {
+        self.0
+            .map(|value| {
+                value
+                    .and_then(|value| {
+                        Enc::encode(&value).map_err(|e| {
+                            ServerFnError::Serialization(e.to_string())
+                        })
+                    })
+                    .map_err(|e| e.ser().unwrap_or_else(|_| e.to_string()))
+            })
+            .chain(stream::once(ready(Err(String::new()))))
+    }
+}
+
+impl<InputEncoding, OutputEncoding> Websocket<InputEncoding, OutputEncoding> {
+    /// Upgrades the request to a websocket, and runs the server function once the
+    /// connection has been established.
+    ///
+    /// This is called by the `#[server]` macro in place of the usual request/response
+    /// handling, and should not usually be called directly.
+    #[doc(hidden)]
+    #[allow(clippy::manual_async_fn)]
+    pub fn run_server<Request, Response, I, O, CustErr, F, Fut>(
+        path: &'static str,
+        req: Request,
+        run_body: F,
+    ) -> impl Future<Output = Response> + Send
+    where
+        Request: WebsocketReq<CustErr, Response = Response> + Send + 'static,
+        Response: Res<CustErr>,
+        InputEncoding: Decodes<I> + 'static,
+        OutputEncoding: Encodes<O> + 'static,
+        I: Send + 'static,
+        O: Send + 'static,
+        CustErr: FromStr + Display + Send + 'static,
+        F: FnOnce(BoxedStream<I, CustErr>) -> Fut + Send + 'static,
+        Fut: Future<
+                Output = Result<
+                    BoxedStream<O, CustErr>,
+                    ServerFnError<CustErr>,
+                >,
+            > + Send
+            + 'static,
+    {
+        async move {
+            req.try_into_websocket(move |incoming, mut outgoing| async move {
+                let input = BoxedStream::from_frames::<InputEncoding>(incoming);
+                let mut frames = match run_body(input).await {
+                    Ok(output) => {
+                        output.into_frames::<OutputEncoding>().boxed()
+                    }
+                    Err(e) => stream::iter([
+                        Err(e.ser().unwrap_or_else(|_| e.to_string())),
+                        Err(String::new()),
+                    ])
+                    .boxed(),
+                }
+                .map(Ok);
+                // the client has most likely disconnected if sending fails, in which case
+                // there is nobody left to report the error to
+                _ = outgoing.send_all(&mut frames).await;
+                _ = outgoing.close().await;
+            })
+            .await
+            .unwrap_or_else(|e| {
+                Response::error_response_with_status(
+                    path,
+                    e.default_status_code(),
+                    &e,
+                )
+            })
+        }
+    }
+
+    /// Opens a websocket connection to the server function, sending each message of
+    /// `input` and returning the stream of messages sent back by the server.
+    ///
+    /// This is called by the `#[server]` macro in place of the usual request/response
+    /// handling, and should not usually be called directly.
+    #[doc(hidden)]
+    #[allow(clippy::manual_async_fn)]
+    pub fn run_client<C, I, O, CustErr>(
+        path: &'static str,
+        input: BoxedStream<I, CustErr>,
+    ) -> impl Future<
+        Output = Result<BoxedStream<O, CustErr>, ServerFnError<CustErr>>,
+    > + Send
+    where
+        C: Client<CustErr>,
+        InputEncoding: Encodes<I> + 'static,
+        OutputEncoding: Decodes<O> + 'static,
+        I: Send + 'static,
+        O: Send + 'static,
+        CustErr: FromStr + Display + Send + 'static,
+    {
+        async move {
+            let (incoming, mut outgoing) = C::open_websocket(path).await?;
+            let mut frames = input.into_frames::<InputEncoding>().map(Ok);
+
+            // the input is sent while the output is being read, and stops as soon as
+            // the server closes the connection
+            let (send, abort_send) = abortable(async move {
+                _ = outgoing.send_all(&mut frames).await;
+            });
+            let output =
+                BoxedStream::<O, CustErr>::from_frames::<OutputEncoding>(
+                    incoming,
+                )
+                .0
+                .map(Some)
+                .chain(stream::once(async move {
+                    abort_send.abort();
+                    None
+                }));
+            let send = send.into_stream().map(|_| None);
+            Ok(BoxedStream::new(
+                stream::select(output, send).filter_map(ready),
+            ))
+        }
+    }
+}
+
+// The websocket protocol does not fit into the usual request/response model, so the
+// `#[server]` macro dispatches these server functions through [`Websocket::run_server`]
+// and [`Websocket::run_client`]. These implementations only exist to satisfy the bounds
+// on [`ServerFn`](crate::ServerFn), and are never called by the macro.
+
+impl<InputEncoding, OutputEncoding, T, Request, CustErr>
+    IntoReq<Websocket<InputEncoding, OutputEncoding>, Request, CustErr> for T
+{
+    fn into_req(
+        self,
+        _path: &str,
+        _accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(
+            "Websocket server functions cannot be sent as a single request."
+                .into(),
+        ))
+    }
+}
+
+impl<InputEncoding, OutputEncoding, T, Request, CustErr>
+    FromReq<Websocket<InputEncoding, OutputEncoding>, Request, CustErr> for T
+where
+    Request: Send + 'static,
+{
+    async fn from_req(_req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        Err(ServerFnError::Args(
+            "Websocket server functions cannot be read from a single request."
+                .into(),
+        ))
+    }
+}
+
+impl<InputEncoding, OutputEncoding, T, Response, CustErr>
+    IntoRes<Websocket<InputEncoding, OutputEncoding>, Response, CustErr>
+    for BoxedStream<T, CustErr>
+where
+    T: Send,
+    CustErr: Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Err(ServerFnError::Response(
+            "Websocket server functions cannot be sent as a single response."
+                .into(),
+        ))
+    }
+}
+
+impl<InputEncoding, OutputEncoding, T, Response, CustErr>
+    FromRes<Websocket<InputEncoding, OutputEncoding>, Response, CustErr>
+    for BoxedStream<T, CustErr>
+where
+    Response: Send + 'static,
+{
+    async fn from_res(_res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        Err(ServerFnError::Deserialization(
+            "Websocket server functions cannot be read from a single response."
+                .into(),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::codec::Json;
+    use futures::executor::block_on;
+
+    fn frames(
+        values: Vec<Result<String, ServerFnError>>,
+    ) -> Vec<WebsocketFrame> {
+        let stream = BoxedStream::<String>::new(stream::iter(values));
+        block_on(stream.into_frames::<Json>().collect())
+    }
+
+    fn values(
+        frames: Vec<WebsocketFrame>,
+    ) -> Vec<Result<String, ServerFnError>> {
+        let stream = BoxedStream::<String>::from_frames::<Json>(Box::pin(
+            stream::iter(frames),
+        ));
+        block_on(stream.into_inner().collect())
+    }
+
+    #[test]
+    fn round_trips_values_and_errors() {
+        let sent = vec![
+            Ok("a".to_string()),
+            Err(ServerFnError::ServerError("failed".into())),
+            Ok("b".to_string()),
+        ];
+        let frames = frames(sent.clone());
+        assert_eq!(frames.len(), 4);
+        assert!(frames[0].is_ok());
+        assert!(matches!(&frames[1], Err(text) if !text.is_empty()));
+        assert!(is_end_of_stream(&frames[3]));
+        assert_eq!(values(frames), sent);
+    }
+
+    #[test]
+    fn stops_at_end_of_stream() {
+        let mut frames = frames(vec![Ok("a".to_string())]);
+        // anything after the end of the stream is ignored
+        frames.push(Ok(Json::encode(&"b".to_string()).unwrap()));
+        assert_eq!(values(frames), [Ok("a".to_string())]);
+
+        // an empty stream is only the end-of-stream frame
+        let frames = self::frames(vec![]);
+        assert_eq!(frames, [Err(String::new())]);
+        assert!(values(frames).is_empty());
+    }
+
+    #[test]
+    fn reports_undecodable_frames() {
+        let frames =
+            vec![Ok(Bytes::from_static(b"not json")), Err(String::new())];
+        assert!(matches!(
+            values(frames).as_slice(),
+            [Err(ServerFnError::Deserialization(_))]
+        ));
+    }
+
+    #[cfg(feature = "axum")]
+    mod axum {
+        use super::*;
+        use ::axum::{body::Body, routing::get, Router};
+        use http::Request;
+        use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+        async fn serve() -> String {
+            let app = Router::new().route(
+                "/ws",
+                get(|req: Request<Body>| {
+                    Websocket::<Json, Json>::run_server::<
+                        _,
+                        _,
+                        String,
+                        String,
+                        NoCustomError,
+                        _,
+                        _,
+                    >("/ws", req, |input| async move {
+                        let mut input = input.into_inner();
+                        // the first message decides whether the call fails
+                        match input.next().await {
+                            Some(Ok(msg)) if msg == "fail" => Err(
+                                ServerFnError::ServerError("refused".into()),
+                            ),
+                            first => Ok(stream::iter(first)
+                                .chain(input)
+                                .map(|msg| msg.map(|msg| msg.to_uppercase()))
+                                .into()),
+                        }
+                    })
+                }),
+            );
+            let listener =
+                tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move { ::axum::serve(listener, app).await });
+            format!("ws://{addr}/ws")
+        }
+
+        async fn call(url: &str, sent: &[&str]) -> Vec<WebsocketFrame> {
+            let (mut socket, _) = connect_async(url).await.unwrap();
+            for msg in sent {
+                let bytes = Json::encode(&msg.to_string()).unwrap();
+                socket.send(Message::Binary(bytes.into())).await.unwrap();
+            }
+            socket.send(Message::Text(String::new())).await.unwrap();
+            let mut received = Vec::new();
+            while let Some(Ok(msg)) = socket.next().await {
+                match msg {
+                    Message::Binary(bytes) => received.push(Ok(bytes.into())),
+                    Message::Text(text) => received.push(Err(text)),
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+            received
+        }
+
+        #[tokio::test]
+        async fn runs_server_fn_over_websocket() {
+            let url = serve().await;
+
+            let frames = call(&url, &["hello", "world"]).await;
+            assert_eq!(
+                values(frames),
+                [Ok("HELLO".to_string()), Ok("WORLD".to_string())]
+            );
+
+            let frames = call(&url, &["fail"]).await;
+            assert_eq!(frames.len(), 2);
+            assert!(is_end_of_stream(&frames[1]));
+            assert_eq!(
+                values(frames),
+                [Err(ServerFnError::ServerError("refused".into()))]
+            );
+        }
+    }
+}
@@ -0,0 +1,973 @@
+//! Once [`set_csrf_protection`] has been called, the server integrations check every server
+//! function request that uses a method other than `GET`, `HEAD`, or `OPTIONS`, before the
+//! function body runs (websocket handshakes, which are `GET` requests, only go through the
+//! first check, see below):
+//!
+//! 1. If the browser says where the request comes from, with the `Sec-Fetch-Site` or `Origin`
+//!    header, it must come from the same origin as the server or one of the
+//!    [allowed origins](CsrfProtection::allowed_origin).
+//! 2. If the request carries any cookies, it must also carry the token from the
+//!    [`CSRF_COOKIE`], either in the [`CSRF_HEADER`] or in a [`CSRF_FIELD`] form field, and the
+//!    token must have been signed with the secret key.
+//!
+//! The token cookie is issued when a page is rendered on the server and on server function
+//! responses. `<ActionForm/>` and the router’s `<Form method="post"/>` add the token to the form
+//! as a hidden input, so plain form posts keep working before or without WASM, and the browser
+//! client adds it as a header to every server function call.
+//!
+//! Browsers let any page open a websocket and send cookies with the handshake, but a page cannot
+//! add a header to it. So the handshake of a [`Websocket`](crate::codec::Websocket) server
+//! function must pass the first check, and the server also makes that check itself when it
+//! upgrades the connection, in case the request did not go through an integration.
+//!
+//! Requests without any cookies carry no credentials a forged request could make use of, so they
+//! only need to pass the first check. This means that clients outside the browser, like the
+//! `reqwest` client, can call server functions without a token unless they send cookies.
+//!
+//! This is the signed double-submit cookie pattern: a request is accepted if the token it sends
+//! matches its cookie and carries a valid signature. Another site cannot read the cookie, so it
+//! cannot send the token along with a forged request, and the signature stops it from making up a
+//! token of its own.
+//!
+//! Name the cookie that identifies the user's session with
+//! [`session_cookie`](CsrfProtection::session_cookie) to bind each token to that session: the
+//! value of the session cookie is signed along with the token, so a token is only accepted with
+//! the session it was issued for, and a new one is issued when the session changes. Without it,
+//! a page that can set cookies for your domain, like one on a subdomain, can plant a token that
+//! was issued to someone else.
+//!
+//! A token in the [`CSRF_FIELD`] is read from `application/x-www-form-urlencoded` and
+//! `multipart/form-data` bodies, up to the [body limit](CsrfProtection::body_limit).
+//!
+//! ```rust,ignore
+//! use server_fn::csrf::{set_csrf_protection, CsrfProtection};
+//!
+//! let secret = std::env::var("CSRF_SECRET").expect("CSRF_SECRET is not set");
+//! set_csrf_protection(
+//!     CsrfProtection::new(secret).allowed_origin("https://admin.example.com"),
+//! );
+//! ```
+
+/// The cookie that stores the CSRF token for the browser session.
+pub const CSRF_COOKIE: &str = "leptos_csrf";
+
+/// The request header in which the client sends the CSRF token.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// The form field in which a plain form post sends the CSRF token.
+pub const CSRF_FIELD: &str = "_csrf";
+
+/// The CSRF token for the current request, which is provided as context while a page is rendered
+/// on the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(pub String);
+
+/// Finds the value of the cookie called `name` in a `Cookie` header.
+pub fn find_cookie<'a>(cookies: &'a str, name: &str) -> Option<&'a str> {
+    cookies.split(';').find_map(|cookie| {
+        let (key, value) = cookie.split_once('=')?;
+        (key.trim() == name).then(|| value.trim())
+    })
+}
+
+/// Reads the CSRF token from the [`CSRF_COOKIE`] of the current document, if one has been issued.
+#[cfg(feature = "browser")]
+pub fn token_from_cookie() -> Option<String> {
+    use wasm_bindgen::JsCast;
+
+    let cookies = web_sys::window()?
+        .document()?
+        .dyn_into::<web_sys::HtmlDocument>()
+        .ok()?
+        .cookie()
+        .ok()?;
+    find_cookie(&cookies, CSRF_COOKIE).map(str::to_string)
+}
+
+#[cfg(feature = "csrf")]
+pub use server::*;
+
+#[cfg(feature = "csrf")]
+mod server {
+    use super::{find_cookie, CsrfToken, CSRF_COOKIE, CSRF_FIELD, CSRF_HEADER};
+    use crate::{
+        error::{NoCustomError, ServerFnError},
+        response::Res,
+    };
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use hmac::{Hmac, Mac};
+    use rand::{thread_rng, RngCore};
+    use sha2::Sha256;
+    use std::{fmt, sync::OnceLock};
+    use thiserror::Error;
+
+    static PROTECTION: OnceLock<CsrfProtection> = OnceLock::new();
+
+    /// Turns on CSRF protection for every server function. Returns `Err(_)` if it has already
+    /// been turned on.
+    pub fn set_csrf_protection(
+        protection: CsrfProtection,
+    ) -> Result<(), CsrfProtection> {
+        PROTECTION.set(protection)
+    }
+
+    /// Returns the CSRF protection set by [`set_csrf_protection`], if any.
+    pub fn csrf_protection() -> Option<&'static CsrfProtection> {
+        PROTECTION.get()
+    }
+
+    /// The reason a request failed the CSRF checks.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+    #[non_exhaustive]
+    pub enum CsrfError {
+        /// The browser sent the request from a different origin.
+        #[error("The request was sent from a different origin.")]
+        CrossOrigin,
+        /// The request carries cookies but no CSRF token.
+        #[error("The request is missing its CSRF token.")]
+        MissingToken,
+        /// The CSRF token is not signed with the secret key, or does not match the cookie.
+        #[error("The request has an invalid CSRF token.")]
+        InvalidToken,
+    }
+
+    /// Settings for CSRF protection. See the [module documentation](crate::csrf) for the checks
+    /// that are made.
+    #[derive(Clone)]
+    pub struct CsrfProtection {
+        key: Vec<u8>,
+        allowed_origins: Vec<String>,
+        secure_cookie: bool,
+        body_limit: usize,
+        session_cookie: Option<String>,
+    }
+
+    impl fmt::Debug for CsrfProtection {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("CsrfProtection")
+                .field("allowed_origins", &self.allowed_origins)
+                .field("secure_cookie", &self.secure_cookie)
+                .field("body_limit", &self.body_limit)
+                .field("session_cookie", &self.session_cookie)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl CsrfProtection {
+        /// Creates the settings, with the secret key that tokens are signed with.
+        ///
+        /// The key should be long and random, and the same on every server that handles
+        /// requests for the app, so that tokens issued by one are accepted by the others.
+        pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+            Self {
+                key: secret.into(),
+                allowed_origins: Vec::new(),
+                secure_cookie: true,
+                body_limit: 2 * 1024 * 1024,
+                session_cookie: None,
+            }
+        }
+
+        /// Allows requests from another origin, like `https://admin.example.com`. Requests from
+        /// the origin of the server itself are always allowed.
+        pub fn allowed_origin(mut self, origin: impl Into<String>) -> Self {
+            self.allowed_origins.push(origin.into());
+            self
+        }
+
+        /// Whether the token cookie is only sent over HTTPS. Defaults to `true`.
+        ///
+        /// Browsers make an exception for `localhost`, but this needs to be turned off to test
+        /// the app over plain HTTP on any other host.
+        pub fn secure_cookie(mut self, secure: bool) -> Self {
+            self.secure_cookie = secure;
+            self
+        }
+
+        /// The largest form body, in bytes, that is read to find the [`CSRF_FIELD`]. Defaults to
+        /// 2 MiB.
+        ///
+        /// A form post without the [`CSRF_HEADER`] is rejected if its body is larger than this.
+        pub fn body_limit(mut self, limit: usize) -> Self {
+            self.body_limit = limit;
+            self
+        }
+
+        /// The name of the cookie that identifies the user's session, like `id`, to bind each
+        /// token to. Defaults to none, which leaves tokens unbound.
+        ///
+        /// A request without the session cookie, like one from a user who has not logged in, is
+        /// given a token that is not bound to any session.
+        pub fn session_cookie(mut self, name: impl Into<String>) -> Self {
+            self.session_cookie = Some(name.into());
+            self
+        }
+
+        /// Returns the value of the session cookie in a `Cookie` header.
+        fn session<'a>(&self, cookies: Option<&'a str>) -> Option<&'a str> {
+            let name = self.session_cookie.as_deref()?;
+            find_cookie(cookies?, name)
+        }
+
+        fn mac(&self, nonce: &[u8], session: Option<&str>) -> Hmac<Sha256> {
+            let mut mac = Hmac::<Sha256>::new_from_slice(&self.key)
+                .expect("HMAC accepts keys of any length");
+            mac.update(nonce);
+            // the nonce always has the same length, so it cannot run into the session
+            if let Some(session) = session {
+                mac.update(session.as_bytes());
+            }
+            mac
+        }
+
+        /// Generates a new token for the session with the given id, or for no session, signed
+        /// with the secret key.
+        pub fn generate_token(&self, session: Option<&str>) -> String {
+            let mut nonce = [0; 32];
+            thread_rng().fill_bytes(&mut nonce);
+            let signature = self.mac(&nonce, session).finalize().into_bytes();
+            format!(
+                "{}.{}",
+                URL_SAFE_NO_PAD.encode(nonce),
+                URL_SAFE_NO_PAD.encode(signature)
+            )
+        }
+
+        /// Checks that a token was signed with the secret key for the session with the given id,
+        /// or for no session.
+        pub fn verify_token(&self, token: &str, session: Option<&str>) -> bool {
+            let Some((nonce, signature)) = token.split_once('.') else {
+                return false;
+            };
+            match (
+                URL_SAFE_NO_PAD.decode(nonce),
+                URL_SAFE_NO_PAD.decode(signature),
+            ) {
+                (Ok(nonce), Ok(signature)) => {
+                    self.mac(&nonce, session).verify_slice(&signature).is_ok()
+                }
+                _ => false,
+            }
+        }
+
+        /// Returns the token of the session the `Cookie` header belongs to, or issues a new one.
+        ///
+        /// A new token comes with the value of the `Set-Cookie` header that stores it. A token
+        /// that was issued for another session is replaced.
+        pub fn session_token(
+            &self,
+            cookies: Option<&str>,
+        ) -> (CsrfToken, Option<String>) {
+            let session = self.session(cookies);
+            match cookies
+                .and_then(|cookies| find_cookie(cookies, CSRF_COOKIE))
+                .filter(|token| self.verify_token(token, session))
+            {
+                Some(token) => (CsrfToken(token.to_string()), None),
+                None => {
+                    let token = self.generate_token(session);
+                    let cookie = self.cookie(&token);
+                    (CsrfToken(token), Some(cookie))
+                }
+            }
+        }
+
+        fn cookie(&self, token: &str) -> String {
+            // read by the client, so that it can send the token back, so it cannot be `HttpOnly`
+            let secure = if self.secure_cookie { "; Secure" } else { "" };
+            format!("{CSRF_COOKIE}={token}; Path=/; SameSite=Lax{secure}")
+        }
+
+        /// Checks a request, before the server function runs.
+        ///
+        /// `host` is the host the request was sent to, and `header` looks up the value of a
+        /// request header by its lowercase name. `form_token` is the [`CSRF_FIELD`] of a form
+        /// post, which only needs to be read if the request has no [`CSRF_HEADER`].
+        pub fn verify<'a>(
+            &self,
+            method: &str,
+            host: Option<&str>,
+            header: impl Fn(&str) -> Option<&'a str>,
+            form_token: Option<&str>,
+        ) -> Result<(), CsrfError> {
+            if matches!(method, "GET" | "HEAD" | "OPTIONS") {
+                return if is_websocket_upgrade(&header) {
+                    self.verify_websocket(host, header)
+                } else {
+                    Ok(())
+                };
+            }
+
+            self.verify_origin(
+                host,
+                header("sec-fetch-site"),
+                header("origin"),
+            )?;
+
+            let Some(cookies) = header("cookie") else {
+                return Ok(());
+            };
+            let cookie_token = find_cookie(cookies, CSRF_COOKIE)
+                .ok_or(CsrfError::MissingToken)?;
+            if !self.verify_token(cookie_token, self.session(Some(cookies))) {
+                return Err(CsrfError::InvalidToken);
+            }
+            let token = header(CSRF_HEADER)
+                .or(form_token)
+                .ok_or(CsrfError::MissingToken)?;
+            if constant_time_eq(token.as_bytes(), cookie_token.as_bytes()) {
+                Ok(())
+            } else {
+                Err(CsrfError::InvalidToken)
+            }
+        }
+
+        /// Checks the origin of a websocket handshake, which cannot carry a token.
+        ///
+        /// `host` is the host the request was sent to, and `header` looks up the value of a
+        /// request header by its lowercase name.
+        pub fn verify_websocket<'a>(
+            &self,
+            host: Option<&str>,
+            header: impl Fn(&str) -> Option<&'a str>,
+        ) -> Result<(), CsrfError> {
+            self.verify_origin(host, header("sec-fetch-site"), header("origin"))
+        }
+
+        fn verify_origin(
+            &self,
+            host: Option<&str>,
+            sec_fetch_site: Option<&str>,
+            origin: Option<&str>,
+        ) -> Result<(), CsrfError> {
+            if matches!(sec_fetch_site, Some("same-origin" | "none")) {
+                return Ok(());
+            }
+            match origin {
+                Some(origin) => {
+                    let same_host = origin.split_once("://").is_some_and(
+                        |(_, origin_host)| Some(origin_host) == host,
+                    );
+                    if same_host
+                        || self.allowed_origins.iter().any(|o| o == origin)
+                    {
+                        Ok(())
+                    } else {
+                        Err(CsrfError::CrossOrigin)
+                    }
+                }
+                // a browser that sends `Sec-Fetch-Site` also sends `Origin` on these requests
+                None if sec_fetch_site.is_some() => Err(CsrfError::CrossOrigin),
+                None => Ok(()),
+            }
+        }
+    }
+
+    /// Checks the origin of a websocket handshake against the CSRF protection, if it has been
+    /// turned on. This is called by the server when it upgrades the connection.
+    pub fn verify_websocket_origin<'a>(
+        host: Option<&str>,
+        header: impl Fn(&str) -> Option<&'a str>,
+    ) -> Result<(), CsrfError> {
+        match csrf_protection() {
+            Some(csrf) => csrf.verify_websocket(host, header),
+            None => Ok(()),
+        }
+    }
+
+    fn is_websocket_upgrade<'a>(
+        header: &impl Fn(&str) -> Option<&'a str>,
+    ) -> bool {
+        header("upgrade")
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+    }
+
+    /// Returns the content type of a request whose CSRF token has to be read from its body: a
+    /// form post that does not send the [`CSRF_HEADER`].
+    fn form_content_type<'a>(
+        header: impl Fn(&str) -> Option<&'a str>,
+    ) -> Option<&'a str> {
+        if header(CSRF_HEADER).is_some() {
+            return None;
+        }
+        header("content-type").filter(|content_type| {
+            content_type.starts_with("application/x-www-form-urlencoded")
+                || content_type.starts_with("multipart/form-data")
+        })
+    }
+
+    /// Reads the [`CSRF_FIELD`] from a URL-encoded or multipart form body.
+    pub fn token_from_form(content_type: &str, body: &[u8]) -> Option<String> {
+        if content_type.starts_with("multipart/form-data") {
+            token_from_multipart(content_type, body)
+        } else {
+            url::form_urlencoded::parse(body)
+                .find(|(key, _)| key == CSRF_FIELD)
+                .map(|(_, value)| value.into_owned())
+        }
+    }
+
+    fn token_from_multipart(content_type: &str, body: &[u8]) -> Option<String> {
+        let boundary = content_type.split(';').find_map(|param| {
+            param
+                .trim()
+                .strip_prefix("boundary=")
+                .map(|b| b.trim_matches('"'))
+        })?;
+        let delimiter = format!("--{boundary}");
+        let mut parts = split(body, delimiter.as_bytes()).skip(1);
+        parts.find_map(|part| {
+            let part = part.strip_prefix(b"\r\n")?;
+            let (headers, value) = split_once(part, b"\r\n\r\n")?;
+            let is_token =
+                std::str::from_utf8(headers).ok()?.lines().any(|h| {
+                    let Some((name, value)) = h.split_once(':') else {
+                        return false;
+                    };
+                    name.trim().eq_ignore_ascii_case("content-disposition")
+                        && value.split(';').any(|param| {
+                            param
+                                .trim()
+                                .strip_prefix("name=")
+                                .map(|name| name.trim_matches('"'))
+                                == Some(CSRF_FIELD)
+                        })
+                });
+            let value = value.strip_suffix(b"\r\n").unwrap_or(value);
+            is_token
+                .then(|| String::from_utf8(value.to_vec()).ok())
+                .flatten()
+        })
+    }
+
+    fn split_once<'a>(
+        bytes: &'a [u8],
+        needle: &[u8],
+    ) -> Option<(&'a [u8], &'a [u8])> {
+        let at = bytes.windows(needle.len()).position(|w| w == needle)?;
+        Some((&bytes[..at], &bytes[at + needle.len()..]))
+    }
+
+    fn split<'a>(
+        mut bytes: &'a [u8],
+        needle: &'a [u8],
+    ) -> impl Iterator<Item = &'a [u8]> {
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            match split_once(bytes, needle) {
+                Some((part, rest)) => {
+                    bytes = rest;
+                    Some(part)
+                }
+                None => {
+                    done = true;
+                    Some(bytes)
+                }
+            }
+        })
+    }
+
+    fn reject<R: Res<NoCustomError>>(path: &str, err: ServerFnError) -> R {
+        R::error_response_with_status(path, 403, &err)
+    }
+
+    /// Checks Axum server function requests.
+    #[cfg(feature = "axum-no-default")]
+    pub mod axum {
+        use super::{form_content_type, reject, token_from_form};
+        use crate::error::ServerFnError;
+        use axum::body::Body;
+        use http::{Request, Response};
+
+        /// Checks a server function request against the CSRF protection, if it has been turned
+        /// on, before the server function runs.
+        ///
+        /// If the request does not have a token yet, `set_cookie` is called with the value of
+        /// the `Set-Cookie` header that issues one, which should be added to the response even
+        /// if the request is rejected.
+        ///
+        /// Returns the request, or the response that rejects it.
+        pub async fn verify_request(
+            req: Request<Body>,
+            set_cookie: impl FnOnce(String),
+        ) -> Result<Request<Body>, Response<Body>> {
+            let Some(csrf) = super::csrf_protection() else {
+                return Ok(req);
+            };
+            fn header<'a>(
+                req: &'a Request<Body>,
+                name: &str,
+            ) -> Option<&'a str> {
+                req.headers().get(name).and_then(|v| v.to_str().ok())
+            }
+            let path = req.uri().path().to_string();
+
+            if let (_, Some(cookie)) =
+                csrf.session_token(header(&req, "cookie"))
+            {
+                set_cookie(cookie);
+            }
+
+            let form = form_content_type(|name| header(&req, name))
+                .map(str::to_string);
+            let (req, form_token) = match form {
+                Some(content_type) => {
+                    let (parts, body) = req.into_parts();
+                    let body = axum::body::to_bytes(body, csrf.body_limit)
+                        .await
+                        .map_err(|e| {
+                            reject(&path, ServerFnError::Args(e.to_string()))
+                        })?;
+                    let form_token = token_from_form(&content_type, &body);
+                    (Request::from_parts(parts, Body::from(body)), form_token)
+                }
+                None => (req, None),
+            };
+
+            let host = header(&req, "host")
+                .or_else(|| req.uri().authority().map(|a| a.as_str()));
+            csrf.verify(
+                req.method().as_str(),
+                host,
+                |name| header(&req, name),
+                form_token.as_deref(),
+            )
+            .map_err(|e| {
+                reject(&path, ServerFnError::ServerError(e.to_string()))
+            })?;
+            Ok(req)
+        }
+    }
+
+    /// Checks Actix server function requests.
+    #[cfg(feature = "actix")]
+    pub mod actix {
+        use super::{form_content_type, reject, token_from_form};
+        use crate::{error::ServerFnError, response::actix::ActixResponse};
+        use actix_web::{
+            dev, web::Payload, FromRequest, HttpRequest, HttpResponse,
+        };
+
+        /// Checks a server function request against the CSRF protection, if it has been turned
+        /// on, before the server function runs.
+        ///
+        /// If the request does not have a token yet, `set_cookie` is called with the value of
+        /// the `Set-Cookie` header that issues one, which should be added to the response even
+        /// if the request is rejected.
+        ///
+        /// Returns the request body, or the response that rejects the request.
+        pub async fn verify_request(
+            req: &HttpRequest,
+            payload: Payload,
+            set_cookie: impl FnOnce(String),
+        ) -> Result<Payload, HttpResponse> {
+            let Some(csrf) = super::csrf_protection() else {
+                return Ok(payload);
+            };
+            let header = |name: &str| {
+                req.headers().get(name).and_then(|v| v.to_str().ok())
+            };
+            let reject = |err| reject::<ActixResponse>(req.path(), err).take();
+
+            if let (_, Some(cookie)) = csrf.session_token(header("cookie")) {
+                set_cookie(cookie);
+            }
+
+            let (payload, form_token) = match form_content_type(header) {
+                Some(content_type) => {
+                    let body = payload
+                        .to_bytes_limited(csrf.body_limit)
+                        .await
+                        .map_err(|e| {
+                            reject(ServerFnError::Args(e.to_string()))
+                        })?
+                        .map_err(|e| {
+                            reject(ServerFnError::Args(e.to_string()))
+                        })?;
+                    let form_token = token_from_form(content_type, &body);
+                    let payload = Payload::from_request(
+                        req,
+                        &mut dev::Payload::from(body),
+                    )
+                    .await
+                    .map_err(|e| reject(ServerFnError::Args(e.to_string())))?;
+                    (payload, form_token)
+                }
+                None => (payload, None),
+            };
+
+            let host = header("host")
+                .or_else(|| req.uri().authority().map(|a| a.as_str()));
+            csrf.verify(
+                req.method().as_str(),
+                host,
+                header,
+                form_token.as_deref(),
+            )
+            .map_err(|e| reject(ServerFnError::ServerError(e.to_string())))?;
+            Ok(payload)
+        }
+    }
+
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        a.len() == b.len()
+            && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::collections::HashMap;
+
+        fn protection() -> CsrfProtection {
+            CsrfProtection::new("secret")
+                .allowed_origin("https://admin.example.com")
+        }
+
+        // checks a `POST` to `example.com` with the given headers
+        fn verify(
+            csrf: &CsrfProtection,
+            headers: &[(&str, &str)],
+            form_token: Option<&str>,
+        ) -> Result<(), CsrfError> {
+            let headers = headers.iter().copied().collect::<HashMap<_, _>>();
+            csrf.verify(
+                "POST",
+                Some("example.com"),
+                |name| headers.get(name).copied(),
+                form_token,
+            )
+        }
+
+        #[test]
+        fn verifies_tokens_it_signed() {
+            let csrf = protection();
+            let token = csrf.generate_token(None);
+            assert!(csrf.verify_token(&token, None));
+            assert_ne!(token, csrf.generate_token(None));
+            assert!(!CsrfProtection::new("other").verify_token(&token, None));
+        }
+
+        #[test]
+        fn rejects_forged_tokens() {
+            let csrf = protection();
+            let token = csrf.generate_token(None);
+            let (nonce, signature) = token.split_once('.').unwrap();
+            let other = csrf.generate_token(None);
+            let (other_nonce, _) = other.split_once('.').unwrap();
+            assert!(
+                !csrf.verify_token(&format!("{other_nonce}.{signature}"), None)
+            );
+            assert!(!csrf.verify_token(&format!("{nonce}."), None));
+            assert!(!csrf.verify_token(nonce, None));
+            assert!(!csrf.verify_token("not base64.at all", None));
+            assert!(!csrf.verify_token("", None));
+        }
+
+        #[test]
+        fn binds_tokens_to_the_session() {
+            let csrf = protection().session_cookie("id");
+            let token = csrf.generate_token(Some("alice"));
+            assert!(csrf.verify_token(&token, Some("alice")));
+            assert!(!csrf.verify_token(&token, Some("bob")));
+            assert!(!csrf.verify_token(&token, None));
+
+            let cookie = format!("id=alice; {CSRF_COOKIE}={token}");
+            assert_eq!(
+                verify(
+                    &csrf,
+                    &[("cookie", &cookie), (CSRF_HEADER, &token)],
+                    None
+                ),
+                Ok(())
+            );
+            // a token planted from another session
+            let cookie = format!("id=bob; {CSRF_COOKIE}={token}");
+            assert_eq!(
+                verify(
+                    &csrf,
+                    &[("cookie", &cookie), (CSRF_HEADER, &token)],
+                    None
+                ),
+                Err(CsrfError::InvalidToken)
+            );
+            // which is replaced with one for this session
+            let (new_token, set_cookie) = csrf.session_token(Some(&cookie));
+            assert_ne!(new_token.0, token);
+            assert!(set_cookie.is_some());
+            assert!(csrf.verify_token(&new_token.0, Some("bob")));
+        }
+
+        #[test]
+        fn compares_in_constant_time() {
+            assert!(constant_time_eq(b"token", b"token"));
+            assert!(!constant_time_eq(b"token", b"tokem"));
+            assert!(!constant_time_eq(b"token", b"token2"));
+            assert!(constant_time_eq(b"", b""));
+        }
+
+        #[test]
+        fn allows_same_origin_and_allowed_origins() {
+            let csrf = protection();
+            for (site, origin) in [
+                (Some("same-origin"), None),
+                (Some("none"), None),
+                (None, Some("https://example.com")),
+                (Some("same-site"), Some("https://example.com")),
+                (Some("same-site"), Some("https://admin.example.com")),
+                // not a browser
+                (None, None),
+            ] {
+                assert_eq!(
+                    csrf.verify_origin(Some("example.com"), site, origin),
+                    Ok(()),
+                    "{site:?} {origin:?}"
+                );
+            }
+        }
+
+        #[test]
+        fn rejects_cross_origin_requests() {
+            let csrf = protection();
+            for (site, origin) in [
+                (Some("cross-site"), Some("https://evil.com")),
+                (None, Some("https://evil.com")),
+                (None, Some("https://example.com.evil.com")),
+                (Some("cross-site"), None),
+            ] {
+                assert_eq!(
+                    csrf.verify_origin(Some("example.com"), site, origin),
+                    Err(CsrfError::CrossOrigin),
+                    "{site:?} {origin:?}"
+                );
+            }
+            assert_eq!(
+                verify(&csrf, &[("origin", "https://evil.com")], None),
+                Err(CsrfError::CrossOrigin)
+            );
+        }
+
+        #[test]
+        fn only_checks_unsafe_methods() {
+            let csrf = protection();
+            let headers = [("origin", "https://evil.com"), ("cookie", "a=b")];
+            let headers = headers.iter().copied().collect::<HashMap<_, _>>();
+            for method in ["GET", "HEAD", "OPTIONS"] {
+                assert_eq!(
+                    csrf.verify(
+                        method,
+                        Some("example.com"),
+                        |name| headers.get(name).copied(),
+                        None
+                    ),
+                    Ok(())
+                );
+            }
+        }
+
+        #[test]
+        fn checks_the_origin_of_websocket_handshakes() {
+            let csrf = protection();
+            let check = |origin| {
+                let headers = [
+                    ("upgrade", "websocket"),
+                    ("origin", origin),
+                    ("cookie", "a=b"),
+                ];
+                let headers =
+                    headers.iter().copied().collect::<HashMap<_, _>>();
+                csrf.verify(
+                    "GET",
+                    Some("example.com"),
+                    |name| headers.get(name).copied(),
+                    None,
+                )
+            };
+            assert_eq!(check("https://evil.com"), Err(CsrfError::CrossOrigin));
+            // a handshake cannot carry a token, so none is needed
+            assert_eq!(check("https://example.com"), Ok(()));
+            assert_eq!(check("https://admin.example.com"), Ok(()));
+        }
+
+        #[test]
+        fn does_not_need_a_token_without_cookies() {
+            let csrf = protection();
+            assert_eq!(verify(&csrf, &[], None), Ok(()));
+            assert_eq!(
+                verify(&csrf, &[("origin", "https://example.com")], None),
+                Ok(())
+            );
+        }
+
+        #[test]
+        fn accepts_a_valid_double_submit() {
+            let csrf = protection();
+            let token = csrf.generate_token(None);
+            let cookie = format!("theme=dark; {CSRF_COOKIE}={token}");
+            assert_eq!(
+                verify(
+                    &csrf,
+                    &[("cookie", &cookie), (CSRF_HEADER, &token)],
+                    None
+                ),
+                Ok(())
+            );
+            assert_eq!(
+                verify(&csrf, &[("cookie", &cookie)], Some(&token)),
+                Ok(())
+            );
+        }
+
+        #[test]
+        fn rejects_a_missing_token() {
+            let csrf = protection();
+            let token = csrf.generate_token(None);
+            // cookies, but no token cookie
+            assert_eq!(
+                verify(&csrf, &[("cookie", "session=1")], Some(&token)),
+                Err(CsrfError::MissingToken)
+            );
+            // a token cookie, but no token in the request
+            let cookie = format!("{CSRF_COOKIE}={token}");
+            assert_eq!(
+                verify(&csrf, &[("cookie", &cookie)], None),
+                Err(CsrfError::MissingToken)
+            );
+        }
+
+        #[test]
+        fn rejects_a_forged_token() {
+            let csrf = protection();
+            let token = csrf.generate_token(None);
+            let cookie = format!("{CSRF_COOKIE}={token}");
+            // a valid token that does not match the cookie
+            let other = csrf.generate_token(None);
+            assert_eq!(
+                verify(
+                    &csrf,
+                    &[("cookie", &cookie), (CSRF_HEADER, &other)],
+                    None
+                ),
+                Err(CsrfError::InvalidToken)
+            );
+            // a cookie and token that match, but were not signed with the key
+            let forged = CsrfProtection::new("guess").generate_token(None);
+            let cookie = format!("{CSRF_COOKIE}={forged}");
+            assert_eq!(
+                verify(
+                    &csrf,
+                    &[("cookie", &cookie), (CSRF_HEADER, &forged)],
+                    None
+                ),
+                Err(CsrfError::InvalidToken)
+            );
+        }
+
+        #[test]
+        fn reads_the_token_from_url_encoded_forms() {
+            let content_type = "application/x-www-form-urlencoded";
+            assert_eq!(
+                token_from_form(content_type, b"name=a&_csrf=abc.d%2Be"),
+                Some("abc.d+e".to_string())
+            );
+            assert_eq!(token_from_form(content_type, b"name=a"), None);
+        }
+
+        #[test]
+        fn reads_the_token_from_multipart_forms() {
+            let content_type = "multipart/form-data; boundary=\"XyZ\"";
+            let body = b"--XyZ\r\n\
+                Content-Disposition: form-data; name=\"file\"; filename=\"_csrf\"\r\n\
+                Content-Type: text/plain\r\n\
+                \r\n\
+                not the token\r\n\
+                --XyZ\r\n\
+                content-disposition: form-data; name=\"_csrf\"\r\n\
+                \r\n\
+                abc.def\r\n\
+                --XyZ--\r\n";
+            assert_eq!(
+                token_from_form(content_type, body),
+                Some("abc.def".to_string())
+            );
+            let body = b"--XyZ\r\n\
+                Content-Disposition: form-data; name=\"name\"\r\n\
+                \r\n\
+                _csrf\r\n\
+                --XyZ--\r\n";
+            assert_eq!(token_from_form(content_type, body), None);
+            assert_eq!(token_from_form("multipart/form-data", body), None);
+        }
+
+        #[test]
+        fn only_reads_forms_without_the_header() {
+            let form = [("content-type", "multipart/form-data; boundary=a")];
+            let form = form.iter().copied().collect::<HashMap<_, _>>();
+            assert!(form_content_type(|name| form.get(name).copied()).is_some());
+            let json = [("content-type", "application/json")];
+            let json = json.iter().copied().collect::<HashMap<_, _>>();
+            assert!(form_content_type(|name| json.get(name).copied()).is_none());
+            let header = [
+                ("content-type", "application/x-www-form-urlencoded"),
+                (CSRF_HEADER, "token"),
+            ];
+            let header = header.iter().copied().collect::<HashMap<_, _>>();
+            assert!(
+                form_content_type(|name| header.get(name).copied()).is_none()
+            );
+        }
+
+        #[cfg(feature = "axum-no-default")]
+        #[test]
+        fn checks_axum_form_posts() {
+            use ::axum::body::Body;
+            use futures::executor::block_on;
+            use http::Request;
+
+            // the only test that turns on the protection for the whole process
+            _ = set_csrf_protection(protection().body_limit(1024));
+            let token = csrf_protection().unwrap().generate_token(None);
+            let post = |body: String| {
+                Request::post("/api/save")
+                    .header("host", "example.com")
+                    .header("cookie", format!("{CSRF_COOKIE}={token}"))
+                    .header("content-type", "multipart/form-data; boundary=b")
+                    .body(Body::from(body))
+                    .unwrap()
+            };
+            let form = |value: &str| {
+                format!(
+                    "--b\r\nContent-Disposition: form-data; name=\"_csrf\"\r\n\r\n\
+                     {token}\r\n--b\r\nContent-Disposition: form-data; \
+                     name=\"note\"\r\n\r\n{value}\r\n--b--\r\n"
+                )
+            };
+
+            let req = block_on(axum::verify_request(post(form("hi")), |_| {
+                panic!("the request already has a token")
+            }))
+            .unwrap();
+            let body =
+                block_on(::axum::body::to_bytes(req.into_body(), usize::MAX))
+                    .unwrap();
+            assert_eq!(body, form("hi").as_bytes());
+
+            let res = block_on(axum::verify_request(
+                post(form(&"x".repeat(1024))),
+                |_| {},
+            ))
+            .unwrap_err();
+            assert_eq!(res.status(), 403);
+
+            let res = block_on(axum::verify_request(
+                post(form("hi").replace(&token, "forged")),
+                |_| {},
+            ))
+            .unwrap_err();
+            assert_eq!(res.status(), 403);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+type Callbacks = Arc<Mutex<Vec<Box<dyn FnOnce() + Send>>>>;
+
+thread_local! {
+    static CURRENT: RefCell<Option<Callbacks>> = const { RefCell::new(None) };
+}
+
+/// Registers a callback that runs if the client disconnects before the current server function
+/// has returned.
+///
+/// When a client aborts a call (for example, by calling
+/// [`ActionAbortHandle::abort`](https://docs.rs/reactive_graph/latest/reactive_graph/actions/struct.ActionAbortHandle.html#method.abort)),
+/// the server integrations drop the server function’s `Future` as soon as they notice that the
+/// connection has closed, so it stops at its next `.await`. This hook is for work that is not
+/// canceled just by being dropped, like a query that is still running in the database or a task
+/// that has been moved to a blocking thread pool.
+///
+/// This should be called from inside the body of a server function; it does nothing otherwise.
+/// The callbacks are not run if the server function returns normally.
+///
+/// ```rust,ignore
+/// #[server]
+/// pub async fn long_report() -> Result<Report, ServerFnError> {
+///     let canceled = Arc::new(AtomicBool::new(false));
+///     on_client_disconnect({
+///         let canceled = Arc::clone(&canceled);
+///         move || canceled.store(true, Ordering::Relaxed)
+///     });
+///     tokio::task::spawn_blocking(move || build_report(&canceled)).await?
+/// }
+/// ```
+pub fn on_client_disconnect(callback: impl FnOnce() + Send + 'static) {
+    CURRENT.with(|current| {
+        if let Some(callbacks) = current.borrow().as_ref() {
+            callbacks
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(Box::new(callback));
+        }
+    });
+}
+
+/// Wraps the `Future` of a server function handler, running the callbacks registered with
+/// [`on_client_disconnect`] if it is dropped before it completes.
+pub(crate) struct DisconnectGuard<Res> {
+    inner: Pin<Box<dyn Future<Output = Res> + Send>>,
+    callbacks: Callbacks,
+    completed: bool,
+}
+
+impl<Res> DisconnectGuard<Res> {
+    pub fn new(inner: Pin<Box<dyn Future<Output = Res> + Send>>) -> Self {
+        Self {
+            inner,
+            callbacks: Default::default(),
+            completed: false,
+        }
+    }
+}
+
+impl<Res> Future for DisconnectGuard<Res> {
+    type Output = Res;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Res> {
+        // restores the previous set of callbacks, even if the handler panics
+        struct Reset(Option<Callbacks>);
+
+        impl Drop for Reset {
+            fn drop(&mut self) {
+                let prev = self.0.take();
+                CURRENT.with(|current| *current.borrow_mut() = prev);
+            }
+        }
+
+        let this = &mut *self;
+        let prev = CURRENT.with(|current| {
+            current.borrow_mut().replace(Arc::clone(&this.callbacks))
+        });
+        let _reset = Reset(prev);
+        let res = this.inner.as_mut().poll(cx);
+        if res.is_ready() {
+            this.completed = true;
+        }
+        res
+    }
+}
+
+impl<Res> Drop for DisconnectGuard<Res> {
+    fn drop(&mut self) {
+        if !self.completed {
+            let callbacks = std::mem::take(
+                &mut *self
+                    .callbacks
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner),
+            );
+            for callback in callbacks {
+                callback();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{channel::oneshot, executor::block_on, task::noop_waker_ref};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counter() -> (Arc<AtomicUsize>, impl FnOnce() + Send + 'static) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let callback = {
+            let count = Arc::clone(&count);
+            move || _ = count.fetch_add(1, Ordering::SeqCst)
+        };
+        (count, callback)
+    }
+
+    #[test]
+    fn runs_callbacks_when_dropped_before_completing() {
+        let (count, callback) = counter();
+        let (_tx, rx) = oneshot::channel::<()>();
+        let mut guard = DisconnectGuard::new(Box::pin(async move {
+            on_client_disconnect(callback);
+            _ = rx.await;
+        }));
+
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(Pin::new(&mut guard).poll(&mut cx).is_pending());
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        drop(guard);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn does_not_run_callbacks_after_completing() {
+        let (count, callback) = counter();
+        let guard = DisconnectGuard::new(Box::pin(async move {
+            on_client_disconnect(callback);
+            42
+        }));
+        assert_eq!(block_on(guard), 42);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn ignores_callbacks_registered_outside_a_handler() {
+        let (count, callback) = counter();
+        on_client_disconnect(callback);
+        let (_tx, rx) = oneshot::channel::<()>();
+        let mut guard = DisconnectGuard::new(Box::pin(async move {
+            _ = rx.await;
+        }));
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(Pin::new(&mut guard).poll(&mut cx).is_pending());
+        drop(guard);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn registers_callbacks_with_innermost_handler() {
+        let (outer_count, outer) = counter();
+        let (inner_count, inner) = counter();
+        let (_tx, rx) = oneshot::channel::<()>();
+        let mut guard = DisconnectGuard::new(Box::pin(async move {
+            // the inner handler completes, so only the outer callback runs
+            block_on(DisconnectGuard::new(Box::pin(async move {
+                on_client_disconnect(inner);
+            })));
+            on_client_disconnect(outer);
+            _ = rx.await;
+        }));
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(Pin::new(&mut guard).poll(&mut cx).is_pending());
+        drop(guard);
+        assert_eq!(inner_count.load(Ordering::SeqCst), 0);
+        assert_eq!(outer_count.load(Ordering::SeqCst), 1);
+    }
+}
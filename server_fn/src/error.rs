@@ -298,6 +298,128 @@ where
     }
 }
 
+/// Maps an error to the HTTP status code of the response that carries it to the client.
+///
+/// A server function that returns an error responds with a status code from its variant:
+/// `400 Bad Request` if its arguments could not be deserialized ([`ServerFnError::Args`],
+/// [`ServerFnError::MissingArg`] and [`ServerFnError::Deserialization`]), and
+/// `500 Internal Server Error` otherwise. Implement this for a custom error type to give its own
+/// variants their status codes, which the `#[server]` macro uses for
+/// [`ServerFnError::WrappedServerError`].
+///
+/// The status code should be between `400` and `599`, so that the client still decodes the body
+/// of the response as the typed error.
+///
+/// ```rust,ignore
+/// #[derive(Debug, Clone)]
+/// pub enum AppError {
+///     NotFound,
+///     Forbidden,
+/// }
+///
+/// impl ErrorStatus for AppError {
+///     fn status_code(&self) -> u16 {
+///         match self {
+///             AppError::NotFound => 404,
+///             AppError::Forbidden => 403,
+///         }
+///     }
+/// }
+/// ```
+pub trait ErrorStatus {
+    /// Returns the HTTP status code for this error.
+    fn status_code(&self) -> u16;
+}
+
+impl ErrorStatus for NoCustomError {
+    fn status_code(&self) -> u16 {
+        500
+    }
+}
+
+impl<CustErr> ServerFnError<CustErr> {
+    /// Converts this error to one with a different custom error type, using `f` to convert a
+    /// [`WrappedServerError`](ServerFnError::WrappedServerError) and keeping every other
+    /// variant as it is.
+    pub fn map_custom<E>(
+        self,
+        f: impl FnOnce(CustErr) -> ServerFnError<E>,
+    ) -> ServerFnError<E> {
+        match self {
+            ServerFnError::WrappedServerError(e) => f(e),
+            ServerFnError::Registration(s) => ServerFnError::Registration(s),
+            ServerFnError::Request(s) => ServerFnError::Request(s),
+            ServerFnError::Response(s) => ServerFnError::Response(s),
+            ServerFnError::ServerError(s) => ServerFnError::ServerError(s),
+            ServerFnError::Deserialization(s) => {
+                ServerFnError::Deserialization(s)
+            }
+            ServerFnError::Serialization(s) => ServerFnError::Serialization(s),
+            ServerFnError::Args(s) => ServerFnError::Args(s),
+            ServerFnError::MissingArg(s) => ServerFnError::MissingArg(s),
+        }
+    }
+
+    /// The status code for this error, using `500` for a custom error.
+    pub(crate) fn default_status_code(&self) -> u16 {
+        match self {
+            ServerFnError::Args(_)
+            | ServerFnError::MissingArg(_)
+            | ServerFnError::Deserialization(_) => 400,
+            _ => 500,
+        }
+    }
+}
+
+impl<CustErr: ErrorStatus> ErrorStatus for ServerFnError<CustErr> {
+    fn status_code(&self) -> u16 {
+        match self {
+            ServerFnError::WrappedServerError(e) => e.status_code(),
+            _ => self.default_status_code(),
+        }
+    }
+}
+
+impl<CustErr: ErrorStatus> ErrorStatus for ServerFnErrorErr<CustErr> {
+    fn status_code(&self) -> u16 {
+        match self {
+            ServerFnErrorErr::WrappedServerError(e) => e.status_code(),
+            ServerFnErrorErr::Args(_)
+            | ServerFnErrorErr::MissingArg(_)
+            | ServerFnErrorErr::Deserialization(_) => 400,
+            _ => 500,
+        }
+    }
+}
+
+/// Finds the status code for a server function error, using [`ErrorStatus`] for its custom error
+/// type if that implements it.
+///
+/// This is used by the `#[server]` macro, as `(&&StatusOf(err)).to_status_code()`.
+#[doc(hidden)]
+pub struct StatusOf<'a, E>(pub &'a ServerFnError<E>);
+
+/// Converts a [`StatusOf`] into a status code.
+#[doc(hidden)]
+pub trait ViaStatus {
+    /// Returns the status code.
+    fn to_status_code(&self) -> u16;
+}
+
+// This impl is used if the custom error type implements `ErrorStatus`
+impl<E: ErrorStatus> ViaStatus for &StatusOf<'_, E> {
+    fn to_status_code(&self) -> u16 {
+        self.0.status_code()
+    }
+}
+
+// Otherwise, a custom error is a `500`
+impl<E> ViaStatus for StatusOf<'_, E> {
+    fn to_status_code(&self) -> u16 {
+        self.0.default_status_code()
+    }
+}
+
 impl<E> std::error::Error for ServerFnError<E>
 where
     E: std::error::Error + 'static,
@@ -461,3 +583,79 @@ impl<CustErr> From<ServerFnUrlError<CustErr>> for ServerFnErrorErr<CustErr> {
         error.error.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    enum AppError {
+        NotFound,
+        Forbidden,
+    }
+
+    impl ErrorStatus for AppError {
+        fn status_code(&self) -> u16 {
+            match self {
+                AppError::NotFound => 404,
+                AppError::Forbidden => 403,
+            }
+        }
+    }
+
+    // a custom error type without a status code
+    #[derive(Debug, Clone)]
+    struct Plain;
+
+    // the way the `#[server]` macro finds the status code, which only picks `ErrorStatus` when
+    // the error type is known
+    macro_rules! status_of {
+        ($err:expr) => {
+            (&&StatusOf(&$err)).to_status_code()
+        };
+    }
+
+    #[test]
+    fn argument_errors_are_bad_requests() {
+        for err in [
+            ServerFnError::<NoCustomError>::Args("x".into()),
+            ServerFnError::MissingArg("x".into()),
+            ServerFnError::Deserialization("x".into()),
+        ] {
+            assert_eq!(err.status_code(), 400, "{err:?}");
+        }
+        assert_eq!(
+            ServerFnErrorErr::<NoCustomError>::Args("x".into()).status_code(),
+            400
+        );
+    }
+
+    #[test]
+    fn other_errors_are_server_errors() {
+        for err in [
+            ServerFnError::<NoCustomError>::ServerError("x".into()),
+            ServerFnError::Serialization("x".into()),
+            ServerFnError::Registration("x".into()),
+            ServerFnError::WrappedServerError(NoCustomError),
+        ] {
+            assert_eq!(err.status_code(), 500, "{err:?}");
+        }
+    }
+
+    #[test]
+    fn custom_errors_use_their_status_code() {
+        let err = ServerFnError::WrappedServerError(AppError::NotFound);
+        assert_eq!(err.status_code(), 404);
+        assert_eq!(status_of!(err), 404);
+        let err = ServerFnErrorErr::WrappedServerError(AppError::Forbidden);
+        assert_eq!(err.status_code(), 403);
+        let err = ServerFnError::<AppError>::Args("x".into());
+        assert_eq!(status_of!(err), 400);
+    }
+
+    #[test]
+    fn custom_errors_without_status_code_are_server_errors() {
+        assert_eq!(status_of!(ServerFnError::WrappedServerError(Plain)), 500);
+        assert_eq!(status_of!(ServerFnError::<Plain>::Args("x".into())), 400);
+    }
+}
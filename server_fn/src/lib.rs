@@ -96,9 +96,19 @@
 //! [`serde_qs`]: <https://docs.rs/serde_qs/latest/serde_qs/>
 //! [`cbor`]: <https://docs.rs/cbor/latest/cbor/>
 
+/// Sending several server function calls to the server in a single request.
+#[cfg(feature = "batch")]
+pub mod batch;
+
 /// Implementations of the client side of the server function call.
 pub mod client;
 
+/// Protection against cross-site request forgery.
+pub mod csrf;
+
+/// Cleaning up when a client disconnects before a server function returns.
+pub mod disconnect;
+
 /// Encodings for arguments and results.
 pub mod codec;
 
@@ -107,6 +117,9 @@ pub mod codec;
 pub mod error;
 /// Types to add server middleware to a server function.
 pub mod middleware;
+/// Generating an OpenAPI document that describes the registered server functions.
+#[cfg(feature = "openapi")]
+pub mod openapi;
 /// Utilities to allow client-side redirects.
 pub mod redirect;
 /// Types and traits for  for HTTP requests.
@@ -145,6 +158,12 @@ use response::{ClientRes, Res};
 #[cfg(feature = "rkyv")]
 pub use rkyv;
 #[doc(hidden)]
+#[cfg(feature = "protobuf")]
+pub use prost;
+#[doc(hidden)]
+#[cfg(feature = "openapi")]
+pub use schemars;
+#[doc(hidden)]
 pub use serde;
 #[doc(hidden)]
 #[cfg(feature = "serde-lite")]
@@ -233,6 +252,15 @@ where
         Self::PATH
     }
 
+    /// Returns the HTTP status code of the response when this server function returns an error.
+    ///
+    /// By default, this is `400` for errors in deserializing the arguments and `500` otherwise.
+    /// The `#[server]` macro overrides it to use [`ErrorStatus`](error::ErrorStatus) if the
+    /// custom error type implements it.
+    fn error_status(err: &ServerFnError<Self::Error>) -> u16 {
+        err.default_status_code()
+    }
+
     /// Middleware that should be applied to this server function.
     fn middlewares(
     ) -> Vec<Arc<dyn Layer<Self::ServerRequest, Self::ServerResponse>>> {
@@ -267,7 +295,11 @@ where
                 .map(|res| (res, None))
                 .unwrap_or_else(|e| {
                     (
-                        Self::ServerResponse::error_response(Self::PATH, &e),
+                        Self::ServerResponse::error_response_with_status(
+                            Self::PATH,
+                            Self::error_status(&e),
+                            &e,
+                        ),
                         Some(e),
                     )
                 });
@@ -359,10 +391,25 @@ where
     ) -> impl Future<
         Output = Result<Self::ServerResponse, ServerFnError<Self::Error>>,
     > + Send {
+        // the `Accept` header is needed to pick the encoding of a `Negotiated` response
+        let accepts = Self::OutputEncoding::NEGOTIATED
+            .then(|| req.accepts().map(std::borrow::Cow::into_owned))
+            .flatten();
+        // a client resuming a stream of server-sent events sends the ID of the last one
+        #[cfg(feature = "json")]
+        let last_event_id = Self::OutputEncoding::SERVER_SENT_EVENTS
+            .then(|| req.last_event_id().map(std::borrow::Cow::into_owned))
+            .flatten();
         async {
             let this = Self::from_req(req).await?;
+            #[cfg(feature = "json")]
+            let output =
+                codec::WithLastEventId::new(last_event_id, this.run_body())
+                    .await?;
+            #[cfg(not(feature = "json"))]
             let output = this.run_body().await?;
-            let res = output.into_res().await?;
+            let res =
+                codec::WithAccepts::new(accepts, output.into_res()).await?;
             Ok(res)
         }
     }
@@ -427,8 +474,11 @@ impl<Req, Res> ServerFnTraitObj<Req, Res> {
     }
 
     /// The handler for this server function.
+    ///
+    /// Callbacks registered with [`on_client_disconnect`](disconnect::on_client_disconnect)
+    /// are run if the returned `Future` is dropped before it completes.
     pub fn handler(&self, req: Req) -> impl Future<Output = Res> + Send {
-        (self.handler)(req)
+        disconnect::DisconnectGuard::new((self.handler)(req))
     }
 
     /// The set of middleware that should be applied to this function.
@@ -444,7 +494,7 @@ where
 {
     fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
         let handler = self.handler;
-        Box::pin(async move { handler(req).await })
+        Box::pin(disconnect::DisconnectGuard::new(handler(req)))
     }
 }
 
@@ -478,7 +528,7 @@ impl<Req: 'static, Res: 'static> inventory::Collect
 #[cfg(feature = "axum-no-default")]
 pub mod axum {
     use crate::{
-        middleware::{BoxedService, Service},
+        middleware::{BoxedService, Layer, Service},
         Encoding, LazyServerFnMap, ServerFn, ServerFnTraitObj,
     };
     use axum::body::Body;
@@ -511,10 +561,25 @@ pub mod axum {
     }
 
     /// The set of all registered server function paths.
+    ///
+    /// With the `batch` feature, this includes the [batch endpoint](crate::batch::batch_path)
+    /// for each prefix that server functions are registered under.
     pub fn server_fn_paths() -> impl Iterator<Item = (&'static str, Method)> {
-        REGISTERED_SERVER_FUNCTIONS
+        let paths = REGISTERED_SERVER_FUNCTIONS
             .iter()
             .map(|item| (item.path(), item.method()))
+            .collect::<Vec<_>>();
+        #[cfg(feature = "batch")]
+        let paths = {
+            let batch_paths =
+                crate::batch::batch_paths(paths.iter().map(|(path, _)| *path));
+            let mut paths = paths;
+            paths.extend(
+                batch_paths.into_iter().map(|path| (path, Method::POST)),
+            );
+            paths
+        };
+        paths.into_iter()
     }
 
     /// An Axum handler that responds to a server function request.
@@ -542,10 +607,60 @@ pub mod axum {
         }
     }
 
+    /// An Axum handler that responds with an OpenAPI document describing all registered server
+    /// functions, usually mounted at [`OPENAPI_PATH`](crate::openapi::OPENAPI_PATH).
+    #[cfg(feature = "openapi")]
+    pub async fn handle_openapi() -> Response<Body> {
+        let (title, version) = crate::openapi::get_openapi_info();
+        let document = crate::openapi::openapi_document(title, version);
+        Response::builder()
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(document.to_string()))
+            .unwrap()
+    }
+
     /// Returns the server function at the given path as a service that can be modified.
+    ///
+    /// With the `batch` feature, this also returns a service that handles batched calls at the
+    /// [batch endpoint](crate::batch::batch_path) under any prefix.
     pub fn get_server_fn_service(
         path: &str,
         method: Method,
+    ) -> Option<BoxedService<Request<Body>, Response<Body>>> {
+        #[cfg(feature = "batch")]
+        if crate::batch::is_batch_path(path) && method == Method::POST {
+            return Some(BoxedService::new(batch::BatchService(None)));
+        }
+        registered_server_fn_service(path, method)
+    }
+
+    /// Returns the server function at the given path as a service, like
+    /// [`get_server_fn_service`], but with the `batch` feature, each call in a batch is also
+    /// wrapped in `call_layer`.
+    ///
+    /// An integration can use this to give each call in a batch its own response state (such as
+    /// the status and headers set by the server function), rather than that of the batch
+    /// request. Only the status, `Content-Type`, `Location` and redirect header of a call's
+    /// response are sent back to the client.
+    pub fn get_server_fn_service_with_call_layer(
+        path: &str,
+        method: Method,
+        call_layer: impl Layer<Request<Body>, Response<Body>>,
+    ) -> Option<BoxedService<Request<Body>, Response<Body>>> {
+        #[cfg(feature = "batch")]
+        if crate::batch::is_batch_path(path) && method == Method::POST {
+            return Some(BoxedService::new(batch::BatchService(Some(
+                std::sync::Arc::new(call_layer),
+            ))));
+        }
+        #[cfg(not(feature = "batch"))]
+        let _ = call_layer;
+        registered_server_fn_service(path, method)
+    }
+
+    fn registered_server_fn_service(
+        path: &str,
+        method: Method,
     ) -> Option<BoxedService<Request<Body>, Response<Body>>> {
         let key = (path.into(), method);
         REGISTERED_SERVER_FUNCTIONS.get(&key).map(|server_fn| {
@@ -557,15 +672,401 @@ pub mod axum {
             service
         })
     }
+
+    #[cfg(feature = "batch")]
+    mod batch {
+        use super::registered_server_fn_service;
+        use crate::{
+            batch::{
+                decode_calls, encode_results, get_max_batch_body_size,
+                get_max_batch_calls, BatchCall, BatchResult,
+                BATCH_CONTENT_TYPE,
+            },
+            middleware::{Layer, Service},
+            redirect::REDIRECT_HEADER,
+        };
+        use axum::body::Body;
+        use futures::future::join_all;
+        use http::{
+            header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, LOCATION},
+            request::Parts,
+            HeaderValue, Method, Request, Response, StatusCode,
+        };
+        use http_body_util::{BodyExt, LengthLimitError, Limited};
+        use std::{future::Future, pin::Pin, sync::Arc};
+
+        type CallLayer = Arc<dyn Layer<Request<Body>, Response<Body>>>;
+
+        /// Runs each call in a batch through its own server function and middleware, wrapped in
+        /// the call layer if there is one.
+        pub(super) struct BatchService(pub Option<CallLayer>);
+
+        impl Service<Request<Body>, Response<Body>> for BatchService {
+            fn run(
+                &mut self,
+                req: Request<Body>,
+            ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>>
+            {
+                let call_layer = self.0.clone();
+                Box::pin(async move {
+                    let (parts, body) = req.into_parts();
+                    let max_size = get_max_batch_body_size();
+                    let calls =
+                        match Limited::new(body, max_size).collect().await {
+                            Ok(body) => decode_calls(body.to_bytes()),
+                            Err(e) if e.is::<LengthLimitError>() => {
+                                return Response::builder()
+                                    .status(StatusCode::PAYLOAD_TOO_LARGE)
+                                    .body(Body::from(format!(
+                                    "A batch can be at most {max_size} bytes."
+                                )))
+                                    .unwrap()
+                            }
+                            Err(e) => Err(e.to_string()),
+                        };
+                    let calls = match calls {
+                        Ok(calls) => calls,
+                        Err(e) => {
+                            return Response::builder()
+                                .status(StatusCode::BAD_REQUEST)
+                                .body(Body::from(e))
+                                .unwrap()
+                        }
+                    };
+                    let max = get_max_batch_calls();
+                    if calls.len() > max {
+                        return Response::builder()
+                            .status(StatusCode::PAYLOAD_TOO_LARGE)
+                            .body(Body::from(format!(
+                                "A batch can contain at most {max} calls."
+                            )))
+                            .unwrap();
+                    }
+
+                    let results = join_all(calls.into_iter().map(|call| {
+                        run_call(&parts, call_layer.as_ref(), call)
+                    }))
+                    .await;
+                    match encode_results(&results) {
+                        Ok(body) => Response::builder()
+                            .header(CONTENT_TYPE, BATCH_CONTENT_TYPE)
+                            .body(Body::from(body))
+                            .unwrap(),
+                        Err(e) => Response::builder()
+                            .status(StatusCode::INTERNAL_SERVER_ERROR)
+                            .body(Body::from(e))
+                            .unwrap(),
+                    }
+                })
+            }
+        }
+
+        async fn run_call(
+            parts: &Parts,
+            call_layer: Option<&CallLayer>,
+            call: BatchCall,
+        ) -> BatchResult {
+            let Ok(method) = Method::from_bytes(call.method.as_bytes()) else {
+                return BatchResult::error(400, "Invalid method.");
+            };
+            let Some(mut service) =
+                registered_server_fn_service(&call.path, method.clone())
+            else {
+                return BatchResult::error(
+                    400,
+                    format!(
+                        "Could not find a server function at the route {}.",
+                        call.path
+                    ),
+                );
+            };
+            if let Some(call_layer) = call_layer {
+                service = call_layer.layer(service);
+            }
+
+            // each call sees the headers and extensions of the batch request, but its own
+            // URI, content type and body
+            let mut req = Request::new(Body::from(call.body.clone()));
+            *req.method_mut() = method;
+            *req.version_mut() = parts.version;
+            *req.uri_mut() = match call.uri().parse() {
+                Ok(uri) => uri,
+                Err(e) => return BatchResult::error(400, e.to_string()),
+            };
+            *req.headers_mut() = parts.headers.clone();
+            *req.extensions_mut() = parts.extensions.clone();
+            let headers = req.headers_mut();
+            headers.remove(CONTENT_LENGTH);
+            headers.remove(CONTENT_TYPE);
+            if let Some(content_type) = call
+                .content_type
+                .as_deref()
+                .and_then(|value| HeaderValue::from_str(value).ok())
+            {
+                headers.insert(CONTENT_TYPE, content_type);
+            }
+            match HeaderValue::from_str(&call.accepts) {
+                Ok(accepts) => _ = headers.insert(ACCEPT, accepts),
+                Err(_) => _ = headers.remove(ACCEPT),
+            }
+
+            let (parts, body) = service.run(req).await.into_parts();
+            let body = match body.collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(e) => return BatchResult::error(500, e.to_string()),
+            };
+            BatchResult {
+                status: parts.status.as_u16(),
+                content_type: parts
+                    .headers
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(ToOwned::to_owned),
+                location: parts
+                    .headers
+                    .get(LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .map(ToOwned::to_owned),
+                redirect: parts.headers.contains_key(REDIRECT_HEADER),
+                body,
+            }
+        }
+
+        #[cfg(all(test, feature = "json"))]
+        mod tests {
+            use crate::{
+                axum::{
+                    get_server_fn_service,
+                    get_server_fn_service_with_call_layer, register_explicit,
+                },
+                batch::{
+                    decode_results, encode_calls, get_max_batch_body_size,
+                    get_max_batch_calls, BatchCall,
+                },
+                client::Client,
+                codec::Json,
+                error::{NoCustomError, ServerFnError},
+                middleware::{BoxedService, Layer, Service},
+                request::batch::BatchRequest,
+                response::batch::BatchResponse,
+                ServerFn,
+            };
+            use axum::body::Body;
+            use futures::executor::block_on;
+            use http::{Method, Request, Response, StatusCode};
+            use http_body_util::BodyExt;
+            use serde::{Deserialize, Serialize};
+            use std::{future::Future, pin::Pin};
+
+            struct NotSent;
+
+            impl Client<NoCustomError> for NotSent {
+                type Request = BatchRequest;
+                type Response = BatchResponse;
+
+                async fn send(
+                    _req: Self::Request,
+                ) -> Result<Self::Response, ServerFnError> {
+                    unreachable!("the test only runs the server")
+                }
+            }
+
+            #[derive(Serialize, Deserialize)]
+            struct Add {
+                a: u8,
+                b: u8,
+            }
+
+            impl ServerFn for Add {
+                const PATH: &'static str = "/batch_test/add";
+                type Client = NotSent;
+                type ServerRequest = Request<Body>;
+                type ServerResponse = Response<Body>;
+                type Output = u8;
+                type InputEncoding = Json;
+                type OutputEncoding = Json;
+                type Error = NoCustomError;
+
+                async fn run_body(self) -> Result<u8, ServerFnError> {
+                    self.a.checked_add(self.b).ok_or_else(|| {
+                        ServerFnError::ServerError("overflow".to_string())
+                    })
+                }
+            }
+
+            fn call(path: &str, body: &str) -> BatchCall {
+                BatchCall {
+                    method: "POST".to_string(),
+                    path: path.to_string(),
+                    query: None,
+                    content_type: Some("application/json".to_string()),
+                    accepts: "application/json".to_string(),
+                    body: body.to_string().into(),
+                }
+            }
+
+            #[test]
+            fn runs_each_call_in_batch() {
+                register_explicit::<Add>();
+                assert!(crate::axum::server_fn_paths()
+                    .any(|(path, _)| path == "/batch_test/_batch"));
+
+                let body = encode_calls(&[
+                    call(Add::PATH, r#"{"a":1,"b":2}"#),
+                    call(Add::PATH, r#"{"a":255,"b":1}"#),
+                    call(Add::PATH, r#"{"a":"one"}"#),
+                    call("/batch_test/missing", "{}"),
+                ])
+                .unwrap();
+                let mut service =
+                    get_server_fn_service("/batch_test/_batch", Method::POST)
+                        .unwrap();
+                let res = block_on(async {
+                    let res = service
+                        .run(
+                            Request::post("/batch_test/_batch")
+                                .body(Body::from(body))
+                                .unwrap(),
+                        )
+                        .await;
+                    assert_eq!(res.status(), 200);
+                    res.into_body().collect().await.unwrap().to_bytes()
+                });
+
+                let results = decode_results(res).unwrap();
+                let statuses =
+                    results.iter().map(|res| res.status).collect::<Vec<_>>();
+                assert_eq!(statuses, [200, 500, 400, 400]);
+                assert_eq!(results[0].body, "3");
+                assert!(String::from_utf8_lossy(&results[1].body)
+                    .contains("overflow"));
+            }
+
+            #[test]
+            fn rejects_invalid_batch() {
+                let mut service =
+                    get_server_fn_service("/batch_test/_batch", Method::POST)
+                        .unwrap();
+                let res = block_on(
+                    service.run(
+                        Request::post("/batch_test/_batch")
+                            .body(Body::from("not a batch"))
+                            .unwrap(),
+                    ),
+                );
+                assert_eq!(res.status(), 400);
+            }
+
+            #[test]
+            fn rejects_batch_with_too_many_calls() {
+                let calls = vec![
+                    call(Add::PATH, r#"{"a":1,"b":2}"#);
+                    get_max_batch_calls() + 1
+                ];
+                let mut service =
+                    get_server_fn_service("/batch_test/_batch", Method::POST)
+                        .unwrap();
+                let res = block_on(
+                    service.run(
+                        Request::post("/batch_test/_batch")
+                            .body(Body::from(encode_calls(&calls).unwrap()))
+                            .unwrap(),
+                    ),
+                );
+                assert_eq!(res.status(), 413);
+            }
+
+            #[test]
+            fn rejects_batch_body_that_is_too_large() {
+                let mut service =
+                    get_server_fn_service("/batch_test/_batch", Method::POST)
+                        .unwrap();
+                let body = vec![b'a'; get_max_batch_body_size() + 1];
+                let res = block_on(
+                    service.run(
+                        Request::post("/batch_test/_batch")
+                            .body(Body::from(body))
+                            .unwrap(),
+                    ),
+                );
+                assert_eq!(res.status(), 413);
+            }
+
+            /// Sets the status of each response it wraps to `202 Accepted`.
+            struct Accepted;
+
+            impl Layer<Request<Body>, Response<Body>> for Accepted {
+                fn layer(
+                    &self,
+                    inner: BoxedService<Request<Body>, Response<Body>>,
+                ) -> BoxedService<Request<Body>, Response<Body>>
+                {
+                    BoxedService::new(AcceptedService(inner))
+                }
+            }
+
+            struct AcceptedService(BoxedService<Request<Body>, Response<Body>>);
+
+            impl Service<Request<Body>, Response<Body>> for AcceptedService {
+                fn run(
+                    &mut self,
+                    req: Request<Body>,
+                ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>>
+                {
+                    let res = self.0 .0.run(req);
+                    Box::pin(async move {
+                        let mut res = res.await;
+                        *res.status_mut() = StatusCode::ACCEPTED;
+                        res
+                    })
+                }
+            }
+
+            #[test]
+            fn wraps_each_call_in_call_layer() {
+                register_explicit::<Add>();
+                let body = encode_calls(&[
+                    call(Add::PATH, r#"{"a":1,"b":2}"#),
+                    call(Add::PATH, r#"{"a":3,"b":4}"#),
+                ])
+                .unwrap();
+                let mut service = get_server_fn_service_with_call_layer(
+                    "/batch_test/_batch",
+                    Method::POST,
+                    Accepted,
+                )
+                .unwrap();
+                let res = block_on(async {
+                    let res = service
+                        .run(
+                            Request::post("/batch_test/_batch")
+                                .body(Body::from(body))
+                                .unwrap(),
+                        )
+                        .await;
+                    // the batch response itself is not wrapped
+                    assert_eq!(res.status(), 200);
+                    res.into_body().collect().await.unwrap().to_bytes()
+                });
+
+                let results = decode_results(res).unwrap();
+                let statuses =
+                    results.iter().map(|res| res.status).collect::<Vec<_>>();
+                assert_eq!(statuses, [202, 202]);
+                assert_eq!(results[1].body, "7");
+            }
+        }
+    }
 }
 
 /// Actix integration.
 #[cfg(feature = "actix")]
 pub mod actix {
     use crate::{
-        middleware::BoxedService, request::actix::ActixRequest,
-        response::actix::ActixResponse, Encoding, LazyServerFnMap, ServerFn,
-        ServerFnTraitObj,
+        middleware::{BoxedService, Layer},
+        request::actix::ActixRequest,
+        response::actix::ActixResponse,
+        Encoding, LazyServerFnMap, ServerFn, ServerFnTraitObj,
     };
     use actix_web::{web::Payload, HttpRequest, HttpResponse};
     use http::Method;
@@ -599,10 +1100,25 @@ pub mod actix {
     }
 
     /// The set of all registered server function paths.
+    ///
+    /// With the `batch` feature, this includes the [batch endpoint](crate::batch::batch_path)
+    /// for each prefix that server functions are registered under.
     pub fn server_fn_paths() -> impl Iterator<Item = (&'static str, Method)> {
-        REGISTERED_SERVER_FUNCTIONS
+        let paths = REGISTERED_SERVER_FUNCTIONS
             .iter()
             .map(|item| (item.path(), item.method()))
+            .collect::<Vec<_>>();
+        #[cfg(feature = "batch")]
+        let paths = {
+            let batch_paths =
+                crate::batch::batch_paths(paths.iter().map(|(path, _)| *path));
+            let mut paths = paths;
+            paths.extend(
+                batch_paths.into_iter().map(|path| (path, Method::POST)),
+            );
+            paths
+        };
+        paths.into_iter()
     }
 
     /// An Actix handler that responds to a server function request.
@@ -633,10 +1149,63 @@ pub mod actix {
         }
     }
 
+    /// An Actix handler that responds with an OpenAPI document describing all registered server
+    /// functions, usually mounted at [`OPENAPI_PATH`](crate::openapi::OPENAPI_PATH).
+    #[cfg(feature = "openapi")]
+    pub async fn handle_openapi() -> HttpResponse {
+        let (title, version) = crate::openapi::get_openapi_info();
+        let document = crate::openapi::openapi_document(title, version);
+        HttpResponse::Ok()
+            .content_type("application/json")
+            .body(document.to_string())
+    }
+
     /// Returns the server function at the given path as a service that can be modified.
+    ///
+    /// With the `batch` feature, this also returns a service that handles batched calls at the
+    /// [batch endpoint](crate::batch::batch_path) under any prefix.
     pub fn get_server_fn_service(
         path: &str,
         method: &actix_web::http::Method,
+    ) -> Option<BoxedService<ActixRequest, ActixResponse>> {
+        #[cfg(feature = "batch")]
+        if crate::batch::is_batch_path(path)
+            && method == actix_web::http::Method::POST
+        {
+            return Some(BoxedService::new(batch::BatchService(None)));
+        }
+        actix_server_fn_service(path, method)
+    }
+
+    /// Returns the server function at the given path as a service, like
+    /// [`get_server_fn_service`], but with the `batch` feature, each call in a batch is also
+    /// wrapped in `call_layer`.
+    ///
+    /// An integration can use this to give each call in a batch its own response state (such as
+    /// the status and headers set by the server function), rather than that of the batch
+    /// request. Only the status, `Content-Type`, `Location` and redirect header of a call's
+    /// response are sent back to the client.
+    pub fn get_server_fn_service_with_call_layer(
+        path: &str,
+        method: &actix_web::http::Method,
+        call_layer: impl Layer<ActixRequest, ActixResponse>,
+    ) -> Option<BoxedService<ActixRequest, ActixResponse>> {
+        #[cfg(feature = "batch")]
+        if crate::batch::is_batch_path(path)
+            && method == actix_web::http::Method::POST
+        {
+            return Some(BoxedService::new(batch::BatchService(Some(
+                std::sync::Arc::new(call_layer),
+            ))));
+        }
+        #[cfg(not(feature = "batch"))]
+        let _ = call_layer;
+        actix_server_fn_service(path, method)
+    }
+
+    fn actix_server_fn_service(
+        path: &str,
+        method: &actix_web::http::Method,
     ) -> Option<BoxedService<ActixRequest, ActixResponse>> {
         use actix_web::http::Method as ActixMethod;
 
@@ -652,6 +1221,13 @@ pub mod actix {
             ActixMethod::CONNECT => Method::CONNECT,
             _ => unreachable!(),
         };
+        registered_server_fn_service(path, method)
+    }
+
+    fn registered_server_fn_service(
+        path: &str,
+        method: Method,
+    ) -> Option<BoxedService<ActixRequest, ActixResponse>> {
         REGISTERED_SERVER_FUNCTIONS.get(&(path.into(), method)).map(
             |server_fn| {
                 let middleware = (server_fn.middleware)();
@@ -663,4 +1239,149 @@ pub mod actix {
             },
         )
     }
+
+    #[cfg(feature = "batch")]
+    mod batch {
+        use super::registered_server_fn_service;
+        use crate::{
+            batch::{
+                decode_calls, encode_results, get_max_batch_body_size,
+                get_max_batch_calls, BatchCall, BatchResult,
+                BATCH_CONTENT_TYPE,
+            },
+            middleware::{Layer, Service},
+            redirect::REDIRECT_HEADER,
+            request::actix::ActixRequest,
+            response::actix::ActixResponse,
+        };
+        use actix_web::{
+            body::to_bytes,
+            dev,
+            http::header::{CONTENT_TYPE, LOCATION},
+            web::Payload,
+            FromRequest, HttpRequest, HttpResponse,
+        };
+        use futures::future::join_all;
+        use http::Method;
+        use send_wrapper::SendWrapper;
+        use std::{future::Future, pin::Pin, sync::Arc};
+
+        type CallLayer = Arc<dyn Layer<ActixRequest, ActixResponse>>;
+
+        /// Runs each call in a batch through its own server function and middleware, wrapped in
+        /// the call layer if there is one.
+        pub(super) struct BatchService(pub Option<CallLayer>);
+
+        impl Service<ActixRequest, ActixResponse> for BatchService {
+            fn run(
+                &mut self,
+                req: ActixRequest,
+            ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>>
+            {
+                let (req, payload) = req.take();
+                let call_layer = self.0.clone();
+                // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+                // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+                Box::pin(SendWrapper::new(async move {
+                    let max_size = get_max_batch_body_size();
+                    let calls = match payload.to_bytes_limited(max_size).await {
+                        Ok(Ok(body)) => decode_calls(body),
+                        Ok(Err(e)) => Err(e.to_string()),
+                        Err(_) => {
+                            return ActixResponse::from(
+                                HttpResponse::PayloadTooLarge().body(format!(
+                                    "A batch can be at most {max_size} bytes."
+                                )),
+                            )
+                        }
+                    };
+                    let calls = match calls {
+                        Ok(calls) => calls,
+                        Err(e) => {
+                            return ActixResponse::from(
+                                HttpResponse::BadRequest().body(e),
+                            )
+                        }
+                    };
+                    let max = get_max_batch_calls();
+                    if calls.len() > max {
+                        return ActixResponse::from(
+                            HttpResponse::PayloadTooLarge().body(format!(
+                                "A batch can contain at most {max} calls."
+                            )),
+                        );
+                    }
+
+                    let results =
+                        join_all(calls.into_iter().map(|call| {
+                            run_call(&req, call_layer.as_ref(), call)
+                        }))
+                        .await;
+                    ActixResponse::from(match encode_results(&results) {
+                        Ok(body) => HttpResponse::Ok()
+                            .content_type(BATCH_CONTENT_TYPE)
+                            .body(body),
+                        Err(e) => HttpResponse::InternalServerError().body(e),
+                    })
+                }))
+            }
+        }
+
+        async fn run_call(
+            req: &HttpRequest,
+            call_layer: Option<&CallLayer>,
+            call: BatchCall,
+        ) -> BatchResult {
+            let Ok(method) = Method::from_bytes(call.method.as_bytes()) else {
+                return BatchResult::error(400, "Invalid method.");
+            };
+            let Some(mut service) =
+                registered_server_fn_service(&call.path, method)
+            else {
+                return BatchResult::error(
+                    400,
+                    format!(
+                        "Could not find a server function at the route {}.",
+                        call.path
+                    ),
+                );
+            };
+            if let Some(call_layer) = call_layer {
+                service = call_layer.layer(service);
+            }
+
+            // each call sees the batch request, but its own query, content type and body
+            let mut body = dev::Payload::from(call.body.clone());
+            let payload = match Payload::from_request(req, &mut body).await {
+                Ok(payload) => payload,
+                Err(e) => return BatchResult::error(500, e.to_string()),
+            };
+            let req =
+                ActixRequest::from_batched_call(req.clone(), payload, &call);
+
+            let res = service.0.run(req).await.take();
+            let status = res.status().as_u16();
+            let content_type = res
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned);
+            let location = res
+                .headers()
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned);
+            let redirect = res.headers().contains_key(REDIRECT_HEADER);
+            match to_bytes(res.into_body()).await {
+                Ok(body) => BatchResult {
+                    status,
+                    content_type,
+                    location,
+                    redirect,
+                    body,
+                },
+                Err(e) => BatchResult::error(500, e.to_string()),
+            }
+        }
+    }
 }
@@ -29,7 +29,7 @@ pub trait Service<Request, Response> {
 #[cfg(feature = "axum-no-default")]
 mod axum {
     use super::{BoxedService, Service};
-    use crate::{response::Res, ServerFnError};
+    use crate::{error::ErrorStatus, response::Res, ServerFnError};
     use axum::body::Body;
     use http::{Request, Response};
     use std::{
@@ -53,7 +53,11 @@ mod axum {
             Box::pin(async move {
                 inner.await.unwrap_or_else(|e| {
                     let err = ServerFnError::new(e);
-                    Response::<Body>::error_response(&path, &err)
+                    Response::<Body>::error_response_with_status(
+                        &path,
+                        err.status_code(),
+                        &err,
+                    )
                 })
             })
         }
@@ -105,6 +109,7 @@ mod axum {
 #[cfg(feature = "actix")]
 mod actix {
     use crate::{
+        error::ErrorStatus,
         request::actix::ActixRequest,
         response::{actix::ActixResponse, Res},
         ServerFnError,
@@ -131,7 +136,12 @@ mod actix {
             Box::pin(async move {
                 inner.await.unwrap_or_else(|e| {
                     let err = ServerFnError::new(e);
-                    ActixResponse::error_response(&path, &err).take()
+                    ActixResponse::error_response_with_status(
+                        &path,
+                        err.status_code(),
+                        &err,
+                    )
+                    .take()
                 })
             })
         }
@@ -152,7 +162,12 @@ mod actix {
             Box::pin(async move {
                 ActixResponse::from(inner.await.unwrap_or_else(|e| {
                     let err = ServerFnError::new(e);
-                    ActixResponse::error_response(&path, &err).take()
+                    ActixResponse::error_response_with_status(
+                        &path,
+                        err.status_code(),
+                        &err,
+                    )
+                    .take()
                 }))
             })
         }
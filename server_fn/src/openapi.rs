@@ -0,0 +1,485 @@
+use crate::codec::{BoxedStream, ByteStream, TextStream};
+use http::Method;
+use schemars::{
+    generate::{SchemaGenerator, SchemaSettings},
+    json_schema, JsonSchema, Schema,
+};
+use serde_json::{json, Map, Value};
+use std::{borrow::Cow, sync::OnceLock};
+
+/// The path at which the OpenAPI document is usually served, relative to the server URL.
+pub const OPENAPI_PATH: &str = "/api/openapi.json";
+
+static INFO: OnceLock<(&'static str, &'static str)> = OnceLock::new();
+
+/// Sets the title and version of the API, as they appear in the `info` section of the
+/// document served by the Axum and Actix `handle_openapi` handlers.
+///
+/// If this is not set, it defaults to `"Server Functions"` and `"0.0.0"`. It can only be set
+/// once: later calls return the title and version they were given as an error.
+pub fn set_openapi_info(
+    title: &'static str,
+    version: &'static str,
+) -> Result<(), (&'static str, &'static str)> {
+    INFO.set((title, version))
+}
+
+/// Returns the title and version set by [`set_openapi_info`].
+pub fn get_openapi_info() -> (&'static str, &'static str) {
+    INFO.get().copied().unwrap_or(("Server Functions", "0.0.0"))
+}
+
+/// A description of a single server function, as an operation in an OpenAPI document.
+///
+/// This is registered for every server function by the `#[server]` macro when the `openapi`
+/// feature is enabled.
+pub struct ServerFnOperation {
+    name: &'static str,
+    docs: &'static str,
+    path: &'static str,
+    method: Method,
+    input_content_type: &'static str,
+    output_content_type: &'static str,
+    input_schema: fn(&mut SchemaGenerator) -> Schema,
+    output_schema: fn(&mut SchemaGenerator) -> Schema,
+}
+
+impl ServerFnOperation {
+    /// Describes a server function.
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
+        name: &'static str,
+        docs: &'static str,
+        path: &'static str,
+        method: Method,
+        input_content_type: &'static str,
+        output_content_type: &'static str,
+        input_schema: fn(&mut SchemaGenerator) -> Schema,
+        output_schema: fn(&mut SchemaGenerator) -> Schema,
+    ) -> Self {
+        Self {
+            name,
+            docs,
+            path,
+            method,
+            input_content_type,
+            output_content_type,
+            input_schema,
+            output_schema,
+        }
+    }
+
+    /// The path of the server function.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// The HTTP method the server function expects.
+    pub fn method(&self) -> Method {
+        self.method.clone()
+    }
+
+    fn to_operation(&self, generator: &mut SchemaGenerator) -> Value {
+        let mut operation = Map::new();
+        operation.insert("operationId".into(), self.name.into());
+        let docs = self.docs.trim();
+        if let Some(summary) = docs.lines().next().filter(|s| !s.is_empty()) {
+            operation.insert("summary".into(), summary.trim().into());
+            operation.insert("description".into(), docs.into());
+        }
+
+        // the description of the arguments struct just links to the server function
+        let mut input = (self.input_schema)(generator);
+        if let Some(input) = input.as_object_mut() {
+            input.remove("description");
+        }
+        if self.method == Method::GET {
+            // arguments are sent in the query string, so each one is its own parameter
+            let required = input
+                .get("required")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let properties = input
+                .get("properties")
+                .and_then(Value::as_object)
+                .cloned()
+                .unwrap_or_default();
+            let parameters = properties
+                .into_iter()
+                .map(|(name, schema)| {
+                    let mut parameter = json!({
+                        "name": name,
+                        "in": "query",
+                        "required": required.contains(&Value::from(name.as_str())),
+                        "schema": schema,
+                    });
+                    if is_object(&schema, generator) {
+                        parameter["style"] = "deepObject".into();
+                        parameter["explode"] = true.into();
+                    }
+                    parameter
+                })
+                .collect::<Vec<_>>();
+            operation.insert("parameters".into(), parameters.into());
+        } else {
+            let content_type = self.input_content_type;
+            let schema = if content_type.starts_with("multipart/form-data") {
+                // the fields of the form are up to the server function itself
+                json!({ "type": "object" })
+            } else {
+                input.to_value()
+            };
+            operation.insert(
+                "requestBody".into(),
+                json!({
+                    "required": true,
+                    "content": { content_type: { "schema": schema } },
+                }),
+            );
+        }
+
+        let output = (self.output_schema)(generator);
+        operation.insert(
+            "responses".into(),
+            json!({
+                "200": {
+                    "description": "The value returned by the server function.",
+                    "content": {
+                        self.output_content_type: { "schema": output },
+                    },
+                },
+                "4XX": { "$ref": "#/components/responses/ServerFnError" },
+                "5XX": { "$ref": "#/components/responses/ServerFnError" },
+            }),
+        );
+        operation.into()
+    }
+}
+
+inventory::collect!(ServerFnOperation);
+
+/// Returns the schema of a server function’s arguments or output.
+#[doc(hidden)]
+pub fn __schema_for<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+/// Returns the schema of a server function’s arguments, with its fields inlined.
+#[doc(hidden)]
+pub fn __inline_schema_for<T: JsonSchema>(
+    generator: &mut SchemaGenerator,
+) -> Schema {
+    T::json_schema(generator)
+}
+
+/// Generates an OpenAPI 3.1 document describing every registered server function.
+///
+/// Each server function is documented as an operation at its path, with its arguments as either
+/// query parameters (for `GET` encodings like [`GetUrl`](crate::codec::GetUrl)) or the request
+/// body in its input encoding, its return type as the body of a `200` response, and any
+/// [`ServerFnError`](crate::ServerFnError) as a `4XX` or `5XX` response. JSON schemas are
+/// generated with [`schemars`], so every argument and return type must implement
+/// [`JsonSchema`](schemars::JsonSchema).
+pub fn openapi_document(title: &str, version: &str) -> Value {
+    let mut generator = SchemaSettings::draft2020_12()
+        .with(|settings| {
+            settings.definitions_path = "/components/schemas".into();
+            settings.meta_schema = None;
+        })
+        .into_generator();
+
+    let mut operations = inventory::iter::<ServerFnOperation>
+        .into_iter()
+        .collect::<Vec<_>>();
+    operations.sort_by_key(|op| (op.path, op.method.as_str().to_owned()));
+
+    let mut paths = Map::new();
+    for op in operations {
+        let item = paths
+            .entry(op.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[op.method.as_str().to_ascii_lowercase()] =
+            op.to_operation(&mut generator);
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": { "title": title, "version": version },
+        "paths": paths,
+        "components": {
+            "schemas": generator.take_definitions(true),
+            "responses": {
+                "ServerFnError": {
+                    "description": "The server function returned an error, or \
+                                    its arguments could not be deserialized. \
+                                    The body is the kind of error and its \
+                                    message, separated by `|`.",
+                    "content": {
+                        "text/plain": {
+                            "schema": {
+                                "type": "string",
+                                "examples": ["ServerError|something went wrong"],
+                            },
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// Whether a schema (or the schema it refers to) describes an object, possibly an optional one.
+fn is_object(schema: &Value, generator: &SchemaGenerator) -> bool {
+    let schema = match schema.get("$ref").and_then(Value::as_str) {
+        Some(reference) => reference
+            .rsplit('/')
+            .next()
+            .and_then(|name| generator.definitions().get(name))
+            .unwrap_or(schema),
+        None => schema,
+    };
+    let is_object_type = match schema.get("type") {
+        Some(Value::String(ty)) => ty == "object",
+        // `Option<T>` can be `{ "type": ["object", "null"] }`...
+        Some(Value::Array(types)) => types.iter().any(|ty| ty == "object"),
+        _ => false,
+    };
+    // ...or `{ "anyOf": [{ "$ref": .. }, { "type": "null" }] }`
+    is_object_type
+        || ["anyOf", "oneOf"].into_iter().any(|key| {
+            schema
+                .get(key)
+                .and_then(Value::as_array)
+                .is_some_and(|variants| {
+                    variants.iter().any(|variant| is_object(variant, generator))
+                })
+        })
+}
+
+impl<CustErr> JsonSchema for ByteStream<CustErr> {
+    fn schema_name() -> Cow<'static, str> {
+        "ByteStream".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "string", "contentMediaType": "application/octet-stream" })
+    }
+}
+
+impl<CustErr> JsonSchema for TextStream<CustErr> {
+    fn schema_name() -> Cow<'static, str> {
+        "TextStream".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "string" })
+    }
+}
+
+impl<T: JsonSchema, CustErr> JsonSchema for BoxedStream<T, CustErr> {
+    fn schema_name() -> Cow<'static, str> {
+        format!("BoxedStream_{}", T::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "array", "items": generator.subschema_for::<T>() })
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: JsonSchema, CustErr> JsonSchema
+    for crate::codec::JsonStream<T, CustErr>
+{
+    fn schema_name() -> Cow<'static, str> {
+        format!("JsonStream_{}", T::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "array", "items": generator.subschema_for::<T>() })
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: JsonSchema, CustErr> JsonSchema
+    for crate::codec::ServerSentEvents<T, CustErr>
+{
+    fn schema_name() -> Cow<'static, str> {
+        format!("ServerSentEvents_{}", T::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "array", "items": generator.subschema_for::<T>() })
+    }
+}
+
+#[cfg(feature = "multipart")]
+impl JsonSchema for crate::codec::MultipartData {
+    fn schema_name() -> Cow<'static, str> {
+        "MultipartData".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "object" })
+    }
+}
+
+#[cfg(feature = "protobuf")]
+impl<T: JsonSchema, CustErr> JsonSchema
+    for crate::codec::ProtobufStream<T, CustErr>
+{
+    fn schema_name() -> Cow<'static, str> {
+        format!("ProtobufStream_{}", T::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "array", "items": generator.subschema_for::<T>() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Filter {
+        tag: String,
+    }
+
+    /// Lists posts.
+    ///
+    /// Only published posts are listed.
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct ListPosts {
+        page: u32,
+        filter: Option<Filter>,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct CreatePost {
+        title: String,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Post {
+        id: u32,
+        title: String,
+    }
+
+    inventory::submit! {
+        ServerFnOperation::new(
+            "list_posts",
+            "Lists posts.\n\nOnly published posts are listed.",
+            "/openapi_test/list_posts",
+            Method::GET,
+            "application/x-www-form-urlencoded",
+            "application/json",
+            __inline_schema_for::<ListPosts>,
+            __schema_for::<Vec<Post>>,
+        )
+    }
+
+    inventory::submit! {
+        ServerFnOperation::new(
+            "create_post",
+            "",
+            "/openapi_test/create_post",
+            Method::POST,
+            "application/json",
+            "application/json",
+            __inline_schema_for::<CreatePost>,
+            __schema_for::<Post>,
+        )
+    }
+
+    inventory::submit! {
+        ServerFnOperation::new(
+            "upload",
+            "",
+            "/openapi_test/upload",
+            Method::POST,
+            "multipart/form-data",
+            "application/json",
+            __inline_schema_for::<CreatePost>,
+            __schema_for::<()>,
+        )
+    }
+
+    fn operation(document: &Value, path: &str, method: &str) -> Value {
+        document["paths"][path][method].clone()
+    }
+
+    #[test]
+    fn documents_get_arguments_as_query_parameters() {
+        let document = openapi_document("Test", "1.0.0");
+        let op = operation(&document, "/openapi_test/list_posts", "get");
+        assert_eq!(op["operationId"], "list_posts");
+        assert_eq!(op["summary"], "Lists posts.");
+        assert!(op.get("requestBody").is_none());
+
+        let params = op["parameters"].as_array().unwrap();
+        let page = params.iter().find(|p| p["name"] == "page").unwrap();
+        assert_eq!(page["in"], "query");
+        assert_eq!(page["required"], true);
+        assert!(page.get("style").is_none());
+
+        // nested objects, even optional ones, are sent in the `filter[tag]=..` style
+        let filter = params.iter().find(|p| p["name"] == "filter").unwrap();
+        assert_eq!(filter["required"], false);
+        assert_eq!(filter["style"], "deepObject");
+        assert_eq!(filter["explode"], true);
+    }
+
+    #[test]
+    fn documents_post_arguments_as_request_body() {
+        let document = openapi_document("Test", "1.0.0");
+        let op = operation(&document, "/openapi_test/create_post", "post");
+        assert!(op.get("parameters").is_none());
+        assert!(op.get("summary").is_none());
+        let body = &op["requestBody"];
+        assert_eq!(body["required"], true);
+        let schema = &body["content"]["application/json"]["schema"];
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["title"]));
+
+        let op = operation(&document, "/openapi_test/upload", "post");
+        assert_eq!(
+            op["requestBody"]["content"]["multipart/form-data"]["schema"],
+            json!({ "type": "object" })
+        );
+    }
+
+    #[test]
+    fn documents_responses_and_errors() {
+        let document = openapi_document("Test", "1.0.0");
+        assert_eq!(document["info"]["title"], "Test");
+        assert_eq!(document["info"]["version"], "1.0.0");
+
+        let op = operation(&document, "/openapi_test/create_post", "post");
+        let responses = &op["responses"];
+        assert_eq!(
+            responses["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/Post"
+        );
+        for status in ["4XX", "5XX"] {
+            let reference = responses[status]["$ref"].as_str().unwrap();
+            let name =
+                reference.strip_prefix("#/components/responses/").unwrap();
+            assert!(document["components"]["responses"][name]["content"]
+                ["text/plain"]
+                .is_object());
+        }
+        assert!(document["components"]["schemas"]["Post"].is_object());
+    }
+
+    #[test]
+    fn info_can_only_be_set_once() {
+        assert_eq!(set_openapi_info("Blog", "1.0.0"), Ok(()));
+        assert_eq!(set_openapi_info("Other", "2.0.0"), Err(("Other", "2.0.0")));
+        assert_eq!(get_openapi_info(), ("Blog", "1.0.0"));
+    }
+}
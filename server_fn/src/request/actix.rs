@@ -1,7 +1,13 @@
-use crate::{error::ServerFnError, request::Req};
+use crate::{
+    codec::{transport_error, WebsocketFrame, WebsocketSink, WebsocketStream},
+    error::{NoCustomError, ServerFnError},
+    request::{Req, WebsocketReq},
+    response::actix::ActixResponse,
+};
 use actix_web::{web::Payload, HttpRequest};
+use actix_ws::Message;
 use bytes::Bytes;
-use futures::{Stream, StreamExt};
+use futures::{sink, Stream, StreamExt};
 use send_wrapper::SendWrapper;
 use std::{borrow::Cow, future::Future};
 
@@ -10,14 +16,45 @@ use std::{borrow::Cow, future::Future};
 /// This uses a [`SendWrapper`] that allows the Actix `HttpRequest` type to be `Send`, but panics
 /// if it it is ever sent to another thread. Actix pins request handling to a single thread, so this
 /// is necessary to be compatible with traits that require `Send` but should never panic in actual use.
-pub struct ActixRequest(pub(crate) SendWrapper<(HttpRequest, Payload)>);
+pub struct ActixRequest(
+    pub(crate) SendWrapper<(HttpRequest, Payload)>,
+    pub(crate) Option<BatchedCallParts>,
+);
+
+/// The parts of a server function call sent as part of a batch, which are used in place of
+/// those of the batch request itself.
+#[cfg_attr(not(feature = "batch"), allow(dead_code))]
+pub(crate) struct BatchedCallParts {
+    query: Option<String>,
+    content_type: Option<String>,
+    accepts: String,
+}
 
 impl ActixRequest {
     /// Returns the raw Actix request, and its body.
+    ///
+    /// For a call that was sent as part of a batch, this is the request for the whole batch,
+    /// with the body of this call.
     pub fn take(self) -> (HttpRequest, Payload) {
         self.0.take()
     }
 
+    #[cfg(feature = "batch")]
+    pub(crate) fn from_batched_call(
+        req: HttpRequest,
+        payload: Payload,
+        call: &crate::batch::BatchCall,
+    ) -> Self {
+        ActixRequest(
+            SendWrapper::new((req, payload)),
+            Some(BatchedCallParts {
+                query: call.query.clone(),
+                content_type: call.content_type.clone(),
+                accepts: call.accepts.clone(),
+            }),
+        )
+    }
+
     fn header(&self, name: &str) -> Option<Cow<'_, str>> {
         self.0
              .0
@@ -29,7 +66,7 @@ impl ActixRequest {
 
 impl From<(HttpRequest, Payload)> for ActixRequest {
     fn from(value: (HttpRequest, Payload)) -> Self {
-        ActixRequest(SendWrapper::new(value))
+        ActixRequest(SendWrapper::new(value), None)
     }
 }
 
@@ -38,21 +75,34 @@ where
     CustErr: 'static,
 {
     fn as_query(&self) -> Option<&str> {
-        self.0 .0.uri().query()
+        match &self.1 {
+            Some(call) => call.query.as_deref(),
+            None => self.0 .0.uri().query(),
+        }
     }
 
     fn to_content_type(&self) -> Option<Cow<'_, str>> {
-        self.header("Content-Type")
+        match &self.1 {
+            Some(call) => call.content_type.as_deref().map(Cow::Borrowed),
+            None => self.header("Content-Type"),
+        }
     }
 
     fn accepts(&self) -> Option<Cow<'_, str>> {
-        self.header("Accept")
+        match &self.1 {
+            Some(call) => Some(Cow::Borrowed(&call.accepts)),
+            None => self.header("Accept"),
+        }
     }
 
     fn referer(&self) -> Option<Cow<'_, str>> {
         self.header("Referer")
     }
 
+    fn last_event_id(&self) -> Option<Cow<'_, str>> {
+        self.header("Last-Event-ID")
+    }
+
     fn try_into_bytes(
         self,
     ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>> + Send
@@ -98,3 +148,73 @@ where
         Ok(SendWrapper::new(stream))
     }
 }
+
+impl<CustErr> WebsocketReq<CustErr> for ActixRequest
+where
+    CustErr: 'static,
+{
+    type Response = ActixResponse;
+
+    fn try_into_websocket<F, Fut>(
+        self,
+        handler: F,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send
+    where
+        F: FnOnce(WebsocketStream, WebsocketSink) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        SendWrapper::new(async move {
+            let (req, payload) = self.0.take();
+            #[cfg(feature = "csrf")]
+            {
+                let header = |name: &str| {
+                    req.headers().get(name).and_then(|v| v.to_str().ok())
+                };
+                let host = header("host")
+                    .or_else(|| req.uri().authority().map(|a| a.as_str()));
+                crate::csrf::verify_websocket_origin(host, header)
+                    .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+            }
+            let (res, session, msg_stream) = actix_ws::handle(&req, payload)
+                .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+            // unlike other servers, Actix leaves it to us to answer pings
+            let pong_session = session.clone();
+            let stream = msg_stream.filter_map(move |msg| {
+                let mut session = pong_session.clone();
+                async move {
+                    match msg {
+                        Ok(Message::Binary(bytes)) => Some(Ok(bytes)),
+                        Ok(Message::Text(text)) => Some(Err(text.to_string())),
+                        Ok(Message::Ping(bytes)) => {
+                            _ = session.pong(&bytes).await;
+                            None
+                        }
+                        Ok(_) => None,
+                        Err(e) => Some(transport_error(e)),
+                    }
+                }
+            });
+            let sink = sink::unfold(
+                session,
+                |mut session, frame: WebsocketFrame| async move {
+                    match frame {
+                        Ok(bytes) => session.binary(bytes).await,
+                        Err(text) => session.text(text).await,
+                    }
+                    .map_err(|e| {
+                        ServerFnError::<NoCustomError>::Response(e.to_string())
+                    })?;
+                    Ok(session)
+                },
+            );
+            actix_web::rt::spawn(handler(
+                Box::pin(SendWrapper::new(stream)),
+                Box::pin(sink),
+            ));
+            Ok(ActixResponse::from(res))
+        })
+    }
+}
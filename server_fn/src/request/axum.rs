@@ -1,5 +1,17 @@
+#[cfg(feature = "axum")]
+use crate::{
+    codec::{transport_error, WebsocketFrame, WebsocketSink, WebsocketStream},
+    request::WebsocketReq,
+};
 use crate::{error::ServerFnError, request::Req};
 use axum::body::{Body, Bytes};
+#[cfg(feature = "axum")]
+use axum::extract::{
+    ws::{Message, WebSocketUpgrade},
+    FromRequestParts,
+};
+#[cfg(feature = "axum")]
+use futures::{future::ready, SinkExt};
 use futures::{Stream, StreamExt};
 use http::{
     header::{ACCEPT, CONTENT_TYPE, REFERER},
@@ -7,6 +19,8 @@ use http::{
 };
 use http_body_util::BodyExt;
 use std::borrow::Cow;
+#[cfg(feature = "axum")]
+use std::future::Future;
 
 impl<CustErr> Req<CustErr> for Request<Body>
 where
@@ -22,6 +36,12 @@ where
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
 
+    fn last_event_id(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get("Last-Event-ID")
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
     fn accepts(&self) -> Option<Cow<'_, str>> {
         self.headers()
             .get(ACCEPT)
@@ -60,3 +80,56 @@ where
         }))
     }
 }
+
+#[cfg(feature = "axum")]
+impl<CustErr> WebsocketReq<CustErr> for Request<Body>
+where
+    CustErr: 'static,
+{
+    type Response = http::Response<Body>;
+
+    async fn try_into_websocket<F, Fut>(
+        self,
+        handler: F,
+    ) -> Result<Self::Response, ServerFnError<CustErr>>
+    where
+        F: FnOnce(WebsocketStream, WebsocketSink) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (mut parts, _body) = self.into_parts();
+        #[cfg(feature = "csrf")]
+        {
+            let header = |name: &str| {
+                parts.headers.get(name).and_then(|v| v.to_str().ok())
+            };
+            let host = header("host")
+                .or_else(|| parts.uri.authority().map(|a| a.as_str()));
+            crate::csrf::verify_websocket_origin(host, header)
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+        }
+        let upgrade = WebSocketUpgrade::from_request_parts(&mut parts, &())
+            .await
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+        Ok(upgrade.on_upgrade(move |socket| {
+            let (sink, stream) = socket.split();
+            let stream = stream.filter_map(|msg| {
+                ready(match msg {
+                    Ok(Message::Binary(bytes)) => Some(Ok(Bytes::from(bytes))),
+                    Ok(Message::Text(text)) => Some(Err(text)),
+                    // pings are answered by the socket itself
+                    Ok(_) => None,
+                    Err(e) => Some(transport_error(e)),
+                })
+            });
+            let sink = sink
+                .with(|frame: WebsocketFrame| {
+                    ready(Ok::<_, axum::Error>(match frame {
+                        Ok(bytes) => Message::Binary(bytes.into()),
+                        Err(text) => Message::Text(text),
+                    }))
+                })
+                .sink_map_err(|e| ServerFnError::Response(e.to_string()));
+            handler(Box::pin(stream), Box::pin(sink))
+        }))
+    }
+}
@@ -0,0 +1,106 @@
+use super::ClientReq;
+use crate::{batch::BatchCall, error::ServerFnError};
+use bytes::Bytes;
+use futures::Stream;
+use std::convert::Infallible;
+
+/// A server function call that will be sent to the server as part of a batch.
+///
+/// Batched calls are buffered in memory, so they can only be built from text or binary
+/// bodies. Form data, multipart and streaming requests are not supported.
+#[derive(Debug)]
+pub struct BatchRequest(pub(crate) BatchCall);
+
+impl BatchRequest {
+    fn new(
+        method: &str,
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: Option<&str>,
+        body: Bytes,
+    ) -> Self {
+        Self(BatchCall {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.map(ToOwned::to_owned),
+            content_type: Some(content_type.to_string()),
+            accepts: accepts.to_string(),
+            body,
+        })
+    }
+}
+
+impl<CustErr> ClientReq<CustErr> for BatchRequest {
+    type FormData = Infallible;
+
+    fn try_new_get(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            "GET",
+            path,
+            accepts,
+            content_type,
+            Some(query),
+            Bytes::new(),
+        ))
+    }
+
+    fn try_new_post(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: String,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            "POST",
+            path,
+            accepts,
+            content_type,
+            None,
+            body.into(),
+        ))
+    }
+
+    fn try_new_post_bytes(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new("POST", path, accepts, content_type, None, body))
+    }
+
+    fn try_new_post_form_data(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        match body {}
+    }
+
+    fn try_new_multipart(
+        _path: &str,
+        _accepts: &str,
+        body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        match body {}
+    }
+
+    fn try_new_streaming(
+        path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(format!(
+            "Could not batch the call to {path}: streaming requests cannot be \
+             batched."
+        )))
+    }
+}
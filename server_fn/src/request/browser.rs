@@ -1,5 +1,9 @@
 use super::ClientReq;
-use crate::{client::get_server_url, error::ServerFnError};
+use crate::{
+    client::get_server_url,
+    csrf::{token_from_cookie, CSRF_HEADER},
+    error::ServerFnError,
+};
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
 pub use gloo_net::http::Request;
@@ -20,9 +24,30 @@ pub struct BrowserRequest(pub(crate) SendWrapper<RequestInner>);
 #[derive(Debug)]
 pub(crate) struct RequestInner {
     pub(crate) request: Request,
+    // the same JavaScript object as `request`, which `gloo_net` does not give access to
+    raw: web_sys::Request,
     pub(crate) abort_ctrl: Option<AbortOnDrop>,
 }
 
+impl RequestInner {
+    fn new(request: Request, abort_ctrl: Option<AbortOnDrop>) -> Self {
+        let raw = web_sys::Request::from(request);
+        if let Some(token) = token_from_cookie() {
+            _ = raw.headers().set(CSRF_HEADER, &token);
+        }
+        #[cfg(feature = "json")]
+        if let Some(id) = crate::codec::last_event_id() {
+            _ = raw.headers().set(crate::codec::LAST_EVENT_ID, &id);
+        }
+        Self {
+            // `web_sys::Request::clone` would copy the request, rather than the reference to it
+            request: Request::from(Clone::clone(&raw)),
+            raw,
+            abort_ctrl,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct AbortOnDrop(Option<AbortController>);
 
@@ -41,6 +66,26 @@ impl Drop for AbortOnDrop {
     }
 }
 
+impl BrowserRequest {
+    /// Copies the request so that it can be sent again, or returns `None` if its body cannot be
+    /// copied, for example because it has already been sent.
+    ///
+    /// The copy is aborted separately from the original.
+    pub fn try_clone(&self) -> Option<Self> {
+        // a clone would follow the abort signal of the original, so it gets one of its own
+        let copy = web_sys::Request::clone(&self.0.raw).ok()?;
+        let (abort_ctrl, abort_signal) = abort_signal();
+        let init = RequestInit::new();
+        init.set_signal(abort_signal.as_ref());
+        let copy =
+            web_sys::Request::new_with_request_and_init(&copy, &init).ok()?;
+        Some(Self(SendWrapper::new(RequestInner::new(
+            Request::from(copy),
+            abort_ctrl,
+        ))))
+    }
+}
+
 impl From<BrowserRequest> for Request {
     fn from(value: BrowserRequest) -> Self {
         value.0.take().request
@@ -101,15 +146,15 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         url.push_str(path);
         url.push('?');
         url.push_str(query);
-        Ok(Self(SendWrapper::new(RequestInner {
-            request: Request::get(&url)
+        Ok(Self(SendWrapper::new(RequestInner::new(
+            Request::get(&url)
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
                 .abort_signal(abort_signal.as_ref())
                 .build()
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
-        })))
+        ))))
     }
 
     fn try_new_post(
@@ -123,15 +168,15 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         let mut url = String::with_capacity(server_url.len() + path.len());
         url.push_str(server_url);
         url.push_str(path);
-        Ok(Self(SendWrapper::new(RequestInner {
-            request: Request::post(&url)
+        Ok(Self(SendWrapper::new(RequestInner::new(
+            Request::post(&url)
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
                 .abort_signal(abort_signal.as_ref())
                 .body(body)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
-        })))
+        ))))
     }
 
     fn try_new_post_bytes(
@@ -147,15 +192,15 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         url.push_str(path);
         let body: &[u8] = &body;
         let body = Uint8Array::from(body).buffer();
-        Ok(Self(SendWrapper::new(RequestInner {
-            request: Request::post(&url)
+        Ok(Self(SendWrapper::new(RequestInner::new(
+            Request::post(&url)
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
                 .abort_signal(abort_signal.as_ref())
                 .body(body)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
-        })))
+        ))))
     }
 
     fn try_new_multipart(
@@ -168,14 +213,14 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         let mut url = String::with_capacity(server_url.len() + path.len());
         url.push_str(server_url);
         url.push_str(path);
-        Ok(Self(SendWrapper::new(RequestInner {
-            request: Request::post(&url)
+        Ok(Self(SendWrapper::new(RequestInner::new(
+            Request::post(&url)
                 .header("Accept", accepts)
                 .abort_signal(abort_signal.as_ref())
                 .body(body.0.take())
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
-        })))
+        ))))
     }
 
     fn try_new_post_form_data(
@@ -196,15 +241,15 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                         },
                     ))
                 })?;
-        Ok(Self(SendWrapper::new(RequestInner {
-            request: Request::post(path)
+        Ok(Self(SendWrapper::new(RequestInner::new(
+            Request::post(path)
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
                 .abort_signal(abort_signal.as_ref())
                 .body(url_params)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
-        })))
+        ))))
     }
 
     fn try_new_streaming(
@@ -213,14 +258,12 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         content_type: &str,
         body: impl Stream<Item = Bytes> + 'static,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        // TODO abort signal
         let (request, abort_ctrl) =
             streaming_request(path, accepts, content_type, body)
                 .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
-        Ok(Self(SendWrapper::new(RequestInner {
-            request,
-            abort_ctrl,
-        })))
+        Ok(Self(SendWrapper::new(RequestInner::new(
+            request, abort_ctrl,
+        ))))
     }
 }
 
@@ -68,6 +68,12 @@ where
             .map(|val| String::from_utf8_lossy(val.as_bytes()))
     }
 
+    fn last_event_id(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get("Last-Event-ID")
+            .map(|val| String::from_utf8_lossy(val.as_bytes()))
+    }
+
     fn as_query(&self) -> Option<&str> {
         self.uri().query()
     }
@@ -0,0 +1,122 @@
+use super::ClientReq;
+use crate::error::ServerFnError;
+use axum::body::Body;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use http::{
+    header::{ACCEPT, CONTENT_TYPE},
+    Method, Request,
+};
+use std::convert::Infallible;
+
+/// A server function call that is dispatched directly to the registered handler, without
+/// being sent over the network.
+///
+/// Form data and multipart requests are not supported.
+#[derive(Debug)]
+pub struct InProcessRequest(pub(crate) Request<Body>);
+
+impl InProcessRequest {
+    fn new<CustErr>(
+        method: Method,
+        uri: String,
+        accepts: &str,
+        content_type: &str,
+        body: Body,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(CONTENT_TYPE, content_type)
+            .header(ACCEPT, accepts);
+        #[cfg(feature = "json")]
+        let req = match crate::codec::last_event_id() {
+            Some(id) => req.header(crate::codec::LAST_EVENT_ID, id),
+            None => req,
+        };
+        req.body(body)
+            .map(Self)
+            .map_err(|e| ServerFnError::Request(e.to_string()))
+    }
+}
+
+impl<CustErr> ClientReq<CustErr> for InProcessRequest {
+    type FormData = Infallible;
+
+    fn try_new_get(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Self::new(
+            Method::GET,
+            format!("{path}?{query}"),
+            accepts,
+            content_type,
+            Body::empty(),
+        )
+    }
+
+    fn try_new_post(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: String,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Self::new(
+            Method::POST,
+            path.to_string(),
+            accepts,
+            content_type,
+            Body::from(body),
+        )
+    }
+
+    fn try_new_post_bytes(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Self::new(
+            Method::POST,
+            path.to_string(),
+            accepts,
+            content_type,
+            Body::from(body),
+        )
+    }
+
+    fn try_new_post_form_data(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        match body {}
+    }
+
+    fn try_new_multipart(
+        _path: &str,
+        _accepts: &str,
+        body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        match body {}
+    }
+
+    fn try_new_streaming(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Self::new(
+            Method::POST,
+            path.to_string(),
+            accepts,
+            content_type,
+            Body::from_stream(body.map(Ok::<_, Infallible>)),
+        )
+    }
+}
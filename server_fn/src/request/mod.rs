@@ -1,4 +1,7 @@
-use crate::error::ServerFnError;
+use crate::{
+    codec::{WebsocketSink, WebsocketStream},
+    error::ServerFnError,
+};
 use bytes::Bytes;
 use futures::Stream;
 use std::{borrow::Cow, future::Future};
@@ -9,11 +12,17 @@ pub mod actix;
 /// Request types for Axum.
 #[cfg(feature = "axum-no-default")]
 pub mod axum;
+/// Request types for batched server function calls.
+#[cfg(feature = "batch")]
+pub mod batch;
 /// Request types for the browser.
 #[cfg(feature = "browser")]
 pub mod browser;
 #[cfg(feature = "generic")]
 pub mod generic;
+/// Request types for server functions that are called in-process.
+#[cfg(feature = "in-process")]
+pub mod in_process;
 /// Request types for [`reqwest`].
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
@@ -92,6 +101,14 @@ where
     /// Returns the `Referer` header, if any.
     fn referer(&self) -> Option<Cow<'_, str>>;
 
+    /// Returns the `Last-Event-ID` header, if any.
+    ///
+    /// This is sent by a client that is resuming an interrupted stream of
+    /// [`ServerSentEvents`](crate::codec::ServerSentEvents).
+    fn last_event_id(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
     /// Attempts to extract the body of the request into [`Bytes`].
     fn try_into_bytes(
         self,
@@ -111,6 +128,28 @@ where
     >;
 }
 
+/// Represents a request received by the server that can be upgraded to a websocket.
+pub trait WebsocketReq<CustErr>
+where
+    Self: Sized,
+{
+    /// The type of the response that completes the websocket handshake.
+    type Response;
+
+    /// Attempts to upgrade the request to a websocket connection, returning the handshake
+    /// response.
+    ///
+    /// Once the connection has been upgraded, the server integration calls `handler` with
+    /// the incoming and outgoing halves of the socket, and drives the future it returns.
+    fn try_into_websocket<F, Fut>(
+        self,
+        handler: F,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send
+    where
+        F: FnOnce(WebsocketStream, WebsocketSink) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static;
+}
+
 /// A mocked request type that can be used in place of the actual server request,
 /// when compiling for the browser.
 pub struct BrowserMockReq;
@@ -151,3 +190,21 @@ where
         Ok(futures::stream::once(async { unreachable!() }))
     }
 }
+
+impl<CustErr> WebsocketReq<CustErr> for BrowserMockReq
+where
+    CustErr: 'static,
+{
+    type Response = crate::response::BrowserMockRes;
+
+    async fn try_into_websocket<F, Fut>(
+        self,
+        _handler: F,
+    ) -> Result<Self::Response, ServerFnError<CustErr>>
+    where
+        F: FnOnce(WebsocketStream, WebsocketSink) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        unreachable!()
+    }
+}
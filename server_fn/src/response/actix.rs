@@ -81,8 +81,18 @@ where
     }
 
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self {
+        Self::error_response_with_status(path, 500, err)
+    }
+
+    fn error_response_with_status(
+        path: &str,
+        status: u16,
+        err: &ServerFnError<CustErr>,
+    ) -> Self {
+        let status = StatusCode::from_u16(status)
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
         ActixResponse(SendWrapper::new(
-            HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
+            HttpResponse::build(status)
                 .append_header((SERVER_FN_ERROR_HEADER, path))
                 .body(err.ser().unwrap_or_else(|_| err.to_string())),
         ))
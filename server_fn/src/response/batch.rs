@@ -0,0 +1,53 @@
+use super::ClientRes;
+use crate::{batch::BatchResult, error::ServerFnError};
+use bytes::Bytes;
+use futures::{future::ready, stream, Stream};
+use http::StatusCode;
+
+/// The response to a single server function call that was sent as part of a batch.
+///
+/// The body of the response is buffered in memory, so streaming responses arrive all at once.
+#[derive(Debug)]
+pub struct BatchResponse(pub(crate) BatchResult);
+
+impl<CustErr> ClientRes<CustErr> for BatchResponse {
+    async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
+        String::from_utf8(self.0.body.into())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        Ok(self.0.body)
+    }
+
+    fn try_into_stream(
+        self,
+    ) -> Result<
+        impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+        ServerFnError<CustErr>,
+    > {
+        Ok(stream::once(ready(Ok(self.0.body))))
+    }
+
+    fn status(&self) -> u16 {
+        self.0.status
+    }
+
+    fn status_text(&self) -> String {
+        StatusCode::from_u16(self.0.status)
+            .map(|status| status.to_string())
+            .unwrap_or_else(|_| self.0.status.to_string())
+    }
+
+    fn location(&self) -> String {
+        self.0.location.clone().unwrap_or_default()
+    }
+
+    fn has_redirect(&self) -> bool {
+        self.0.redirect
+    }
+
+    fn content_type(&self) -> Option<String> {
+        self.0.content_type.clone()
+    }
+}
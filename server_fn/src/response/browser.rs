@@ -1,16 +1,41 @@
 use super::ClientRes;
-use crate::{error::ServerFnError, redirect::REDIRECT_HEADER};
+use crate::{
+    error::ServerFnError, redirect::REDIRECT_HEADER,
+    request::browser::AbortOnDrop,
+};
 use bytes::Bytes;
-use futures::{Stream, StreamExt};
+use futures::{stream, Stream, StreamExt};
 pub use gloo_net::http::Response;
 use js_sys::Uint8Array;
 use send_wrapper::SendWrapper;
-use std::future::Future;
+use std::{future::Future, ops::Deref, task::Poll};
 use wasm_bindgen::JsCast;
 use wasm_streams::ReadableStream;
 
 /// The response to a `fetch` request made in the browser.
-pub struct BrowserResponse(pub(crate) SendWrapper<Response>);
+///
+/// The request is aborted if this is dropped before its body has been read.
+pub struct BrowserResponse(
+    pub(crate) SendWrapper<Response>,
+    pub(crate) SendWrapper<Option<AbortOnDrop>>,
+);
+
+impl BrowserResponse {
+    /// Prevents the request from being aborted once the whole body has been read.
+    fn complete(abort_ctrl: &mut SendWrapper<Option<AbortOnDrop>>) {
+        if let Some(ctrl) = abort_ctrl.as_mut() {
+            ctrl.prevent_cancellation();
+        }
+    }
+}
+
+impl Deref for BrowserResponse {
+    type Target = Response;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
 
 impl<CustErr> ClientRes<CustErr> for BrowserResponse {
     fn try_into_string(
@@ -20,10 +45,13 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         // the browser won't send this async work between threads (because it's single-threaded)
         // so we can safely wrap this
         SendWrapper::new(async move {
-            self.0
+            let BrowserResponse(res, mut abort_ctrl) = self;
+            let text = res
                 .text()
                 .await
-                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+                .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+            Self::complete(&mut abort_ctrl);
+            Ok(text)
         })
     }
 
@@ -34,11 +62,13 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         // the browser won't send this async work between threads (because it's single-threaded)
         // so we can safely wrap this
         SendWrapper::new(async move {
-            self.0
-                .binary()
-                .await
-                .map(Bytes::from)
-                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+            let BrowserResponse(res, mut abort_ctrl) = self;
+            let bytes =
+                res.binary().await.map(Bytes::from).map_err(|e| {
+                    ServerFnError::Deserialization(e.to_string())
+                })?;
+            Self::complete(&mut abort_ctrl);
+            Ok(bytes)
         })
     }
 
@@ -48,7 +78,8 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
         ServerFnError<CustErr>,
     > {
-        let stream = ReadableStream::from_raw(self.0.body().unwrap())
+        let BrowserResponse(res, mut abort_ctrl) = self;
+        let stream = ReadableStream::from_raw(res.body().unwrap())
             .into_stream()
             .map(|data| match data {
                 Err(e) => {
@@ -63,7 +94,12 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
                     data.copy_to(&mut buf);
                     Ok(Bytes::from(buf))
                 }
-            });
+            })
+            // the request is aborted if the stream is dropped before it ends
+            .chain(stream::poll_fn(move |_| {
+                Self::complete(&mut abort_ctrl);
+                Poll::Ready(None)
+            }));
         Ok(SendWrapper::new(stream))
     }
 
@@ -85,4 +121,8 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
     fn has_redirect(&self) -> bool {
         self.0.headers().get(REDIRECT_HEADER).is_some()
     }
+
+    fn content_type(&self) -> Option<String> {
+        self.0.headers().get("Content-Type")
+    }
 }
@@ -89,8 +89,18 @@ where
     }
 
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self {
+        Self::error_response_with_status(path, 500, err)
+    }
+
+    fn error_response_with_status(
+        path: &str,
+        status: u16,
+        err: &ServerFnError<CustErr>,
+    ) -> Self {
+        let status = http::StatusCode::from_u16(status)
+            .unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
         Response::builder()
-            .status(http::StatusCode::INTERNAL_SERVER_ERROR)
+            .status(status)
             .header(SERVER_FN_ERROR_HEADER, path)
             .body(err.ser().unwrap_or_else(|_| err.to_string()).into())
             .unwrap()
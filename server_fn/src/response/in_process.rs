@@ -0,0 +1,97 @@
+use super::ClientRes;
+use crate::{error::ServerFnError, redirect::REDIRECT_HEADER};
+use axum::body::Body;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use http::{
+    header::{CONTENT_TYPE, LOCATION},
+    Response,
+};
+use http_body_util::BodyExt;
+use std::{
+    pin::Pin,
+    sync::{Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+/// The response returned by a server function that was called in-process.
+#[derive(Debug)]
+pub struct InProcessResponse(pub(crate) Response<Body>);
+
+impl<CustErr> ClientRes<CustErr> for InProcessResponse {
+    async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
+        let bytes = <Self as ClientRes<CustErr>>::try_into_bytes(self).await?;
+        String::from_utf8(bytes.into())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        self.0
+            .into_body()
+            .collect()
+            .await
+            .map(|body| body.to_bytes())
+            .map_err(|e| ServerFnError::Response(e.to_string()))
+    }
+
+    fn try_into_stream(
+        self,
+    ) -> Result<
+        impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+        ServerFnError<CustErr>,
+    > {
+        let stream = self.0.into_body().into_data_stream().map(|chunk| {
+            chunk.map_err(|e| ServerFnError::Response(e.to_string()))
+        });
+        Ok(SyncStream(Mutex::new(Box::pin(stream))))
+    }
+
+    fn status(&self) -> u16 {
+        self.0.status().as_u16()
+    }
+
+    fn status_text(&self) -> String {
+        self.0.status().to_string()
+    }
+
+    fn location(&self) -> String {
+        self.0
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn has_redirect(&self) -> bool {
+        self.0.headers().contains_key(REDIRECT_HEADER)
+    }
+
+    fn content_type(&self) -> Option<String> {
+        self.0
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(ToOwned::to_owned)
+    }
+}
+
+/// A response body is `Send` but not `Sync`. Because the stream is only ever polled through a
+/// mutable reference, wrapping it in a `Mutex` makes it `Sync` without locking.
+struct SyncStream<S>(Mutex<Pin<Box<S>>>);
+
+impl<S: Stream> Stream for SyncStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .0
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+            .poll_next(cx)
+    }
+}
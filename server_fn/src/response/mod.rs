@@ -1,6 +1,9 @@
 /// Response types for Actix.
 #[cfg(feature = "actix")]
 pub mod actix;
+/// Response types for batched server function calls.
+#[cfg(feature = "batch")]
+pub mod batch;
 /// Response types for the browser.
 #[cfg(feature = "browser")]
 pub mod browser;
@@ -9,6 +12,9 @@ pub mod generic;
 /// Response types for Axum.
 #[cfg(feature = "axum-no-default")]
 pub mod http;
+/// Response types for server functions that are called in-process.
+#[cfg(feature = "in-process")]
+pub mod in_process;
 /// Response types for [`reqwest`].
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
@@ -46,6 +52,21 @@ where
     /// Converts an error into a response, with a `500` status code and the error text as its body.
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self;
 
+    /// Converts an error into a response, with the given status code and the error text as its
+    /// body.
+    ///
+    /// The default implementation ignores `status` and uses
+    /// [`error_response`](Res::error_response), so that response types written before status
+    /// codes were supported keep working.
+    fn error_response_with_status(
+        path: &str,
+        status: u16,
+        err: &ServerFnError<CustErr>,
+    ) -> Self {
+        _ = status;
+        Self::error_response(path, err)
+    }
+
     /// Redirect the response by setting a 302 code and Location header.
     fn redirect(&mut self, path: &str);
 }
@@ -81,6 +102,11 @@ pub trait ClientRes<CustErr> {
 
     /// Whether the response has the [`REDIRECT_HEADER`](crate::redirect::REDIRECT_HEADER) set.
     fn has_redirect(&self) -> bool;
+
+    /// The `Content-Type` header of the response, if any.
+    fn content_type(&self) -> Option<String> {
+        None
+    }
 }
 
 /// A mocked response type that can be used in place of the actual server response,
@@ -46,4 +46,11 @@ impl<CustErr> ClientRes<CustErr> for Response {
     fn has_redirect(&self) -> bool {
         self.headers().get("Location").is_some()
     }
+
+    fn content_type(&self) -> Option<String> {
+        self.headers()
+            .get("Content-Type")
+            .and_then(|value| value.to_str().ok())
+            .map(ToOwned::to_owned)
+    }
 }
@@ -230,6 +230,7 @@ pub fn server_macro_impl(
         None => Some("PostUrl".to_string()),
         _ => None,
     };
+    let input_type = input.clone();
     let input = input
         .map(|n| {
             if builtin_encoding {
@@ -243,6 +244,13 @@ pub fn server_macro_impl(
                 #server_fn_path::codec::PostUrl
             }
         });
+    let is_websocket = input_ident.as_deref() == Some("Websocket");
+    // a websocket uses the same protocol in both directions
+    let output = if is_websocket && output.is_none() {
+        input_type
+    } else {
+        output
+    };
     let output = output
         .map(|n| {
             if builtin_encoding {
@@ -353,6 +361,18 @@ pub fn server_macro_impl(
         let path = path.join("::");
         format!("{path}::serde")
     });
+    let schemars_path = server_fn_path
+        .as_ref()
+        .map(|path| {
+            let path = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>();
+            let path = path.join("::");
+            format!("{path}::schemars")
+        })
+        .unwrap_or_else(|| "server_fn::schemars".to_string());
     let server_fn_path = server_fn_path
         .map(|path| quote!(#path))
         .unwrap_or_else(|| quote! { server_fn });
@@ -396,6 +416,40 @@ pub fn server_macro_impl(
         quote! {}
     };
 
+    // registration of the OpenAPI operation, with schemas for the arguments and output
+    let openapi = cfg!(all(feature = "ssr", feature = "openapi"));
+    let openapi_derive = openapi.then(|| {
+        quote! {
+            #[derive(#server_fn_path::schemars::JsonSchema)]
+            #[schemars(crate = #schemars_path)]
+        }
+    });
+    let openapi_inventory = openapi.then(|| {
+        let docs = body
+            .docs
+            .iter()
+            .map(|(doc, _)| doc.strip_prefix(' ').unwrap_or(doc))
+            .collect::<Vec<_>>()
+            .join("\n");
+        quote! {
+            #server_fn_path::inventory::submit! {{
+                use #server_fn_path::{ServerFn, codec::Encoding};
+                #server_fn_path::openapi::ServerFnOperation::new(
+                    #fn_name_as_str,
+                    #docs,
+                    #wrapped_struct_name_turbofish::PATH,
+                    <#wrapped_struct_name as ServerFn>::InputEncoding::METHOD,
+                    <#wrapped_struct_name as ServerFn>::InputEncoding::CONTENT_TYPE,
+                    <#wrapped_struct_name as ServerFn>::OutputEncoding::CONTENT_TYPE,
+                    #server_fn_path::openapi::__inline_schema_for::<#struct_name>,
+                    #server_fn_path::openapi::__schema_for::<
+                        <#wrapped_struct_name as ServerFn>::Output
+                    >,
+                )
+            }}
+        }
+    });
+
     // run_body in the trait implementation
     let run_body = if cfg!(feature = "ssr") {
         let destructure = if let Some(wrapper) = custom_wrapper.as_ref() {
@@ -495,7 +549,10 @@ pub fn server_macro_impl(
         ),
         Some("MultipartFormData")
         | Some("Streaming")
-        | Some("StreamingText") => (PathInfo::None, quote! {}),
+        | Some("StreamingText")
+        | Some("Websocket")
+        | Some("Protobuf")
+        | Some("StreamingProtobuf") => (PathInfo::None, quote! {}),
         Some("SerdeLite") => (
             PathInfo::Serde,
             quote! {
@@ -628,6 +685,109 @@ pub fn server_macro_impl(
         }
     };
 
+    // websocket server functions don't fit the usual request/response model, so they
+    // replace the default client and server logic with that of the `Websocket` protocol
+    let websocket = if is_websocket {
+        let (field_name, field_ty) = match (fn_args.len(), first_field) {
+            (1, Some(field)) => field,
+            _ => {
+                return Err(syn::Error::new(
+                    body.inputs.span(),
+                    "websocket server functions must take exactly one \
+                     argument, the stream of incoming messages",
+                ))
+            }
+        };
+        let data = if let Some(wrapper) = custom_wrapper.as_ref() {
+            quote! { #wrapper(#struct_name { #field_name }) }
+        } else {
+            quote! { #struct_name { #field_name } }
+        };
+        quote! {
+            fn run_on_server(
+                req: Self::ServerRequest,
+            ) -> impl std::future::Future<Output = Self::ServerResponse> + Send {
+                <#input>::run_server(
+                    Self::PATH,
+                    req,
+                    |#field_name: #field_ty| async move {
+                        use #server_fn_path::ServerFn;
+                        let data = #data;
+                        data.run_body().await
+                    },
+                )
+            }
+
+            fn run_on_client(
+                self,
+            ) -> impl std::future::Future<
+                Output = Result<
+                    Self::Output,
+                    #server_fn_path::ServerFnError<Self::Error>,
+                >,
+            > + Send {
+                let #data = self;
+                <#input>::run_client::<Self::Client, _, _, _>(
+                    Self::PATH,
+                    #field_name,
+                )
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // a `Protobuf` argument is sent as the message itself, so the arguments struct is a
+    // transparent `prost::Message` that delegates to it
+    let protobuf = if input_ident.as_deref() == Some("Protobuf") {
+        let (field_name, field_ty) = match (fn_args.len(), first_field) {
+            (1, Some(field)) => field,
+            _ => {
+                return Err(syn::Error::new(
+                    body.inputs.span(),
+                    "server functions with the `Protobuf` input encoding \
+                     must take exactly one argument, a `prost::Message`",
+                ))
+            }
+        };
+        let prost = quote! { #server_fn_path::prost };
+        quote! {
+            impl Default for #struct_name {
+                fn default() -> Self {
+                    Self { #field_name: Default::default() }
+                }
+            }
+
+            impl #prost::Message for #struct_name {
+                fn encode_raw(&self, buf: &mut impl #prost::bytes::BufMut) {
+                    <#field_ty as #prost::Message>::encode_raw(&self.#field_name, buf)
+                }
+
+                fn merge_field(
+                    &mut self,
+                    tag: u32,
+                    wire_type: #prost::encoding::WireType,
+                    buf: &mut impl #prost::bytes::Buf,
+                    ctx: #prost::encoding::DecodeContext,
+                ) -> Result<(), #prost::DecodeError> {
+                    <#field_ty as #prost::Message>::merge_field(
+                        &mut self.#field_name, tag, wire_type, buf, ctx,
+                    )
+                }
+
+                fn encoded_len(&self) -> usize {
+                    <#field_ty as #prost::Message>::encoded_len(&self.#field_name)
+                }
+
+                fn clear(&mut self) {
+                    <#field_ty as #prost::Message>::clear(&mut self.#field_name)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     // only emit the dummy (unmodified server-only body) for the server build
     let dummy = cfg!(feature = "ssr").then_some(dummy);
     let middlewares = if cfg!(feature = "ssr") {
@@ -647,12 +807,15 @@ pub fn server_macro_impl(
         #docs
         #[derive(Debug, #derives)]
         #addl_path
+        #openapi_derive
         pub struct #struct_name {
             #(#fields),*
         }
 
         #from_impl
 
+        #protobuf
+
         impl #server_fn_path::ServerFn for #wrapped_struct_name {
             const PATH: &'static str = #path;
 
@@ -664,15 +827,24 @@ pub fn server_macro_impl(
             type OutputEncoding = #output;
             type Error = #error_ty;
 
+            fn error_status(err: &#server_fn_path::ServerFnError<#error_ty>) -> u16 {
+                use #server_fn_path::error::ViaStatus;
+                (&&#server_fn_path::error::StatusOf(err)).to_status_code()
+            }
+
             fn middlewares() -> Vec<std::sync::Arc<dyn #server_fn_path::middleware::Layer<#req, #res>>> {
                 #middlewares
             }
 
             #run_body
+
+            #websocket
         }
 
         #inventory
 
+        #openapi_inventory
+
         #func
 
         #dummy